@@ -1,8 +1,6 @@
 extern crate parity_wasm;
-extern crate time;
 
-use std::fs;
-use time::Instant;
+use std::{fs, time::Instant};
 
 fn rate(file_name: &'static str, iterations: u64) {
 	let file_size = fs::metadata(file_name)
@@ -15,7 +13,7 @@ fn rate(file_name: &'static str, iterations: u64) {
 		let _module = parity_wasm::deserialize_file(file_name);
 		let end = Instant::now();
 
-		total_ms += (end - start).whole_milliseconds();
+		total_ms += (end - start).as_millis();
 	}
 
 	println!(
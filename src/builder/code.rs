@@ -1,4 +1,5 @@
 use super::{
+	instructions::InstructionsBuilder,
 	invoke::{Identity, Invoke},
 	misc::{ValueTypeBuilder, ValueTypesBuilder},
 };
@@ -54,13 +55,13 @@ where
 	}
 
 	/// Start build new argument
-	pub fn param(self) -> ValueTypeBuilder<Self> {
-		ValueTypeBuilder::with_callback(self)
+	pub fn param(self) -> ValueTypeBuilder<SignatureParams<F>> {
+		ValueTypeBuilder::with_callback(SignatureParams(self))
 	}
 
 	/// Start build multiple arguments
-	pub fn params(self) -> ValueTypesBuilder<Self> {
-		ValueTypesBuilder::with_callback(self)
+	pub fn params(self) -> ValueTypesBuilder<SignatureParams<F>> {
+		ValueTypesBuilder::with_callback(SignatureParams(self))
 	}
 
 	/// Add result to signature builder
@@ -79,13 +80,13 @@ where
 	}
 
 	/// Start building new result
-	pub fn result(self) -> ValueTypeBuilder<Self> {
-		ValueTypeBuilder::with_callback(self)
+	pub fn result(self) -> ValueTypeBuilder<SignatureResults<F>> {
+		ValueTypeBuilder::with_callback(SignatureResults(self))
 	}
 
 	/// Start building multiple results
-	pub fn results(self) -> ValueTypeBuilder<Self> {
-		ValueTypeBuilder::with_callback(self)
+	pub fn results(self) -> ValueTypesBuilder<SignatureResults<F>> {
+		ValueTypesBuilder::with_callback(SignatureResults(self))
 	}
 
 	/// Finish current builder
@@ -99,26 +100,53 @@ where
 	}
 }
 
-impl<F, I> Invoke<I> for SignatureBuilder<F>
+/// Chaining helper which adds built value types to the signature parameters
+pub struct SignatureParams<F>(SignatureBuilder<F>);
+
+impl<F> Invoke<elements::ValueType> for SignatureParams<F>
 where
 	F: Invoke<elements::FunctionType>,
-	I: IntoIterator<Item = elements::ValueType>,
 {
-	type Result = Self;
+	type Result = SignatureBuilder<F>;
 
-	fn invoke(self, args: I) -> Self {
-		self.with_params(args)
+	fn invoke(self, arg: elements::ValueType) -> SignatureBuilder<F> {
+		self.0.with_param(arg)
 	}
 }
 
-impl<F> Invoke<elements::ValueType> for SignatureBuilder<F>
+impl<F> Invoke<Vec<elements::ValueType>> for SignatureParams<F>
 where
 	F: Invoke<elements::FunctionType>,
 {
-	type Result = Self;
+	type Result = SignatureBuilder<F>;
+
+	fn invoke(self, args: Vec<elements::ValueType>) -> SignatureBuilder<F> {
+		self.0.with_params(args)
+	}
+}
+
+/// Chaining helper which adds built value types to the signature results
+pub struct SignatureResults<F>(SignatureBuilder<F>);
+
+impl<F> Invoke<elements::ValueType> for SignatureResults<F>
+where
+	F: Invoke<elements::FunctionType>,
+{
+	type Result = SignatureBuilder<F>;
 
-	fn invoke(self, arg: elements::ValueType) -> Self {
-		self.with_result(arg)
+	fn invoke(self, arg: elements::ValueType) -> SignatureBuilder<F> {
+		self.0.with_result(arg)
+	}
+}
+
+impl<F> Invoke<Vec<elements::ValueType>> for SignatureResults<F>
+where
+	F: Invoke<elements::FunctionType>,
+{
+	type Result = SignatureBuilder<F>;
+
+	fn invoke(self, args: Vec<elements::ValueType>) -> SignatureBuilder<F> {
+		self.0.with_results(args)
 	}
 }
 
@@ -284,6 +312,16 @@ where
 		self
 	}
 
+	/// Set code of the function using a structured [`InstructionsBuilder`]
+	pub fn with_code<B>(self, build: B) -> Self
+	where
+		B: FnOnce(&mut InstructionsBuilder),
+	{
+		let mut code = InstructionsBuilder::new();
+		build(&mut code);
+		self.with_instructions(code.build())
+	}
+
 	/// Finish current builder spawning resulting struct
 	pub fn build(self) -> F::Result {
 		self.callback.invoke(self.body)
@@ -483,4 +521,57 @@ mod tests {
 		assert_eq!(func.code.locals().len(), 0);
 		assert_eq!(func.code.code().elements().len(), 1);
 	}
+
+	#[test]
+	fn signature_params_and_results() {
+		use super::{signature, Signature};
+		use elements::ValueType::*;
+
+		let sig = signature()
+			.param()
+			.i32()
+			.params()
+			.i64()
+			.f32()
+			.build()
+			.result()
+			.f64()
+			.build_sig();
+		match sig {
+			Signature::Inline(func_type) => {
+				assert_eq!(func_type.params(), &[I32, I64, F32]);
+				assert_eq!(func_type.results(), &[F64]);
+			},
+			_ => panic!("Expected inline signature"),
+		}
+
+		let sig = signature().results().i32().i64().build().build_sig();
+		match sig {
+			Signature::Inline(func_type) => {
+				assert!(func_type.params().is_empty());
+				assert_eq!(func_type.results(), &[I32, I64]);
+			},
+			_ => panic!("Expected inline signature"),
+		}
+	}
+
+	#[test]
+	fn structured_code() {
+		use elements::{BlockType, Instruction::*};
+
+		let func = function()
+			.body()
+			.with_code(|code| {
+				code.block(BlockType::NoResult, |code, label| {
+					code.push(GetLocal(0)).br_if(label);
+				});
+			})
+			.build()
+			.build();
+
+		assert_eq!(
+			func.code.code().elements(),
+			&[Block(BlockType::NoResult), GetLocal(0), BrIf(0), End, End]
+		);
+	}
 }
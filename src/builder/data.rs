@@ -9,6 +9,8 @@ pub struct DataSegmentBuilder<F = Identity> {
 	mem_index: u32,
 	offset: elements::InitExpr,
 	value: Vec<u8>,
+	#[cfg(feature = "bulk")]
+	passive: bool,
 }
 
 impl DataSegmentBuilder {
@@ -32,6 +34,8 @@ impl<F> DataSegmentBuilder<F> {
 			mem_index: 0,
 			offset: elements::InitExpr::empty(),
 			value: Vec::new(),
+			#[cfg(feature = "bulk")]
+			passive: false,
 		}
 	}
 
@@ -46,6 +50,14 @@ impl<F> DataSegmentBuilder<F> {
 		self.value = value;
 		self
 	}
+
+	/// Make the segment passive, i.e. only copied into memory by `memory.init`.
+	/// The offset is ignored for passive segments.
+	#[cfg(feature = "bulk")]
+	pub fn passive(mut self) -> Self {
+		self.passive = true;
+		self
+	}
 }
 
 impl<F> DataSegmentBuilder<F>
@@ -54,6 +66,12 @@ where
 {
 	/// Finish current builder, spawning resulting struct
 	pub fn build(self) -> F::Result {
+		#[cfg(feature = "bulk")]
+		if self.passive {
+			let mut segment = elements::DataSegment::new(self.mem_index, None, self.value);
+			segment.set_passive(true);
+			return self.callback.invoke(segment)
+		}
 		self.callback.invoke(elements::DataSegment::new(
 			self.mem_index,
 			Some(self.offset),
@@ -0,0 +1,85 @@
+use super::invoke::{Identity, Invoke};
+use crate::elements;
+use alloc::vec::Vec;
+
+/// Element segment builder
+pub struct ElementSegmentBuilder<F = Identity> {
+	callback: F,
+	table_index: u32,
+	offset: elements::InitExpr,
+	members: Vec<u32>,
+	#[cfg(feature = "bulk")]
+	passive: bool,
+}
+
+impl ElementSegmentBuilder {
+	/// New element segment builder
+	pub fn new() -> Self {
+		ElementSegmentBuilder::with_callback(Identity)
+	}
+}
+
+impl Default for ElementSegmentBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<F> ElementSegmentBuilder<F> {
+	/// New element segment builder inside the chain context
+	pub fn with_callback(callback: F) -> Self {
+		ElementSegmentBuilder {
+			callback,
+			table_index: 0,
+			offset: elements::InitExpr::empty(),
+			members: Vec::new(),
+			#[cfg(feature = "bulk")]
+			passive: false,
+		}
+	}
+
+	/// Set the index of the table the segment initializes
+	pub fn table(mut self, index: u32) -> Self {
+		self.table_index = index;
+		self
+	}
+
+	/// Set offset initialization instruction. `End` instruction will be added automatically.
+	pub fn offset(mut self, instruction: elements::Instruction) -> Self {
+		self.offset = elements::InitExpr::new(vec![instruction, elements::Instruction::End]);
+		self
+	}
+
+	/// Set the function indices placed in the table
+	pub fn members(mut self, members: Vec<u32>) -> Self {
+		self.members = members;
+		self
+	}
+
+	/// Make the segment passive, i.e. only copied into a table by `table.init`.
+	#[cfg(feature = "bulk")]
+	pub fn passive(mut self) -> Self {
+		self.passive = true;
+		self
+	}
+}
+
+impl<F> ElementSegmentBuilder<F>
+where
+	F: Invoke<elements::ElementSegment>,
+{
+	/// Finish current builder, spawning resulting struct
+	pub fn build(self) -> F::Result {
+		#[cfg(feature = "bulk")]
+		if self.passive {
+			let mut segment = elements::ElementSegment::new(self.table_index, None, self.members);
+			segment.set_passive(true);
+			return self.callback.invoke(segment)
+		}
+		self.callback.invoke(elements::ElementSegment::new(
+			self.table_index,
+			Some(self.offset),
+			self.members,
+		))
+	}
+}
@@ -0,0 +1,223 @@
+use crate::elements::{BlockType, BrTableData, Instruction, Instructions};
+use alloc::vec::Vec;
+
+/// Branch target handed out for every structured control instruction.
+///
+/// Branches taking a `Label` are encoded with the relative depth the label has at the point
+/// where the branch is emitted, so nesting can change without recounting depths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Label(usize);
+
+/// Instruction sequence builder with structured control flow
+///
+/// # Examples
+///
+/// ```
+/// use parity_wasm::builder::InstructionsBuilder;
+/// use parity_wasm::elements::{BlockType, Instruction::*};
+///
+/// let mut code = InstructionsBuilder::new();
+/// code.block(BlockType::NoResult, |code, exit| {
+///     code.loop_(BlockType::NoResult, |code, repeat| {
+///         code.push(GetLocal(0)).br_if(exit);
+///         code.push(GetLocal(0)).push(I32Const(1)).push(I32Sub).push(SetLocal(0));
+///         code.br(repeat);
+///     });
+/// });
+///
+/// assert_eq!(
+///     code.build().elements(),
+///     &[
+///         Block(BlockType::NoResult),
+///         Loop(BlockType::NoResult),
+///         GetLocal(0), BrIf(1),
+///         GetLocal(0), I32Const(1), I32Sub, SetLocal(0),
+///         Br(0),
+///         End,
+///         End,
+///         End,
+///     ]
+/// );
+/// ```
+#[derive(Debug)]
+pub struct InstructionsBuilder {
+	instructions: Vec<Instruction>,
+	// Labels of the currently open blocks, innermost last.
+	open: Vec<Label>,
+	next_label: usize,
+}
+
+impl InstructionsBuilder {
+	/// New builder for a function body; the function itself is the outermost label.
+	pub fn new() -> Self {
+		InstructionsBuilder { instructions: Vec::new(), open: vec![Label(0)], next_label: 1 }
+	}
+
+	/// Label of the function body; branching to it returns from the function.
+	pub fn function_label(&self) -> Label {
+		Label(0)
+	}
+
+	/// Append a single instruction.
+	pub fn push(&mut self, instruction: Instruction) -> &mut Self {
+		self.instructions.push(instruction);
+		self
+	}
+
+	/// Append all instructions from the iterator.
+	pub fn extend<I>(&mut self, instructions: I) -> &mut Self
+	where
+		I: IntoIterator<Item = Instruction>,
+	{
+		self.instructions.extend(instructions);
+		self
+	}
+
+	/// Emit a `block`; branching to its label continues after the block.
+	pub fn block<B>(&mut self, block_type: BlockType, body: B) -> &mut Self
+	where
+		B: FnOnce(&mut Self, Label),
+	{
+		self.nested(Instruction::Block(block_type), body)
+	}
+
+	/// Emit a `loop`; branching to its label jumps back to the start of the loop.
+	pub fn loop_<B>(&mut self, block_type: BlockType, body: B) -> &mut Self
+	where
+		B: FnOnce(&mut Self, Label),
+	{
+		self.nested(Instruction::Loop(block_type), body)
+	}
+
+	/// Emit an `if` without an `else` arm.
+	pub fn if_<B>(&mut self, block_type: BlockType, then: B) -> &mut Self
+	where
+		B: FnOnce(&mut Self, Label),
+	{
+		self.nested(Instruction::If(block_type), then)
+	}
+
+	/// Emit an `if` with both arms; both receive the label of the `if` block.
+	pub fn if_else<T, E>(&mut self, block_type: BlockType, then: T, otherwise: E) -> &mut Self
+	where
+		T: FnOnce(&mut Self, Label),
+		E: FnOnce(&mut Self, Label),
+	{
+		self.nested(Instruction::If(block_type), |code, label| {
+			then(code, label);
+			code.push(Instruction::Else);
+			otherwise(code, label);
+		})
+	}
+
+	/// Emit `br` to the given label.
+	///
+	/// # Panics
+	///
+	/// If the label's block has already been closed.
+	pub fn br(&mut self, label: Label) -> &mut Self {
+		let depth = self.relative_depth(label);
+		self.push(Instruction::Br(depth))
+	}
+
+	/// Emit `br_if` to the given label.
+	///
+	/// # Panics
+	///
+	/// If the label's block has already been closed.
+	pub fn br_if(&mut self, label: Label) -> &mut Self {
+		let depth = self.relative_depth(label);
+		self.push(Instruction::BrIf(depth))
+	}
+
+	/// Emit `br_table` with the given targets and default label.
+	///
+	/// # Panics
+	///
+	/// If any of the labels' blocks has already been closed.
+	pub fn br_table(&mut self, targets: &[Label], default: Label) -> &mut Self {
+		let table = targets.iter().map(|label| self.relative_depth(*label)).collect();
+		let default = self.relative_depth(default);
+		self.push(Instruction::BrTable(alloc::boxed::Box::new(BrTableData { table, default })))
+	}
+
+	/// Finish the function body, appending the final `End`.
+	pub fn build(mut self) -> Instructions {
+		self.instructions.push(Instruction::End);
+		Instructions::new(self.instructions)
+	}
+
+	fn nested<B>(&mut self, start: Instruction, body: B) -> &mut Self
+	where
+		B: FnOnce(&mut Self, Label),
+	{
+		self.instructions.push(start);
+		let label = Label(self.next_label);
+		self.next_label += 1;
+		self.open.push(label);
+		body(self, label);
+		self.open.pop();
+		self.push(Instruction::End)
+	}
+
+	fn relative_depth(&self, label: Label) -> u32 {
+		let depth = self.open.iter().rev().position(|open| *open == label);
+		depth.expect("branch to a label whose block is already closed") as u32
+	}
+}
+
+impl Default for InstructionsBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::InstructionsBuilder;
+	use crate::elements::{BlockType, Instruction::*, ValueType};
+
+	#[test]
+	fn if_else_and_br_table() {
+		let mut code = InstructionsBuilder::new();
+		let function = code.function_label();
+		code.block(BlockType::NoResult, |code, outer| {
+			code.push(GetLocal(0)).if_else(
+				BlockType::Value(ValueType::I32),
+				|code, _| {
+					code.push(I32Const(1));
+				},
+				|code, _| {
+					code.push(GetLocal(1)).br_table(&[outer, function], outer);
+				},
+			);
+			code.push(Drop);
+		});
+
+		let expected = vec![
+			Block(BlockType::NoResult),
+			GetLocal(0),
+			If(BlockType::Value(ValueType::I32)),
+			I32Const(1),
+			Else,
+			GetLocal(1),
+			BrTable(Box::new(crate::elements::BrTableData { table: Box::new([1, 2]), default: 1 })),
+			End,
+			Drop,
+			End,
+			End,
+		];
+		assert_eq!(code.build().elements(), &expected[..]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn closed_label() {
+		let mut code = InstructionsBuilder::new();
+		let mut escaped = None;
+		code.block(BlockType::NoResult, |_, label| escaped = Some(label));
+		code.block(BlockType::NoResult, |code, _| {
+			code.br(escaped.unwrap());
+		});
+	}
+}
@@ -2,13 +2,16 @@
 
 mod code;
 mod data;
+mod element;
 mod export;
 mod global;
 mod import;
+mod instructions;
 mod invoke;
 mod memory;
 mod misc;
 mod module;
+mod stub;
 mod table;
 
 pub use self::{
@@ -17,11 +20,14 @@ pub use self::{
 		SignatureBuilder, SignaturesBuilder, TypeRefBuilder,
 	},
 	data::DataSegmentBuilder,
+	element::ElementSegmentBuilder,
 	export::{export, ExportBuilder, ExportInternalBuilder},
 	global::{global, GlobalBuilder},
 	import::{import, ImportBuilder},
+	instructions::{InstructionsBuilder, Label},
 	invoke::Identity,
 	memory::MemoryBuilder,
 	module::{from_module, module, CodeLocation, ModuleBuilder},
+	stub::{import_stub, import_stubs, StubBody},
 	table::{TableBuilder, TableDefinition, TableEntryDefinition},
 };
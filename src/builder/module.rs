@@ -1,12 +1,12 @@
 use super::{
 	code::{self, FunctionBuilder, SignaturesBuilder},
-	data, export, global, import,
+	data, element, export, global, import,
 	invoke::{Identity, Invoke},
 	memory::{self, MemoryBuilder},
 	table::{self, TableBuilder},
 };
 use crate::elements;
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 
 /// Module builder
 pub struct ModuleBuilder<F = Identity> {
@@ -86,6 +86,12 @@ impl From<elements::Module> for ModuleScaffold {
 				elements::Section::Code(sect) => {
 					code = Some(sect);
 				},
+				// Bodies of a lazily decoded module are parsed so that added functions join
+				// them in a single code section. Undecodable bodies are kept as they are.
+				elements::Section::LazyCode(sect) => match sect.parse() {
+					Ok(sect) => code = Some(sect),
+					Err(_) => other.push(elements::Section::LazyCode(sect)),
+				},
 				elements::Section::Data(sect) => {
 					data = Some(sect);
 				},
@@ -149,6 +155,11 @@ impl From<ModuleScaffold> for elements::Module {
 		if !element.entries().is_empty() {
 			sections.push(elements::Section::Element(element));
 		}
+		// `memory.init` and `data.drop` are only valid with a data count section present.
+		#[cfg(feature = "bulk")]
+		if module.data.entries().iter().any(|segment| segment.passive()) {
+			sections.push(elements::Section::DataCount(module.data.entries().len() as u32));
+		}
 		let code = module.code;
 		if !code.bodies().is_empty() {
 			sections.push(elements::Section::Code(code));
@@ -157,8 +168,24 @@ impl From<ModuleScaffold> for elements::Module {
 		if !data.entries().is_empty() {
 			sections.push(elements::Section::Data(data));
 		}
-		sections.extend(module.other);
-		elements::Module::new(sections)
+		let mut result = elements::Module::new(sections);
+		for section in module.other {
+			// Sections the scaffold does not track (data count, tags, custom sections and
+			// those added with `with_section`) keep their place in the section order.
+			let order = section.order();
+			if order != 0 && result.sections().iter().any(|s| s.order() == order) {
+				// A data count section duplicating the one generated above is redundant. Other
+				// duplicates are kept, appended to the end.
+				if !matches!(section, elements::Section::DataCount(_)) {
+					result.sections_mut().push(section);
+				}
+				continue
+			}
+			result
+				.insert_section(section)
+				.expect("no section of the same kind is present; qed");
+		}
+		result
 	}
 }
 
@@ -185,6 +212,8 @@ where
 	}
 
 	/// Builder from raw module
+	///
+	/// A lazily decoded code section is parsed, so functions can be added to the module.
 	pub fn with_module(mut self, module: elements::Module) -> Self {
 		self.module = module.into();
 		self
@@ -429,6 +458,48 @@ where
 		data::DataSegmentBuilder::with_callback(self)
 	}
 
+	/// Add element segment to the builder
+	pub fn with_element_segment(mut self, segment: elements::ElementSegment) -> Self {
+		self.module.element.entries_mut().push(segment);
+		self
+	}
+
+	/// Element segment builder
+	/// # Examples
+	/// ```
+	/// use parity_wasm::builder::module;
+	/// use parity_wasm::elements::Instruction::*;
+	///
+	/// let module = module()
+	///    .table()
+	///         .with_min(2)
+	///         .build()
+	///    .elements()
+	///         .offset(I32Const(1))
+	///         .members(vec![0])
+	///         .build()
+	///    .build();
+	///
+	/// assert_eq!(module.elements_section().expect("element section to exist").entries().len(), 1);
+	/// ```
+	pub fn elements(self) -> element::ElementSegmentBuilder<Self> {
+		element::ElementSegmentBuilder::with_callback(self)
+	}
+
+	/// With start function
+	pub fn with_start(mut self, index: u32) -> Self {
+		self.module.start = Some(index);
+		self
+	}
+
+	/// With custom section, emitted after all other sections
+	pub fn with_custom_section(self, name: impl Into<String>, payload: Vec<u8>) -> Self {
+		self.with_section(elements::Section::Custom(elements::CustomSection::new(
+			name.into(),
+			payload,
+		)))
+	}
+
 	/// Build module (final step)
 	pub fn build(self) -> F::Result {
 		self.callback.invoke(self.module.into())
@@ -529,6 +600,17 @@ where
 	}
 }
 
+impl<F> Invoke<elements::ElementSegment> for ModuleBuilder<F>
+where
+	F: Invoke<elements::Module>,
+{
+	type Result = Self;
+
+	fn invoke(self, segment: elements::ElementSegment) -> Self {
+		self.with_element_segment(segment)
+	}
+}
+
 impl<F> Invoke<elements::DataSegment> for ModuleBuilder<F>
 where
 	F: Invoke<elements::Module>,
@@ -572,6 +654,36 @@ mod tests {
 	use super::module;
 	use crate::elements;
 
+	#[test]
+	fn lazy_code() {
+		let bytes = module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.build()
+			.build()
+			.build()
+			.into_bytes()
+			.expect("module to serialize");
+		let lazy = elements::Module::from_bytes_lazy(&bytes).expect("module to deserialize");
+
+		let module = super::from_module(lazy)
+			.function()
+			.signature()
+			.build()
+			.body()
+			.build()
+			.build()
+			.build();
+		assert!(module.lazy_code_section().is_none());
+		assert_eq!(module.code_section().expect("code section to exist").bodies().len(), 2);
+
+		let bytes = module.into_bytes().expect("module to serialize");
+		let module = elements::Module::from_bytes(bytes).expect("module to deserialize");
+		assert_eq!(module.code_section().expect("code section to exist").bodies().len(), 2);
+	}
+
 	#[test]
 	fn smoky() {
 		let module = module().build();
@@ -632,6 +744,79 @@ mod tests {
 		assert_eq!(module.data_section().expect("data section to exist").entries().len(), 1);
 	}
 
+	#[cfg(feature = "bulk")]
+	#[test]
+	fn passive_data() {
+		let mut module = module()
+			.data()
+			.offset(elements::Instruction::I32Const(16))
+			.value(vec![0u8, 15])
+			.build()
+			.data()
+			.passive()
+			.value(vec![10u8, 5, 25])
+			.build()
+			.build();
+
+		let segments = module.data_section().expect("data section to exist").entries();
+		assert!(!segments[0].passive());
+		assert!(segments[1].passive());
+		assert!(segments[1].offset().is_none());
+		assert_eq!(module.data_count_section(), Some(2));
+
+		module.clear_data_count_section();
+		assert_eq!(module.data_count_section(), None);
+		module.set_data_count_section(2);
+		let sections = module.sections().iter().map(|s| s.order()).collect::<Vec<_>>();
+		assert_eq!(sections, vec![0x0b, 0x0d]);
+
+		let rebuilt = super::from_module(module.clone()).build();
+		assert_eq!(rebuilt, module);
+	}
+
+	#[test]
+	fn duplicate_sections_kept() {
+		let table = elements::TableType::new(1, None);
+		let module = module()
+			.with_table(table)
+			.with_section(elements::Section::Table(Default::default()))
+			.build();
+		assert_eq!(
+			module.sections(),
+			&[
+				elements::Section::Table(elements::TableSection::with_entries(vec![table])),
+				elements::Section::Table(Default::default()),
+			]
+		);
+	}
+
+	#[test]
+	fn remaining_sections() {
+		let module = module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.build()
+			.build()
+			.table()
+			.with_min(1)
+			.build()
+			.elements()
+			.offset(elements::Instruction::I32Const(0))
+			.members(vec![0])
+			.build()
+			.with_start(0)
+			.with_custom_section("meta", vec![1, 2])
+			.build();
+
+		let segments = module.elements_section().expect("element section to exist").entries();
+		assert_eq!(segments[0].members(), &[0]);
+		assert_eq!(module.start_section(), Some(0));
+		let custom = module.custom_sections().next().expect("custom section to exist");
+		assert_eq!((custom.name(), custom.payload()), ("meta", &[1u8, 2][..]));
+	}
+
 	#[test]
 	fn reuse_types() {
 		let module = module()
@@ -0,0 +1,288 @@
+use super::{
+	code::{FunctionDefinition, Signature},
+	memory::MemoryDefinition,
+	module::ModuleBuilder,
+	table::TableDefinition,
+};
+use crate::elements;
+use alloc::{string::String, vec::Vec};
+
+/// Behaviour of the functions generated by [`import_stubs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StubBody {
+	/// Every stub function executes `unreachable`, i.e. traps when called.
+	Trap,
+	/// Every stub function returns zero value(s) of its result types.
+	Zero,
+}
+
+fn zero_value(value_type: elements::ValueType) -> elements::Instruction {
+	match value_type {
+		elements::ValueType::I32 => elements::Instruction::I32Const(0),
+		elements::ValueType::I64 => elements::Instruction::I64Const(0),
+		elements::ValueType::F32 => elements::Instruction::F32Const(0),
+		elements::ValueType::F64 => elements::Instruction::F64Const(0),
+		#[cfg(feature = "simd")]
+		elements::ValueType::V128 => elements::Instruction::Simd(elements::SimdInstruction::V128Const(
+			alloc::boxed::Box::new([0u8; 16]),
+		)),
+	}
+}
+
+fn stub_body(func_type: &elements::FunctionType, body: StubBody) -> elements::FuncBody {
+	let mut instructions = match body {
+		StubBody::Trap => vec![elements::Instruction::Unreachable],
+		StubBody::Zero => func_type.results().iter().cloned().map(zero_value).collect(),
+	};
+	instructions.push(elements::Instruction::End);
+	elements::FuncBody::new(Vec::new(), elements::Instructions::new(instructions))
+}
+
+/// Build a stub module providing every import that `module` requests from `module_name`.
+///
+/// Each imported function, table, memory, global and tag is defined in the stub and exported
+/// under the same field name, so the stub can be registered as `module_name` in a host
+/// environment to satisfy `module`'s imports. Tables and memories get the imported limits,
+/// globals are initialized with zero. Fails with [`elements::Error::IndexOutOfRange`] if the
+/// type index of an imported function or tag can not be resolved in `module`'s type section.
+pub fn import_stub(
+	module: &elements::Module,
+	module_name: &str,
+	body: StubBody,
+) -> Result<elements::Module, elements::Error> {
+	let types = module.type_section().map(|section| section.types()).unwrap_or(&[]);
+	let imports = module.import_section().map(|section| section.entries()).unwrap_or(&[]);
+
+	let mut builder = ModuleBuilder::new();
+	#[cfg(feature = "exceptions")]
+	let mut tags = Vec::new();
+	for entry in imports.iter().filter(|entry| entry.module() == module_name) {
+		let internal = match *entry.external() {
+			elements::External::Function(type_ref) => {
+				let elements::Type::Function(ref func_type) = types
+					.get(type_ref as usize)
+					.ok_or(elements::Error::IndexOutOfRange(type_ref))?;
+				let location = builder.push_function(FunctionDefinition {
+					is_main: false,
+					signature: Signature::Inline(func_type.clone()),
+					code: stub_body(func_type, body),
+				});
+				elements::Internal::Function(location.body)
+			},
+			elements::External::Table(ref table_type) => {
+				let limits = table_type.limits();
+				elements::Internal::Table(builder.push_table(TableDefinition {
+					min: limits.initial(),
+					max: limits.maximum(),
+					elements: Vec::new(),
+				}))
+			},
+			elements::External::Memory(ref memory_type) => {
+				let limits = memory_type.limits();
+				elements::Internal::Memory(builder.push_memory(MemoryDefinition {
+					min: limits.initial(),
+					max: limits.maximum(),
+					data: Vec::new(),
+				}))
+			},
+			elements::External::Global(ref global_type) => {
+				let init_expr = elements::InitExpr::new(vec![
+					zero_value(global_type.content_type()),
+					elements::Instruction::End,
+				]);
+				elements::Internal::Global(
+					builder.push_global(elements::GlobalEntry::new(*global_type, init_expr)),
+				)
+			},
+			#[cfg(feature = "exceptions")]
+			elements::External::Tag(ref tag_type) => {
+				let elements::Type::Function(ref func_type) = types
+					.get(tag_type.type_ref() as usize)
+					.ok_or(elements::Error::IndexOutOfRange(tag_type.type_ref()))?;
+				let type_ref = builder.push_signature(Signature::Inline(func_type.clone()));
+				tags.push(elements::TagType::new(type_ref));
+				elements::Internal::Tag(tags.len() as u32 - 1)
+			},
+		};
+		builder.push_export(elements::ExportEntry::new(entry.field().into(), internal));
+	}
+
+	#[allow(unused_mut)]
+	let mut stub = builder.build();
+
+	#[cfg(feature = "atomics")]
+	{
+		let shared = imports
+			.iter()
+			.filter(|entry| entry.module() == module_name)
+			.filter_map(|entry| match *entry.external() {
+				elements::External::Memory(ref memory_type) => Some(memory_type.limits().shared()),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		if let Some(memory_section) = stub.memory_section_mut() {
+			for (memory_type, shared) in memory_section.entries_mut().iter_mut().zip(shared) {
+				memory_type.set_shared(shared);
+			}
+		}
+	}
+
+	#[cfg(feature = "exceptions")]
+	if !tags.is_empty() {
+		stub.insert_section(elements::Section::Tag(elements::TagSection::with_entries(tags)))?;
+	}
+
+	Ok(stub)
+}
+
+/// Build stub modules for every distinct module name found in `module`'s import section.
+///
+/// Returns pairs of the import module name and the stub module (see [`import_stub`]),
+/// in the order the names first appear in the import section. Fails like [`import_stub`].
+///
+/// # Examples
+///
+/// ```
+/// use parity_wasm::{builder, elements};
+///
+/// let module = builder::module()
+///     .import()
+///         .module("env")
+///         .field("log")
+///         .external().func(0)
+///         .build()
+///     .function()
+///         .signature().param().i32().build()
+///         .body().build()
+///         .build()
+///     .build();
+///
+/// let stubs = builder::import_stubs(&module, builder::StubBody::Trap).unwrap();
+/// assert_eq!(stubs.len(), 1);
+/// assert_eq!(stubs[0].0, "env");
+/// let exports = stubs[0].1.export_section().expect("export section to exist");
+/// assert_eq!(exports.entries()[0].field(), "log");
+/// ```
+pub fn import_stubs(
+	module: &elements::Module,
+	body: StubBody,
+) -> Result<Vec<(String, elements::Module)>, elements::Error> {
+	let mut names: Vec<&str> = Vec::new();
+	if let Some(import_section) = module.import_section() {
+		for entry in import_section.entries() {
+			if !names.contains(&entry.module()) {
+				names.push(entry.module());
+			}
+		}
+	}
+
+	names
+		.into_iter()
+		.map(|name| Ok((name.into(), import_stub(module, name, body)?)))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{import_stub, import_stubs, StubBody};
+	use crate::{builder, elements};
+
+	fn importing_module() -> elements::Module {
+		builder::module()
+			.with_signatures(vec![builder::signature()
+				.with_param(elements::ValueType::I32)
+				.with_result(elements::ValueType::I64)
+				.build_sig()])
+			.with_import(elements::ImportEntry::new(
+				"env".into(),
+				"get".into(),
+				elements::External::Function(0),
+			))
+			.with_import(elements::ImportEntry::new(
+				"env".into(),
+				"memory".into(),
+				elements::External::Memory(elements::MemoryType::new(1, Some(2))),
+			))
+			.with_import(elements::ImportEntry::new(
+				"global".into(),
+				"counter".into(),
+				elements::External::Global(elements::GlobalType::new(
+					elements::ValueType::F64,
+					true,
+				)),
+			))
+			.build()
+	}
+
+	#[test]
+	fn stubs_per_module_name() {
+		let stubs = import_stubs(&importing_module(), StubBody::Zero).expect("stubs to build");
+		let names = stubs.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, vec!["env", "global"]);
+
+		let global = &stubs[1].1;
+		assert_eq!(global.global_section().expect("global section to exist").entries().len(), 1);
+		assert_eq!(global.export_section().expect("export section to exist").entries().len(), 1);
+	}
+
+	#[test]
+	fn function_bodies() {
+		let module = importing_module();
+
+		let zero = import_stub(&module, "env", StubBody::Zero).expect("stub to build");
+		assert_eq!(
+			zero.code_section().expect("code section to exist").bodies()[0]
+				.code()
+				.elements(),
+			&[elements::Instruction::I64Const(0), elements::Instruction::End]
+		);
+		assert_eq!(zero.type_section().expect("type section to exist").types().len(), 1);
+
+		let memory = &zero.memory_section().expect("memory section to exist").entries()[0];
+		assert_eq!(memory.limits().initial(), 1);
+		assert_eq!(memory.limits().maximum(), Some(2));
+
+		let exports = zero.export_section().expect("export section to exist").entries();
+		assert_eq!(
+			exports[0],
+			elements::ExportEntry::new("get".into(), elements::Internal::Function(0))
+		);
+		assert_eq!(
+			exports[1],
+			elements::ExportEntry::new("memory".into(), elements::Internal::Memory(0))
+		);
+
+		let trap = import_stub(&module, "env", StubBody::Trap).expect("stub to build");
+		assert_eq!(
+			trap.code_section().expect("code section to exist").bodies()[0]
+				.code()
+				.elements(),
+			&[elements::Instruction::Unreachable, elements::Instruction::End]
+		);
+	}
+
+	#[test]
+	fn roundtrip() {
+		let stub = import_stub(&importing_module(), "env", StubBody::Trap).expect("stub to build");
+		let buf = elements::serialize(stub.clone()).expect("serialization to succeed");
+		let deserialized: elements::Module =
+			elements::deserialize_buffer(&buf).expect("deserialization to succeed");
+		assert_eq!(stub, deserialized);
+	}
+
+	#[test]
+	fn unknown_type() {
+		let module = builder::module()
+			.with_import(elements::ImportEntry::new(
+				"env".into(),
+				"get".into(),
+				elements::External::Function(3),
+			))
+			.build();
+		assert!(matches!(
+			import_stub(&module, "env", StubBody::Trap),
+			Err(elements::Error::IndexOutOfRange(3))
+		));
+		assert!(import_stubs(&module, StubBody::Trap).is_err());
+	}
+}
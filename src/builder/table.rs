@@ -3,7 +3,7 @@ use crate::elements;
 use alloc::vec::Vec;
 
 /// Table definition
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub struct TableDefinition {
 	/// Minimum length
 	pub min: u32,
@@ -79,9 +79,3 @@ where
 		self.callback.invoke(self.table)
 	}
 }
-
-impl Default for TableDefinition {
-	fn default() -> Self {
-		TableDefinition { min: 0, max: None, elements: Vec::new() }
-	}
-}
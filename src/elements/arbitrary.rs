@@ -0,0 +1,551 @@
+use alloc::{format, vec::Vec};
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::{
+	BlockType, CodeSection, CustomSection, DataSection, DataSegment, ElementSection,
+	ElementSegment, ExportEntry, ExportSection, External, Func, FuncBody, FunctionSection,
+	FunctionType, GlobalEntry, GlobalSection, GlobalType, ImportEntry, ImportSection, InitExpr,
+	Instruction, Instructions, Internal, Local, MemorySection, MemoryType, Module, Section,
+	TableSection, TableType, Type, TypeSection, ValueType,
+};
+
+/// Deepest nesting of generated expressions.
+const MAX_DEPTH: usize = 4;
+
+const VALUE_TYPES: [ValueType; 4] =
+	[ValueType::I32, ValueType::I64, ValueType::F32, ValueType::F64];
+
+fn any_value_type(u: &mut Unstructured) -> Result<ValueType> {
+	Ok(*u.choose(&VALUE_TYPES)?)
+}
+
+fn constant(u: &mut Unstructured, value_type: ValueType) -> Result<Instruction> {
+	Ok(match value_type {
+		ValueType::I32 => Instruction::I32Const(u.arbitrary()?),
+		ValueType::I64 => Instruction::I64Const(u.arbitrary()?),
+		ValueType::F32 => Instruction::F32Const(u.arbitrary()?),
+		ValueType::F64 => Instruction::F64Const(u.arbitrary()?),
+		#[cfg(feature = "simd")]
+		ValueType::V128 => unreachable!("only MVP value types are generated"),
+	})
+}
+
+fn binary(u: &mut Unstructured, value_type: ValueType) -> Result<Instruction> {
+	use Instruction::*;
+	let ops: &[Instruction] = match value_type {
+		ValueType::I32 =>
+			&[I32Add, I32Sub, I32Mul, I32DivU, I32RemS, I32And, I32Xor, I32Shl, I32Rotl],
+		ValueType::I64 =>
+			&[I64Add, I64Sub, I64Mul, I64DivS, I64RemU, I64Or, I64Xor, I64ShrS, I64Rotr],
+		ValueType::F32 => &[F32Add, F32Sub, F32Mul, F32Div, F32Min, F32Max, F32Copysign],
+		ValueType::F64 => &[F64Add, F64Sub, F64Mul, F64Div, F64Min, F64Max, F64Copysign],
+		#[cfg(feature = "simd")]
+		ValueType::V128 => unreachable!("only MVP value types are generated"),
+	};
+	Ok(u.choose(ops)?.clone())
+}
+
+fn comparison(value_type: ValueType) -> Instruction {
+	match value_type {
+		ValueType::I32 => Instruction::I32LtS,
+		ValueType::I64 => Instruction::I64GeU,
+		ValueType::F32 => Instruction::F32Eq,
+		ValueType::F64 => Instruction::F64Gt,
+		#[cfg(feature = "simd")]
+		ValueType::V128 => unreachable!("only MVP value types are generated"),
+	}
+}
+
+fn load(u: &mut Unstructured, value_type: ValueType) -> Result<Instruction> {
+	let offset = u.int_in_range(0..=64)?;
+	Ok(match value_type {
+		ValueType::I32 => Instruction::I32Load(u.int_in_range(0..=2)?, offset),
+		ValueType::I64 => Instruction::I64Load32U(u.int_in_range(0..=2)?, offset),
+		ValueType::F32 => Instruction::F32Load(u.int_in_range(0..=2)?, offset),
+		ValueType::F64 => Instruction::F64Load(u.int_in_range(0..=3)?, offset),
+		#[cfg(feature = "simd")]
+		ValueType::V128 => unreachable!("only MVP value types are generated"),
+	})
+}
+
+fn store(u: &mut Unstructured, value_type: ValueType) -> Result<Instruction> {
+	let offset = u.int_in_range(0..=64)?;
+	Ok(match value_type {
+		ValueType::I32 => Instruction::I32Store8(0, offset),
+		ValueType::I64 => Instruction::I64Store(u.int_in_range(0..=3)?, offset),
+		ValueType::F32 => Instruction::F32Store(u.int_in_range(0..=2)?, offset),
+		ValueType::F64 => Instruction::F64Store(u.int_in_range(0..=3)?, offset),
+		#[cfg(feature = "simd")]
+		ValueType::V128 => unreachable!("only MVP value types are generated"),
+	})
+}
+
+/// Index spaces visible to generated code.
+struct Context {
+	types: Vec<FunctionType>,
+	/// Type of every function in the function index space.
+	functions: Vec<u32>,
+	globals: Vec<GlobalType>,
+	memory: bool,
+	table: bool,
+}
+
+/// Generator of a single well-typed function body.
+struct Body<'c> {
+	context: &'c Context,
+	locals: Vec<ValueType>,
+	/// Result type of every enclosing block, innermost last.
+	labels: Vec<Option<ValueType>>,
+	code: Vec<Instruction>,
+}
+
+impl<'c> Body<'c> {
+	/// Push the arguments of `type_ref` and the given call instruction.
+	fn call(
+		&mut self,
+		u: &mut Unstructured,
+		type_ref: u32,
+		call: Instruction,
+		depth: usize,
+	) -> Result<()> {
+		let params = self.context.types[type_ref as usize].params().to_vec();
+		for param in params {
+			self.expr(u, param, depth + 1)?;
+		}
+		if let Instruction::CallIndirect(..) = call {
+			let len = self.context.functions.len() as u32;
+			self.code.push(Instruction::I32Const(u.int_in_range(0..=len)? as i32));
+		}
+		self.code.push(call);
+		Ok(())
+	}
+
+	/// Emit code leaving a single value of `value_type` on the stack.
+	fn expr(&mut self, u: &mut Unstructured, value_type: ValueType, depth: usize) -> Result<()> {
+		if depth >= MAX_DEPTH || u.is_empty() {
+			self.code.push(constant(u, value_type)?);
+			return Ok(())
+		}
+		match u.int_in_range(0..=8)? {
+			1 => {
+				let locals = self.indices_of(&self.locals, value_type);
+				if !locals.is_empty() {
+					self.code.push(Instruction::GetLocal(*u.choose(&locals)?));
+					return Ok(())
+				}
+			},
+			2 => {
+				let types: Vec<_> = self.context.globals.iter().map(|g| g.content_type()).collect();
+				let globals = self.indices_of(&types, value_type);
+				if !globals.is_empty() {
+					self.code.push(Instruction::GetGlobal(*u.choose(&globals)?));
+					return Ok(())
+				}
+			},
+			3 => {
+				self.expr(u, value_type, depth + 1)?;
+				self.expr(u, value_type, depth + 1)?;
+				self.code.push(binary(u, value_type)?);
+				return Ok(())
+			},
+			4 => {
+				let callees: Vec<u32> = (0..self.context.functions.len() as u32)
+					.filter(|&f| {
+						let type_ref = self.context.functions[f as usize] as usize;
+						self.context.types[type_ref].results() == [value_type]
+					})
+					.collect();
+				if !callees.is_empty() {
+					let callee = *u.choose(&callees)?;
+					let type_ref = self.context.functions[callee as usize];
+					return self.call(u, type_ref, Instruction::Call(callee), depth)
+				}
+			},
+			5 => {
+				self.code.push(Instruction::Block(BlockType::Value(value_type)));
+				self.labels.push(Some(value_type));
+				self.statements(u, depth + 1)?;
+				self.expr(u, value_type, depth + 1)?;
+				self.labels.pop();
+				self.code.push(Instruction::End);
+				return Ok(())
+			},
+			6 => {
+				self.expr(u, ValueType::I32, depth + 1)?;
+				self.code.push(Instruction::If(BlockType::Value(value_type)));
+				self.labels.push(Some(value_type));
+				self.expr(u, value_type, depth + 1)?;
+				self.code.push(Instruction::Else);
+				self.expr(u, value_type, depth + 1)?;
+				self.labels.pop();
+				self.code.push(Instruction::End);
+				return Ok(())
+			},
+			7 if self.context.memory => {
+				self.expr(u, ValueType::I32, depth + 1)?;
+				self.code.push(load(u, value_type)?);
+				return Ok(())
+			},
+			8 if value_type == ValueType::I32 => {
+				let operand = any_value_type(u)?;
+				self.expr(u, operand, depth + 1)?;
+				self.expr(u, operand, depth + 1)?;
+				self.code.push(comparison(operand));
+				return Ok(())
+			},
+			_ => {},
+		}
+		self.code.push(constant(u, value_type)?);
+		Ok(())
+	}
+
+	/// Emit a sequence of instructions leaving the stack as it was.
+	fn statements(&mut self, u: &mut Unstructured, depth: usize) -> Result<()> {
+		if depth >= MAX_DEPTH {
+			return Ok(())
+		}
+		for _ in 0..u.int_in_range(0..=3)? {
+			self.statement(u, depth)?;
+		}
+		Ok(())
+	}
+
+	fn statement(&mut self, u: &mut Unstructured, depth: usize) -> Result<()> {
+		match u.int_in_range(0..=8)? {
+			1 if !self.locals.is_empty() => {
+				let local = u.int_in_range(0..=self.locals.len() - 1)?;
+				self.expr(u, self.locals[local], depth + 1)?;
+				self.code.push(Instruction::SetLocal(local as u32));
+			},
+			2 => {
+				let mutable: Vec<u32> = (0..self.context.globals.len() as u32)
+					.filter(|&g| self.context.globals[g as usize].is_mutable())
+					.collect();
+				if !mutable.is_empty() {
+					let global = *u.choose(&mutable)?;
+					self.expr(u, self.context.globals[global as usize].content_type(), depth + 1)?;
+					self.code.push(Instruction::SetGlobal(global));
+				}
+			},
+			3 => {
+				let value_type = any_value_type(u)?;
+				self.expr(u, value_type, depth + 1)?;
+				self.code.push(Instruction::Drop);
+			},
+			4 if self.context.memory => {
+				let value_type = any_value_type(u)?;
+				self.expr(u, ValueType::I32, depth + 1)?;
+				self.expr(u, value_type, depth + 1)?;
+				self.code.push(store(u, value_type)?);
+			},
+			5 => {
+				let block = if u.arbitrary()? { Instruction::Loop } else { Instruction::Block };
+				self.code.push(block(BlockType::NoResult));
+				self.labels.push(None);
+				self.statements(u, depth + 1)?;
+				self.labels.pop();
+				self.code.push(Instruction::End);
+			},
+			6 => {
+				let targets: Vec<u32> = self
+					.labels
+					.iter()
+					.rev()
+					.enumerate()
+					.filter(|(_, label)| label.is_none())
+					.map(|(depth, _)| depth as u32)
+					.collect();
+				if !targets.is_empty() {
+					let target = *u.choose(&targets)?;
+					self.expr(u, ValueType::I32, depth + 1)?;
+					self.code.push(Instruction::BrIf(target));
+				}
+			},
+			7 => {
+				let callee = u.int_in_range(0..=self.context.functions.len() - 1)?;
+				let type_ref = self.context.functions[callee];
+				self.call(u, type_ref, Instruction::Call(callee as u32), depth)?;
+				self.drop_results(type_ref);
+			},
+			8 if self.context.table => {
+				let type_ref = u.int_in_range(0..=self.context.types.len() - 1)? as u32;
+				self.call(u, type_ref, Instruction::CallIndirect(type_ref, 0), depth)?;
+				self.drop_results(type_ref);
+			},
+			_ => self.code.push(Instruction::Nop),
+		}
+		Ok(())
+	}
+
+	fn drop_results(&mut self, type_ref: u32) {
+		for _ in self.context.types[type_ref as usize].results() {
+			self.code.push(Instruction::Drop);
+		}
+	}
+
+	fn indices_of(&self, types: &[ValueType], value_type: ValueType) -> Vec<u32> {
+		(0..types.len() as u32).filter(|&i| types[i as usize] == value_type).collect()
+	}
+}
+
+fn init_expr(instruction: Instruction) -> InitExpr {
+	InitExpr::new(vec![instruction, Instruction::End])
+}
+
+/// Generate a module that passes validation, using only MVP features.
+///
+/// Function bodies are built from well-typed expressions over the module's locals, globals,
+/// functions, memory and table, so they exercise transforms beyond the module structure.
+/// Generated code may still trap when executed, e.g. on division by zero or out of bounds
+/// memory accesses.
+pub fn arbitrary_valid_module(u: &mut Unstructured) -> Result<Module> {
+	let mut types = Vec::new();
+	for _ in 0..u.int_in_range(1..=4)? {
+		let params =
+			(0..u.int_in_range(0..=3)?).map(|_| any_value_type(u)).collect::<Result<_>>()?;
+		let results = if u.arbitrary()? { vec![any_value_type(u)?] } else { Vec::new() };
+		types.push(FunctionType::new(params, results));
+	}
+	let type_count = types.len() as u32;
+
+	let mut imports = Vec::new();
+	let mut functions = Vec::new();
+	let mut globals = Vec::new();
+	for index in 0..u.int_in_range(0..=2)? {
+		let type_ref = u.int_in_range(0..=type_count - 1)?;
+		let external = External::Function(type_ref);
+		imports.push(ImportEntry::new("env".into(), format!("f{}", index), external));
+		functions.push(type_ref);
+	}
+	if u.arbitrary()? {
+		let global_type = GlobalType::new(any_value_type(u)?, false);
+		imports.push(ImportEntry::new("env".into(), "g0".into(), External::Global(global_type)));
+		globals.push(global_type);
+	}
+	let imported_functions = functions.len() as u32;
+
+	let mut declared = Vec::new();
+	for _ in 0..u.int_in_range(1..=4)? {
+		let type_ref = u.int_in_range(0..=type_count - 1)?;
+		declared.push(Func::new(type_ref));
+		functions.push(type_ref);
+	}
+
+	let mut global_entries = Vec::new();
+	for _ in 0..u.int_in_range(0..=3)? {
+		let global_type = GlobalType::new(any_value_type(u)?, u.arbitrary()?);
+		global_entries.push(GlobalEntry::new(
+			global_type,
+			init_expr(constant(u, global_type.content_type())?),
+		));
+		globals.push(global_type);
+	}
+
+	let memory = if u.arbitrary()? {
+		let min = u.int_in_range(0..=2)?;
+		Some(MemoryType::new(min, Some(min + u.int_in_range(0..=2)?)))
+	} else {
+		None
+	};
+
+	let context = Context {
+		types: types.clone(),
+		functions: functions.clone(),
+		globals,
+		memory: memory.is_some(),
+		table: u.arbitrary()?,
+	};
+
+	let mut bodies = Vec::new();
+	for func in &declared {
+		let func_type = &types[func.type_ref() as usize];
+		let locals = (0..u.int_in_range(0..=2)?)
+			.map(|_| Ok(Local::new(u.int_in_range(1..=2)?, any_value_type(u)?)))
+			.collect::<Result<Vec<_>>>()?;
+		let mut local_types = func_type.params().to_vec();
+		for local in &locals {
+			local_types.extend((0..local.count()).map(|_| local.value_type()));
+		}
+		let result = func_type.results().first().copied();
+		let mut body =
+			Body { context: &context, locals: local_types, labels: vec![result], code: Vec::new() };
+		body.statements(u, 0)?;
+		if let Some(result) = result {
+			body.expr(u, result, 0)?;
+		}
+		body.code.push(Instruction::End);
+		bodies.push(FuncBody::new(locals, Instructions::new(body.code)));
+	}
+
+	let mut exports: Vec<_> = (0..declared.len() as u32)
+		.map(|index| {
+			let function = imported_functions + index;
+			ExportEntry::new(format!("f{}", function), Internal::Function(function))
+		})
+		.collect();
+	if memory.is_some() {
+		exports.push(ExportEntry::new("memory".into(), Internal::Memory(0)));
+	}
+
+	let mut sections = vec![Section::Type(TypeSection::with_types(
+		types.into_iter().map(Type::Function).collect(),
+	))];
+	if !imports.is_empty() {
+		sections.push(Section::Import(ImportSection::with_entries(imports)));
+	}
+	sections.push(Section::Function(FunctionSection::with_entries(declared)));
+	if context.table {
+		let len = functions.len() as u32;
+		sections
+			.push(Section::Table(TableSection::with_entries(vec![TableType::new(len, Some(len))])));
+	}
+	if let Some(memory) = memory {
+		sections.push(Section::Memory(MemorySection::with_entries(vec![memory])));
+	}
+	if !global_entries.is_empty() {
+		sections.push(Section::Global(GlobalSection::with_entries(global_entries)));
+	}
+	sections.push(Section::Export(ExportSection::with_entries(exports)));
+	let start = (imported_functions..functions.len() as u32).find(|&f| {
+		let func_type = &context.types[functions[f as usize] as usize];
+		func_type.params().is_empty() && func_type.results().is_empty()
+	});
+	if let Some(start) = start {
+		if u.arbitrary()? {
+			sections.push(Section::Start(start));
+		}
+	}
+	if context.table {
+		let members = (0..functions.len() as u32).collect();
+		let segment = ElementSegment::new(0, Some(init_expr(Instruction::I32Const(0))), members);
+		sections.push(Section::Element(ElementSection::with_entries(vec![segment])));
+	}
+	sections.push(Section::Code(CodeSection::with_bodies(bodies)));
+	if let Some(memory) = memory {
+		if memory.limits().initial() > 0 {
+			let offset = init_expr(Instruction::I32Const(u.int_in_range(0..=64)?));
+			let len = u.int_in_range(0..=16)?;
+			let value = u.bytes(len)?.to_vec();
+			sections.push(Section::Data(DataSection::with_entries(vec![DataSegment::new(
+				0,
+				Some(offset),
+				value,
+			)])));
+		}
+	}
+
+	Ok(Module::new(sections))
+}
+
+/// Replace an index in `module` with one at or past the end of its index space.
+fn break_index(u: &mut Unstructured, module: &mut Module) -> Result<()> {
+	let functions = module.functions_space() as u32;
+	let edge = *u.choose(&[functions, functions + 1, u32::MAX])?;
+	let section_count = module.sections().len();
+	let section = &mut module.sections_mut()[u.int_in_range(0..=section_count - 1)?];
+	match section {
+		Section::Start(index) => *index = edge,
+		Section::Export(section) =>
+			for export in section.entries_mut() {
+				if let Internal::Function(index) = export.internal_mut() {
+					*index = edge;
+				}
+			},
+		Section::Function(section) =>
+			if let Some(func) = section.entries_mut().first_mut() {
+				*func.type_ref_mut() = edge;
+			},
+		Section::Element(section) =>
+			for segment in section.entries_mut() {
+				segment.members_mut().push(edge);
+			},
+		Section::Code(section) =>
+			for body in section.bodies_mut() {
+				for instruction in body.code_mut().elements_mut() {
+					if let Instruction::Call(index) = instruction {
+						*index = edge;
+					}
+				}
+			},
+		_ => {},
+	}
+	Ok(())
+}
+
+/// Modules built from valid ones with structural damage.
+///
+/// Sections may be reordered, duplicated, removed or interleaved with custom sections, and
+/// indices may point right past or far beyond their index space, so that the result covers
+/// the cases code handling untrusted modules has to reject. Use [`arbitrary_valid_module`]
+/// where a valid module is needed.
+impl<'a> Arbitrary<'a> for Module {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let mut module = arbitrary_valid_module(u)?;
+		for _ in 0..u.int_in_range(0..=3)? {
+			let len = module.sections().len();
+			let sections = module.sections_mut();
+			match u.int_in_range(0..=4)? {
+				0 => sections.swap(u.int_in_range(0..=len - 1)?, u.int_in_range(0..=len - 1)?),
+				1 if len > 1 => {
+					sections.remove(u.int_in_range(0..=len - 1)?);
+				},
+				2 => {
+					let section = sections[u.int_in_range(0..=len - 1)?].clone();
+					sections.insert(u.int_in_range(0..=len)?, section);
+				},
+				3 => {
+					let custom = CustomSection::new(u.arbitrary()?, u.arbitrary()?);
+					sections.insert(u.int_in_range(0..=len)?, Section::Custom(custom));
+				},
+				_ => break_index(u, &mut module)?,
+			}
+		}
+		Ok(module)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::arbitrary_valid_module;
+	use crate::elements::{deserialize_buffer, serialize, Module, ValidatorConfig};
+	use ::arbitrary::{Arbitrary, Unstructured};
+
+	/// Deterministic input bytes for the generators.
+	fn input(seed: u64) -> Vec<u8> {
+		let mut state = seed;
+		(0..4096)
+			.map(|_| {
+				state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+				(state >> 56) as u8
+			})
+			.collect()
+	}
+
+	#[test]
+	fn valid_modules_roundtrip() {
+		for seed in 0..64 {
+			let bytes = input(seed);
+			let module = arbitrary_valid_module(&mut Unstructured::new(&bytes))
+				.expect("module to be generated");
+			ValidatorConfig::default()
+				.validate(&module)
+				.expect("module within the default limits");
+			let serialized = serialize(module.clone()).expect("module to serialize");
+			assert_eq!(
+				deserialize_buffer::<Module>(&serialized).expect("module to deserialize"),
+				module
+			);
+		}
+	}
+
+	#[test]
+	fn arbitrary_modules_serialize() {
+		for seed in 0..64 {
+			let bytes = input(seed);
+			let module =
+				Module::arbitrary(&mut Unstructured::new(&bytes)).expect("module to be generated");
+			serialize(module).expect("module to serialize");
+		}
+	}
+}
@@ -0,0 +1,356 @@
+use crate::io;
+use alloc::vec::Vec;
+
+use super::{
+	module::WASM_MAGIC_NUMBER, Deserialize, Error, External, InitExpr, Internal, Module, Section,
+	Uint32, VarUint32, VarUint7,
+};
+
+/// Reader over a byte slice which can lend out sub-slices of the input.
+struct SliceReader<'a> {
+	bytes: &'a [u8],
+	position: usize,
+}
+
+impl<'a> SliceReader<'a> {
+	fn new(bytes: &'a [u8]) -> Self {
+		SliceReader { bytes, position: 0 }
+	}
+
+	fn is_empty(&self) -> bool {
+		self.position == self.bytes.len()
+	}
+
+	fn take(&mut self, length: usize) -> Result<&'a [u8], Error> {
+		if self.bytes.len() - self.position < length {
+			return Err(Error::UnexpectedEof)
+		}
+		let slice = &self.bytes[self.position..self.position + length];
+		self.position += length;
+		Ok(slice)
+	}
+
+	fn take_counted(&mut self) -> Result<&'a [u8], Error> {
+		let length = u32::from(VarUint32::deserialize(self)?) as usize;
+		self.take(length)
+	}
+
+	fn take_str(&mut self) -> Result<&'a str, Error> {
+		core::str::from_utf8(self.take_counted()?).map_err(|_| Error::NonUtf8String)
+	}
+
+	fn finish(&self) -> Result<(), Error> {
+		if self.is_empty() {
+			Ok(())
+		} else {
+			Err(io::Error::TrailingData.into())
+		}
+	}
+}
+
+impl io::Read for SliceReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<()> {
+		if self.bytes.len() - self.position < buf.len() {
+			return Err(io::Error::UnexpectedEof)
+		}
+		buf.copy_from_slice(&self.bytes[self.position..self.position + buf.len()]);
+		self.position += buf.len();
+		Ok(())
+	}
+}
+
+/// Borrowed view over an encoded module.
+///
+/// Unlike [`Module`], which owns every decoded item, `ModuleRef` only splits the buffer into
+/// sections and hands out names, custom section payloads and data segment contents as slices
+/// of the original bytes. Everything else is decoded into the owned model on demand.
+#[derive(Debug, Clone)]
+pub struct ModuleRef<'a> {
+	bytes: &'a [u8],
+	version: u32,
+	sections: Vec<SectionRef<'a>>,
+}
+
+impl<'a> ModuleRef<'a> {
+	/// Split `bytes` into sections without decoding their contents.
+	///
+	/// Only the header and section framing are checked; section contents and ordering are
+	/// validated by [`to_module`](ModuleRef::to_module) and the accessors.
+	pub fn parse(bytes: &'a [u8]) -> Result<Self, Error> {
+		let mut reader = SliceReader::new(bytes);
+		if reader.take(4).map_err(|_| Error::InvalidMagic)? != WASM_MAGIC_NUMBER {
+			return Err(Error::InvalidMagic)
+		}
+		let version: u32 = Uint32::deserialize(&mut reader)?.into();
+		if version != 1 {
+			return Err(Error::UnsupportedVersion(version))
+		}
+
+		let mut sections = Vec::new();
+		while !reader.is_empty() {
+			let start = reader.position;
+			let id: u8 = VarUint7::deserialize(&mut reader)?.into();
+			let payload = reader.take_counted()?;
+			sections.push(SectionRef { id, raw: &bytes[start..reader.position], payload });
+		}
+
+		Ok(ModuleRef { bytes, version, sections })
+	}
+
+	/// Version of the module.
+	pub fn version(&self) -> u32 {
+		self.version
+	}
+
+	/// Sections in the order they appear in the buffer.
+	pub fn sections(&self) -> &[SectionRef<'a>] {
+		&self.sections
+	}
+
+	/// Custom sections with names and payloads borrowed from the buffer.
+	pub fn custom_sections(&self) -> Result<Vec<CustomSectionRef<'a>>, Error> {
+		self.sections.iter().filter_map(SectionRef::custom).collect()
+	}
+
+	/// Import entries with module and field names borrowed from the buffer.
+	pub fn imports(&self) -> Result<Vec<ImportRef<'a>>, Error> {
+		self.read_entries(0x02, |reader| {
+			let module = reader.take_str()?;
+			let field = reader.take_str()?;
+			let external = External::deserialize(reader)?;
+			Ok(ImportRef { module, field, external })
+		})
+	}
+
+	/// Export entries with field names borrowed from the buffer.
+	pub fn exports(&self) -> Result<Vec<ExportRef<'a>>, Error> {
+		self.read_entries(0x07, |reader| {
+			let field = reader.take_str()?;
+			let internal = Internal::deserialize(reader)?;
+			Ok(ExportRef { field, internal })
+		})
+	}
+
+	/// Data segments with contents borrowed from the buffer.
+	pub fn data_segments(&self) -> Result<Vec<DataSegmentRef<'a>>, Error> {
+		self.read_entries(0x0b, read_data_segment)
+	}
+
+	/// Decode the whole module into the owned model.
+	pub fn to_module(&self) -> Result<Module, Error> {
+		Module::from_bytes(self.bytes)
+	}
+
+	fn read_entries<T, F>(&self, id: u8, mut read: F) -> Result<Vec<T>, Error>
+	where
+		F: FnMut(&mut SliceReader<'a>) -> Result<T, Error>,
+	{
+		let section = match self.sections.iter().find(|section| section.id == id) {
+			Some(section) => section,
+			None => return Ok(Vec::new()),
+		};
+		let mut reader = SliceReader::new(section.payload);
+		let count: u32 = VarUint32::deserialize(&mut reader)?.into();
+		let entries = (0..count).map(|_| read(&mut reader)).collect::<Result<Vec<_>, _>>()?;
+		reader.finish()?;
+		Ok(entries)
+	}
+}
+
+#[cfg(not(feature = "bulk"))]
+fn read_data_segment<'a>(reader: &mut SliceReader<'a>) -> Result<DataSegmentRef<'a>, Error> {
+	let index = VarUint32::deserialize(reader)?.into();
+	let offset = InitExpr::deserialize(reader)?;
+	Ok(DataSegmentRef { index, offset: Some(offset), value: reader.take_counted()? })
+}
+
+#[cfg(feature = "bulk")]
+fn read_data_segment<'a>(reader: &mut SliceReader<'a>) -> Result<DataSegmentRef<'a>, Error> {
+	// Same flags as in `DataSegment`: active in memory 0, passive, active with explicit index.
+	let flags: u32 = VarUint32::deserialize(reader)?.into();
+	let index = match flags {
+		0 | 1 => 0,
+		2 => VarUint32::deserialize(reader)?.into(),
+		_ => return Err(Error::InvalidSegmentFlags(flags)),
+	};
+	let offset = if flags == 1 { None } else { Some(InitExpr::deserialize(reader)?) };
+	Ok(DataSegmentRef { index, offset, value: reader.take_counted()? })
+}
+
+/// Single section of a [`ModuleRef`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SectionRef<'a> {
+	id: u8,
+	raw: &'a [u8],
+	payload: &'a [u8],
+}
+
+impl<'a> SectionRef<'a> {
+	/// Section id.
+	pub fn id(&self) -> u8 {
+		self.id
+	}
+
+	/// Section contents, without the id and size.
+	pub fn payload(&self) -> &'a [u8] {
+		self.payload
+	}
+
+	/// Name and payload of a custom section, `None` for other sections.
+	pub fn custom(&self) -> Option<Result<CustomSectionRef<'a>, Error>> {
+		if self.id != 0 {
+			return None
+		}
+		let mut reader = SliceReader::new(self.payload);
+		Some(
+			reader
+				.take_str()
+				.map(|name| CustomSectionRef { name, payload: &self.payload[reader.position..] }),
+		)
+	}
+
+	/// Decode the section into the owned model.
+	pub fn to_section(&self) -> Result<Section, Error> {
+		super::deserialize_buffer(self.raw)
+	}
+}
+
+/// Custom section borrowed from the module buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomSectionRef<'a> {
+	name: &'a str,
+	payload: &'a [u8],
+}
+
+impl<'a> CustomSectionRef<'a> {
+	/// Name of the custom section.
+	pub fn name(&self) -> &'a str {
+		self.name
+	}
+
+	/// Payload of the custom section.
+	pub fn payload(&self) -> &'a [u8] {
+		self.payload
+	}
+}
+
+/// Import entry borrowed from the module buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImportRef<'a> {
+	module: &'a str,
+	field: &'a str,
+	external: External,
+}
+
+impl<'a> ImportRef<'a> {
+	/// Module reference of the import entry.
+	pub fn module(&self) -> &'a str {
+		self.module
+	}
+
+	/// Field reference of the import entry.
+	pub fn field(&self) -> &'a str {
+		self.field
+	}
+
+	/// Local binding of the import entry.
+	pub fn external(&self) -> &External {
+		&self.external
+	}
+}
+
+/// Export entry borrowed from the module buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportRef<'a> {
+	field: &'a str,
+	internal: Internal,
+}
+
+impl<'a> ExportRef<'a> {
+	/// Public name of the export.
+	pub fn field(&self) -> &'a str {
+		self.field
+	}
+
+	/// Internal reference of the export entry.
+	pub fn internal(&self) -> &Internal {
+		&self.internal
+	}
+}
+
+/// Data segment with contents borrowed from the module buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataSegmentRef<'a> {
+	index: u32,
+	offset: Option<InitExpr>,
+	value: &'a [u8],
+}
+
+impl<'a> DataSegmentRef<'a> {
+	/// Linear memory index.
+	pub fn index(&self) -> u32 {
+		self.index
+	}
+
+	/// An i32 initializer expression that computes the offset at which to place the data.
+	///
+	/// `None` for passive segments.
+	pub fn offset(&self) -> &Option<InitExpr> {
+		&self.offset
+	}
+
+	/// Initial value of the data segment.
+	pub fn value(&self) -> &'a [u8] {
+		self.value
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ModuleRef;
+	use crate::{builder, elements};
+
+	#[test]
+	fn borrowed_view() {
+		let mut module = builder::module()
+			.memory()
+			.build()
+			.data()
+			.offset(elements::Instruction::I32Const(16))
+			.value(vec![1, 2, 3])
+			.build()
+			.export()
+			.field("memory")
+			.internal()
+			.memory(0)
+			.build()
+			.build();
+		module.set_custom_section("meta", vec![4, 5]);
+		let bytes = module.clone().into_bytes().expect("failed to serialize");
+
+		let view = ModuleRef::parse(&bytes).expect("failed to split sections");
+		let data = view.data_segments().expect("data section to decode");
+		assert_eq!(data[0].value(), &[1, 2, 3]);
+		assert!(bytes.as_ptr_range().contains(&data[0].value().as_ptr()));
+
+		let exports = view.exports().expect("export section to decode");
+		assert_eq!(exports[0].field(), "memory");
+		assert!(view.imports().expect("missing import section is empty").is_empty());
+
+		let custom = view.custom_sections().expect("custom section to decode");
+		assert_eq!((custom[0].name(), custom[0].payload()), ("meta", &[4u8, 5][..]));
+
+		assert_eq!(
+			view.sections()[0].to_section().expect("section to decode"),
+			module.sections()[0]
+		);
+		assert_eq!(view.to_module().expect("module to decode"), module);
+	}
+
+	#[test]
+	fn truncated() {
+		let bytes = builder::module().memory().build().build().into_bytes().unwrap();
+		assert!(ModuleRef::parse(&bytes[..bytes.len() - 1]).is_err());
+		assert!(ModuleRef::parse(&bytes[..2]).is_err());
+	}
+}
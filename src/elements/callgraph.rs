@@ -0,0 +1,226 @@
+use alloc::vec::Vec;
+
+use super::{
+	Error, External, Func, FunctionType, ImportCountType, Instruction, Internal, Module, Type,
+};
+
+/// Call graph of a module.
+///
+/// Besides direct calls, every `call_indirect` is treated as a possible call of each function
+/// placed in the table it goes through, via active segments of that table or any passive
+/// segment, whose signature matches the expected one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallGraph {
+	direct: Vec<Vec<u32>>,
+	indirect: Vec<Vec<u32>>,
+	roots: Vec<u32>,
+}
+
+fn sorted(mut callees: Vec<u32>) -> Vec<u32> {
+	callees.sort_unstable();
+	callees.dedup();
+	callees
+}
+
+impl CallGraph {
+	/// Build the call graph of `module`.
+	///
+	/// Lazily decoded code sections have to be parsed first, otherwise
+	/// [`Error::UnparsedCode`] is returned.
+	pub fn build(module: &Module) -> Result<Self, Error> {
+		if module.lazy_code_section().is_some() {
+			return Err(Error::UnparsedCode)
+		}
+
+		let types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+		let signature = |type_ref: u32| -> Result<&FunctionType, Error> {
+			match types.get(type_ref as usize) {
+				Some(Type::Function(func_type)) => Ok(func_type),
+				None => Err(Error::InconsistentMetadata),
+			}
+		};
+		let signatures = module
+			.imports()
+			.filter_map(|external| match *external {
+				External::Function(type_ref) => Some(type_ref),
+				_ => None,
+			})
+			.chain(
+				module
+					.function_section()
+					.into_iter()
+					.flat_map(|s| s.entries())
+					.map(Func::type_ref),
+			)
+			.map(signature)
+			.collect::<Result<Vec<_>, _>>()?;
+
+		// Functions that may end up in the given table.
+		let segments = module.elements_section().map(|s| s.entries()).unwrap_or(&[]);
+		let table_members = |table: u32| {
+			segments
+				.iter()
+				.filter(move |segment| segment.offset().is_none() || segment.index() == table)
+				.flat_map(|segment| segment.members().iter().copied())
+		};
+
+		let imported_functions = module.import_count(ImportCountType::Function);
+		let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+		let mut direct = vec![Vec::new(); signatures.len()];
+		let mut indirect = vec![Vec::new(); signatures.len()];
+		for (index, body) in bodies.iter().enumerate() {
+			let caller = imported_functions + index;
+			if caller >= signatures.len() {
+				return Err(Error::InconsistentCode)
+			}
+			for instruction in body.code().elements() {
+				match *instruction {
+					Instruction::Call(callee) => direct[caller].push(callee),
+					Instruction::CallIndirect(type_ref, table) => {
+						let expected = signature(type_ref)?;
+						indirect[caller].extend(table_members(table as u32).filter(|&callee| {
+							signatures.get(callee as usize).map_or(false, |sig| *sig == expected)
+						}));
+					},
+					_ => {},
+				}
+			}
+		}
+
+		// Exported functions, the start function and, if the host can reach the tables,
+		// everything placed in them.
+		let exports = module.export_section().map(|s| s.entries()).unwrap_or(&[]);
+		let mut roots: Vec<u32> = exports
+			.iter()
+			.filter_map(|export| match *export.internal() {
+				Internal::Function(index) => Some(index),
+				_ => None,
+			})
+			.chain(module.start_section())
+			.collect();
+		let tables_shared =
+			exports.iter().any(|export| matches!(export.internal(), Internal::Table(_))) ||
+				module.imports().any(|external| matches!(external, External::Table(_)));
+		if tables_shared {
+			roots.extend(segments.iter().flat_map(|segment| segment.members().iter().copied()));
+		}
+
+		Ok(CallGraph {
+			direct: direct.into_iter().map(sorted).collect(),
+			indirect: indirect.into_iter().map(sorted).collect(),
+			roots: sorted(roots),
+		})
+	}
+
+	/// Number of functions in the graph, imported ones included.
+	pub fn len(&self) -> usize {
+		self.direct.len()
+	}
+
+	/// Whether the module has no functions.
+	pub fn is_empty(&self) -> bool {
+		self.direct.is_empty()
+	}
+
+	/// Functions called directly by `function`, sorted and without duplicates.
+	pub fn direct_callees(&self, function: u32) -> &[u32] {
+		self.direct.get(function as usize).map(|callees| &callees[..]).unwrap_or(&[])
+	}
+
+	/// Functions `function` may call through a table, sorted and without duplicates.
+	pub fn indirect_callees(&self, function: u32) -> &[u32] {
+		self.indirect.get(function as usize).map(|callees| &callees[..]).unwrap_or(&[])
+	}
+
+	/// Functions that may call `function`, directly or through a table.
+	pub fn callers(&self, function: u32) -> Vec<u32> {
+		(0..self.len() as u32)
+			.filter(|&caller| {
+				self.direct_callees(caller).binary_search(&function).is_ok() ||
+					self.indirect_callees(caller).binary_search(&function).is_ok()
+			})
+			.collect()
+	}
+
+	/// Functions the embedder can call: exports, the start function and the members of
+	/// element segments if a table is imported or exported.
+	pub fn roots(&self) -> &[u32] {
+		&self.roots
+	}
+
+	/// Whether each function can be reached from `roots`, indexed by function index.
+	pub fn reachable_from(&self, roots: &[u32]) -> Vec<bool> {
+		let mut reachable = vec![false; self.len()];
+		let mut queue = roots.to_vec();
+		while let Some(function) = queue.pop() {
+			match reachable.get_mut(function as usize) {
+				Some(seen) if !*seen => *seen = true,
+				_ => continue,
+			}
+			queue.extend_from_slice(self.direct_callees(function));
+			queue.extend_from_slice(self.indirect_callees(function));
+		}
+		reachable
+	}
+
+	/// Whether each function can be reached from the [`roots`](CallGraph::roots).
+	pub fn reachable(&self) -> Vec<bool> {
+		self.reachable_from(&self.roots)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CallGraph;
+	use crate::{
+		builder,
+		elements::{Instruction::*, Instructions, ValueType},
+	};
+
+	fn function(code: Vec<crate::elements::Instruction>) -> builder::FunctionDefinition {
+		builder::function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(code))
+			.build()
+			.build()
+	}
+
+	#[test]
+	fn call_graph() {
+		let mut module = builder::module()
+			.table()
+			.with_min(2)
+			.with_element(0, vec![1, 3])
+			.build()
+			.export()
+			.field("main")
+			.internal()
+			.func(0)
+			.build();
+		module.push_function(function(vec![Call(2), I32Const(0), CallIndirect(0, 0), End]));
+		module.push_function(function(vec![End]));
+		module.push_function(function(vec![Call(2), End]));
+		module.push_function(
+			builder::function()
+				.signature()
+				.with_param(ValueType::I32)
+				.build()
+				.body()
+				.build()
+				.build(),
+		);
+		module.push_function(function(vec![Call(0), End]));
+		let graph = CallGraph::build(&module.build()).expect("graph to be built");
+
+		assert_eq!(graph.len(), 5);
+		assert_eq!(graph.direct_callees(0), &[2]);
+		// Function 3 is in the table, but has a different signature.
+		assert_eq!(graph.indirect_callees(0), &[1]);
+		assert_eq!(graph.callers(2), vec![0, 2]);
+		assert_eq!(graph.roots(), &[0]);
+		assert_eq!(graph.reachable(), vec![true, true, true, false, false]);
+		assert_eq!(graph.reachable_from(&[4]), vec![true, true, true, false, true]);
+	}
+}
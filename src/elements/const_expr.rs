@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+
+use super::{Error, ImportCountType, InitExpr, Instruction, Module, ValueType};
+
+/// Value of a constant expression.
+///
+/// Floats are kept as their bit patterns, like in `F32Const` and `F64Const`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstValue {
+	/// 32-bit integer.
+	I32(i32),
+	/// 64-bit integer.
+	I64(i64),
+	/// 32-bit float.
+	F32(u32),
+	/// 64-bit float.
+	F64(u64),
+}
+
+impl ConstValue {
+	/// Type of the value.
+	pub fn value_type(&self) -> ValueType {
+		match *self {
+			ConstValue::I32(_) => ValueType::I32,
+			ConstValue::I64(_) => ValueType::I64,
+			ConstValue::F32(_) => ValueType::F32,
+			ConstValue::F64(_) => ValueType::F64,
+		}
+	}
+}
+
+/// Evaluator of constant expressions, as used to initialize globals and as segment offsets.
+///
+/// Besides constants and `global.get`, the integer `add`, `sub` and `mul` instructions of the
+/// extended constant expressions proposal are supported.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConstExprEvaluator {
+	globals: Vec<ConstValue>,
+}
+
+impl ConstExprEvaluator {
+	/// Evaluator with the given values of the global index space.
+	pub fn new(globals: Vec<ConstValue>) -> Self {
+		ConstExprEvaluator { globals }
+	}
+
+	/// Evaluator for the constant expressions of `module`.
+	///
+	/// `imported` holds the values of the imported globals. The globals defined by the
+	/// module are initialized in order, each one seeing the globals before it.
+	pub fn for_module(module: &Module, imported: &[ConstValue]) -> Result<Self, Error> {
+		if imported.len() != module.import_count(ImportCountType::Global) {
+			return Err(Error::InvalidConstExpr)
+		}
+		let mut evaluator = ConstExprEvaluator::new(imported.to_vec());
+		for entry in module.global_section().map(|s| s.entries()).unwrap_or(&[]) {
+			let value = evaluator.eval(entry.init_expr())?;
+			if value.value_type() != entry.global_type().content_type() {
+				return Err(Error::InvalidConstExpr)
+			}
+			evaluator.globals.push(value);
+		}
+		Ok(evaluator)
+	}
+
+	/// Values of the global index space.
+	pub fn globals(&self) -> &[ConstValue] {
+		&self.globals
+	}
+
+	/// Evaluate `expr` to a single value.
+	pub fn eval(&self, expr: &InitExpr) -> Result<ConstValue, Error> {
+		let mut stack = Vec::new();
+		for instruction in expr.code() {
+			let value = match *instruction {
+				Instruction::I32Const(value) => ConstValue::I32(value),
+				Instruction::I64Const(value) => ConstValue::I64(value),
+				Instruction::F32Const(value) => ConstValue::F32(value),
+				Instruction::F64Const(value) => ConstValue::F64(value),
+				Instruction::GetGlobal(index) =>
+					*self.globals.get(index as usize).ok_or(Error::InvalidConstExpr)?,
+				Instruction::End => break,
+				ref instruction => {
+					let rhs = stack.pop().ok_or(Error::InvalidConstExpr)?;
+					let lhs = stack.pop().ok_or(Error::InvalidConstExpr)?;
+					binary(instruction, lhs, rhs)?
+				},
+			};
+			stack.push(value);
+		}
+		match stack[..] {
+			[value] => Ok(value),
+			_ => Err(Error::InvalidConstExpr),
+		}
+	}
+
+	/// Evaluate a segment offset, which has to be an `i32`.
+	pub fn eval_offset(&self, expr: &InitExpr) -> Result<u32, Error> {
+		match self.eval(expr)? {
+			ConstValue::I32(offset) => Ok(offset as u32),
+			_ => Err(Error::InvalidConstExpr),
+		}
+	}
+}
+
+fn binary(
+	instruction: &Instruction,
+	lhs: ConstValue,
+	rhs: ConstValue,
+) -> Result<ConstValue, Error> {
+	use ConstValue::*;
+	Ok(match (instruction, lhs, rhs) {
+		(Instruction::I32Add, I32(lhs), I32(rhs)) => I32(lhs.wrapping_add(rhs)),
+		(Instruction::I32Sub, I32(lhs), I32(rhs)) => I32(lhs.wrapping_sub(rhs)),
+		(Instruction::I32Mul, I32(lhs), I32(rhs)) => I32(lhs.wrapping_mul(rhs)),
+		(Instruction::I64Add, I64(lhs), I64(rhs)) => I64(lhs.wrapping_add(rhs)),
+		(Instruction::I64Sub, I64(lhs), I64(rhs)) => I64(lhs.wrapping_sub(rhs)),
+		(Instruction::I64Mul, I64(lhs), I64(rhs)) => I64(lhs.wrapping_mul(rhs)),
+		_ => return Err(Error::InvalidConstExpr),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ConstExprEvaluator, ConstValue};
+	use crate::{
+		builder,
+		elements::{Error, InitExpr, Instruction::*, ValueType},
+	};
+
+	#[test]
+	fn extended_const() {
+		let module = builder::module()
+			.import()
+			.module("env")
+			.field("base")
+			.external()
+			.global(ValueType::I32, false)
+			.build()
+			.global()
+			.init_expr(I32Const(16))
+			.build()
+			.build();
+		let evaluator = ConstExprEvaluator::for_module(&module, &[ConstValue::I32(1024)])
+			.expect("globals to be evaluated");
+		assert_eq!(evaluator.globals(), &[ConstValue::I32(1024), ConstValue::I32(16)]);
+
+		let offset =
+			InitExpr::new(vec![GetGlobal(0), GetGlobal(1), I32Const(2), I32Mul, I32Add, End]);
+		assert_eq!(evaluator.eval_offset(&offset).unwrap(), 1056);
+		let offset = InitExpr::new(vec![I32Const(0), I32Const(1), I32Sub, End]);
+		assert_eq!(evaluator.eval_offset(&offset).unwrap(), u32::MAX);
+
+		for code in [
+			vec![I32Const(0), I64Const(0), I32Add, End],
+			vec![I32Const(0), I32Const(0), End],
+			vec![GetGlobal(2), End],
+			vec![I32Const(1), I32Const(0), I32DivU, End],
+		] {
+			assert!(matches!(evaluator.eval(&InitExpr::new(code)), Err(Error::InvalidConstExpr)));
+		}
+		assert!(ConstExprEvaluator::for_module(&module, &[]).is_err());
+	}
+}
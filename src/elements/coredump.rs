@@ -0,0 +1,329 @@
+use crate::io;
+use alloc::{string::String, vec::Vec};
+
+use super::{
+	ConstExprEvaluator, ConstValue, CustomSection, DataSection, DataSegment, Deserialize, Error,
+	GlobalEntry, GlobalSection, GlobalType, InitExpr, Instruction, MemorySection, MemoryType,
+	Module, Section, Serialize, Uint32, Uint64, Uint8, VarInt32, VarInt64, VarUint32,
+};
+
+const PROCESS_INFO: u8 = 0x00;
+const THREAD_INFO: u8 = 0x00;
+const FRAME: u8 = 0x00;
+const VALUE_MISSING: u8 = 0x01;
+const VALUE_I32: u8 = 0x7f;
+const VALUE_I64: u8 = 0x7e;
+const VALUE_F32: u8 = 0x7d;
+const VALUE_F64: u8 = 0x7c;
+
+const PAGE_SIZE: usize = 65536;
+
+/// Post-mortem snapshot of a trapped instance in the WebAssembly core dump format.
+///
+/// A core dump is itself a module: the process and the stacks of its threads are stored in
+/// the `core` and `corestack` custom sections, while the memory and globals of the instance
+/// become the memory, data and global sections. Only dumps of a single instance are
+/// supported.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoreDump {
+	/// Name of the executable that trapped, usually the module file name.
+	pub executable: String,
+	/// Stacks of the threads at the time of the trap.
+	pub threads: Vec<CoreThread>,
+	/// Contents of the linear memory, if the instance has one.
+	pub memory: Option<Vec<u8>>,
+	/// Values of the globals, in global index space order.
+	pub globals: Vec<ConstValue>,
+}
+
+/// Call stack of a single thread.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoreThread {
+	/// Name of the thread.
+	pub name: String,
+	/// Frames of the stack, innermost first.
+	pub frames: Vec<CoreFrame>,
+}
+
+/// Single function activation on a thread's stack.
+///
+/// Values that were not recorded, e.g. because they were optimized out, are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoreFrame {
+	/// Index of the function in the function index space.
+	pub function: u32,
+	/// Offset of the executing instruction, relative to the start of the function body.
+	pub code_offset: u32,
+	/// Values of the locals, parameters included.
+	pub locals: Vec<Option<ConstValue>>,
+	/// Values on the operand stack, bottom first.
+	pub stack: Vec<Option<ConstValue>>,
+}
+
+impl CoreDump {
+	/// Encode the dump as a module.
+	pub fn into_module(self) -> Result<Module, Error> {
+		let mut sections = Vec::new();
+		let mut core = Vec::new();
+		Uint8::from(PROCESS_INFO).serialize(&mut core)?;
+		self.executable.serialize(&mut core)?;
+		sections.push(Section::Custom(CustomSection::new("core".into(), core)));
+		for thread in self.threads {
+			let mut stack = Vec::new();
+			thread.serialize(&mut stack)?;
+			sections.push(Section::Custom(CustomSection::new("corestack".into(), stack)));
+		}
+
+		if let Some(memory) = self.memory {
+			let pages = (memory.len() + PAGE_SIZE - 1) / PAGE_SIZE;
+			let memory_type = MemoryType::new(pages as u32, None);
+			sections.push(Section::Memory(MemorySection::with_entries(vec![memory_type])));
+			let offset = InitExpr::new(vec![Instruction::I32Const(0), Instruction::End]);
+			let segment = DataSegment::new(0, Some(offset), memory);
+			sections.push(Section::Data(DataSection::with_entries(vec![segment])));
+		}
+
+		let globals: Vec<_> = self
+			.globals
+			.into_iter()
+			.map(|value| {
+				let init = match value {
+					ConstValue::I32(value) => Instruction::I32Const(value),
+					ConstValue::I64(value) => Instruction::I64Const(value),
+					ConstValue::F32(value) => Instruction::F32Const(value),
+					ConstValue::F64(value) => Instruction::F64Const(value),
+				};
+				let global_type = GlobalType::new(value.value_type(), true);
+				GlobalEntry::new(global_type, InitExpr::new(vec![init, Instruction::End]))
+			})
+			.collect();
+		if !globals.is_empty() {
+			sections.push(Section::Global(GlobalSection::with_entries(globals)));
+		}
+
+		let mut module = Module::new(Vec::new());
+		for section in sections {
+			module.insert_section(section)?;
+		}
+		Ok(module)
+	}
+
+	/// Decode a dump from a module.
+	///
+	/// Fails with [`Error::NotCoreDump`] if the module has no `core` section and with
+	/// [`Error::DataOutOfBounds`] if a data segment does not fit the initial size of the memory.
+	pub fn from_module(module: &Module) -> Result<Self, Error> {
+		let mut dump = CoreDump::default();
+		let mut has_core = false;
+		for section in module.custom_sections() {
+			let mut payload = io::Cursor::new(section.payload());
+			match section.name() {
+				"core" => {
+					expect_tag(&mut payload, PROCESS_INFO)?;
+					dump.executable = String::deserialize(&mut payload)?;
+					has_core = true;
+				},
+				"corestack" => dump.threads.push(CoreThread::deserialize(&mut payload)?),
+				_ => continue,
+			}
+			if payload.position() != section.payload().len() {
+				return Err(io::Error::TrailingData.into())
+			}
+		}
+		if !has_core {
+			return Err(Error::NotCoreDump)
+		}
+
+		if let Some(memory_type) = module.memory_section().and_then(|s| s.entries().first()) {
+			let size = (memory_type.limits().initial() as usize).saturating_mul(PAGE_SIZE);
+			let mut memory = Vec::new();
+			for segment in module.data_section().map(|s| s.entries()).unwrap_or(&[]) {
+				let offset = match segment.offset().as_ref().map(|offset| offset.code()) {
+					Some([Instruction::I32Const(offset), Instruction::End]) => *offset as u32,
+					_ => return Err(Error::InvalidConstExpr),
+				} as usize;
+				let end = offset
+					.checked_add(segment.value().len())
+					.filter(|end| *end <= size)
+					.ok_or(Error::DataOutOfBounds)?;
+				if memory.len() < end {
+					memory.resize(end, 0);
+				}
+				memory[offset..end].copy_from_slice(segment.value());
+			}
+			dump.memory = Some(memory);
+		}
+
+		let evaluator = ConstExprEvaluator::default();
+		for entry in module.global_section().map(|s| s.entries()).unwrap_or(&[]) {
+			dump.globals.push(evaluator.eval(entry.init_expr())?);
+		}
+		Ok(dump)
+	}
+}
+
+fn expect_tag<R: io::Read>(reader: &mut R, tag: u8) -> Result<(), Error> {
+	let found = u8::from(Uint8::deserialize(reader)?);
+	if found != tag {
+		return Err(Error::UnknownOpcode(found))
+	}
+	Ok(())
+}
+
+fn write_values<W: io::Write>(
+	writer: &mut W,
+	values: Vec<Option<ConstValue>>,
+) -> Result<(), Error> {
+	VarUint32::from(values.len()).serialize(writer)?;
+	for value in values {
+		match value {
+			None => Uint8::from(VALUE_MISSING).serialize(writer)?,
+			Some(ConstValue::I32(value)) => {
+				Uint8::from(VALUE_I32).serialize(writer)?;
+				VarInt32::from(value).serialize(writer)?;
+			},
+			Some(ConstValue::I64(value)) => {
+				Uint8::from(VALUE_I64).serialize(writer)?;
+				VarInt64::from(value).serialize(writer)?;
+			},
+			Some(ConstValue::F32(value)) => {
+				Uint8::from(VALUE_F32).serialize(writer)?;
+				Uint32::from(value).serialize(writer)?;
+			},
+			Some(ConstValue::F64(value)) => {
+				Uint8::from(VALUE_F64).serialize(writer)?;
+				Uint64::from(value).serialize(writer)?;
+			},
+		}
+	}
+	Ok(())
+}
+
+fn read_values<R: io::Read>(reader: &mut R) -> Result<Vec<Option<ConstValue>>, Error> {
+	let count: u32 = VarUint32::deserialize(reader)?.into();
+	let mut values = Vec::new();
+	for _ in 0..count {
+		let value = match u8::from(Uint8::deserialize(reader)?) {
+			VALUE_MISSING => None,
+			VALUE_I32 => Some(ConstValue::I32(VarInt32::deserialize(reader)?.into())),
+			VALUE_I64 => Some(ConstValue::I64(VarInt64::deserialize(reader)?.into())),
+			VALUE_F32 => Some(ConstValue::F32(Uint32::deserialize(reader)?.into())),
+			VALUE_F64 => Some(ConstValue::F64(Uint64::deserialize(reader)?.into())),
+			other => return Err(Error::UnknownValueType(other as i8)),
+		};
+		values.push(value);
+	}
+	Ok(values)
+}
+
+impl Deserialize for CoreThread {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		expect_tag(reader, THREAD_INFO)?;
+		let name = String::deserialize(reader)?;
+		let count: u32 = VarUint32::deserialize(reader)?.into();
+		let mut frames = Vec::new();
+		for _ in 0..count {
+			frames.push(CoreFrame::deserialize(reader)?);
+		}
+		Ok(CoreThread { name, frames })
+	}
+}
+
+impl Serialize for CoreThread {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		Uint8::from(THREAD_INFO).serialize(writer)?;
+		self.name.serialize(writer)?;
+		VarUint32::from(self.frames.len()).serialize(writer)?;
+		for frame in self.frames {
+			frame.serialize(writer)?;
+		}
+		Ok(())
+	}
+}
+
+impl Deserialize for CoreFrame {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		expect_tag(reader, FRAME)?;
+		let instance: u32 = VarUint32::deserialize(reader)?.into();
+		if instance != 0 {
+			return Err(Error::UnsupportedInstance(instance))
+		}
+		Ok(CoreFrame {
+			function: VarUint32::deserialize(reader)?.into(),
+			code_offset: VarUint32::deserialize(reader)?.into(),
+			locals: read_values(reader)?,
+			stack: read_values(reader)?,
+		})
+	}
+}
+
+impl Serialize for CoreFrame {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		Uint8::from(FRAME).serialize(writer)?;
+		VarUint32::from(0u32).serialize(writer)?;
+		VarUint32::from(self.function).serialize(writer)?;
+		VarUint32::from(self.code_offset).serialize(writer)?;
+		write_values(writer, self.locals)?;
+		write_values(writer, self.stack)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{CoreDump, CoreFrame, CoreThread, PAGE_SIZE};
+	use crate::elements::{
+		ConstValue, DataSection, DataSegment, Error, InitExpr, Instruction, Module, Section,
+	};
+
+	#[test]
+	fn round_trip() {
+		let dump = CoreDump {
+			executable: "app.wasm".into(),
+			threads: vec![CoreThread {
+				name: "main".into(),
+				frames: vec![
+					CoreFrame {
+						function: 3,
+						code_offset: 17,
+						locals: vec![Some(ConstValue::I32(-1)), None],
+						stack: vec![Some(ConstValue::F64(1.5f64.to_bits()))],
+					},
+					CoreFrame { function: 1, code_offset: 4, ..Default::default() },
+				],
+			}],
+			memory: Some(vec![1, 2, 3]),
+			globals: vec![ConstValue::I64(42), ConstValue::F32(0)],
+		};
+
+		let bytes =
+			dump.clone().into_module().and_then(Module::into_bytes).expect("dump to encode");
+		let module = Module::from_bytes(&bytes).expect("dump to be a valid module");
+		assert_eq!(module.memory_section().expect("memory").entries()[0].limits().initial(), 1);
+		assert_eq!(CoreDump::from_module(&module).expect("dump to decode"), dump);
+
+		assert!(matches!(CoreDump::from_module(&Module::default()), Err(Error::NotCoreDump)));
+	}
+
+	#[test]
+	fn data_out_of_bounds() {
+		let dump = CoreDump { memory: Some(vec![0; 3]), ..Default::default() };
+		let mut module = dump.into_module().expect("dump to encode");
+		let offset =
+			InitExpr::new(vec![Instruction::I32Const(PAGE_SIZE as i32 - 1), Instruction::End]);
+		let segment = DataSegment::new(0, Some(offset), vec![1, 2]);
+		for section in module.sections_mut() {
+			if let Section::Data(data) = section {
+				*data = DataSection::with_entries(vec![segment.clone()]);
+			}
+		}
+		assert!(matches!(CoreDump::from_module(&module), Err(Error::DataOutOfBounds)));
+	}
+}
@@ -0,0 +1,244 @@
+use alloc::{
+	collections::BTreeMap,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::fmt::Write;
+
+use super::{
+	code_offsets,
+	gas::{ends_block, instrument},
+	Error, ImportCountType, Instruction, Module,
+};
+
+/// Straight-line block of instructions reported by one coverage probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoverageProbe {
+	/// Index of the function in the function index space of the original module.
+	pub function: u32,
+	/// Index of the first instruction of the block in the original body.
+	pub instruction: usize,
+	/// Number of instructions in the block.
+	pub len: usize,
+	/// Offset of the first instruction in the original code section, as in [`code_offsets`].
+	pub offset: u32,
+}
+
+/// Mapping from probe ids to the code they cover, produced by [`inject_coverage`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageMap {
+	probes: Vec<CoverageProbe>,
+	offsets: BTreeMap<u32, Vec<u32>>,
+	names: BTreeMap<u32, String>,
+}
+
+impl CoverageMap {
+	/// All probes, indexed by the id passed to `env.coverage`.
+	pub fn probes(&self) -> &[CoverageProbe] {
+		&self.probes
+	}
+
+	/// Executed instructions of every defined function, by function index.
+	///
+	/// `counts` holds how many times each probe was reported during the run, indexed by
+	/// probe id; missing entries count as zero. An instruction counts as executed if the
+	/// block it belongs to was entered, even if a trap happened before reaching it.
+	pub fn bitmaps(&self, counts: &[u64]) -> BTreeMap<u32, Vec<bool>> {
+		self.instruction_counts(counts)
+			.into_iter()
+			.map(|(function, counts)| (function, counts.iter().map(|count| *count > 0).collect()))
+			.collect()
+	}
+
+	fn instruction_counts(&self, counts: &[u64]) -> BTreeMap<u32, Vec<u64>> {
+		let mut functions: BTreeMap<u32, Vec<u64>> = self
+			.offsets
+			.iter()
+			.map(|(function, offsets)| (*function, vec![0; offsets.len()]))
+			.collect();
+		for (id, probe) in self.probes.iter().enumerate() {
+			let count = counts.get(id).copied().unwrap_or(0);
+			if let Some(instructions) = functions.get_mut(&probe.function) {
+				let end = core::cmp::min(probe.instruction + probe.len, instructions.len());
+				instructions[probe.instruction..end].iter_mut().for_each(|c| *c += count);
+			}
+		}
+		functions
+	}
+
+	/// Render the coverage of a run as an LCOV tracefile.
+	///
+	/// Instruction offsets are used as line numbers and functions are named after the name
+	/// section of the original module, falling back to `func[<index>]`.
+	pub fn lcov(&self, source: &str, counts: &[u64]) -> String {
+		let functions = self.instruction_counts(counts);
+		let mut out = String::new();
+		let _ = writeln!(out, "TN:\nSF:{}", source);
+		for (function, offsets) in &self.offsets {
+			let name = self.function_name(*function);
+			let _ = writeln!(out, "FN:{},{}", offsets.first().copied().unwrap_or(0), name);
+		}
+		let mut hit_functions = 0;
+		for (function, instructions) in &functions {
+			let entries = instructions.first().copied().unwrap_or(0);
+			if entries > 0 {
+				hit_functions += 1;
+			}
+			let _ = writeln!(out, "FNDA:{},{}", entries, self.function_name(*function));
+		}
+		let _ = writeln!(out, "FNF:{}\nFNH:{}", functions.len(), hit_functions);
+		let (mut lines, mut hit_lines) = (0, 0);
+		for (function, instructions) in &functions {
+			for (offset, count) in self.offsets[function].iter().zip(instructions) {
+				let _ = writeln!(out, "DA:{},{}", offset, count);
+				lines += 1;
+				if *count > 0 {
+					hit_lines += 1;
+				}
+			}
+		}
+		let _ = writeln!(out, "LF:{}\nLH:{}\nend_of_record", lines, hit_lines);
+		out
+	}
+
+	fn function_name(&self, function: u32) -> String {
+		match self.names.get(&function) {
+			Some(name) => name.clone(),
+			None => format!("func[{}]", function),
+		}
+	}
+}
+
+/// Instrument every function body with calls to an imported `env.coverage(i32)` function.
+///
+/// Bodies are split into the same straight-line blocks as by
+/// [`inject_gas_counter`](super::inject_gas_counter), and the start of each block reports
+/// the id of its probe. The host counts the reports and turns them into coverage with the
+/// returned [`CoverageMap`]. Indices of defined functions move up by one, as with gas
+/// metering. The code section has to be parsed ([`Error::UnparsedCode`] otherwise), and
+/// names are taken from the name section if it is parsed.
+pub fn inject_coverage(module: Module) -> Result<(Module, CoverageMap), Error> {
+	if module.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+	let offsets = code_offsets(&module)?;
+	let imported = module.import_count(ImportCountType::Function) as u32;
+	let names = module
+		.names_section()
+		.and_then(|names| names.functions())
+		.map(|functions| {
+			functions
+				.names()
+				.iter()
+				.map(|(index, name)| (index, name.to_string()))
+				.collect()
+		})
+		.unwrap_or_default();
+
+	let mut map = CoverageMap { names, ..Default::default() };
+	let mut bodies = offsets.iter().zip(imported..);
+	let (module, _) = instrument(module, "coverage", |instructions, coverage_func| {
+		let (body, function) = bodies.next().expect("one transform per body");
+		map.offsets.insert(function, body.instructions().to_vec());
+		probe(instructions, function, body.instructions(), coverage_func, &mut map.probes)
+	})?;
+	Ok((module, map))
+}
+
+fn probe(
+	instructions: &[Instruction],
+	function: u32,
+	offsets: &[u32],
+	coverage_func: u32,
+	probes: &mut Vec<CoverageProbe>,
+) -> (Vec<Instruction>, Vec<Option<usize>>) {
+	let mut probed = Vec::with_capacity(instructions.len());
+	let mut origins = Vec::with_capacity(instructions.len());
+	let mut remaining = instructions;
+	while !remaining.is_empty() {
+		let length = remaining.iter().position(ends_block).map_or(remaining.len(), |end| end + 1);
+		let (block, rest) = remaining.split_at(length);
+		let start = instructions.len() - remaining.len();
+		probed.push(Instruction::I32Const(probes.len() as i32));
+		probed.push(Instruction::Call(coverage_func));
+		origins.extend([None, None]);
+		probes.push(CoverageProbe {
+			function,
+			instruction: start,
+			len: block.len(),
+			offset: offsets[start],
+		});
+		probed.extend_from_slice(block);
+		origins.extend((start..start + block.len()).map(Some));
+		remaining = rest;
+	}
+	(probed, origins)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::inject_coverage;
+	use crate::{
+		builder,
+		elements::{BlockType, External, Instruction::*, Instructions},
+	};
+
+	#[test]
+	fn coverage() {
+		let module = builder::module()
+			.import()
+			.module("env")
+			.field("log")
+			.external()
+			.func(0)
+			.build()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![
+				I32Const(0),
+				If(BlockType::NoResult),
+				Call(0),
+				End,
+				End,
+			]))
+			.build()
+			.build()
+			.build();
+
+		let (module, map) = inject_coverage(module).expect("module to be instrumented");
+		let imports = module.import_section().expect("coverage import").entries();
+		assert_eq!((imports[1].module(), imports[1].field()), ("env", "coverage"));
+		assert_eq!(*imports[1].external(), External::Function(1));
+		assert_eq!(
+			module.code_section().unwrap().bodies()[0].code().elements(),
+			&[
+				I32Const(0),
+				Call(1),
+				I32Const(0),
+				If(BlockType::NoResult),
+				I32Const(1),
+				Call(1),
+				Call(0),
+				End,
+				I32Const(2),
+				Call(1),
+				End,
+			]
+		);
+
+		let probes = map.probes();
+		assert_eq!(probes.len(), 3);
+		assert_eq!((probes[1].function, probes[1].instruction, probes[1].len), (1, 2, 2));
+
+		// The `if` body was skipped.
+		let counts = [1, 0, 1];
+		let bitmaps = map.bitmaps(&counts);
+		assert_eq!(bitmaps[&1], vec![true, true, false, false, true]);
+		let lcov = map.lcov("test.wasm", &counts);
+		assert!(lcov.starts_with("TN:\nSF:test.wasm\nFN:"));
+		assert!(lcov.contains("FNDA:1,func[1]\nFNF:1\nFNH:1\n"));
+		assert!(lcov.ends_with("LF:5\nLH:3\nend_of_record\n"));
+	}
+}
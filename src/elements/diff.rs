@@ -0,0 +1,243 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+
+use super::{
+	module::custom_section_name, serialize, DataSegment, Error, External, FuncBody, GlobalEntry,
+	GlobalType, Module, Type,
+};
+
+/// Difference of a single item between two modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<K> {
+	/// The item only exists in the second module.
+	Added(K),
+	/// The item only exists in the first module.
+	Removed(K),
+	/// The item exists in both modules but differs.
+	Changed(K),
+}
+
+impl<K> Change<K> {
+	/// Index or name of the item.
+	pub fn key(&self) -> &K {
+		match self {
+			Change::Added(key) | Change::Removed(key) | Change::Changed(key) => key,
+		}
+	}
+}
+
+/// Structural difference between two modules, as computed by [`diff`].
+///
+/// Functions, globals and data segments are matched by index, custom sections by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleDiff {
+	functions: Vec<Change<u32>>,
+	globals: Vec<Change<u32>>,
+	data_segments: Vec<Change<u32>>,
+	custom_sections: Vec<Change<String>>,
+}
+
+impl ModuleDiff {
+	/// Changed functions, by index in the function index space.
+	pub fn functions(&self) -> &[Change<u32>] {
+		&self.functions
+	}
+
+	/// Changed globals, by index in the global index space.
+	pub fn globals(&self) -> &[Change<u32>] {
+		&self.globals
+	}
+
+	/// Changed data segments, by index in the data section.
+	pub fn data_segments(&self) -> &[Change<u32>] {
+		&self.data_segments
+	}
+
+	/// Changed custom sections, by name.
+	pub fn custom_sections(&self) -> &[Change<String>] {
+		&self.custom_sections
+	}
+
+	/// Whether no differences were found.
+	pub fn is_empty(&self) -> bool {
+		self.functions.is_empty() &&
+			self.globals.is_empty() &&
+			self.data_segments.is_empty() &&
+			self.custom_sections.is_empty()
+	}
+}
+
+fn write_changes<K: fmt::Display>(
+	f: &mut fmt::Formatter,
+	item: &str,
+	changes: &[Change<K>],
+) -> fmt::Result {
+	for change in changes {
+		let sign = match change {
+			Change::Added(_) => '+',
+			Change::Removed(_) => '-',
+			Change::Changed(_) => '~',
+		};
+		writeln!(f, "{} {} {}", sign, item, change.key())?;
+	}
+	Ok(())
+}
+
+impl fmt::Display for ModuleDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write_changes(f, "function", &self.functions)?;
+		write_changes(f, "global", &self.globals)?;
+		write_changes(f, "data segment", &self.data_segments)?;
+		write_changes(f, "custom section", &self.custom_sections)
+	}
+}
+
+#[derive(PartialEq)]
+enum Function<'a> {
+	Imported(&'a str, &'a str, Option<&'a Type>),
+	Defined(Option<&'a Type>, Option<&'a FuncBody>),
+}
+
+#[derive(PartialEq)]
+enum Global<'a> {
+	Imported(&'a str, &'a str, &'a GlobalType),
+	Defined(&'a GlobalEntry),
+}
+
+fn functions(module: &Module) -> Vec<Function<'_>> {
+	let types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+	let mut functions = Vec::new();
+	for entry in module.import_section().map(|s| s.entries()).unwrap_or(&[]) {
+		if let External::Function(type_ref) = *entry.external() {
+			functions.push(Function::Imported(
+				entry.module(),
+				entry.field(),
+				types.get(type_ref as usize),
+			));
+		}
+	}
+	let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+	for (index, func) in
+		module.function_section().map(|s| s.entries()).unwrap_or(&[]).iter().enumerate()
+	{
+		functions.push(Function::Defined(types.get(func.type_ref() as usize), bodies.get(index)));
+	}
+	functions
+}
+
+fn globals(module: &Module) -> Vec<Global<'_>> {
+	let mut globals = Vec::new();
+	for entry in module.import_section().map(|s| s.entries()).unwrap_or(&[]) {
+		if let External::Global(ref global_type) = *entry.external() {
+			globals.push(Global::Imported(entry.module(), entry.field(), global_type));
+		}
+	}
+	for entry in module.global_section().map(|s| s.entries()).unwrap_or(&[]) {
+		globals.push(Global::Defined(entry));
+	}
+	globals
+}
+
+fn data_segments(module: &Module) -> &[DataSegment] {
+	module.data_section().map(|s| s.entries()).unwrap_or(&[])
+}
+
+fn custom_sections(module: &Module) -> Result<BTreeMap<String, Vec<Vec<u8>>>, Error> {
+	let mut sections = BTreeMap::<String, Vec<Vec<u8>>>::new();
+	for section in module.sections() {
+		if let Some(name) = custom_section_name(section) {
+			sections.entry(name.into()).or_default().push(serialize(section.clone())?);
+		}
+	}
+	Ok(sections)
+}
+
+fn compare<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Change<u32>> {
+	let mut changes = Vec::new();
+	for index in 0..core::cmp::max(a.len(), b.len()) {
+		match (a.get(index), b.get(index)) {
+			(Some(a), Some(b)) if a != b => changes.push(Change::Changed(index as u32)),
+			(Some(_), None) => changes.push(Change::Removed(index as u32)),
+			(None, Some(_)) => changes.push(Change::Added(index as u32)),
+			_ => {},
+		}
+	}
+	changes
+}
+
+/// Compare two modules structurally.
+///
+/// Imported and defined items are compared alike: a function whose import name changed is
+/// reported as changed. Type references are resolved, so reordering the type section alone
+/// yields no differences. Lazily decoded code sections have to be parsed first, otherwise
+/// [`Error::UnparsedCode`] is returned.
+pub fn diff(a: &Module, b: &Module) -> Result<ModuleDiff, Error> {
+	if a.lazy_code_section().is_some() || b.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+
+	let (a_sections, b_sections) = (custom_sections(a)?, custom_sections(b)?);
+	let mut sections = Vec::new();
+	for (name, a_section) in &a_sections {
+		match b_sections.get(name) {
+			Some(b_section) if a_section != b_section =>
+				sections.push(Change::Changed(name.clone())),
+			None => sections.push(Change::Removed(name.clone())),
+			_ => {},
+		}
+	}
+	for name in b_sections.keys().filter(|name| !a_sections.contains_key(*name)) {
+		sections.push(Change::Added(name.clone()));
+	}
+
+	Ok(ModuleDiff {
+		functions: compare(&functions(a), &functions(b)),
+		globals: compare(&globals(a), &globals(b)),
+		data_segments: compare(data_segments(a), data_segments(b)),
+		custom_sections: sections,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{diff, Change};
+	use crate::{
+		builder,
+		elements::{CustomSection, Instruction::*, Instructions, Module, Section, ValueType},
+	};
+
+	fn module(constant: i32, globals: usize) -> Module {
+		let mut module = builder::module()
+			.function()
+			.signature()
+			.with_result(ValueType::I32)
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![I32Const(constant), End]))
+			.build()
+			.build()
+			.data()
+			.offset(I32Const(0))
+			.value(vec![1, 2])
+			.build()
+			.with_section(Section::Custom(CustomSection::new("meta".into(), vec![constant as u8])));
+		for _ in 0..globals {
+			module = module.global().value_type().i32().init_expr(I32Const(0)).build();
+		}
+		module.build()
+	}
+
+	#[test]
+	fn structural_diff() {
+		let a = module(1, 1);
+		assert!(diff(&a, &a).expect("modules to compare").is_empty());
+
+		let b = module(2, 2);
+		let changes = diff(&a, &b).expect("modules to compare");
+		assert_eq!(changes.functions(), &[Change::Changed(0)]);
+		assert_eq!(changes.globals(), &[Change::Added(1)]);
+		assert!(changes.data_segments().is_empty());
+		assert_eq!(changes.custom_sections(), &[Change::Changed("meta".into())]);
+		assert_eq!(format!("{}", changes), "~ function 0\n+ global 1\n~ custom section meta\n");
+	}
+}
@@ -0,0 +1,220 @@
+use crate::io;
+use alloc::{string::String, vec::Vec};
+
+use super::{
+	deserialize_buffer, section::ENTRIES_BUFFER_LENGTH, serialize, CountedList, CountedListWriter,
+	Deserialize, Error, Serialize, VarUint32, VarUint7,
+};
+
+const SUBSECTION_MEM_INFO: u8 = 1;
+const SUBSECTION_NEEDED: u8 = 2;
+const SUBSECTION_EXPORT_INFO: u8 = 3;
+const SUBSECTION_IMPORT_INFO: u8 = 4;
+
+/// Dynamic linking metadata of a shared library module (the `dylink.0` custom section).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DylinkSection {
+	mem_info: Option<DylinkMemInfo>,
+	needed: Vec<String>,
+	export_info: Vec<DylinkSymbolInfo>,
+	import_info: Vec<DylinkImportInfo>,
+}
+
+impl DylinkSection {
+	/// Memory and table requirements of the library.
+	pub fn mem_info(&self) -> Option<&DylinkMemInfo> {
+		self.mem_info.as_ref()
+	}
+
+	/// Memory and table requirements of the library (mutable).
+	pub fn mem_info_mut(&mut self) -> &mut Option<DylinkMemInfo> {
+		&mut self.mem_info
+	}
+
+	/// Names of the libraries this one depends on.
+	pub fn needed(&self) -> &[String] {
+		&self.needed
+	}
+
+	/// Names of the libraries this one depends on (mutable).
+	pub fn needed_mut(&mut self) -> &mut Vec<String> {
+		&mut self.needed
+	}
+
+	/// Symbol flags of exports.
+	pub fn export_info(&self) -> &[DylinkSymbolInfo] {
+		&self.export_info
+	}
+
+	/// Symbol flags of exports (mutable).
+	pub fn export_info_mut(&mut self) -> &mut Vec<DylinkSymbolInfo> {
+		&mut self.export_info
+	}
+
+	/// Symbol flags of imports.
+	pub fn import_info(&self) -> &[DylinkImportInfo] {
+		&self.import_info
+	}
+
+	/// Symbol flags of imports (mutable).
+	pub fn import_info_mut(&mut self) -> &mut Vec<DylinkImportInfo> {
+		&mut self.import_info
+	}
+
+	/// Deserialize a `dylink.0` section from its payload, following the section name.
+	///
+	/// Subsections of unknown types are skipped.
+	pub fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Error> {
+		let mut section = DylinkSection::default();
+		loop {
+			// The subsections extend to the end of the payload.
+			let mut id = [0u8; 1];
+			if rdr.read(&mut id).is_err() {
+				break
+			}
+			let length: usize = u32::from(VarUint32::deserialize(rdr)?) as usize;
+			let payload = buffered_read!(ENTRIES_BUFFER_LENGTH, length, rdr);
+			match id[0] {
+				SUBSECTION_MEM_INFO => section.mem_info = Some(deserialize_buffer(&payload)?),
+				SUBSECTION_NEEDED => section.needed = read_list(&payload)?,
+				SUBSECTION_EXPORT_INFO => section.export_info = read_list(&payload)?,
+				SUBSECTION_IMPORT_INFO => section.import_info = read_list(&payload)?,
+				_ => {},
+			}
+		}
+		Ok(section)
+	}
+}
+
+fn read_list<T: Deserialize<Error = Error>>(payload: &[u8]) -> Result<Vec<T>, Error> {
+	Ok(deserialize_buffer::<CountedList<T>>(payload)?.into_inner())
+}
+
+fn write_subsection<W: io::Write>(wtr: &mut W, id: u8, payload: Vec<u8>) -> Result<(), Error> {
+	VarUint7::from(id).serialize(wtr)?;
+	VarUint32::from(payload.len()).serialize(wtr)?;
+	wtr.write(&payload)?;
+	Ok(())
+}
+
+fn write_list<W, T>(wtr: &mut W, id: u8, entries: Vec<T>) -> Result<(), Error>
+where
+	W: io::Write,
+	T: Serialize<Error = Error>,
+{
+	if entries.is_empty() {
+		return Ok(())
+	}
+	write_subsection(wtr, id, serialize(CountedListWriter(entries.len(), entries.into_iter()))?)
+}
+
+impl Serialize for DylinkSection {
+	type Error = Error;
+
+	/// Serialize the payload of the section, without the section name.
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		if let Some(mem_info) = self.mem_info {
+			write_subsection(wtr, SUBSECTION_MEM_INFO, serialize(mem_info)?)?;
+		}
+		write_list(wtr, SUBSECTION_NEEDED, self.needed)?;
+		write_list(wtr, SUBSECTION_EXPORT_INFO, self.export_info)?;
+		write_list(wtr, SUBSECTION_IMPORT_INFO, self.import_info)
+	}
+}
+
+/// Memory and table requirements of a shared library.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DylinkMemInfo {
+	/// Size of the library's static data in bytes.
+	pub memory_size: u32,
+	/// Alignment of the static data, as a power of two.
+	pub memory_alignment: u32,
+	/// Number of table slots the library needs.
+	pub table_size: u32,
+	/// Alignment of the table slots, as a power of two.
+	pub table_alignment: u32,
+}
+
+impl Deserialize for DylinkMemInfo {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		Ok(DylinkMemInfo {
+			memory_size: VarUint32::deserialize(rdr)?.into(),
+			memory_alignment: VarUint32::deserialize(rdr)?.into(),
+			table_size: VarUint32::deserialize(rdr)?.into(),
+			table_alignment: VarUint32::deserialize(rdr)?.into(),
+		})
+	}
+}
+
+impl Serialize for DylinkMemInfo {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		VarUint32::from(self.memory_size).serialize(wtr)?;
+		VarUint32::from(self.memory_alignment).serialize(wtr)?;
+		VarUint32::from(self.table_size).serialize(wtr)?;
+		VarUint32::from(self.table_alignment).serialize(wtr)
+	}
+}
+
+/// Symbol flags of an export.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DylinkSymbolInfo {
+	/// Name of the export.
+	pub name: String,
+	/// Symbol flags, as in the linking section.
+	pub flags: u32,
+}
+
+impl Deserialize for DylinkSymbolInfo {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let name = String::deserialize(rdr)?;
+		let flags = VarUint32::deserialize(rdr)?.into();
+		Ok(DylinkSymbolInfo { name, flags })
+	}
+}
+
+impl Serialize for DylinkSymbolInfo {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		self.name.serialize(wtr)?;
+		VarUint32::from(self.flags).serialize(wtr)
+	}
+}
+
+/// Symbol flags of an import.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DylinkImportInfo {
+	/// Module name of the import.
+	pub module: String,
+	/// Field name of the import.
+	pub field: String,
+	/// Symbol flags, as in the linking section.
+	pub flags: u32,
+}
+
+impl Deserialize for DylinkImportInfo {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let module = String::deserialize(rdr)?;
+		let field = String::deserialize(rdr)?;
+		let flags = VarUint32::deserialize(rdr)?.into();
+		Ok(DylinkImportInfo { module, field, flags })
+	}
+}
+
+impl Serialize for DylinkImportInfo {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		self.module.serialize(wtr)?;
+		self.field.serialize(wtr)?;
+		VarUint32::from(self.flags).serialize(wtr)
+	}
+}
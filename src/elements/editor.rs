@@ -0,0 +1,207 @@
+use alloc::vec::Vec;
+use core::mem;
+
+#[cfg(feature = "exceptions")]
+use super::ExceptionsInstruction;
+use super::{Error, Instruction};
+
+/// Editor of the instructions of a function body that keeps block nesting intact.
+///
+/// Structured control instructions can only be inserted and removed as whole blocks, and the
+/// closing `end` of the body always stays last, so edits can not unbalance the body. Branch
+/// targets are relative, so edits do not affect branches outside the edited blocks; branches
+/// within inserted code are up to the caller. Created by
+/// [`FuncBody::editor`](super::FuncBody::editor).
+#[derive(Debug)]
+pub struct InstructionEditor<'a> {
+	instructions: &'a mut Vec<Instruction>,
+}
+
+/// Whether `instruction` opens, separates or closes a block.
+fn is_structural(instruction: &Instruction) -> bool {
+	#[cfg(feature = "exceptions")]
+	if let Instruction::Exceptions(
+		ExceptionsInstruction::Catch(_) | ExceptionsInstruction::CatchAll,
+	) = instruction
+	{
+		return true
+	}
+	instruction.is_block() || instruction.is_terminal() || *instruction == Instruction::Else
+}
+
+/// Check that `instructions` consist of complete blocks only.
+fn check_balanced(instructions: &[Instruction]) -> Result<(), Error> {
+	let mut open: Vec<&Instruction> = Vec::new();
+	for instruction in instructions {
+		let valid = match instruction {
+			Instruction::Else => matches!(open.last(), Some(Instruction::If(_))),
+			#[cfg(feature = "exceptions")]
+			Instruction::Exceptions(
+				ExceptionsInstruction::Catch(_) | ExceptionsInstruction::CatchAll,
+			) => matches!(open.last(), Some(Instruction::Exceptions(ExceptionsInstruction::Try(_)))),
+			#[cfg(feature = "exceptions")]
+			Instruction::Exceptions(ExceptionsInstruction::Delegate(_)) =>
+				matches!(open.pop(), Some(Instruction::Exceptions(ExceptionsInstruction::Try(_)))),
+			Instruction::End => open.pop().is_some(),
+			opening if opening.is_block() => {
+				open.push(opening);
+				true
+			},
+			_ => true,
+		};
+		if !valid {
+			return Err(Error::UnbalancedBlocks)
+		}
+	}
+	if !open.is_empty() {
+		return Err(Error::UnbalancedBlocks)
+	}
+	Ok(())
+}
+
+impl<'a> InstructionEditor<'a> {
+	pub(crate) fn new(instructions: &'a mut Vec<Instruction>) -> Self {
+		InstructionEditor { instructions }
+	}
+
+	/// Instructions of the body.
+	pub fn instructions(&self) -> &[Instruction] {
+		self.instructions
+	}
+
+	/// Position of the `end` closing the block opened at `index`.
+	pub fn block_end(&self, index: usize) -> Option<usize> {
+		if !self.instructions.get(index)?.is_block() {
+			return None
+		}
+		let mut depth = 0usize;
+		for (position, instruction) in self.instructions.iter().enumerate().skip(index) {
+			if instruction.is_block() {
+				depth += 1;
+			} else if instruction.is_terminal() {
+				depth -= 1;
+				if depth == 0 {
+					return Some(position)
+				}
+			}
+		}
+		None
+	}
+
+	/// Insert a single instruction before the one at `index`.
+	///
+	/// Blocks have to be inserted whole with [`insert_all`](Self::insert_all), other structured
+	/// control instructions fail with [`Error::UnbalancedBlocks`].
+	pub fn insert(&mut self, index: usize, instruction: Instruction) -> Result<(), Error> {
+		self.insert_all(index, vec![instruction])
+	}
+
+	/// Insert a sequence of complete blocks and other instructions before the one at `index`.
+	///
+	/// `index` can be at most the position of the final `end`, otherwise
+	/// [`Error::InvalidEditPosition`] is returned. Incomplete blocks fail with
+	/// [`Error::UnbalancedBlocks`].
+	pub fn insert_all(
+		&mut self,
+		index: usize,
+		instructions: Vec<Instruction>,
+	) -> Result<(), Error> {
+		if index >= self.instructions.len() {
+			return Err(Error::InvalidEditPosition(index))
+		}
+		check_balanced(&instructions)?;
+		self.instructions.splice(index..index, instructions);
+		Ok(())
+	}
+
+	/// Remove the instruction at `index`, or the whole block if it opens one.
+	///
+	/// Returns the removed instructions. `else`, `end` and other instructions belonging to a
+	/// block can not be removed on their own and fail with [`Error::InvalidEditPosition`].
+	pub fn remove(&mut self, index: usize) -> Result<Vec<Instruction>, Error> {
+		let end = match self.instructions.get(index) {
+			Some(instruction) if instruction.is_block() =>
+				self.block_end(index).ok_or(Error::InconsistentCode)?,
+			Some(instruction) if !is_structural(instruction) => index,
+			_ => return Err(Error::InvalidEditPosition(index)),
+		};
+		Ok(self.instructions.drain(index..=end).collect())
+	}
+
+	/// Replace the instruction at `index`, returning the previous one.
+	///
+	/// An instruction that opens a block can only be replaced by one of the same kind, e.g.
+	/// to change the type of a `block`; other structured control instructions can not be
+	/// replaced. Fails with [`Error::InvalidEditPosition`] otherwise.
+	pub fn replace(
+		&mut self,
+		index: usize,
+		instruction: Instruction,
+	) -> Result<Instruction, Error> {
+		let current = self.instructions.get_mut(index).ok_or(Error::InvalidEditPosition(index))?;
+		let compatible = if current.is_block() {
+			instruction.is_block() && mem::discriminant(current) == mem::discriminant(&instruction)
+		} else {
+			!is_structural(current) && !is_structural(&instruction)
+		};
+		if !compatible {
+			return Err(Error::InvalidEditPosition(index))
+		}
+		Ok(mem::replace(current, instruction))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::{BlockType, Error, FuncBody, Instruction::*, Instructions, ValueType};
+
+	#[test]
+	fn edit() {
+		let mut body = FuncBody::new(
+			vec![],
+			Instructions::new(vec![
+				Block(BlockType::NoResult),
+				I32Const(0),
+				BrIf(0),
+				End,
+				Nop,
+				End,
+			]),
+		);
+		let mut editor = body.editor();
+		assert_eq!(editor.block_end(0), Some(3));
+
+		assert!(matches!(
+			editor.insert(0, Block(BlockType::NoResult)),
+			Err(Error::UnbalancedBlocks)
+		));
+		assert!(matches!(editor.insert(0, End), Err(Error::UnbalancedBlocks)));
+		assert!(matches!(editor.insert(6, Nop), Err(Error::InvalidEditPosition(6))));
+		editor
+			.insert_all(4, vec![Loop(BlockType::NoResult), Br(0), End])
+			.expect("complete block to be inserted");
+		assert!(matches!(
+			editor.insert_all(0, vec![If(BlockType::NoResult), Else]),
+			Err(Error::UnbalancedBlocks)
+		));
+
+		assert!(matches!(editor.remove(3), Err(Error::InvalidEditPosition(3))));
+		assert!(matches!(editor.remove(9), Err(Error::InvalidEditPosition(9))));
+		assert_eq!(editor.remove(0).expect("block to be removed").len(), 4);
+		assert_eq!(editor.remove(3).expect("nop to be removed"), vec![Nop]);
+
+		assert!(matches!(
+			editor.replace(0, Block(BlockType::NoResult)),
+			Err(Error::InvalidEditPosition(0))
+		));
+		assert!(matches!(editor.replace(2, Nop), Err(Error::InvalidEditPosition(2))));
+		assert_eq!(editor.replace(1, Unreachable).expect("plain instruction replaced"), Br(0));
+		let previous = editor.replace(0, Loop(BlockType::Value(ValueType::I32)));
+		assert_eq!(previous.expect("block type to be changed"), Loop(BlockType::NoResult));
+
+		assert_eq!(
+			body.code().elements(),
+			&[Loop(BlockType::Value(ValueType::I32)), Unreachable, End, End]
+		);
+	}
+}
@@ -13,6 +13,9 @@ pub enum Internal {
 	Memory(u32),
 	/// Global reference.
 	Global(u32),
+	/// Tag reference.
+	#[cfg(feature = "exceptions")]
+	Tag(u32),
 }
 
 impl Deserialize for Internal {
@@ -25,6 +28,8 @@ impl Deserialize for Internal {
 			0x01 => Ok(Internal::Table(VarUint32::deserialize(reader)?.into())),
 			0x02 => Ok(Internal::Memory(VarUint32::deserialize(reader)?.into())),
 			0x03 => Ok(Internal::Global(VarUint32::deserialize(reader)?.into())),
+			#[cfg(feature = "exceptions")]
+			0x04 => Ok(Internal::Tag(VarUint32::deserialize(reader)?.into())),
 			_ => Err(Error::UnknownInternalKind(kind.into())),
 		}
 	}
@@ -39,6 +44,8 @@ impl Serialize for Internal {
 			Internal::Table(arg) => (0x01, arg),
 			Internal::Memory(arg) => (0x02, arg),
 			Internal::Global(arg) => (0x03, arg),
+			#[cfg(feature = "exceptions")]
+			Internal::Tag(arg) => (0x04, arg),
 		};
 
 		VarUint7::from(bt).serialize(writer)?;
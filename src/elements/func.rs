@@ -1,8 +1,11 @@
 use super::{
-	CountedList, CountedListWriter, CountedWriter, Deserialize, Error, Instructions, Serialize,
-	ValueType, VarUint32,
+	CountedList, CountedListWriter, CountedWriter, Deserialize, Error, InstructionEditor,
+	Instructions, InstructionsWithOffsets, Serialize, ValueType, VarUint32, MAX_NESTING_DEPTH,
+};
+use crate::{
+	elements::section::{SectionReader, ENTRIES_BUFFER_LENGTH},
+	io,
 };
-use crate::{elements::section::SectionReader, io};
 use alloc::vec::Vec;
 
 /// Function signature (type reference)
@@ -125,6 +128,11 @@ impl FuncBody {
 	pub fn code_mut(&mut self) -> &mut Instructions {
 		&mut self.instructions
 	}
+
+	/// Editor of the instruction list that keeps block nesting intact.
+	pub fn editor(&mut self) -> InstructionEditor<'_> {
+		InstructionEditor::new(self.instructions.elements_mut())
+	}
 }
 
 impl Deserialize for FuncBody {
@@ -132,21 +140,27 @@ impl Deserialize for FuncBody {
 
 	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
 		let mut body_reader = SectionReader::new(reader)?;
-		let locals: Vec<Local> = CountedList::<Local>::deserialize(&mut body_reader)?.into_inner();
-
-		// The specification obliges us to count the total number of local variables while
-		// decoding the binary format.
-		locals
-			.iter()
-			.try_fold(0u32, |acc, &Local { count, .. }| acc.checked_add(count))
-			.ok_or(Error::TooManyLocals)?;
-
-		let instructions = Instructions::deserialize(&mut body_reader)?;
+		let body = read_body(&mut body_reader, MAX_NESTING_DEPTH)?;
 		body_reader.close()?;
-		Ok(FuncBody { locals, instructions })
+		Ok(body)
 	}
 }
 
+/// Decode locals and instructions of a function body, size prefix excluded.
+fn read_body<R: io::Read>(reader: &mut R, max_depth: usize) -> Result<FuncBody, Error> {
+	let locals: Vec<Local> = CountedList::<Local>::deserialize(reader)?.into_inner();
+
+	// The specification obliges us to count the total number of local variables while
+	// decoding the binary format.
+	locals
+		.iter()
+		.try_fold(0u32, |acc, &Local { count, .. }| acc.checked_add(count))
+		.ok_or(Error::TooManyLocals)?;
+
+	let instructions = Instructions::deserialize_with_depth(reader, max_depth)?;
+	Ok(FuncBody { locals, instructions })
+}
+
 impl Serialize for FuncBody {
 	type Error = Error;
 
@@ -154,8 +168,7 @@ impl Serialize for FuncBody {
 		let mut counted_writer = CountedWriter::new(writer);
 
 		let data = self.locals;
-		let counted_list =
-			CountedListWriter::<Local, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<Local, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 
 		let code = self.instructions;
@@ -166,3 +179,88 @@ impl Serialize for FuncBody {
 		Ok(())
 	}
 }
+
+/// Function body kept in its binary form until [`parse`](UnparsedFuncBody::parse) is called.
+///
+/// Produced by [`Module::from_bytes_lazy`](super::Module::from_bytes_lazy).
+#[derive(Debug, Clone)]
+pub struct UnparsedFuncBody {
+	bytes: Vec<u8>,
+	// Offsets of the size prefix and of the locals in the code section payload the body was
+	// decoded from, both 0 if it was decoded on its own.
+	offset: u32,
+	start: u32,
+}
+
+impl UnparsedFuncBody {
+	/// Encoded locals and instructions of the body, without the size prefix.
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	/// Offset of the size prefix of the body in the code section payload.
+	pub(crate) fn offset(&self) -> u32 {
+		self.offset
+	}
+
+	/// Offset of the locals of the body in the code section payload.
+	pub(crate) fn start(&self) -> u32 {
+		self.start
+	}
+
+	/// Read a body of the code section, recording where it is in the section payload.
+	pub(crate) fn deserialize_in_section(reader: &mut SectionReader) -> Result<Self, Error> {
+		let offset = reader.position() as u32;
+		let mut body = UnparsedFuncBody::deserialize(reader)?;
+		body.offset = offset;
+		body.start = reader.position() as u32 - body.bytes.len() as u32;
+		Ok(body)
+	}
+
+	/// Decode locals and instructions of the body.
+	pub fn parse(&self) -> Result<FuncBody, Error> {
+		self.parse_with_depth(MAX_NESTING_DEPTH)
+	}
+
+	/// Instructions of the body with their offsets relative to the start of [`bytes`].
+	///
+	/// [`bytes`]: UnparsedFuncBody::bytes
+	pub fn instructions_with_offsets(&self) -> Result<InstructionsWithOffsets<'_>, Error> {
+		InstructionsWithOffsets::new(&self.bytes, 0)
+	}
+
+	pub(crate) fn parse_with_depth(&self, max_depth: usize) -> Result<FuncBody, Error> {
+		let mut reader = io::Cursor::new(&self.bytes[..]);
+		let body = read_body(&mut reader, max_depth)?;
+		if reader.position() != self.bytes.len() {
+			return Err(io::Error::InvalidData.into())
+		}
+		Ok(body)
+	}
+}
+
+impl Deserialize for UnparsedFuncBody {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		let length = u32::from(VarUint32::deserialize(reader)?) as usize;
+		let bytes = buffered_read!(ENTRIES_BUFFER_LENGTH, length, reader);
+		Ok(UnparsedFuncBody { bytes, offset: 0, start: 0 })
+	}
+}
+
+impl PartialEq for UnparsedFuncBody {
+	fn eq(&self, other: &Self) -> bool {
+		self.bytes == other.bytes
+	}
+}
+
+impl Serialize for UnparsedFuncBody {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		VarUint32::from(self.bytes.len() as u32).serialize(writer)?;
+		writer.write(&self.bytes)?;
+		Ok(())
+	}
+}
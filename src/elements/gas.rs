@@ -0,0 +1,336 @@
+use alloc::vec::Vec;
+
+#[cfg(feature = "exceptions")]
+use super::ExceptionsInstruction;
+use super::{
+	code_offsets, Error, External, FunctionType, ImportEntry, ImportSection, Instruction, Internal,
+	Module, OffsetMap, Section, Type, TypeSection, ValueType,
+};
+
+/// Cost model used by [`inject_gas_counter`].
+///
+/// Any `Fn(&Instruction) -> u32` closure can be used as cost rules.
+pub trait CostRules {
+	/// Gas charged for executing `instruction`.
+	fn instruction_cost(&self, instruction: &Instruction) -> u32;
+}
+
+impl<F: Fn(&Instruction) -> u32> CostRules for F {
+	fn instruction_cost(&self, instruction: &Instruction) -> u32 {
+		self(instruction)
+	}
+}
+
+/// Cost rules charging the same amount for every instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantCost(pub u32);
+
+impl CostRules for ConstantCost {
+	fn instruction_cost(&self, _instruction: &Instruction) -> u32 {
+		self.0
+	}
+}
+
+/// Instrument every function body with calls to an imported `env.gas(i32)` function.
+///
+/// Function bodies are split into straight-line blocks: a new one starts at the function
+/// entry and after every instruction that begins or ends a structured block or branches.
+/// The start of each block calls `gas` with the summed cost of the block's instructions,
+/// so loops are charged on every iteration and skipped branches are not charged at all.
+///
+/// The `gas` import is appended after the existing imports, so the indices of all functions
+/// defined in the module move up by one; calls, exports, element segments, the start
+/// function and the name section are updated accordingly. Modules with a lazily decoded code
+/// section are rejected with [`Error::UnparsedCode`]; parse them with
+/// [`Module::parse_code`] first.
+pub fn inject_gas_counter<R: CostRules>(module: Module, rules: &R) -> Result<Module, Error> {
+	Ok(inject(module, rules)?.0)
+}
+
+/// Same as [`inject_gas_counter`], also returning how code offsets moved so debug
+/// information can be kept in sync.
+pub fn inject_gas_counter_with_offsets<R: CostRules>(
+	module: Module,
+	rules: &R,
+) -> Result<(Module, OffsetMap), Error> {
+	let before = code_offsets(&module)?;
+	let (module, origins) = inject(module, rules)?;
+	let offsets = OffsetMap::new(&before, &code_offsets(&module)?, &origins)?;
+	Ok((module, offsets))
+}
+
+/// Original index of every instruction of every rewritten body, `None` for added ones.
+type Origins = Vec<Vec<Option<usize>>>;
+
+/// Instrumented module and the origins of the instructions of every body.
+fn inject<R: CostRules>(module: Module, rules: &R) -> Result<(Module, Origins), Error> {
+	instrument(module, "gas", |instructions, gas_func| meter(instructions, rules, gas_func))
+}
+
+/// Append an `env.<field>` import of type `(i32) -> ()` and rewrite every function body.
+///
+/// `transform` receives the instructions of each body, with calls already renumbered, and
+/// the index of the new import, and returns the new instructions along with the original
+/// index each of them derives from. Lazily decoded code sections are rejected, as their
+/// calls could not be renumbered.
+pub(crate) fn instrument<F>(
+	mut module: Module,
+	field: &str,
+	mut transform: F,
+) -> Result<(Module, Origins), Error>
+where
+	F: FnMut(&[Instruction], u32) -> (Vec<Instruction>, Vec<Option<usize>>),
+{
+	if module.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+	let import_func = module.import_count(super::ImportCountType::Function) as u32;
+	let import_type = import_type(&mut module)?;
+	let import = ImportEntry::new("env".into(), field.into(), External::Function(import_type));
+	match module.import_section_mut() {
+		Some(imports) => imports.entries_mut().push(import),
+		None => {
+			let section = ImportSection::with_entries(vec![import]);
+			module.insert_section(Section::Import(section))?;
+		},
+	}
+
+	let shift = |index: &mut u32| {
+		if *index >= import_func {
+			*index += 1;
+		}
+	};
+
+	let mut origins = Vec::new();
+	for section in module.sections_mut() {
+		match section {
+			Section::Code(code) =>
+				for body in code.bodies_mut() {
+					let instructions = body.code_mut().elements_mut();
+					for instruction in instructions.iter_mut() {
+						if let Instruction::Call(index) = instruction {
+							shift(index);
+						}
+					}
+					let (transformed, body_origins) = transform(instructions, import_func);
+					*instructions = transformed;
+					origins.push(body_origins);
+				},
+			Section::Export(exports) =>
+				for export in exports.entries_mut() {
+					if let Internal::Function(index) = export.internal_mut() {
+						shift(index);
+					}
+				},
+			Section::Element(elements) =>
+				for segment in elements.entries_mut() {
+					segment.members_mut().iter_mut().for_each(shift);
+				},
+			Section::Start(index) => shift(index),
+			Section::Name(names) => {
+				if let Some(functions) = names.functions_mut() {
+					let shifted = shift_keys(core::mem::take(functions.names_mut()), import_func);
+					*functions.names_mut() = shifted;
+				}
+				if let Some(locals) = names.locals_mut() {
+					let shifted =
+						shift_keys(core::mem::take(locals.local_names_mut()), import_func);
+					*locals.local_names_mut() = shifted;
+				}
+			},
+			_ => {},
+		}
+	}
+
+	Ok((module, origins))
+}
+
+/// Index of the `(i32) -> ()` signature, added to the type section if missing.
+fn import_type(module: &mut Module) -> Result<u32, Error> {
+	let signature = Type::Function(FunctionType::new(vec![ValueType::I32], vec![]));
+	Ok(match module.type_section_mut() {
+		Some(types) => match types.types().iter().position(|existing| *existing == signature) {
+			Some(index) => index as u32,
+			None => {
+				types.types_mut().push(signature);
+				types.types().len() as u32 - 1
+			},
+		},
+		None => {
+			module.insert_section(Section::Type(TypeSection::with_types(vec![signature])))?;
+			0
+		},
+	})
+}
+
+fn shift_keys<T>(map: super::IndexMap<T>, from: u32) -> super::IndexMap<T> {
+	map.into_iter()
+		.map(|(index, value)| (if index >= from { index + 1 } else { index }, value))
+		.collect()
+}
+
+/// Whether a new metered block starts right after `instruction`.
+pub(crate) fn ends_block(instruction: &Instruction) -> bool {
+	match instruction {
+		Instruction::Block(_) |
+		Instruction::Loop(_) |
+		Instruction::If(_) |
+		Instruction::Else |
+		Instruction::End |
+		Instruction::Br(_) |
+		Instruction::BrIf(_) |
+		Instruction::BrTable(_) |
+		Instruction::Return |
+		Instruction::Unreachable => true,
+		#[cfg(feature = "exceptions")]
+		Instruction::Exceptions(
+			ExceptionsInstruction::Try(_) |
+			ExceptionsInstruction::Catch(_) |
+			ExceptionsInstruction::CatchAll |
+			ExceptionsInstruction::Throw(_) |
+			ExceptionsInstruction::Rethrow(_) |
+			ExceptionsInstruction::Delegate(_),
+		) => true,
+		_ => false,
+	}
+}
+
+fn meter<R: CostRules>(
+	instructions: &[Instruction],
+	rules: &R,
+	gas_func: u32,
+) -> (Vec<Instruction>, Vec<Option<usize>>) {
+	let mut metered = Vec::with_capacity(instructions.len());
+	let mut origins = Vec::with_capacity(instructions.len());
+	let mut remaining = instructions;
+	while !remaining.is_empty() {
+		let length = remaining.iter().position(ends_block).map_or(remaining.len(), |end| end + 1);
+		let (block, rest) = remaining.split_at(length);
+		let cost = block.iter().fold(0u32, |cost, instruction| {
+			cost.saturating_add(rules.instruction_cost(instruction))
+		});
+		if cost > 0 {
+			metered.push(Instruction::I32Const(cost as i32));
+			metered.push(Instruction::Call(gas_func));
+			origins.extend([None, None]);
+		}
+		let start = instructions.len() - remaining.len();
+		metered.extend_from_slice(block);
+		origins.extend((start..start + block.len()).map(Some));
+		remaining = rest;
+	}
+	(metered, origins)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{inject_gas_counter, inject_gas_counter_with_offsets, ConstantCost};
+	use crate::{
+		builder,
+		elements::{
+			BlockType, Error, External, ImportCountType, Instruction::*, Instructions, Internal,
+			Module,
+		},
+	};
+
+	#[test]
+	fn metered_blocks() {
+		let module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![
+				I32Const(1),
+				Drop,
+				Loop(BlockType::NoResult),
+				Call(0),
+				I32Const(0),
+				BrIf(0),
+				End,
+				End,
+			]))
+			.build()
+			.build()
+			.export()
+			.field("run")
+			.internal()
+			.func(0)
+			.build()
+			.build();
+
+		let module = inject_gas_counter(module, &ConstantCost(1)).expect("module to be metered");
+
+		let imports = module.import_section().expect("gas import").entries();
+		assert_eq!((imports[0].module(), imports[0].field()), ("env", "gas"));
+		assert_eq!(*imports[0].external(), External::Function(1));
+		assert_eq!(
+			*module.export_section().unwrap().entries()[0].internal(),
+			Internal::Function(1)
+		);
+
+		let body = &module.code_section().unwrap().bodies()[0];
+		assert_eq!(
+			body.code().elements(),
+			&[
+				I32Const(3),
+				Call(0),
+				I32Const(1),
+				Drop,
+				Loop(BlockType::NoResult),
+				I32Const(3),
+				Call(0),
+				Call(1),
+				I32Const(0),
+				BrIf(0),
+				I32Const(1),
+				Call(0),
+				End,
+				I32Const(1),
+				Call(0),
+				End,
+			]
+		);
+	}
+
+	#[test]
+	fn offsets() {
+		let module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![Nop, End]))
+			.build()
+			.build()
+			.build();
+
+		let (_, offsets) = inject_gas_counter_with_offsets(module, &ConstantCost(1))
+			.expect("offsets to be computed");
+		// Body at 1, `nop` at 3 and `end` at 4 move past `i32.const 2; call 0` (4 bytes).
+		assert_eq!(offsets.entries(), &[(1, 1), (3, 7), (4, 8), (5, 9)]);
+	}
+
+	#[test]
+	fn free_instructions() {
+		let module =
+			builder::module().function().signature().build().body().build().build().build();
+		let module = inject_gas_counter(module, &|_: &_| 0).expect("module to be metered");
+		assert_eq!(module.code_section().unwrap().bodies()[0].code().elements(), &[End]);
+	}
+
+	#[test]
+	fn lazy_code() {
+		let module =
+			builder::module().function().signature().build().body().build().build().build();
+		let lazy = Module::from_bytes_lazy(module.into_bytes().unwrap()).unwrap();
+		assert!(matches!(
+			inject_gas_counter(lazy.clone(), &ConstantCost(1)),
+			Err(Error::UnparsedCode)
+		));
+
+		let module = inject_gas_counter(lazy.parse_code().unwrap(), &ConstantCost(1))
+			.expect("parsed module to be metered");
+		assert_eq!(module.import_count(ImportCountType::Function), 1);
+	}
+}
@@ -53,6 +53,51 @@ impl Serialize for GlobalType {
 	}
 }
 
+/// Tag definition struct (exception handling proposal).
+#[cfg(feature = "exceptions")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TagType {
+	type_ref: u32,
+}
+
+#[cfg(feature = "exceptions")]
+impl TagType {
+	/// New tag type with the exception attribute, referencing the given function type.
+	pub fn new(type_ref: u32) -> Self {
+		TagType { type_ref }
+	}
+
+	/// Index of the function type describing the tag parameters.
+	pub fn type_ref(&self) -> u32 {
+		self.type_ref
+	}
+}
+
+#[cfg(feature = "exceptions")]
+impl Deserialize for TagType {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		// Exception (0x00) is the only tag attribute defined so far.
+		let attribute: u8 = Uint8::deserialize(reader)?.into();
+		if attribute != 0 {
+			return Err(Error::UnknownTagAttribute(attribute))
+		}
+		Ok(TagType { type_ref: VarUint32::deserialize(reader)?.into() })
+	}
+}
+
+#[cfg(feature = "exceptions")]
+impl Serialize for TagType {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		Uint8::from(0).serialize(writer)?;
+		VarUint32::from(self.type_ref).serialize(writer)?;
+		Ok(())
+	}
+}
+
 /// Table entry
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct TableType {
@@ -244,6 +289,9 @@ pub enum External {
 	Memory(MemoryType),
 	/// Describes local global entry to be imported as.
 	Global(GlobalType),
+	/// Describes local tag definition to be imported as.
+	#[cfg(feature = "exceptions")]
+	Tag(TagType),
 }
 
 impl Deserialize for External {
@@ -256,6 +304,8 @@ impl Deserialize for External {
 			0x01 => Ok(External::Table(TableType::deserialize(reader)?)),
 			0x02 => Ok(External::Memory(MemoryType::deserialize(reader)?)),
 			0x03 => Ok(External::Global(GlobalType::deserialize(reader)?)),
+			#[cfg(feature = "exceptions")]
+			0x04 => Ok(External::Tag(TagType::deserialize(reader)?)),
 			_ => Err(Error::UnknownExternalKind(kind.into())),
 		}
 	}
@@ -284,6 +334,11 @@ impl Serialize for External {
 				VarInt7::from(0x03).serialize(writer)?;
 				gt.serialize(writer)?;
 			},
+			#[cfg(feature = "exceptions")]
+			Tag(tt) => {
+				VarInt7::from(0x04).serialize(writer)?;
+				tt.serialize(writer)?;
+			},
 		}
 
 		Ok(())
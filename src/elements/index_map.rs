@@ -1,3 +1,6 @@
+// `slow_assertions` is enabled with `RUSTFLAGS="--cfg slow_assertions"`.
+#![allow(unknown_lints, unexpected_cfgs)]
+
 use crate::io;
 use alloc::vec::Vec;
 
@@ -43,7 +46,7 @@ impl<T> IndexMap<T> {
 	/// Return the name for the specified index, if it exists.
 	pub fn get(&self, idx: u32) -> Option<&T> {
 		match self.entries.get(idx as usize) {
-			Some(&Some(ref value)) => Some(value),
+			Some(Some(value)) => Some(value),
 			Some(&None) | None => None,
 		}
 	}
@@ -85,7 +88,7 @@ impl<T> IndexMap<T> {
 			existing
 		};
 		if mem::size_of::<usize>() > 4 {
-			debug_assert!(self.entries.len() <= (u32::max_value() as usize) + 1);
+			debug_assert!(self.entries.len() <= (u32::MAX as usize) + 1);
 		}
 		#[cfg(slow_assertions)]
 		debug_assert_eq!(self.len, self.slow_len());
@@ -130,7 +133,7 @@ impl<T> IndexMap<T> {
 	}
 
 	/// Create a non-consuming iterator over this `IndexMap`'s keys and values.
-	pub fn iter(&self) -> Iter<T> {
+	pub fn iter(&self) -> Iter<'_, T> {
 		// Note that this does the right thing because we use `&self`.
 		self.into_iter()
 	}
@@ -154,7 +157,8 @@ impl<T> IndexMap<T> {
 		F: Fn(u32, &mut R) -> Result<T, Error>,
 	{
 		let len: u32 = VarUint32::deserialize(rdr)?.into();
-		let mut map = IndexMap::with_capacity(len as usize);
+		// The declared length is not trusted for the allocation.
+		let mut map = IndexMap::with_capacity(core::cmp::min(len as usize, max_entry_space));
 		let mut prev_idx = None;
 		for _ in 0..len {
 			let idx: u32 = VarUint32::deserialize(rdr)?.into();
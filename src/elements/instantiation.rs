@@ -0,0 +1,123 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use super::{Error, Module};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+	Unvisited,
+	Visiting,
+	Done,
+}
+
+/// Order in which named modules have to be instantiated so that every module is
+/// instantiated after the modules it imports from.
+///
+/// Returns indices into `modules`. Imports from module names not in `modules`, e.g. host
+/// functions under `env`, are ignored, and modules without mutual dependencies keep their
+/// relative order. Fails with [`Error::ImportCycle`] if modules import from each other in a
+/// cycle, including a module importing from itself, and with [`Error::DuplicateModuleName`] if
+/// two modules have the same name.
+pub fn instantiation_order(modules: &[(&str, &Module)]) -> Result<Vec<usize>, Error> {
+	let mut by_name = BTreeMap::new();
+	for (index, (name, _)) in modules.iter().enumerate() {
+		if by_name.insert(*name, index).is_some() {
+			return Err(Error::DuplicateModuleName((*name).into()))
+		}
+	}
+	let dependencies: Vec<Vec<usize>> = modules
+		.iter()
+		.map(|(_, module)| {
+			let mut dependencies: Vec<usize> = module
+				.import_section()
+				.map(|s| s.entries())
+				.unwrap_or(&[])
+				.iter()
+				.filter_map(|entry| by_name.get(entry.module()).copied())
+				.collect();
+			dependencies.sort_unstable();
+			dependencies.dedup();
+			dependencies
+		})
+		.collect();
+
+	let mut marks = vec![Mark::Unvisited; modules.len()];
+	let mut order = Vec::with_capacity(modules.len());
+	for root in 0..modules.len() {
+		if marks[root] != Mark::Unvisited {
+			continue
+		}
+		// Depth-first search keeping the current path on an explicit stack, with the position
+		// of the next dependency to visit for every module on it.
+		let mut path = vec![(root, 0)];
+		marks[root] = Mark::Visiting;
+		while let Some((module, next)) = path.last_mut() {
+			let module = *module;
+			match dependencies[module].get(*next) {
+				Some(&dependency) => {
+					*next += 1;
+					match marks[dependency] {
+						Mark::Unvisited => {
+							marks[dependency] = Mark::Visiting;
+							path.push((dependency, 0));
+						},
+						Mark::Visiting => {
+							let start =
+								path.iter().position(|(m, _)| *m == dependency).unwrap_or(0);
+							let mut cycle: Vec<String> =
+								path[start..].iter().map(|(m, _)| modules[*m].0.into()).collect();
+							cycle.push(modules[dependency].0.into());
+							return Err(Error::ImportCycle(cycle))
+						},
+						Mark::Done => {},
+					}
+				},
+				None => {
+					marks[module] = Mark::Done;
+					order.push(module);
+					path.pop();
+				},
+			}
+		}
+	}
+	Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::instantiation_order;
+	use crate::{
+		builder,
+		elements::{Error, Module},
+	};
+
+	fn importing(modules: &[&str]) -> Module {
+		let mut module = builder::module();
+		for name in modules {
+			module = module.import().module(name).field("f").external().func(0).build();
+		}
+		module.function().signature().build().build().build()
+	}
+
+	#[test]
+	fn order() {
+		let app = importing(&["lib", "env"]);
+		let lib = importing(&["core"]);
+		let core = importing(&["env"]);
+		let standalone = importing(&[]);
+		let modules = [("app", &app), ("standalone", &standalone), ("lib", &lib), ("core", &core)];
+		assert_eq!(instantiation_order(&modules).expect("no cycles"), vec![3, 2, 0, 1]);
+
+		let core = importing(&["app"]);
+		let modules = [("app", &app), ("lib", &lib), ("core", &core)];
+		match instantiation_order(&modules) {
+			Err(Error::ImportCycle(cycle)) => assert_eq!(cycle, ["app", "lib", "core", "app"]),
+			other => panic!("expected a cycle, got {:?}", other),
+		}
+
+		let modules = [("app", &app), ("app", &lib)];
+		assert!(matches!(
+			instantiation_order(&modules),
+			Err(Error::DuplicateModuleName(name)) if name == "app"
+		));
+	}
+}
@@ -0,0 +1,125 @@
+use crate::io;
+
+use super::{Deserialize, Error, Instruction, Serialize, MAX_NESTING_DEPTH};
+
+/// Decoder of an instruction sequence, one instruction at a time.
+///
+/// Reads up to and including the `end` closing the sequence, e.g. a function body after
+/// its locals or a constant expression, without collecting the instructions. As an iterator
+/// it stops after that `end` or the first error.
+#[derive(Debug)]
+pub struct OpcodeReader<R> {
+	reader: R,
+	depth: usize,
+}
+
+impl<R: io::Read> OpcodeReader<R> {
+	/// Decode the instruction sequence at the current position of `reader`.
+	pub fn new(reader: R) -> Self {
+		OpcodeReader { reader, depth: 1 }
+	}
+
+	/// Decode the next instruction, or return `None` once the sequence is complete.
+	pub fn read(&mut self) -> Result<Option<Instruction>, Error> {
+		if self.depth == 0 {
+			return Ok(None)
+		}
+		let instruction = Instruction::deserialize(&mut self.reader)?;
+		if instruction.is_terminal() {
+			self.depth -= 1;
+		} else if instruction.is_block() {
+			if self.depth > MAX_NESTING_DEPTH {
+				return Err(Error::NestingTooDeep(MAX_NESTING_DEPTH))
+			}
+			self.depth += 1;
+		}
+		Ok(Some(instruction))
+	}
+
+	/// Whether the closing `end` has been read.
+	pub fn is_finished(&self) -> bool {
+		self.depth == 0
+	}
+
+	/// Underlying reader.
+	pub fn get_ref(&self) -> &R {
+		&self.reader
+	}
+
+	/// Unwrap the underlying reader, positioned after the last decoded instruction.
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+}
+
+impl<R: io::Read> Iterator for OpcodeReader<R> {
+	type Item = Result<Instruction, Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let result = self.read().transpose();
+		if let Some(Err(_)) = result {
+			self.depth = 0;
+		}
+		result
+	}
+}
+
+/// Encoder of instructions, one at a time.
+///
+/// Together with [`OpcodeReader`] it allows rewriting code without materializing it: decode
+/// instructions, write those to keep, and write others in place of those to patch. Nesting
+/// is not checked, so it is up to the caller to keep blocks balanced.
+#[derive(Debug)]
+pub struct OpcodeWriter<W> {
+	writer: W,
+}
+
+impl<W: io::Write> OpcodeWriter<W> {
+	/// Encode instructions into `writer`.
+	pub fn new(writer: W) -> Self {
+		OpcodeWriter { writer }
+	}
+
+	/// Encode one instruction.
+	pub fn write(&mut self, instruction: Instruction) -> Result<(), Error> {
+		instruction.serialize(&mut self.writer)
+	}
+
+	/// Unwrap the underlying writer.
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{OpcodeReader, OpcodeWriter};
+	use crate::{
+		elements::{serialize, BlockType, Instruction::*, Instructions},
+		io,
+	};
+
+	#[test]
+	fn patch() {
+		let code = Instructions::new(vec![Block(BlockType::NoResult), I32Const(1), Drop, End, End]);
+		let mut bytes = serialize(code).expect("code to serialize");
+		// Trailing data after the sequence is left unread.
+		bytes.push(0xff);
+
+		let mut reader = OpcodeReader::new(io::Cursor::new(&bytes[..]));
+		let mut writer = OpcodeWriter::new(Vec::new());
+		for instruction in &mut reader {
+			match instruction.expect("instruction to decode") {
+				I32Const(1) => writer.write(I32Const(1000)),
+				other => writer.write(other),
+			}
+			.expect("instruction to encode");
+		}
+		assert!(reader.is_finished());
+		assert_eq!(reader.into_inner().position(), bytes.len() - 1);
+
+		let expected =
+			Instructions::new(vec![Block(BlockType::NoResult), I32Const(1000), Drop, End, End]);
+		assert_eq!(writer.into_inner(), serialize(expected).expect("code to serialize"));
+	}
+}
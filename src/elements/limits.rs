@@ -0,0 +1,303 @@
+use super::{
+	code_offsets, CodeSection, Error, FunctionOffsets, Instruction, Module, Section, Type,
+	MAX_NESTING_DEPTH,
+};
+
+/// Implementation limits enforced by [`ValidatorConfig::validate`].
+///
+/// The defaults are the implementation limits of the WebAssembly JavaScript API, so that
+/// modules web embeddings accept pass; the API sets no limit on `br_table` targets, which
+/// defaults to one million. Lower them to bound the resources a runtime commits to an untrusted module
+/// before running it; a single function declaring billions of locals is a valid module, but
+/// not one worth instantiating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidatorConfig {
+	/// Maximum number of parameters of a function type.
+	pub max_params: usize,
+	/// Maximum number of results of a function type.
+	pub max_results: usize,
+	/// Maximum number of locals a function body declares, parameters excluded.
+	pub max_locals: usize,
+	/// Maximum size of an encoded function body in bytes, its size prefix excluded.
+	pub max_function_size: usize,
+	/// Maximum number of functions, imported ones included.
+	pub max_functions: usize,
+	/// Maximum number of imports.
+	pub max_imports: usize,
+	/// Maximum number of exports.
+	pub max_exports: usize,
+	/// Maximum number of targets of a `br_table` instruction, the default one excluded.
+	pub max_br_table_targets: usize,
+}
+
+impl Default for ValidatorConfig {
+	fn default() -> Self {
+		ValidatorConfig {
+			max_params: 1_000,
+			max_results: 1_000,
+			max_locals: 50_000,
+			max_function_size: 7_654_321,
+			max_functions: 1_000_000,
+			max_imports: 100_000,
+			max_exports: 100_000,
+			max_br_table_targets: 1_000_000,
+		}
+	}
+}
+
+fn check(limit: &'static str, value: usize, max: usize) -> Result<(), Error> {
+	if value > max {
+		return Err(Error::LimitExceeded { limit, value, max })
+	}
+	Ok(())
+}
+
+/// Size of an encoded function body, without the size prefix in its shortest form.
+fn body_size(offsets: &FunctionOffsets) -> usize {
+	let total = u64::from(offsets.end() - offsets.body());
+	let mut prefix = 1;
+	while total - prefix >= 1 << (7 * prefix) {
+		prefix += 1;
+	}
+	(total - prefix) as usize
+}
+
+impl ValidatorConfig {
+	/// Check `module` against the limits.
+	///
+	/// Lazily decoded code sections have to be parsed first, otherwise
+	/// [`Error::UnparsedCode`] is returned.
+	pub fn validate(&self, module: &Module) -> Result<(), Error> {
+		if module.lazy_code_section().is_some() {
+			return Err(Error::UnparsedCode)
+		}
+
+		let imports = module.import_section().map(|s| s.entries().len()).unwrap_or(0);
+		check("imports", imports, self.max_imports)?;
+		let exports = module.export_section().map(|s| s.entries().len()).unwrap_or(0);
+		check("exports", exports, self.max_exports)?;
+		check("functions", module.functions_space(), self.max_functions)?;
+
+		for Type::Function(func_type) in module.type_section().map(|s| s.types()).unwrap_or(&[]) {
+			check("params", func_type.params().len(), self.max_params)?;
+			check("results", func_type.results().len(), self.max_results)?;
+		}
+
+		let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+		for (body, offsets) in bodies.iter().zip(code_offsets(module)?) {
+			let locals = body
+				.locals()
+				.iter()
+				.try_fold(0u32, |acc, local| acc.checked_add(local.count()))
+				.ok_or(Error::TooManyLocals)?;
+			check("locals", locals as usize, self.max_locals)?;
+			check("function body bytes", body_size(&offsets), self.max_function_size)?;
+
+			for instruction in body.code().elements() {
+				if let Instruction::BrTable(table) = instruction {
+					check("br_table targets", table.table.len(), self.max_br_table_targets)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Decoding budget for [`Module::from_bytes_with_config`].
+///
+/// Decoding never allocates ahead of the input, so memory use is bounded by `max_size`; the
+/// other limits reject modules that are cheap to send but expensive to process further.
+///
+/// Names are always checked to be valid UTF-8 and known sections to appear at most once and
+/// in order. Integers may be padded with redundant bytes, as the specification allows, unless
+/// `strict` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializeConfig {
+	/// Maximum size of the encoded module in bytes.
+	pub max_size: usize,
+	/// Maximum nesting depth of blocks in a function body, capped at [`MAX_NESTING_DEPTH`].
+	pub max_nesting_depth: usize,
+	/// Maximum number of entries in a single section.
+	pub max_section_entries: usize,
+	/// Reject modules that are not encoded in the shortest form, e.g. with padded LEB128
+	/// integers. Checked by encoding the module again, which doubles the cost of decoding.
+	pub strict: bool,
+}
+
+impl Default for DeserializeConfig {
+	fn default() -> Self {
+		DeserializeConfig {
+			max_size: 1 << 30,
+			max_nesting_depth: MAX_NESTING_DEPTH,
+			max_section_entries: 1_000_000,
+			strict: false,
+		}
+	}
+}
+
+fn section_entries(section: &Section) -> usize {
+	match *section {
+		Section::Type(ref section) => section.types().len(),
+		Section::Import(ref section) => section.entries().len(),
+		Section::Function(ref section) => section.entries().len(),
+		Section::Table(ref section) => section.entries().len(),
+		Section::Memory(ref section) => section.entries().len(),
+		#[cfg(feature = "exceptions")]
+		Section::Tag(ref section) => section.entries().len(),
+		Section::Global(ref section) => section.entries().len(),
+		Section::Export(ref section) => section.entries().len(),
+		Section::Element(ref section) => section.entries().len(),
+		Section::Code(ref section) => section.bodies().len(),
+		Section::LazyCode(ref section) => section.bodies().len(),
+		Section::Data(ref section) => section.entries().len(),
+		_ => 0,
+	}
+}
+
+impl DeserializeConfig {
+	/// Decode a module within the budget.
+	pub(crate) fn deserialize(&self, input: &[u8]) -> Result<Module, Error> {
+		check("bytes", input.len(), self.max_size)?;
+		let mut module = Module::from_bytes_lazy(input)?;
+		let max_depth = core::cmp::min(self.max_nesting_depth, MAX_NESTING_DEPTH);
+		for section in module.sections_mut() {
+			check("section entries", section_entries(section), self.max_section_entries)?;
+			if let Section::LazyCode(ref code_section) = *section {
+				let bodies = code_section
+					.bodies()
+					.iter()
+					.map(|body| body.parse_with_depth(max_depth))
+					.collect::<Result<_, _>>()?;
+				*section = Section::Code(CodeSection::with_bodies(bodies));
+			}
+		}
+		if self.strict {
+			let encoded = module.clone().into_bytes()?;
+			let mismatch = input
+				.iter()
+				.zip(&encoded)
+				.position(|(original, canonical)| original != canonical);
+			if let Some(offset) = mismatch.or_else(|| {
+				(input.len() != encoded.len()).then(|| core::cmp::min(input.len(), encoded.len()))
+			}) {
+				return Err(Error::NonCanonicalEncoding(offset))
+			}
+		}
+		Ok(module)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{DeserializeConfig, ValidatorConfig};
+	use crate::{
+		builder,
+		elements::{
+			BlockType, BrTableData, Error, Instruction::*, Instructions, Local, Module, ValueType,
+		},
+	};
+
+	#[test]
+	fn limits() {
+		let module = builder::module()
+			.function()
+			.signature()
+			.with_params(vec![ValueType::I32; 3])
+			.build()
+			.body()
+			.with_locals(vec![Local::new(10, ValueType::I64), Local::new(10, ValueType::I32)])
+			.with_instructions(Instructions::new(vec![
+				Block(BlockType::NoResult),
+				GetLocal(0),
+				BrTable(Box::new(BrTableData { table: Box::new([0, 0, 0]), default: 0 })),
+				End,
+				End,
+			]))
+			.build()
+			.build()
+			.build();
+
+		assert!(ValidatorConfig::default().validate(&module).is_ok());
+
+		let config = ValidatorConfig { max_locals: 19, ..Default::default() };
+		assert!(matches!(
+			config.validate(&module),
+			Err(Error::LimitExceeded { limit: "locals", value: 20, max: 19 })
+		));
+		let config = ValidatorConfig { max_params: 2, ..Default::default() };
+		assert!(matches!(
+			config.validate(&module),
+			Err(Error::LimitExceeded { limit: "params", .. })
+		));
+		let config = ValidatorConfig { max_br_table_targets: 2, ..Default::default() };
+		assert!(matches!(
+			config.validate(&module),
+			Err(Error::LimitExceeded { limit: "br_table targets", .. })
+		));
+		let config = ValidatorConfig { max_function_size: 16, ..Default::default() };
+		assert!(matches!(
+			config.validate(&module),
+			Err(Error::LimitExceeded { limit: "function body bytes", value: 17, max: 16 })
+		));
+		let config = ValidatorConfig { max_function_size: 17, ..Default::default() };
+		assert!(config.validate(&module).is_ok());
+		let config = ValidatorConfig { max_functions: 0, ..Default::default() };
+		assert!(config.validate(&module).is_err());
+	}
+
+	#[test]
+	fn bounded_deserialization() {
+		let mut code = vec![Block(BlockType::NoResult); 3];
+		code.extend(vec![End; 4]);
+		let mut module = builder::module();
+		module.push_function(
+			builder::function()
+				.signature()
+				.build()
+				.body()
+				.with_instructions(Instructions::new(code))
+				.build()
+				.build(),
+		);
+		module.push_function(builder::function().signature().build().build());
+		let bytes = module.build().into_bytes().expect("module to serialize");
+
+		let module = Module::from_bytes_with_config(&bytes, &DeserializeConfig::default())
+			.expect("module within the default budget");
+		assert!(module.code_section().is_some());
+
+		let config = DeserializeConfig { max_size: bytes.len() - 1, ..Default::default() };
+		assert!(matches!(
+			Module::from_bytes_with_config(&bytes, &config),
+			Err(Error::LimitExceeded { limit: "bytes", .. })
+		));
+		let config = DeserializeConfig { max_nesting_depth: 2, ..Default::default() };
+		assert!(matches!(
+			Module::from_bytes_with_config(&bytes, &config),
+			Err(Error::NestingTooDeep(2))
+		));
+		let config = DeserializeConfig { max_section_entries: 1, ..Default::default() };
+		assert!(matches!(
+			Module::from_bytes_with_config(&bytes, &config),
+			Err(Error::LimitExceeded { limit: "section entries", value: 2, max: 1 })
+		));
+	}
+
+	#[test]
+	fn strict_deserialization() {
+		let module = builder::module().function().signature().build().build().build();
+		let mut bytes = module.into_bytes().expect("module to serialize");
+		let strict = DeserializeConfig { strict: true, ..Default::default() };
+		assert!(Module::from_bytes_with_config(&bytes, &strict).is_ok());
+
+		// Pad the size of the type section with a redundant byte.
+		assert_eq!(bytes[8], 1);
+		bytes[9] |= 0x80;
+		bytes.insert(10, 0x00);
+		assert!(Module::from_bytes_with_config(&bytes, &DeserializeConfig::default()).is_ok());
+		assert!(matches!(
+			Module::from_bytes_with_config(&bytes, &strict),
+			Err(Error::NonCanonicalEncoding(9))
+		));
+	}
+}
@@ -0,0 +1,496 @@
+use crate::io;
+use alloc::{string::String, vec::Vec};
+
+use super::{
+	deserialize_buffer, section::ENTRIES_BUFFER_LENGTH, serialize, CountedList, CountedListWriter,
+	CountedWriter, Deserialize, Error, Serialize, VarUint32, VarUint7,
+};
+
+const LINKING_VERSION: u32 = 2;
+
+const SUBSECTION_SEGMENT_INFO: u8 = 5;
+const SUBSECTION_INIT_FUNCS: u8 = 6;
+const SUBSECTION_COMDAT_INFO: u8 = 7;
+const SUBSECTION_SYMBOL_TABLE: u8 = 8;
+
+const SYMBOL_FUNCTION: u8 = 0;
+const SYMBOL_DATA: u8 = 1;
+const SYMBOL_GLOBAL: u8 = 2;
+const SYMBOL_SECTION: u8 = 3;
+const SYMBOL_TAG: u8 = 4;
+const SYMBOL_TABLE: u8 = 5;
+
+/// Symbol flag marking a symbol that is not defined in this object.
+pub const SYMBOL_UNDEFINED: u32 = 0x10;
+/// Symbol flag marking an undefined symbol that still carries a name.
+pub const SYMBOL_EXPLICIT_NAME: u32 = 0x40;
+
+/// Linking metadata of a relocatable object file (the `linking` custom section).
+///
+/// Note that initially it is not parsed until `parse_linking` is called explicitly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkingSection {
+	version: u32,
+	subsections: Vec<LinkingSubsection>,
+}
+
+impl LinkingSection {
+	/// New section of the current metadata version.
+	pub fn new(subsections: Vec<LinkingSubsection>) -> Self {
+		LinkingSection { version: LINKING_VERSION, subsections }
+	}
+
+	/// Version of the linking metadata.
+	pub fn version(&self) -> u32 {
+		self.version
+	}
+
+	/// Subsections in the order they appear.
+	pub fn subsections(&self) -> &[LinkingSubsection] {
+		&self.subsections
+	}
+
+	/// Subsections in the order they appear (mutable).
+	pub fn subsections_mut(&mut self) -> &mut Vec<LinkingSubsection> {
+		&mut self.subsections
+	}
+
+	/// Entries of the symbol table subsection, if present.
+	pub fn symbol_table(&self) -> Option<&[SymbolInfo]> {
+		self.subsections.iter().find_map(|subsection| match subsection {
+			LinkingSubsection::SymbolTable(symbols) => Some(&symbols[..]),
+			_ => None,
+		})
+	}
+}
+
+impl LinkingSection {
+	/// Deserialize a linking section from its payload, following the section name.
+	pub fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Error> {
+		let version = VarUint32::deserialize(rdr)?.into();
+		if version != LINKING_VERSION {
+			return Err(Error::UnsupportedLinkingVersion(version))
+		}
+
+		let mut subsections = Vec::new();
+		loop {
+			// The subsections extend to the end of the payload.
+			let mut id = [0u8; 1];
+			if rdr.read(&mut id).is_err() {
+				break
+			}
+			let length: usize = u32::from(VarUint32::deserialize(rdr)?) as usize;
+			let payload = buffered_read!(ENTRIES_BUFFER_LENGTH, length, rdr);
+			subsections.push(match id[0] {
+				SUBSECTION_SEGMENT_INFO => LinkingSubsection::SegmentInfo(read_list(&payload)?),
+				SUBSECTION_INIT_FUNCS => LinkingSubsection::InitFuncs(read_list(&payload)?),
+				SUBSECTION_COMDAT_INFO => LinkingSubsection::ComdatInfo(read_list(&payload)?),
+				SUBSECTION_SYMBOL_TABLE => LinkingSubsection::SymbolTable(read_list(&payload)?),
+				id => LinkingSubsection::Unknown { id, payload },
+			});
+		}
+
+		Ok(LinkingSection { version, subsections })
+	}
+}
+
+fn read_list<T: Deserialize<Error = Error>>(payload: &[u8]) -> Result<Vec<T>, Error> {
+	Ok(deserialize_buffer::<CountedList<T>>(payload)?.into_inner())
+}
+
+fn write_list<T: Serialize<Error = Error>>(entries: Vec<T>) -> Result<Vec<u8>, Error> {
+	serialize(CountedListWriter(entries.len(), entries.into_iter()))
+}
+
+impl Serialize for LinkingSection {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		let mut counted_writer = CountedWriter::new(wtr);
+
+		String::from("linking").serialize(&mut counted_writer)?;
+		VarUint32::from(self.version).serialize(&mut counted_writer)?;
+
+		for subsection in self.subsections {
+			let (id, payload) = match subsection {
+				LinkingSubsection::SegmentInfo(entries) =>
+					(SUBSECTION_SEGMENT_INFO, write_list(entries)?),
+				LinkingSubsection::InitFuncs(entries) =>
+					(SUBSECTION_INIT_FUNCS, write_list(entries)?),
+				LinkingSubsection::ComdatInfo(entries) =>
+					(SUBSECTION_COMDAT_INFO, write_list(entries)?),
+				LinkingSubsection::SymbolTable(entries) =>
+					(SUBSECTION_SYMBOL_TABLE, write_list(entries)?),
+				LinkingSubsection::Unknown { id, payload } => (id, payload),
+			};
+			VarUint7::from(id).serialize(&mut counted_writer)?;
+			VarUint32::from(payload.len()).serialize(&mut counted_writer)?;
+			io::Write::write(&mut counted_writer, &payload)?;
+		}
+
+		counted_writer.done()?;
+
+		Ok(())
+	}
+}
+
+/// Subsection of the linking section.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkingSubsection {
+	/// Names, alignments and flags of data segments.
+	SegmentInfo(Vec<SegmentInfo>),
+	/// Functions to call on startup.
+	InitFuncs(Vec<InitFunc>),
+	/// Groups of items to deduplicate when linking.
+	ComdatInfo(Vec<Comdat>),
+	/// Symbols referenced by relocations.
+	SymbolTable(Vec<SymbolInfo>),
+	/// Subsection of a type not known to this crate, kept as is.
+	Unknown {
+		/// Subsection type.
+		id: u8,
+		/// Raw subsection contents.
+		payload: Vec<u8>,
+	},
+}
+
+/// Linking information of a data segment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentInfo {
+	/// Name of the segment.
+	pub name: String,
+	/// Alignment of the segment, as a power of two.
+	pub alignment: u32,
+	/// Segment flags.
+	pub flags: u32,
+}
+
+impl Deserialize for SegmentInfo {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let name = String::deserialize(rdr)?;
+		let alignment = VarUint32::deserialize(rdr)?.into();
+		let flags = VarUint32::deserialize(rdr)?.into();
+		Ok(SegmentInfo { name, alignment, flags })
+	}
+}
+
+impl Serialize for SegmentInfo {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		self.name.serialize(wtr)?;
+		VarUint32::from(self.alignment).serialize(wtr)?;
+		VarUint32::from(self.flags).serialize(wtr)
+	}
+}
+
+/// Function to call on startup.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InitFunc {
+	/// Priority; lower values are called first.
+	pub priority: u32,
+	/// Index of the function symbol in the symbol table.
+	pub symbol: u32,
+}
+
+impl Deserialize for InitFunc {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let priority = VarUint32::deserialize(rdr)?.into();
+		let symbol = VarUint32::deserialize(rdr)?.into();
+		Ok(InitFunc { priority, symbol })
+	}
+}
+
+impl Serialize for InitFunc {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		VarUint32::from(self.priority).serialize(wtr)?;
+		VarUint32::from(self.symbol).serialize(wtr)
+	}
+}
+
+/// Group of items of which the linker keeps a single copy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comdat {
+	/// Name of the group.
+	pub name: String,
+	/// Group flags.
+	pub flags: u32,
+	/// Items in the group.
+	pub members: Vec<ComdatMember>,
+}
+
+impl Deserialize for Comdat {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let name = String::deserialize(rdr)?;
+		let flags = VarUint32::deserialize(rdr)?.into();
+		let members = CountedList::deserialize(rdr)?.into_inner();
+		Ok(Comdat { name, flags, members })
+	}
+}
+
+impl Serialize for Comdat {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		self.name.serialize(wtr)?;
+		VarUint32::from(self.flags).serialize(wtr)?;
+		CountedListWriter(self.members.len(), self.members.into_iter()).serialize(wtr)
+	}
+}
+
+/// Item of a [`Comdat`] group.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComdatMember {
+	/// Kind of the item (data segment, function, global, tag, table or section).
+	pub kind: u8,
+	/// Index of the item in its index space.
+	pub index: u32,
+}
+
+impl Deserialize for ComdatMember {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let kind = VarUint7::deserialize(rdr)?.into();
+		let index = VarUint32::deserialize(rdr)?.into();
+		Ok(ComdatMember { kind, index })
+	}
+}
+
+impl Serialize for ComdatMember {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		VarUint7::from(self.kind).serialize(wtr)?;
+		VarUint32::from(self.index).serialize(wtr)
+	}
+}
+
+/// Symbol table entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolInfo {
+	/// Symbol flags, such as [`SYMBOL_UNDEFINED`].
+	pub flags: u32,
+	/// The symbol itself.
+	pub symbol: Symbol,
+}
+
+impl SymbolInfo {
+	/// Whether the symbol is defined outside of this object.
+	pub fn is_undefined(&self) -> bool {
+		self.flags & SYMBOL_UNDEFINED != 0
+	}
+
+	fn has_name(&self) -> bool {
+		!self.is_undefined() || self.flags & SYMBOL_EXPLICIT_NAME != 0
+	}
+}
+
+/// Symbol referring to an item of the module.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Symbol {
+	/// Function symbol.
+	Function {
+		/// Index in the function index space.
+		index: u32,
+		/// Symbol name; absent for undefined symbols without an explicit name.
+		name: Option<String>,
+	},
+	/// Data symbol.
+	Data {
+		/// Symbol name.
+		name: String,
+		/// Location of the data; absent for undefined symbols.
+		definition: Option<DataDefinition>,
+	},
+	/// Global symbol.
+	Global {
+		/// Index in the global index space.
+		index: u32,
+		/// Symbol name; absent for undefined symbols without an explicit name.
+		name: Option<String>,
+	},
+	/// Section symbol.
+	Section {
+		/// Index of the section.
+		index: u32,
+	},
+	/// Tag symbol.
+	Tag {
+		/// Index in the tag index space.
+		index: u32,
+		/// Symbol name; absent for undefined symbols without an explicit name.
+		name: Option<String>,
+	},
+	/// Table symbol.
+	Table {
+		/// Index in the table index space.
+		index: u32,
+		/// Symbol name; absent for undefined symbols without an explicit name.
+		name: Option<String>,
+	},
+}
+
+/// Location of a defined data symbol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DataDefinition {
+	/// Index of the data segment.
+	pub segment: u32,
+	/// Offset within the segment.
+	pub offset: u32,
+	/// Size of the data.
+	pub size: u32,
+}
+
+impl Deserialize for SymbolInfo {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(rdr: &mut R) -> Result<Self, Self::Error> {
+		let kind: u8 = VarUint7::deserialize(rdr)?.into();
+		let flags = VarUint32::deserialize(rdr)?.into();
+		let mut info = SymbolInfo { flags, symbol: Symbol::Section { index: 0 } };
+		let has_name = info.has_name();
+
+		let index_and_name = |rdr: &mut R| -> Result<(u32, Option<String>), Error> {
+			let index = VarUint32::deserialize(rdr)?.into();
+			let name = if has_name { Some(String::deserialize(rdr)?) } else { None };
+			Ok((index, name))
+		};
+		info.symbol = match kind {
+			SYMBOL_FUNCTION => {
+				let (index, name) = index_and_name(rdr)?;
+				Symbol::Function { index, name }
+			},
+			SYMBOL_GLOBAL => {
+				let (index, name) = index_and_name(rdr)?;
+				Symbol::Global { index, name }
+			},
+			SYMBOL_TAG => {
+				let (index, name) = index_and_name(rdr)?;
+				Symbol::Tag { index, name }
+			},
+			SYMBOL_TABLE => {
+				let (index, name) = index_and_name(rdr)?;
+				Symbol::Table { index, name }
+			},
+			SYMBOL_DATA => {
+				let name = String::deserialize(rdr)?;
+				let definition = if info.is_undefined() {
+					None
+				} else {
+					Some(DataDefinition {
+						segment: VarUint32::deserialize(rdr)?.into(),
+						offset: VarUint32::deserialize(rdr)?.into(),
+						size: VarUint32::deserialize(rdr)?.into(),
+					})
+				};
+				Symbol::Data { name, definition }
+			},
+			SYMBOL_SECTION => Symbol::Section { index: VarUint32::deserialize(rdr)?.into() },
+			kind => return Err(Error::UnknownSymbolKind(kind)),
+		};
+		Ok(info)
+	}
+}
+
+impl Serialize for SymbolInfo {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, wtr: &mut W) -> Result<(), Error> {
+		let kind = match self.symbol {
+			Symbol::Function { .. } => SYMBOL_FUNCTION,
+			Symbol::Data { .. } => SYMBOL_DATA,
+			Symbol::Global { .. } => SYMBOL_GLOBAL,
+			Symbol::Section { .. } => SYMBOL_SECTION,
+			Symbol::Tag { .. } => SYMBOL_TAG,
+			Symbol::Table { .. } => SYMBOL_TABLE,
+		};
+		VarUint7::from(kind).serialize(wtr)?;
+		VarUint32::from(self.flags).serialize(wtr)?;
+
+		match self.symbol {
+			Symbol::Function { index, name } |
+			Symbol::Global { index, name } |
+			Symbol::Tag { index, name } |
+			Symbol::Table { index, name } => {
+				VarUint32::from(index).serialize(wtr)?;
+				if let Some(name) = name {
+					name.serialize(wtr)?;
+				}
+			},
+			Symbol::Data { name, definition } => {
+				name.serialize(wtr)?;
+				if let Some(definition) = definition {
+					VarUint32::from(definition.segment).serialize(wtr)?;
+					VarUint32::from(definition.offset).serialize(wtr)?;
+					VarUint32::from(definition.size).serialize(wtr)?;
+				}
+			},
+			Symbol::Section { index } => VarUint32::from(index).serialize(wtr)?,
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		super::{deserialize_file, Module, Section},
+		DataDefinition, InitFunc, LinkingSection, LinkingSubsection, SegmentInfo, Symbol,
+		SymbolInfo, SYMBOL_UNDEFINED,
+	};
+
+	#[test]
+	fn linking_section_roundtrip() {
+		let linking = LinkingSection::new(vec![
+			LinkingSubsection::SymbolTable(vec![
+				SymbolInfo {
+					flags: SYMBOL_UNDEFINED,
+					symbol: Symbol::Function { index: 0, name: None },
+				},
+				SymbolInfo {
+					flags: 0,
+					symbol: Symbol::Data {
+						name: "message".into(),
+						definition: Some(DataDefinition { segment: 0, offset: 4, size: 12 }),
+					},
+				},
+			]),
+			LinkingSubsection::SegmentInfo(vec![SegmentInfo {
+				name: ".rodata".into(),
+				alignment: 2,
+				flags: 0,
+			}]),
+			LinkingSubsection::InitFuncs(vec![InitFunc { priority: 65535, symbol: 0 }]),
+			LinkingSubsection::Unknown { id: 42, payload: vec![1, 2, 3] },
+		]);
+
+		let mut module =
+			deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		module.sections_mut().push(Section::Linking(linking.clone()));
+		let module = Module::from_bytes(module.into_bytes().expect("failed to serialize"))
+			.expect("failed to deserialize")
+			.parse_linking()
+			.expect("failed to parse linking section");
+
+		assert_eq!(module.linking_section(), Some(&linking));
+		assert_eq!(module.linking_section().unwrap().symbol_table().unwrap().len(), 2);
+	}
+
+	#[test]
+	fn unsupported_version() {
+		// relocatable.wasm was produced with the pre-versioned metadata format.
+		let module =
+			deserialize_file("./res/cases/v1/relocatable.wasm").expect("failed to deserialize");
+		assert!(module.parse_linking().is_err());
+	}
+}
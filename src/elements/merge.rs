@@ -0,0 +1,640 @@
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "multi_value")]
+use super::BlockType;
+#[cfg(feature = "bulk")]
+use super::BulkInstruction;
+use super::{
+	CodeSection, DataSection, DataSegment, ElementSection, ElementSegment, Error, ExportEntry,
+	ExportSection, External, Func, FuncBody, FunctionSection, FunctionType, GlobalEntry,
+	GlobalSection, ImportEntry, ImportSection, InitExpr, Instruction, Instructions, Internal,
+	MemorySection, Module, Section, TableSection, Type, TypeSection,
+};
+#[cfg(feature = "exceptions")]
+use super::{ExceptionsInstruction, TagSection, TagType};
+
+const FUNCTION: usize = 0;
+const TABLE: usize = 1;
+const MEMORY: usize = 2;
+const GLOBAL: usize = 3;
+#[cfg(feature = "exceptions")]
+const TAG: usize = 4;
+
+#[cfg(not(feature = "exceptions"))]
+const KINDS: usize = 4;
+#[cfg(feature = "exceptions")]
+const KINDS: usize = 5;
+
+fn external_kind(external: &External) -> usize {
+	match *external {
+		External::Function(_) => FUNCTION,
+		External::Table(_) => TABLE,
+		External::Memory(_) => MEMORY,
+		External::Global(_) => GLOBAL,
+		#[cfg(feature = "exceptions")]
+		External::Tag(_) => TAG,
+	}
+}
+
+fn internal_target(internal: &Internal) -> (usize, u32) {
+	match *internal {
+		Internal::Function(index) => (FUNCTION, index),
+		Internal::Table(index) => (TABLE, index),
+		Internal::Memory(index) => (MEMORY, index),
+		Internal::Global(index) => (GLOBAL, index),
+		#[cfg(feature = "exceptions")]
+		Internal::Tag(index) => (TAG, index),
+	}
+}
+
+fn defined_counts(module: &Module) -> [usize; KINDS] {
+	let mut counts = [0; KINDS];
+	counts[FUNCTION] = module.function_section().map(|s| s.entries().len()).unwrap_or(0);
+	counts[TABLE] = module.table_section().map(|s| s.entries().len()).unwrap_or(0);
+	counts[MEMORY] = module.memory_section().map(|s| s.entries().len()).unwrap_or(0);
+	counts[GLOBAL] = module.global_section().map(|s| s.entries().len()).unwrap_or(0);
+	#[cfg(feature = "exceptions")]
+	{
+		counts[TAG] = module.tag_section().map(|s| s.entries().len()).unwrap_or(0);
+	}
+	counts
+}
+
+/// Entry of a module index space before imports are resolved.
+#[derive(Clone, Copy)]
+enum Slot {
+	/// Index in the merged module.
+	Final(u32),
+	/// Import satisfied by the export of another module, given as (module, index).
+	Alias(usize, u32),
+}
+
+/// Index translation for one of the merged modules.
+struct Mapping {
+	types: Vec<u32>,
+	spaces: [Vec<u32>; KINDS],
+	#[cfg_attr(not(feature = "bulk"), allow(dead_code))]
+	data_base: u32,
+	#[cfg_attr(not(feature = "bulk"), allow(dead_code))]
+	element_base: u32,
+}
+
+impl Mapping {
+	fn index(&self, kind: usize, index: u32) -> Result<u32, Error> {
+		self.spaces[kind]
+			.get(index as usize)
+			.copied()
+			.ok_or_else(|| Error::Merge(format!("index {} is out of range", index)))
+	}
+
+	fn narrow_index(&self, kind: usize, index: u8) -> Result<u8, Error> {
+		let merged = self.index(kind, index as u32)?;
+		u8::try_from(merged)
+			.map_err(|_| Error::Merge(format!("index {} does not fit the encoding", merged)))
+	}
+
+	fn type_ref(&self, index: u32) -> Result<u32, Error> {
+		self.types
+			.get(index as usize)
+			.copied()
+			.ok_or_else(|| Error::Merge(format!("type index {} is out of range", index)))
+	}
+
+	#[cfg(feature = "multi_value")]
+	fn block_type(&self, block_type: &mut BlockType) -> Result<(), Error> {
+		if let BlockType::TypeIndex(index) = block_type {
+			*index = self.type_ref(*index)?;
+		}
+		Ok(())
+	}
+
+	fn instructions(&self, instructions: &mut [Instruction]) -> Result<(), Error> {
+		for instruction in instructions {
+			match instruction {
+				Instruction::Call(index) => *index = self.index(FUNCTION, *index)?,
+				Instruction::CallIndirect(type_ref, table) => {
+					*type_ref = self.type_ref(*type_ref)?;
+					*table = self.narrow_index(TABLE, *table)?;
+				},
+				Instruction::GetGlobal(index) | Instruction::SetGlobal(index) =>
+					*index = self.index(GLOBAL, *index)?,
+				#[cfg(not(feature = "multi_memory"))]
+				Instruction::CurrentMemory(memory) | Instruction::GrowMemory(memory) =>
+					*memory = self.narrow_index(MEMORY, *memory)?,
+				#[cfg(feature = "multi_memory")]
+				Instruction::CurrentMemory(memory) | Instruction::GrowMemory(memory) =>
+					*memory = self.index(MEMORY, *memory)?,
+				#[cfg(feature = "multi_memory")]
+				Instruction::MemoryIndexed(memory, _) => *memory = self.index(MEMORY, *memory)?,
+				#[cfg(feature = "multi_value")]
+				Instruction::Block(block_type) |
+				Instruction::Loop(block_type) |
+				Instruction::If(block_type) => self.block_type(block_type)?,
+				#[cfg(feature = "bulk")]
+				Instruction::Bulk(bulk) => match bulk {
+					BulkInstruction::MemoryInit(index) | BulkInstruction::MemoryDrop(index) =>
+						*index += self.data_base,
+					BulkInstruction::TableInit(index) | BulkInstruction::TableDrop(index) =>
+						*index += self.element_base,
+					_ => {},
+				},
+				#[cfg(all(feature = "exceptions", feature = "multi_value"))]
+				Instruction::Exceptions(ExceptionsInstruction::Try(block_type)) =>
+					self.block_type(block_type)?,
+				#[cfg(feature = "exceptions")]
+				Instruction::Exceptions(
+					ExceptionsInstruction::Catch(tag) | ExceptionsInstruction::Throw(tag),
+				) => *tag = self.index(TAG, *tag)?,
+				_ => {},
+			}
+		}
+		Ok(())
+	}
+
+	fn init_expr(&self, init_expr: &InitExpr) -> Result<InitExpr, Error> {
+		let mut code = init_expr.code().to_vec();
+		self.instructions(&mut code)?;
+		Ok(InitExpr::new(code))
+	}
+}
+
+/// Statically link `modules` into a single module.
+///
+/// Every module comes with the name other modules import it by. An import naming one of the
+/// other modules is replaced by a direct reference to the item that module exports under the
+/// imported field, which must be of the same kind (and, for functions and globals, of the same
+/// type). All other imports are kept, identical ones only once.
+///
+/// The merged module contains the types, functions, tables, memories, globals and segments of
+/// all modules, in order. Only the exports of the first module are kept. If more than one
+/// module has a start function, a new start function calling them in order is added. Custom
+/// sections, including names and relocations, are dropped because their indices would no
+/// longer be valid.
+///
+/// Loads and stores without an explicit memory index address memory 0, so the first memory of
+/// every module has to stay the first memory of the merged module, i.e. at most one memory may
+/// be defined or imported unless the others are shared through imports.
+///
+/// Code sections of modules decoded with [`Module::from_bytes_lazy`] have to be parsed with
+/// [`Module::parse_code`] first, otherwise [`Error::UnparsedCode`] is returned.
+pub fn merge(modules: Vec<(String, Module)>) -> Result<Module, Error> {
+	if modules.iter().any(|(_, module)| module.lazy_code_section().is_some()) {
+		return Err(Error::UnparsedCode)
+	}
+
+	let lookup_module = |name: &str, except: usize| {
+		modules
+			.iter()
+			.enumerate()
+			.position(|(index, (module_name, _))| index != except && module_name == name)
+	};
+
+	// Type section, with identical signatures shared between modules.
+	let mut types: Vec<FunctionType> = Vec::new();
+	let mut type_maps = Vec::new();
+	for (_, module) in &modules {
+		let module_types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+		let type_map = module_types
+			.iter()
+			.map(|Type::Function(func_type)| intern_type(&mut types, func_type))
+			.collect::<Vec<_>>();
+		type_maps.push(type_map);
+	}
+
+	// Imports that stay imports, and the index space slots of every module.
+	let mut imports: Vec<ImportEntry> = Vec::new();
+	let mut import_counts = [0u32; KINDS];
+	let mut slots: Vec<[Vec<Slot>; KINDS]> = Vec::new();
+	for (module_index, (_, module)) in modules.iter().enumerate() {
+		let mut module_slots: [Vec<Slot>; KINDS] = Default::default();
+		for entry in module.import_section().map(|s| s.entries()).unwrap_or(&[]) {
+			let kind = external_kind(entry.external());
+			let slot = match lookup_module(entry.module(), module_index) {
+				Some(target) =>
+					Slot::Alias(target, resolve_export(&modules, module_index, entry, target)?),
+				None => {
+					let mut external = *entry.external();
+					match external {
+						External::Function(ref mut type_ref) =>
+							*type_ref = lookup_type(&type_maps[module_index], *type_ref)?,
+						#[cfg(feature = "exceptions")]
+						External::Tag(ref mut tag_type) =>
+							*tag_type = TagType::new(lookup_type(
+								&type_maps[module_index],
+								tag_type.type_ref(),
+							)?),
+						_ => {},
+					}
+					let entry =
+						ImportEntry::new(entry.module().into(), entry.field().into(), external);
+					Slot::Final(match imports.iter().position(|existing| *existing == entry) {
+						Some(position) => imports[..position]
+							.iter()
+							.filter(|existing| external_kind(existing.external()) == kind)
+							.count() as u32,
+						None => {
+							imports.push(entry);
+							import_counts[kind] += 1;
+							import_counts[kind] - 1
+						},
+					})
+				},
+			};
+			module_slots[kind].push(slot);
+		}
+		slots.push(module_slots);
+	}
+
+	// Items defined by the modules follow all imports.
+	let mut next_defined = import_counts;
+	let mut data_base = 0;
+	let mut element_base = 0;
+	let mut bases = Vec::new();
+	for (module_index, (_, module)) in modules.iter().enumerate() {
+		let counts = defined_counts(module);
+		for kind in 0..KINDS {
+			for _ in 0..counts[kind] {
+				slots[module_index][kind].push(Slot::Final(next_defined[kind]));
+				next_defined[kind] += 1;
+			}
+		}
+		bases.push((data_base, element_base));
+		data_base += module.data_section().map(|s| s.entries().len()).unwrap_or(0) as u32;
+		element_base += module.elements_section().map(|s| s.entries().len()).unwrap_or(0) as u32;
+	}
+
+	let total_slots: usize = slots.iter().flat_map(|spaces| spaces.iter()).map(Vec::len).sum();
+	let mut mappings = Vec::new();
+	for (module_index, type_map) in type_maps.into_iter().enumerate() {
+		let mut spaces: [Vec<u32>; KINDS] = Default::default();
+		for (kind, space) in spaces.iter_mut().enumerate() {
+			for index in 0..slots[module_index][kind].len() {
+				space.push(resolve_slot(&slots, total_slots, module_index, kind, index as u32)?);
+			}
+		}
+		let (data_base, element_base) = bases[module_index];
+		mappings.push(Mapping { types: type_map, spaces, data_base, element_base });
+	}
+
+	for ((name, _), mapping) in modules.iter().zip(&mappings) {
+		let mut implicit_zero = vec![MEMORY];
+		if cfg!(feature = "bulk") {
+			implicit_zero.push(TABLE);
+		}
+		for kind in implicit_zero {
+			if mapping.spaces[kind].first().map_or(false, |index| *index != 0) {
+				return Err(Error::Merge(format!(
+					"memory or table 0 of module {} can not stay at index 0",
+					name
+				)))
+			}
+		}
+	}
+
+	let mut functions = Vec::new();
+	let mut bodies = Vec::new();
+	let mut tables = Vec::new();
+	let mut memories = Vec::new();
+	let mut globals = Vec::new();
+	#[cfg(feature = "exceptions")]
+	let mut tags = Vec::new();
+	let mut elements = Vec::new();
+	let mut data = Vec::new();
+	let mut starts = Vec::new();
+	let mut has_data_count = false;
+	let mut exports = Vec::new();
+
+	for (module_index, ((_, module), mapping)) in modules.iter().zip(&mappings).enumerate() {
+		for func in module.function_section().map(|s| s.entries()).unwrap_or(&[]) {
+			functions.push(Func::new(mapping.type_ref(func.type_ref())?));
+		}
+		for body in module.code_section().map(|s| s.bodies()).unwrap_or(&[]) {
+			let mut code = body.code().elements().to_vec();
+			mapping.instructions(&mut code)?;
+			bodies.push(FuncBody::new(body.locals().to_vec(), Instructions::new(code)));
+		}
+		tables.extend_from_slice(module.table_section().map(|s| s.entries()).unwrap_or(&[]));
+		memories.extend_from_slice(module.memory_section().map(|s| s.entries()).unwrap_or(&[]));
+		for global in module.global_section().map(|s| s.entries()).unwrap_or(&[]) {
+			globals.push(GlobalEntry::new(
+				*global.global_type(),
+				mapping.init_expr(global.init_expr())?,
+			));
+		}
+		#[cfg(feature = "exceptions")]
+		for tag in module.tag_section().map(|s| s.entries()).unwrap_or(&[]) {
+			tags.push(TagType::new(mapping.type_ref(tag.type_ref())?));
+		}
+		for segment in module.elements_section().map(|s| s.entries()).unwrap_or(&[]) {
+			let offset =
+				segment.offset().as_ref().map(|offset| mapping.init_expr(offset)).transpose()?;
+			let members = segment
+				.members()
+				.iter()
+				.map(|member| mapping.index(FUNCTION, *member))
+				.collect::<Result<Vec<_>, _>>()?;
+			#[allow(unused_mut)]
+			let mut merged = ElementSegment::new(mapping.index(TABLE, segment.index())?, offset, members);
+			#[cfg(feature = "bulk")]
+			{
+				merged.set_passive(segment.passive());
+				merged.set_declared(segment.declared());
+				merged.set_expressions(segment.expressions());
+			}
+			elements.push(merged);
+		}
+		for segment in module.data_section().map(|s| s.entries()).unwrap_or(&[]) {
+			let offset =
+				segment.offset().as_ref().map(|offset| mapping.init_expr(offset)).transpose()?;
+			#[allow(unused_mut)]
+			let mut merged = DataSegment::new(
+				mapping.index(MEMORY, segment.index())?,
+				offset,
+				segment.value().to_vec(),
+			);
+			#[cfg(feature = "bulk")]
+			merged.set_passive(segment.passive());
+			data.push(merged);
+		}
+		if let Some(start) = module.start_section() {
+			starts.push(mapping.index(FUNCTION, start)?);
+		}
+		has_data_count |= module.data_count_section().is_some();
+		if module_index == 0 {
+			for export in module.export_section().map(|s| s.entries()).unwrap_or(&[]) {
+				let (kind, index) = internal_target(export.internal());
+				let index = mapping.index(kind, index)?;
+				let internal = match kind {
+					FUNCTION => Internal::Function(index),
+					TABLE => Internal::Table(index),
+					MEMORY => Internal::Memory(index),
+					#[cfg(feature = "exceptions")]
+					TAG => Internal::Tag(index),
+					_ => Internal::Global(index),
+				};
+				exports.push(ExportEntry::new(export.field().into(), internal));
+			}
+		}
+	}
+
+	let start = match starts.len() {
+		0 => None,
+		1 => Some(starts[0]),
+		_ => {
+			let type_ref = intern_type(&mut types, &FunctionType::default());
+			functions.push(Func::new(type_ref));
+			let mut code = starts.into_iter().map(Instruction::Call).collect::<Vec<_>>();
+			code.push(Instruction::End);
+			bodies.push(FuncBody::new(Vec::new(), Instructions::new(code)));
+			Some(next_defined[FUNCTION])
+		},
+	};
+
+	let mut sections = Vec::new();
+	if !types.is_empty() {
+		sections.push(Section::Type(TypeSection::with_types(
+			types.into_iter().map(Type::Function).collect(),
+		)));
+	}
+	if !imports.is_empty() {
+		sections.push(Section::Import(ImportSection::with_entries(imports)));
+	}
+	if !functions.is_empty() {
+		sections.push(Section::Function(FunctionSection::with_entries(functions)));
+	}
+	if !tables.is_empty() {
+		sections.push(Section::Table(TableSection::with_entries(tables)));
+	}
+	if !memories.is_empty() {
+		sections.push(Section::Memory(MemorySection::with_entries(memories)));
+	}
+	#[cfg(feature = "exceptions")]
+	if !tags.is_empty() {
+		sections.push(Section::Tag(TagSection::with_entries(tags)));
+	}
+	if !globals.is_empty() {
+		sections.push(Section::Global(GlobalSection::with_entries(globals)));
+	}
+	if !exports.is_empty() {
+		sections.push(Section::Export(ExportSection::with_entries(exports)));
+	}
+	if let Some(start) = start {
+		sections.push(Section::Start(start));
+	}
+	if !elements.is_empty() {
+		sections.push(Section::Element(ElementSection::with_entries(elements)));
+	}
+	if has_data_count {
+		sections.push(Section::DataCount(data.len() as u32));
+	}
+	if !bodies.is_empty() {
+		sections.push(Section::Code(CodeSection::with_bodies(bodies)));
+	}
+	if !data.is_empty() {
+		sections.push(Section::Data(DataSection::with_entries(data)));
+	}
+	Ok(Module::new(sections))
+}
+
+fn intern_type(types: &mut Vec<FunctionType>, func_type: &FunctionType) -> u32 {
+	match types.iter().position(|existing| existing == func_type) {
+		Some(position) => position as u32,
+		None => {
+			types.push(func_type.clone());
+			types.len() as u32 - 1
+		},
+	}
+}
+
+fn lookup_type(type_map: &[u32], type_ref: u32) -> Result<u32, Error> {
+	type_map
+		.get(type_ref as usize)
+		.copied()
+		.ok_or_else(|| Error::Merge(format!("type index {} is out of range", type_ref)))
+}
+
+/// Index of the item `target` exports for `entry`, checking that it matches the import.
+fn resolve_export(
+	modules: &[(String, Module)],
+	importer: usize,
+	entry: &ImportEntry,
+	target: usize,
+) -> Result<u32, Error> {
+	let unresolved =
+		|| Error::Merge(format!("import {}.{} can not be resolved", entry.module(), entry.field()));
+	let importing = &modules[importer].1;
+	let exporting = &modules[target].1;
+	let export = exporting
+		.export_section()
+		.and_then(|s| s.entries().iter().find(|export| export.field() == entry.field()))
+		.ok_or_else(unresolved)?;
+	let (kind, index) = internal_target(export.internal());
+	if kind != external_kind(entry.external()) {
+		return Err(unresolved())
+	}
+	let compatible = match *entry.external() {
+		External::Function(type_ref) => {
+			let imported = importing.type_section().and_then(|s| s.types().get(type_ref as usize));
+			match (imported, exporting.function_type(index)) {
+				(Some(Type::Function(imported)), Some(exported)) => imported == exported,
+				_ => false,
+			}
+		},
+		External::Global(global_type) => exporting.global_type(index) == Some(global_type),
+		_ => true,
+	};
+	if !compatible {
+		return Err(Error::Merge(format!(
+			"import {}.{} does not match the type of the export",
+			entry.module(),
+			entry.field()
+		)))
+	}
+	Ok(index)
+}
+
+fn resolve_slot(
+	slots: &[[Vec<Slot>; KINDS]],
+	max_steps: usize,
+	mut module: usize,
+	kind: usize,
+	mut index: u32,
+) -> Result<u32, Error> {
+	for _ in 0..=max_steps {
+		match slots[module][kind].get(index as usize) {
+			Some(Slot::Final(merged)) => return Ok(*merged),
+			Some(Slot::Alias(target, target_index)) => {
+				module = *target;
+				index = *target_index;
+			},
+			None => return Err(Error::Merge(format!("index {} is out of range", index))),
+		}
+	}
+	Err(Error::Merge("imports and exports form a cycle".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::merge;
+	use crate::{
+		builder,
+		elements::{self, Error, Instruction::*, Internal, Module, ValueType},
+	};
+
+	fn runtime() -> Module {
+		builder::module()
+			.function()
+			.signature()
+			.param()
+			.i32()
+			.build()
+			.body()
+			.build()
+			.build()
+			.export()
+			.field("log")
+			.internal()
+			.func(0)
+			.build()
+			.build()
+	}
+
+	fn user() -> Module {
+		builder::module()
+			.import()
+			.module("env")
+			.field("abort")
+			.external()
+			.func(0)
+			.build()
+			.import()
+			.module("runtime")
+			.field("log")
+			.external()
+			.func(1)
+			.build()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(elements::Instructions::new(vec![
+				I32Const(7),
+				Call(1),
+				Call(0),
+				End,
+			]))
+			.build()
+			.build()
+			.with_signatures(vec![
+				builder::signature().build_sig(),
+				builder::signature().param().i32().build_sig(),
+			])
+			.export()
+			.field("main")
+			.internal()
+			.func(2)
+			.build()
+			.build()
+	}
+
+	#[test]
+	fn resolves_imports() {
+		let merged = merge(vec![("user".into(), user()), ("runtime".into(), runtime())])
+			.expect("modules to merge");
+
+		let imports = merged.import_section().expect("unresolved import to be kept").entries();
+		assert_eq!(imports.len(), 1);
+		assert_eq!((imports[0].module(), imports[0].field()), ("env", "abort"));
+
+		// abort stays import 0, main follows it and the runtime's log comes last.
+		let bodies = merged.code_section().expect("code section").bodies();
+		assert_eq!(bodies.len(), 2);
+		assert_eq!(bodies[0].code().elements(), &[I32Const(7), Call(2), Call(0), End]);
+		assert_eq!(merged.function_type(2).expect("log").params(), &[ValueType::I32]);
+		assert_eq!(merged.type_section().expect("type section").types().len(), 2);
+
+		let exports = merged.export_section().expect("exports of the first module").entries();
+		assert_eq!(exports.len(), 1);
+		assert_eq!(*exports[0].internal(), Internal::Function(1));
+	}
+
+	#[test]
+	fn signature_mismatch() {
+		let mut runtime = runtime();
+		runtime.type_section_mut().unwrap().types_mut()[0] =
+			elements::Type::Function(elements::FunctionType::new(vec![ValueType::I64], vec![]));
+		assert!(matches!(
+			merge(vec![("user".into(), user()), ("runtime".into(), runtime)]),
+			Err(Error::Merge(_))
+		));
+	}
+
+	#[test]
+	fn lazy_inputs() {
+		let bytes = runtime().into_bytes().expect("runtime to serialize");
+		let lazy = Module::from_bytes_lazy(&bytes).expect("runtime to deserialize");
+		assert!(matches!(
+			merge(vec![("user".into(), user()), ("runtime".into(), lazy.clone())]),
+			Err(Error::UnparsedCode)
+		));
+
+		let parsed = lazy.parse_code().expect("code to parse");
+		assert!(merge(vec![("user".into(), user()), ("runtime".into(), parsed)]).is_ok());
+	}
+
+	#[test]
+	fn start_functions() {
+		let with_start = |name: &str| {
+			let mut module =
+				builder::module().function().signature().build().body().build().build().build();
+			module.set_start_section(0);
+			(name.into(), module)
+		};
+		let merged = merge(vec![with_start("a"), with_start("b")]).expect("modules to merge");
+
+		assert_eq!(merged.start_section(), Some(2));
+		let bodies = merged.code_section().expect("code section").bodies();
+		assert_eq!(bodies[2].code().elements(), &[Call(0), Call(1), End]);
+		assert!(merged.import_section().is_none());
+	}
+}
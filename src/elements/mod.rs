@@ -24,37 +24,95 @@ macro_rules! buffered_read {
 	}};
 }
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod borrowed;
+mod callgraph;
+mod const_expr;
+mod coredump;
+mod coverage;
+mod diff;
+mod dylink_section;
+mod editor;
 mod export_entry;
 mod func;
+mod gas;
 mod global_entry;
 mod import_entry;
 mod index_map;
+mod instantiation;
+mod instruction_io;
+mod limits;
+mod linking_section;
+mod merge;
 mod module;
 mod name_section;
+mod offsets;
 mod ops;
+mod optimize;
 mod primitives;
+mod producers_section;
 mod reloc_section;
 mod section;
 mod segment;
+mod split;
+mod stack_height;
+mod stats;
+mod streaming;
+mod typed_section;
 mod types;
 
 pub use self::{
+	borrowed::{CustomSectionRef, DataSegmentRef, ExportRef, ImportRef, ModuleRef, SectionRef},
+	callgraph::CallGraph,
+	const_expr::{ConstExprEvaluator, ConstValue},
+	coredump::{CoreDump, CoreFrame, CoreThread},
+	coverage::{inject_coverage, CoverageMap, CoverageProbe},
+	diff::{diff, Change, ModuleDiff},
+	dylink_section::{DylinkImportInfo, DylinkMemInfo, DylinkSection, DylinkSymbolInfo},
+	editor::InstructionEditor,
 	export_entry::{ExportEntry, Internal},
+	gas::{inject_gas_counter, inject_gas_counter_with_offsets, ConstantCost, CostRules},
 	global_entry::GlobalEntry,
 	import_entry::{External, GlobalType, ImportEntry, MemoryType, ResizableLimits, TableType},
+	instantiation::instantiation_order,
+	instruction_io::{OpcodeReader, OpcodeWriter},
+	limits::{DeserializeConfig, ValidatorConfig},
+	linking_section::{
+		Comdat, ComdatMember, DataDefinition, InitFunc, LinkingSection, LinkingSubsection,
+		SegmentInfo, Symbol, SymbolInfo, SYMBOL_EXPLICIT_NAME, SYMBOL_UNDEFINED,
+	},
+	merge::merge,
 	module::{peek_size, ImportCountType, Module},
-	ops::{opcodes, BrTableData, InitExpr, Instruction, Instructions},
+	offsets::{
+		code_offsets, FunctionOffsets, InstructionsWithOffsets, OffsetMap, SourceLocation,
+		SourceLocator,
+	},
+	ops::{opcodes, BrTableData, InitExpr, Instruction, Instructions, MAX_NESTING_DEPTH},
+	optimize::optimize,
 	primitives::{
-		CountedList, CountedListWriter, CountedWriter, Uint32, Uint64, Uint8, VarInt32, VarInt64,
-		VarInt7, VarUint1, VarUint32, VarUint64, VarUint7,
+		read_varint32, read_varint64, read_varuint32, read_varuint64, write_varint32,
+		write_varint64, write_varuint32, write_varuint64, CountedList, CountedListWriter,
+		CountedWriter, Uint32, Uint64, Uint8, VarInt32, VarInt64, VarInt7, VarUint1, VarUint32,
+		VarUint64, VarUint7,
 	},
+	producers_section::{ProducerValue, ProducersField, ProducersSection, PROCESSED_BY},
 	section::{
 		CodeSection, CustomSection, DataSection, ElementSection, ExportSection, FunctionSection,
-		GlobalSection, ImportSection, MemorySection, Section, TableSection, TypeSection,
+		GlobalSection, ImportSection, LazyCodeSection, MemorySection, Section, TableSection,
+		TypeSection,
 	},
+	split::split,
+	stack_height::{inject_stack_limiter, inject_stack_limiter_with_offsets, STACK_DEPTH_EXPORT},
+	stats::{analyze, FunctionStats, ModuleStats, SectionStats},
+	streaming::StreamingDeserializer,
+	typed_section::{CustomSectionCodec, CustomSectionRegistry, TypedCustomSection},
 	types::{BlockType, FunctionType, TableElementType, Type, ValueType},
 };
 
+#[cfg(feature = "arbitrary")]
+pub use self::arbitrary::arbitrary_valid_module;
+
 #[cfg(feature = "atomics")]
 pub use self::ops::AtomicsInstruction;
 
@@ -64,14 +122,20 @@ pub use self::ops::SimdInstruction;
 #[cfg(feature = "sign_ext")]
 pub use self::ops::SignExtInstruction;
 
+#[cfg(feature = "sat_trunc")]
+pub use self::ops::SatTruncInstruction;
+
 #[cfg(feature = "bulk")]
 pub use self::ops::BulkInstruction;
 
+#[cfg(feature = "exceptions")]
+pub use self::{import_entry::TagType, ops::ExceptionsInstruction, section::TagSection};
+
 #[cfg(any(feature = "simd", feature = "atomics"))]
 pub use self::ops::MemArg;
 
 pub use self::{
-	func::{Func, FuncBody, Local},
+	func::{Func, FuncBody, Local, UnparsedFuncBody},
 	index_map::IndexMap,
 	name_section::{
 		FunctionNameSubsection, LocalNameSubsection, ModuleNameSubsection, NameMap, NameSection,
@@ -172,6 +236,74 @@ pub enum Error {
 	DuplicatedNameSubsections(u8),
 	/// Unknown name subsection type.
 	UnknownNameSubsectionType(u8),
+	/// Control blocks are nested deeper than the given limit.
+	NestingTooDeep(usize),
+	/// Unknown tag attribute (should be 0).
+	#[cfg(feature = "exceptions")]
+	UnknownTagAttribute(u8),
+	/// Function body contains an instruction rejected by a denylist.
+	DeniedInstruction {
+		/// Index of the function in the function index space.
+		function: u32,
+		/// Position of the instruction within the function body.
+		position: usize,
+		/// Byte offset of the instruction, relative to the start of the code section payload.
+		offset: u32,
+	},
+	/// Memory instruction has an alignment hint larger than allowed.
+	InvalidAlignment {
+		/// Index of the function in the function index space.
+		function: u32,
+		/// Position of the instruction within the function body.
+		position: usize,
+		/// Byte offset of the instruction, relative to the start of the code section payload.
+		offset: u32,
+	},
+	/// Modules could not be merged.
+	Merge(String),
+	/// Unsupported version of the linking section.
+	UnsupportedLinkingVersion(u32),
+	/// Unknown symbol kind in the linking section.
+	UnknownSymbolKind(u8),
+	/// Module uses floating point types.
+	FloatingPointDisabled,
+	/// Constant expression is malformed or can not be evaluated.
+	InvalidConstExpr,
+	/// Module exceeds a limit of the validator configuration.
+	LimitExceeded {
+		/// Name of the exceeded limit.
+		limit: &'static str,
+		/// Actual value.
+		value: usize,
+		/// Configured maximum.
+		max: usize,
+	},
+	/// Module is not encoded in the shortest form, first differing at the given byte offset.
+	NonCanonicalEncoding(usize),
+	/// Modules import from each other in a cycle, listed by name with the first repeated.
+	ImportCycle(Vec<String>),
+	/// Several modules are given the same name.
+	DuplicateModuleName(String),
+	/// Code section is decoded lazily and has to be parsed with `Module::parse_code` first.
+	UnparsedCode,
+	/// Index refers past the end of its index space.
+	IndexOutOfRange(u32),
+	/// Module has no export with the given name.
+	ExportNotFound(String),
+	/// Module has no import with the given module and field name.
+	ImportNotFound(String, String),
+	/// Module has more than one export with the given name.
+	DuplicateExport(String),
+	/// Instructions do not consist of complete blocks.
+	UnbalancedBlocks,
+	/// Instruction editor can not change the instruction at the given position.
+	InvalidEditPosition(usize),
+	/// Module has no `core` section and so is not a core dump.
+	NotCoreDump,
+	/// Core dump refers to an instance other than the first one.
+	UnsupportedInstance(u32),
+	/// Data segment does not fit the memory it initializes.
+	DataOutOfBounds,
 }
 
 impl fmt::Display for Error {
@@ -216,6 +348,46 @@ impl fmt::Display for Error {
 			Error::TooManyLocals => write!(f, "Too many locals"),
 			Error::DuplicatedNameSubsections(n) => write!(f, "Duplicated name subsections: {}", n),
 			Error::UnknownNameSubsectionType(n) => write!(f, "Unknown subsection type: {}", n),
+			Error::NestingTooDeep(limit) =>
+				write!(f, "Control blocks nested deeper than {} levels", limit),
+			#[cfg(feature = "exceptions")]
+			Error::UnknownTagAttribute(attribute) => write!(f, "Unknown tag attribute {}", attribute),
+			Error::DeniedInstruction { function, position, offset } => write!(
+				f,
+				"Denied instruction in function {} at position {} (offset {})",
+				function, position, offset
+			),
+			Error::InvalidAlignment { function, position, offset } => write!(
+				f,
+				"Invalid alignment in function {} at position {} (offset {})",
+				function, position, offset
+			),
+			Error::Merge(ref msg) => write!(f, "Failed to merge modules: {}", msg),
+			Error::UnsupportedLinkingVersion(version) =>
+				write!(f, "Unsupported linking section version {}", version),
+			Error::UnknownSymbolKind(kind) => write!(f, "Unknown symbol kind {}", kind),
+			Error::FloatingPointDisabled => write!(f, "Floating point types are disabled"),
+			Error::InvalidConstExpr => write!(f, "Invalid constant expression"),
+			Error::LimitExceeded { limit, value, max } =>
+				write!(f, "Number of {} ({}) exceeds the limit of {}", limit, value, max),
+			Error::NonCanonicalEncoding(offset) =>
+				write!(f, "Non-canonical encoding at offset {}", offset),
+			Error::ImportCycle(ref modules) =>
+				write!(f, "Cyclic imports between modules: {}", modules.join(" -> ")),
+			Error::DuplicateModuleName(ref name) => write!(f, "Duplicate module name {}", name),
+			Error::UnparsedCode => write!(f, "Lazy code section has to be parsed first"),
+			Error::IndexOutOfRange(index) => write!(f, "Index {} is out of range", index),
+			Error::ExportNotFound(ref name) => write!(f, "Export {} not found", name),
+			Error::ImportNotFound(ref module, ref field) =>
+				write!(f, "Import {}.{} not found", module, field),
+			Error::DuplicateExport(ref name) => write!(f, "Duplicate export {}", name),
+			Error::UnbalancedBlocks => write!(f, "Unbalanced block structure"),
+			Error::InvalidEditPosition(position) =>
+				write!(f, "Can not edit instruction at position {}", position),
+			Error::NotCoreDump => write!(f, "Module is not a core dump"),
+			Error::UnsupportedInstance(instance) =>
+				write!(f, "Unsupported core dump instance {}", instance),
+			Error::DataOutOfBounds => write!(f, "Data segment does not fit the memory"),
 		}
 	}
 }
@@ -259,6 +431,30 @@ impl ::std::error::Error for Error {
 			Error::TooManyLocals => "Too many locals",
 			Error::DuplicatedNameSubsections(_) => "Duplicated name subsections",
 			Error::UnknownNameSubsectionType(_) => "Unknown name subsections type",
+			Error::NestingTooDeep(_) => "Control blocks nested too deep",
+			#[cfg(feature = "exceptions")]
+			Error::UnknownTagAttribute(_) => "Unknown tag attribute",
+			Error::DeniedInstruction { .. } => "Denied instruction",
+			Error::InvalidAlignment { .. } => "Invalid alignment",
+			Error::Merge(ref msg) => &msg[..],
+			Error::UnsupportedLinkingVersion(_) => "Unsupported linking section version",
+			Error::UnknownSymbolKind(_) => "Unknown symbol kind",
+			Error::FloatingPointDisabled => "Floating point types are disabled",
+			Error::InvalidConstExpr => "Invalid constant expression",
+			Error::LimitExceeded { .. } => "Limit exceeded",
+			Error::NonCanonicalEncoding(_) => "Non-canonical encoding",
+			Error::ImportCycle(_) => "Cyclic imports between modules",
+			Error::DuplicateModuleName(_) => "Duplicate module name",
+			Error::UnparsedCode => "Lazy code section has to be parsed first",
+			Error::IndexOutOfRange(_) => "Index is out of range",
+			Error::ExportNotFound(_) => "Export not found",
+			Error::ImportNotFound(..) => "Import not found",
+			Error::DuplicateExport(_) => "Duplicate export",
+			Error::UnbalancedBlocks => "Unbalanced block structure",
+			Error::InvalidEditPosition(_) => "Can not edit instruction at position",
+			Error::NotCoreDump => "Module is not a core dump",
+			Error::UnsupportedInstance(_) => "Unsupported core dump instance",
+			Error::DataOutOfBounds => "Data segment does not fit the memory",
 		}
 	}
 }
@@ -3,18 +3,34 @@ use alloc::{borrow::ToOwned, string::String, vec::Vec};
 
 use super::{
 	deserialize_buffer,
+	dylink_section::DylinkSection,
+	limits::DeserializeConfig,
+	linking_section::LinkingSection,
 	name_section::NameSection,
+	offsets::code_offsets,
+	producers_section::ProducersSection,
 	reloc_section::RelocSection,
 	section::{
 		CodeSection, CustomSection, DataSection, ElementSection, ExportSection, FunctionSection,
-		GlobalSection, ImportSection, MemorySection, Section, TableSection, TypeSection,
+		GlobalSection, ImportSection, LazyCodeSection, MemorySection, Section, TableSection,
+		TypeSection,
 	},
-	serialize, Deserialize, Error, External, Serialize, Uint32,
+	serialize,
+	typed_section::{CustomSectionCodec, CustomSectionRegistry},
+	CountedList, Deserialize, Error, External, Func, FunctionType, GlobalType, Instruction, Local,
+	MemoryType, Serialize, TableType, Type, Uint32, ValueType,
 };
 
+#[cfg(feature = "multi_value")]
+use super::BlockType;
+#[cfg(all(feature = "exceptions", feature = "multi_value"))]
+use super::ExceptionsInstruction;
+#[cfg(feature = "exceptions")]
+use super::{section::TagSection, TagType};
+
 use core::cmp;
 
-const WASM_MAGIC_NUMBER: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+pub(super) const WASM_MAGIC_NUMBER: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
 
 /// WebAssembly module
 #[derive(Debug, Clone, PartialEq)]
@@ -35,6 +51,9 @@ pub enum ImportCountType {
 	Table,
 	/// Count memories
 	Memory,
+	/// Count tags
+	#[cfg(feature = "exceptions")]
+	Tag,
 }
 
 impl Default for Module {
@@ -58,11 +77,45 @@ impl Module {
 		deserialize_buffer::<Module>(input.as_ref())
 	}
 
+	/// Deserialize a module from bytes, leaving function bodies undecoded.
+	///
+	/// The code section is kept as [`Section::LazyCode`], whose bodies can be decoded one
+	/// by one with [`LazyCodeSection::parse_body`], or all at once with
+	/// [`parse_code`](Module::parse_code).
+	pub fn from_bytes_lazy<T: AsRef<[u8]>>(input: T) -> Result<Self, Error> {
+		let input = input.as_ref();
+		let mut reader = io::Cursor::new(input);
+		let module = deserialize_module(&mut reader, true)?;
+		if reader.position() != input.len() {
+			return Err(io::Error::TrailingData.into())
+		}
+		Ok(module)
+	}
+
+	/// Deserialize a module from bytes within the decoding budget of `config`.
+	pub fn from_bytes_with_config<T: AsRef<[u8]>>(
+		input: T,
+		config: &DeserializeConfig,
+	) -> Result<Self, Error> {
+		config.deserialize(input.as_ref())
+	}
+
 	/// Serialize a module to a vector.
 	pub fn into_bytes(self) -> Result<Vec<u8>, Error> {
 		serialize::<Module>(self)
 	}
 
+	/// Hash of the serialized module.
+	///
+	/// Decoding and serializing the module again yields the same hash. The hash is 64-bit
+	/// FNV-1a, suitable to identify modules but not to defend against deliberate collisions.
+	pub fn content_hash(&self) -> Result<u64, Error> {
+		let bytes = self.clone().into_bytes()?;
+		Ok(bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+			(hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+		}))
+	}
+
 	/// Destructure the module, yielding sections
 	pub fn into_sections(self) -> Vec<Section> {
 		self.sections
@@ -133,6 +186,26 @@ impl Module {
 		None
 	}
 
+	/// Undecoded code section reference, if any.
+	pub fn lazy_code_section(&self) -> Option<&LazyCodeSection> {
+		for section in self.sections() {
+			if let Section::LazyCode(ref code_section) = *section {
+				return Some(code_section)
+			}
+		}
+		None
+	}
+
+	/// Decode all function bodies of the lazy code section, if any.
+	pub fn parse_code(mut self) -> Result<Self, Error> {
+		for section in self.sections.iter_mut() {
+			if let Section::LazyCode(ref code_section) = *section {
+				*section = Section::Code(code_section.parse()?);
+			}
+		}
+		Ok(self)
+	}
+
 	/// Types section reference, if any.
 	pub fn type_section(&self) -> Option<&TypeSection> {
 		for section in self.sections() {
@@ -293,6 +366,28 @@ impl Module {
 		None
 	}
 
+	/// Tag section reference, if any.
+	#[cfg(feature = "exceptions")]
+	pub fn tag_section(&self) -> Option<&TagSection> {
+		for section in self.sections() {
+			if let Section::Tag(ref section) = *section {
+				return Some(section)
+			}
+		}
+		None
+	}
+
+	/// Tag section mutable reference, if any.
+	#[cfg(feature = "exceptions")]
+	pub fn tag_section_mut(&mut self) -> Option<&mut TagSection> {
+		for section in self.sections_mut() {
+			if let Section::Tag(ref mut section) = *section {
+				return Some(section)
+			}
+		}
+		None
+	}
+
 	/// Functions signatures section reference, if any.
 	pub fn function_section(&self) -> Option<&FunctionSection> {
 		for section in self.sections() {
@@ -351,6 +446,34 @@ impl Module {
 		}
 	}
 
+	/// Module's data count section (if any).
+	pub fn data_count_section(&self) -> Option<u32> {
+		for section in self.sections() {
+			if let Section::DataCount(count) = *section {
+				return Some(count)
+			}
+		}
+		None
+	}
+
+	/// Changes the module's data count section.
+	pub fn set_data_count_section(&mut self, new_count: u32) {
+		for section in self.sections_mut().iter_mut() {
+			if let Section::DataCount(_count) = *section {
+				*section = Section::DataCount(new_count);
+				return
+			}
+		}
+		// This should not fail, because we update the existing section above.
+		self.insert_section(Section::DataCount(new_count))
+			.expect("insert_section should not fail");
+	}
+
+	/// Removes the module's data count section.
+	pub fn clear_data_count_section(&mut self) {
+		self.sections_mut().retain(|section| !matches!(section, Section::DataCount(_)));
+	}
+
 	/// Returns an iterator over the module's custom sections
 	pub fn custom_sections(&self) -> impl Iterator<Item = &CustomSection> {
 		self.sections()
@@ -359,39 +482,47 @@ impl Module {
 	}
 
 	/// Sets the payload associated with the given custom section, or adds a new custom section,
-	/// as appropriate.
+	/// as appropriate. A section decoded with [`decode_custom_sections`] is replaced by a plain
+	/// one.
+	///
+	/// [`decode_custom_sections`]: Module::decode_custom_sections
 	pub fn set_custom_section(&mut self, name: impl Into<String>, payload: Vec<u8>) {
 		let name: String = name.into();
 		for section in self.sections_mut() {
-			if let Section::Custom(ref mut sect) = *section {
-				if sect.name() == name {
-					*sect = CustomSection::new(name, payload);
-					return
-				}
+			let found = match *section {
+				Section::Custom(ref sect) => sect.name() == name,
+				Section::Typed(ref sect) => sect.name() == name,
+				_ => false,
+			};
+			if found {
+				*section = Section::Custom(CustomSection::new(name, payload));
+				return
 			}
 		}
 		self.sections_mut().push(Section::Custom(CustomSection::new(name, payload)));
 	}
 
 	/// Removes the given custom section, if it exists.
-	/// Returns the removed section if it existed, or None otherwise.
+	/// Returns the removed section if it existed, or None otherwise. A section decoded with
+	/// [`decode_custom_sections`](Module::decode_custom_sections) is removed as well and
+	/// returned encoded, or as None if it fails to encode.
 	pub fn clear_custom_section(&mut self, name: impl AsRef<str>) -> Option<CustomSection> {
 		let name: &str = name.as_ref();
 
 		let sections = self.sections_mut();
 
 		for i in 0..sections.len() {
-			let mut remove = false;
-			if let Section::Custom(ref sect) = sections[i] {
-				if sect.name() == name {
-					remove = true;
-				}
-			}
+			let remove = match sections[i] {
+				Section::Custom(ref sect) => sect.name() == name,
+				Section::Typed(ref sect) => sect.name() == name,
+				_ => false,
+			};
 
 			if remove {
 				let removed = sections.remove(i);
 				match removed {
 					Section::Custom(sect) => return Some(sect),
+					Section::Typed(sect) => return sect.to_custom().ok(),
 					_ => unreachable!(), // This is the section we just matched on, so...
 				}
 			}
@@ -399,6 +530,238 @@ impl Module {
 		None
 	}
 
+	/// Decode the payload of the custom section `name`, if present, encoding it again first if
+	/// it was decoded with `decode_custom_sections`.
+	fn decode_custom_section<T, F>(&self, name: &str, decode: F) -> Result<Option<T>, Error>
+	where
+		F: FnOnce(&[u8]) -> Result<T, Error>,
+	{
+		for section in self.sections() {
+			match *section {
+				Section::Custom(ref sect) if sect.name() == name =>
+					return decode(sect.payload()).map(Some),
+				Section::Typed(ref sect) if sect.name() == name =>
+					return decode(sect.to_custom()?.payload()).map(Some),
+				_ => {},
+			}
+		}
+		Ok(None)
+	}
+
+	/// Decodes the `producers` custom section, if present.
+	pub fn producers_section(&self) -> Result<Option<ProducersSection>, Error> {
+		self.decode_custom_section("producers", deserialize_buffer)
+	}
+
+	/// Encodes `producers` into the `producers` custom section, replacing an existing one.
+	pub fn set_producers_section(&mut self, producers: ProducersSection) -> Result<(), Error> {
+		self.set_custom_section("producers", serialize(producers)?);
+		Ok(())
+	}
+
+	/// Decodes the `dylink.0` custom section of a shared library module, if present.
+	pub fn dylink_info(&self) -> Result<Option<DylinkSection>, Error> {
+		self.decode_custom_section("dylink.0", |payload| {
+			DylinkSection::deserialize(&mut io::Cursor::new(payload))
+		})
+	}
+
+	/// Encodes `dylink` into the `dylink.0` custom section, replacing an existing one.
+	///
+	/// The section has to precede all other sections, so it is moved to the front.
+	pub fn set_dylink_info(&mut self, dylink: DylinkSection) -> Result<(), Error> {
+		self.clear_custom_section("dylink.0");
+		let section = CustomSection::new("dylink.0".to_owned(), serialize(dylink)?);
+		self.sections_mut().insert(0, Section::Custom(section));
+		Ok(())
+	}
+
+	/// Removes all custom sections whose name matches `predicate`, including parsed name and
+	/// relocation sections.
+	pub fn strip_custom_sections<F: FnMut(&str) -> bool>(&mut self, mut predicate: F) {
+		self.sections_mut()
+			.retain(|section| !custom_section_name(section).map_or(false, &mut predicate));
+	}
+
+	/// Keeps only the custom sections whose name matches `predicate`; other sections are not
+	/// affected.
+	pub fn retain_custom_sections<F: FnMut(&str) -> bool>(&mut self, mut predicate: F) {
+		self.strip_custom_sections(|name| !predicate(name));
+	}
+
+	/// Removes the name section and DWARF (`.debug_*`) sections.
+	pub fn strip_debug(&mut self) {
+		self.strip_custom_sections(|name| name == "name" || name.starts_with(".debug_"));
+	}
+
+	/// Rename all imports of `module`.`field` to `new_module`.`new_field`.
+	///
+	/// Fails with [`Error::ImportNotFound`] if there is no such import. Indices are unaffected, so nothing else changes.
+	pub fn rename_import(
+		&mut self,
+		module: &str,
+		field: &str,
+		new_module: &str,
+		new_field: &str,
+	) -> Result<(), Error> {
+		let renamed = self.rename_imports(|m, f| {
+			(m == module && f == field).then(|| (new_module.into(), new_field.into()))
+		});
+		if renamed == 0 {
+			return Err(Error::ImportNotFound(module.into(), field.into()))
+		}
+		Ok(())
+	}
+
+	/// Rename imports with a mapping from module and field names to new ones.
+	///
+	/// Imports for which `map` returns `None` are left as they are. Returns the number of
+	/// renamed imports.
+	pub fn rename_imports<F>(&mut self, mut map: F) -> usize
+	where
+		F: FnMut(&str, &str) -> Option<(String, String)>,
+	{
+		let mut renamed = 0;
+		for entry in self.import_section_mut().into_iter().flat_map(|s| s.entries_mut()) {
+			if let Some((module, field)) = map(entry.module(), entry.field()) {
+				*entry.module_mut() = module;
+				*entry.field_mut() = field;
+				renamed += 1;
+			}
+		}
+		renamed
+	}
+
+	/// Rename the export `field` to `new_field`.
+	///
+	/// Fails with [`Error::ExportNotFound`] if there is no such export and with
+	/// [`Error::DuplicateExport`] if another export is already named `new_field`.
+	pub fn rename_export(&mut self, field: &str, new_field: &str) -> Result<(), Error> {
+		let exists = self
+			.export_section()
+			.map_or(false, |s| s.entries().iter().any(|entry| entry.field() == field));
+		if !exists {
+			return Err(Error::ExportNotFound(field.into()))
+		}
+		self.rename_exports(|f| (f == field).then(|| new_field.into()))
+	}
+
+	/// Rename exports with a mapping from export names to new ones.
+	///
+	/// Exports for which `map` returns `None` keep their name. Fails with
+	/// [`Error::DuplicateExport`] without changing the module if two exports would end up with
+	/// the same name.
+	pub fn rename_exports<F>(&mut self, mut map: F) -> Result<(), Error>
+	where
+		F: FnMut(&str) -> Option<String>,
+	{
+		let entries = match self.export_section_mut() {
+			Some(section) => section.entries_mut(),
+			None => return Ok(()),
+		};
+		let names: Vec<Option<String>> = entries.iter().map(|entry| map(entry.field())).collect();
+		let mut fields: Vec<&str> = entries
+			.iter()
+			.zip(&names)
+			.map(|(entry, name)| name.as_deref().unwrap_or_else(|| entry.field()))
+			.collect();
+		fields.sort_unstable();
+		if let Some(pair) = fields.windows(2).find(|pair| pair[0] == pair[1]) {
+			return Err(Error::DuplicateExport(pair[0].into()))
+		}
+		for (entry, name) in entries.iter_mut().zip(names) {
+			if let Some(name) = name {
+				*entry.field_mut() = name;
+			}
+		}
+		Ok(())
+	}
+
+	/// Deduplicate identical entries of the type section.
+	///
+	/// Every reference to a type is redirected to the first of the identical entries and the
+	/// others are removed. Returns the number of removed entries. Lazily decoded code sections
+	/// have to be parsed first.
+	pub fn canonicalize_types(&mut self) -> Result<usize, Error> {
+		if self.lazy_code_section().is_some() {
+			return Err(Error::UnparsedCode)
+		}
+		let types = match self.type_section_mut() {
+			Some(section) => section.types_mut(),
+			None => return Ok(0),
+		};
+		let mut unique: Vec<Type> = Vec::new();
+		let mapping: Vec<u32> = types
+			.drain(..)
+			.map(|ty| match unique.iter().position(|existing| *existing == ty) {
+				Some(index) => index as u32,
+				None => {
+					unique.push(ty);
+					unique.len() as u32 - 1
+				},
+			})
+			.collect();
+		let removed = mapping.len() - unique.len();
+		*types = unique;
+		if removed == 0 {
+			return Ok(0)
+		}
+
+		let remap = |type_ref: &mut u32| -> Result<(), Error> {
+			*type_ref =
+				*mapping.get(*type_ref as usize).ok_or(Error::IndexOutOfRange(*type_ref))?;
+			Ok(())
+		};
+		#[cfg(feature = "exceptions")]
+		let remap_tag = |tag: &mut TagType| -> Result<(), Error> {
+			let mut type_ref = tag.type_ref();
+			remap(&mut type_ref)?;
+			*tag = TagType::new(type_ref);
+			Ok(())
+		};
+		for section in self.sections_mut() {
+			match section {
+				Section::Import(section) =>
+					for import in section.entries_mut() {
+						match import.external_mut() {
+							External::Function(type_ref) => remap(type_ref)?,
+							#[cfg(feature = "exceptions")]
+							External::Tag(tag) => remap_tag(tag)?,
+							_ => {},
+						}
+					},
+				Section::Function(section) =>
+					for func in section.entries_mut() {
+						remap(func.type_ref_mut())?;
+					},
+				#[cfg(feature = "exceptions")]
+				Section::Tag(section) =>
+					for tag in section.entries_mut() {
+						remap_tag(tag)?;
+					},
+				Section::Code(section) =>
+					for body in section.bodies_mut() {
+						for instruction in body.code_mut().elements_mut() {
+							match instruction {
+								Instruction::CallIndirect(type_ref, _) => remap(type_ref)?,
+								#[cfg(feature = "multi_value")]
+								Instruction::Block(BlockType::TypeIndex(type_ref)) |
+								Instruction::Loop(BlockType::TypeIndex(type_ref)) |
+								Instruction::If(BlockType::TypeIndex(type_ref)) => remap(type_ref)?,
+								#[cfg(all(feature = "exceptions", feature = "multi_value"))]
+								Instruction::Exceptions(ExceptionsInstruction::Try(
+									BlockType::TypeIndex(type_ref),
+								)) => remap(type_ref)?,
+								_ => {},
+							}
+						}
+					},
+				_ => {},
+			}
+		}
+		Ok(removed)
+	}
+
 	/// True if a name section is present.
 	///
 	/// NOTE: this can return true even if the section was not parsed, hence `names_section()` may return `None`
@@ -528,6 +891,89 @@ impl Module {
 		}
 	}
 
+	/// Linking section, if any.
+	///
+	/// Only returns `Some` after the section was parsed with `parse_linking`.
+	pub fn linking_section(&self) -> Option<&LinkingSection> {
+		for section in self.sections() {
+			if let Section::Linking(ref linking_section) = *section {
+				return Some(linking_section)
+			}
+		}
+		None
+	}
+
+	/// Try to parse the linking section in place.
+	///
+	/// The `linking` custom section will be converted to a linking section. If it fails to be
+	/// decoded, Err variant is returned with the index of the section and the error.
+	pub fn parse_linking(mut self) -> Result<Self, ((usize, Error), Self)> {
+		let position = self.sections.iter().position(
+			|section| matches!(*section, Section::Custom(ref custom) if custom.name() == "linking"),
+		);
+		if let Some(i) = position {
+			let payload = match self.sections[i] {
+				Section::Custom(ref custom) => custom.payload(),
+				_ => unreachable!(),
+			};
+			let mut rdr = io::Cursor::new(payload);
+			let linking_section = match LinkingSection::deserialize(&mut rdr) {
+				Ok(linking_section) => linking_section,
+				Err(e) => return Err(((i, e), self)),
+			};
+			self.sections[i] = Section::Linking(linking_section);
+		}
+		Ok(self)
+	}
+
+	/// Try to decode custom sections with the codecs of `registry` in place.
+	///
+	/// Custom sections with a registered name will convert to typed sections.
+	/// If some of them will fail to be decoded, Err variant is returned with the list of
+	/// (index, Error) tuples of failed sections.
+	pub fn decode_custom_sections(
+		mut self,
+		registry: &CustomSectionRegistry,
+	) -> Result<Self, (Vec<(usize, Error)>, Self)> {
+		let mut parse_errors = Vec::new();
+
+		for (i, section) in self.sections.iter_mut().enumerate() {
+			let decoded = match *section {
+				Section::Custom(ref custom) => registry.decode(custom),
+				_ => None,
+			};
+			match decoded {
+				Some(Ok(typed_section)) => *section = Section::Typed(typed_section),
+				Some(Err(e)) => parse_errors.push((i, e)),
+				None => {},
+			}
+		}
+
+		if !parse_errors.is_empty() {
+			Err((parse_errors, self))
+		} else {
+			Ok(self)
+		}
+	}
+
+	/// Value of the typed section `name`, if any and it is of type `T`.
+	///
+	/// Only returns `Some` after the section was decoded with `decode_custom_sections`.
+	pub fn typed_section<T: CustomSectionCodec>(&self, name: &str) -> Option<&T> {
+		self.sections().iter().find_map(|section| match section {
+			Section::Typed(typed) if typed.name() == name => typed.value(),
+			_ => None,
+		})
+	}
+
+	/// Value of the typed section `name` (mutable), if any and it is of type `T`.
+	pub fn typed_section_mut<T: CustomSectionCodec>(&mut self, name: &str) -> Option<&mut T> {
+		self.sections_mut().iter_mut().find_map(|section| match section {
+			Section::Typed(typed) if typed.name() == name => typed.value_mut(),
+			_ => None,
+		})
+	}
+
 	/// Count imports by provided type.
 	pub fn import_count(&self, count_type: ImportCountType) -> usize {
 		self.import_section()
@@ -535,6 +981,12 @@ impl Module {
 				is.entries()
 					.iter()
 					.filter(|import| {
+						#[cfg(feature = "exceptions")]
+						if let (ImportCountType::Tag, External::Tag(_)) =
+							(count_type, *import.external())
+						{
+							return true
+						}
 						matches!(
 							(count_type, *import.external()),
 							(ImportCountType::Function, External::Function(_)) |
@@ -571,60 +1023,239 @@ impl Module {
 		self.import_count(ImportCountType::Memory) +
 			self.memory_section().map(|ms| ms.entries().len()).unwrap_or(0)
 	}
-}
-
-impl Deserialize for Module {
-	type Error = super::Error;
 
-	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
-		let mut sections = Vec::new();
+	/// Query tag space.
+	#[cfg(feature = "exceptions")]
+	pub fn tag_space(&self) -> usize {
+		self.import_count(ImportCountType::Tag) +
+			self.tag_section().map(|ts| ts.entries().len()).unwrap_or(0)
+	}
 
-		let mut magic = [0u8; 4];
-		reader.read(&mut magic)?;
-		if magic != WASM_MAGIC_NUMBER {
-			return Err(Error::InvalidMagic)
+	/// Signature of the function at `index` in the function index space.
+	///
+	/// Imported functions come first, followed by the functions defined in the module.
+	pub fn function_type(&self, index: u32) -> Option<&FunctionType> {
+		let type_ref = self
+			.imports()
+			.filter_map(|external| match *external {
+				External::Function(type_ref) => Some(type_ref),
+				_ => None,
+			})
+			.chain(
+				self.function_section()
+					.into_iter()
+					.flat_map(|fs| fs.entries())
+					.map(Func::type_ref),
+			)
+			.nth(index as usize)?;
+		match *self.type_section()?.types().get(type_ref as usize)? {
+			Type::Function(ref func_type) => Some(func_type),
 		}
+	}
 
-		let version: u32 = Uint32::deserialize(reader)?.into();
+	/// Type of the global at `index` in the global index space (imports first).
+	pub fn global_type(&self, index: u32) -> Option<GlobalType> {
+		self.imports()
+			.filter_map(|external| match *external {
+				External::Global(global_type) => Some(global_type),
+				_ => None,
+			})
+			.chain(
+				self.global_section()
+					.into_iter()
+					.flat_map(|gs| gs.entries())
+					.map(|entry| *entry.global_type()),
+			)
+			.nth(index as usize)
+	}
+
+	/// Type of the table at `index` in the table index space (imports first).
+	pub fn table_type(&self, index: u32) -> Option<TableType> {
+		self.imports()
+			.filter_map(|external| match *external {
+				External::Table(table_type) => Some(table_type),
+				_ => None,
+			})
+			.chain(self.table_section().into_iter().flat_map(|ts| ts.entries()).copied())
+			.nth(index as usize)
+	}
 
-		if version != 1 {
-			return Err(Error::UnsupportedVersion(version))
-		}
+	/// Type of the memory at `index` in the memory index space (imports first).
+	pub fn memory_type(&self, index: u32) -> Option<MemoryType> {
+		self.imports()
+			.filter_map(|external| match *external {
+				External::Memory(memory_type) => Some(memory_type),
+				_ => None,
+			})
+			.chain(self.memory_section().into_iter().flat_map(|ms| ms.entries()).copied())
+			.nth(index as usize)
+	}
 
-		let mut last_section_order = 0;
+	pub(crate) fn imports(&self) -> impl Iterator<Item = &External> {
+		self.import_section()
+			.into_iter()
+			.flat_map(|is| is.entries())
+			.map(|entry| entry.external())
+	}
 
-		loop {
-			match Section::deserialize(reader) {
-				Err(Error::UnexpectedEof) => break,
-				Err(e) => return Err(e),
-				Ok(section) => {
-					if section.order() != 0 {
-						match last_section_order {
-							x if x > section.order() => return Err(Error::SectionsOutOfOrder),
-							x if x == section.order() =>
-								return Err(Error::DuplicatedSections(last_section_order)),
-							_ => {},
-						};
+	/// Check function bodies against an instruction denylist.
+	///
+	/// Returns [`Error::DeniedInstruction`] for the first instruction for which `is_denied`
+	/// returns `true`, naming the function (in the function index space, so imported functions
+	/// are counted), the position of the instruction within the function body and its byte
+	/// offset as in [`code_offsets`](super::code_offsets). Bodies of a lazy code section are
+	/// decoded one instruction at a time for the check.
+	pub fn check_instructions<F>(&self, is_denied: F) -> Result<(), Error>
+	where
+		F: Fn(&Instruction) -> bool,
+	{
+		let imported_functions = self.import_count(ImportCountType::Function);
+		let denied = |index: usize, position: usize, offset: u32| Error::DeniedInstruction {
+			function: (imported_functions + index) as u32,
+			position,
+			offset,
+		};
 
-						last_section_order = section.order();
+		if let Some(code) = self.lazy_code_section() {
+			for index in 0..code.bodies().len() {
+				let instructions =
+					code.instructions_with_offsets(index).expect("index is in range; qed")?;
+				for (position, instruction) in instructions.enumerate() {
+					let (offset, instruction) = instruction?;
+					if is_denied(&instruction) {
+						return Err(denied(index, position, offset))
 					}
-					sections.push(section);
-				},
+				}
 			}
+			return Ok(())
 		}
 
-		let module = Module { magic: u32::from_le_bytes(magic), version, sections };
+		let bodies = self.code_section().map(|cs| cs.bodies()).unwrap_or(&[]);
+		for (index, body) in bodies.iter().enumerate() {
+			if let Some(position) = body.code().elements().iter().position(&is_denied) {
+				let offset = code_offsets(self)?[index].instructions()[position];
+				return Err(denied(index, position, offset))
+			}
+		}
+		Ok(())
+	}
 
-		if module.code_section().map(|cs| cs.bodies().len()).unwrap_or(0) !=
-			module.function_section().map(|fs| fs.entries().len()).unwrap_or(0)
-		{
-			return Err(Error::InconsistentCode)
+	/// Check that no memory instruction has an alignment hint larger than allowed.
+	///
+	/// Returns [`Error::InvalidAlignment`] for the first offending instruction, located like
+	/// with `check_instructions`.
+	pub fn check_alignment(&self) -> Result<(), Error> {
+		self.check_instructions(|instruction| !instruction.has_valid_alignment())
+			.map_err(|err| match err {
+				Error::DeniedInstruction { function, position, offset } =>
+					Error::InvalidAlignment { function, position, offset },
+				other => other,
+			})
+	}
+
+	/// Check that the module does not use floating point at all.
+	///
+	/// Float instructions are reported as [`Error::DeniedInstruction`], like with
+	/// `check_instructions`; float types in signatures, globals or locals result in
+	/// [`Error::FloatingPointDisabled`].
+	pub fn check_no_floats(&self) -> Result<(), Error> {
+		self.check_instructions(Instruction::is_float)?;
+
+		let is_float =
+			|value_type: &ValueType| matches!(value_type, ValueType::F32 | ValueType::F64);
+		let types = self.type_section().map(|ts| ts.types()).unwrap_or(&[]);
+		let in_signatures = types.iter().any(|Type::Function(func_type)| {
+			func_type.params().iter().chain(func_type.results()).any(is_float)
+		});
+		let in_globals = self
+			.imports()
+			.filter_map(|external| match *external {
+				External::Global(global_type) => Some(global_type),
+				_ => None,
+			})
+			.chain(
+				self.global_section()
+					.into_iter()
+					.flat_map(|gs| gs.entries())
+					.map(|entry| *entry.global_type()),
+			)
+			.any(|global_type| is_float(&global_type.content_type()));
+		let mut in_locals = self
+			.code_section()
+			.into_iter()
+			.flat_map(|cs| cs.bodies())
+			.flat_map(|body| body.locals())
+			.any(|local| is_float(&local.value_type()));
+		for body in self.lazy_code_section().into_iter().flat_map(|cs| cs.bodies()) {
+			let locals = CountedList::<Local>::deserialize(&mut io::Cursor::new(body.bytes()))?;
+			in_locals |= locals.into_inner().iter().any(|local| is_float(&local.value_type()));
 		}
 
-		Ok(module)
+		if in_signatures || in_globals || in_locals {
+			return Err(Error::FloatingPointDisabled)
+		}
+		Ok(())
 	}
 }
 
+impl Deserialize for Module {
+	type Error = super::Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		deserialize_module(reader, false)
+	}
+}
+
+fn deserialize_module<R: io::Read>(reader: &mut R, lazy_code: bool) -> Result<Module, Error> {
+	let mut sections = Vec::new();
+
+	let mut magic = [0u8; 4];
+	reader.read(&mut magic)?;
+	if magic != WASM_MAGIC_NUMBER {
+		return Err(Error::InvalidMagic)
+	}
+
+	let version: u32 = Uint32::deserialize(reader)?.into();
+
+	if version != 1 {
+		return Err(Error::UnsupportedVersion(version))
+	}
+
+	let mut last_section_order = 0;
+
+	loop {
+		match Section::deserialize_with(reader, lazy_code) {
+			Err(Error::UnexpectedEof) => break,
+			Err(e) => return Err(e),
+			Ok(section) => {
+				if section.order() != 0 {
+					match last_section_order {
+						x if x > section.order() => return Err(Error::SectionsOutOfOrder),
+						x if x == section.order() =>
+							return Err(Error::DuplicatedSections(last_section_order)),
+						_ => {},
+					};
+
+					last_section_order = section.order();
+				}
+				sections.push(section);
+			},
+		}
+	}
+
+	let module = Module { magic: u32::from_le_bytes(magic), version, sections };
+
+	let body_count = match module.lazy_code_section() {
+		Some(lazy) => lazy.bodies().len(),
+		None => module.code_section().map(|cs| cs.bodies().len()).unwrap_or(0),
+	};
+	if body_count != module.function_section().map(|fs| fs.entries().len()).unwrap_or(0) {
+		return Err(Error::InconsistentCode)
+	}
+
+	Ok(module)
+}
+
 impl Serialize for Module {
 	type Error = Error;
 
@@ -660,6 +1291,17 @@ impl<'a> io::Read for PeekSection<'a> {
 	}
 }
 
+pub(crate) fn custom_section_name(section: &Section) -> Option<&str> {
+	match section {
+		Section::Custom(custom) => Some(custom.name()),
+		Section::Name(_) => Some("name"),
+		Section::Reloc(reloc) => Some(reloc.name()),
+		Section::Linking(_) => Some("linking"),
+		Section::Typed(typed) => Some(typed.name()),
+		_ => None,
+	}
+}
+
 /// Returns size of the module in the provided stream.
 pub fn peek_size(source: &[u8]) -> usize {
 	if source.len() < 9 {
@@ -706,8 +1348,9 @@ pub fn peek_size(source: &[u8]) -> usize {
 mod integration_tests {
 	use super::{
 		super::{
-			deserialize_buffer, deserialize_file, serialize, CodeSection, ExportSection,
-			FunctionSection, Section, TypeSection,
+			deserialize_buffer, deserialize_file, serialize, CodeSection, DylinkMemInfo,
+			DylinkSection, Error, ExportSection, FunctionSection, ProducersSection, Section,
+			TypeSection, PROCESSED_BY,
 		},
 		Module,
 	};
@@ -957,7 +1600,70 @@ mod integration_tests {
 		assert_eq!(module.start_section().expect("Did not find any start section"), 0);
 
 		let sections = module.sections().iter().map(|s| s.order()).collect::<Vec<_>>();
-		assert_eq!(sections, vec![1, 2, 3, 6, 7, 8, 9, 11, 12]);
+		assert_eq!(sections, vec![1, 2, 3, 7, 8, 9, 10, 12, 13]);
+	}
+
+	#[test]
+	fn producers_section() {
+		let mut module =
+			deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		assert_eq!(module.producers_section().expect("missing section is not an error"), None);
+
+		let mut producers = ProducersSection::default();
+		producers.add_value("language", "Rust", "");
+		producers.add_value(PROCESSED_BY, "parity-wasm", "0.1");
+		producers.add_processed_by_self();
+		module.set_producers_section(producers.clone()).expect("failed to encode");
+
+		let module = Module::from_bytes(module.into_bytes().expect("failed to serialize"))
+			.expect("failed to deserialize");
+		let decoded = module.producers_section().expect("failed to decode").expect("section");
+		assert_eq!(decoded, producers);
+		let processed_by = decoded.field("processed-by").expect("processed-by field").values();
+		assert_eq!(processed_by.len(), 1);
+		assert_eq!(processed_by[0].version(), env!("CARGO_PKG_VERSION"));
+	}
+
+	#[test]
+	fn dylink_info() {
+		let mut module =
+			deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		assert_eq!(module.dylink_info().expect("missing section is not an error"), None);
+
+		let mut dylink = DylinkSection::default();
+		*dylink.mem_info_mut() = Some(DylinkMemInfo {
+			memory_size: 1024,
+			memory_alignment: 4,
+			table_size: 2,
+			table_alignment: 0,
+		});
+		dylink.needed_mut().push("libc.so".into());
+		module.set_dylink_info(dylink.clone()).expect("failed to encode");
+
+		let module = Module::from_bytes(module.into_bytes().expect("failed to serialize"))
+			.expect("failed to deserialize");
+		assert!(
+			matches!(module.sections()[0], Section::Custom(ref custom) if custom.name() == "dylink.0")
+		);
+		assert_eq!(module.dylink_info().expect("failed to decode"), Some(dylink));
+	}
+
+	#[test]
+	fn strip_custom_sections() {
+		let mut module =
+			deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		module.set_custom_section("name", vec![0]);
+		module.set_custom_section(".debug_info", vec![1]);
+		module.set_custom_section("producers", vec![2]);
+		let sections = module.sections().len();
+
+		module.strip_debug();
+		assert_eq!(module.sections().len(), sections - 2);
+		assert_eq!(module.custom_sections().map(|s| s.name()).collect::<Vec<_>>(), ["producers"]);
+
+		module.retain_custom_sections(|name| name != "producers");
+		assert!(module.custom_sections().next().is_none());
+		assert_eq!(module.sections().len(), sections - 3);
 	}
 
 	#[test]
@@ -966,14 +1672,14 @@ mod integration_tests {
 			.expect("failed to deserialize");
 
 		let sections = module.sections().iter().map(|s| s.order()).collect::<Vec<_>>();
-		assert_eq!(sections, vec![1, 2, 3, 6, 7, 9, 11, 12, 0]);
+		assert_eq!(sections, vec![1, 2, 3, 7, 8, 10, 12, 13, 0]);
 
 		assert!(module.start_section().is_none());
 		module.set_start_section(0);
 		assert_eq!(module.start_section().expect("Dorder not find any start section"), 0);
 
 		let sections = module.sections().iter().map(|s| s.order()).collect::<Vec<_>>();
-		assert_eq!(sections, vec![1, 2, 3, 6, 7, 8, 9, 11, 12, 0]);
+		assert_eq!(sections, vec![1, 2, 3, 7, 8, 9, 10, 12, 13, 0]);
 	}
 
 	#[test]
@@ -1043,6 +1749,341 @@ mod integration_tests {
 		assert!(deserialize_buffer::<Module>(&serialized).is_ok());
 	}
 
+	#[test]
+	fn rename_imports_and_exports() {
+		let mut module = crate::builder::module()
+			.import()
+			.module("env")
+			.field("memory")
+			.external()
+			.memory(1, None)
+			.build()
+			.import()
+			.module("env")
+			.field("abort")
+			.external()
+			.func(0)
+			.build()
+			.export()
+			.field("a")
+			.internal()
+			.memory(0)
+			.build()
+			.export()
+			.field("b")
+			.internal()
+			.memory(0)
+			.build()
+			.build();
+
+		module
+			.rename_import("env", "abort", "host", "abort")
+			.expect("import to be renamed");
+		assert!(matches!(
+			module.rename_import("env", "abort", "host", "abort"),
+			Err(Error::ImportNotFound(..))
+		));
+		let renamed = module.rename_imports(|m, f| (m == "env").then(|| ("host".into(), f.into())));
+		assert_eq!(renamed, 1);
+		let imports = module.import_section().unwrap().entries();
+		assert!(imports.iter().all(|entry| entry.module() == "host"));
+
+		assert!(
+			matches!(module.rename_export("a", "b"), Err(Error::DuplicateExport(name)) if name == "b")
+		);
+		assert!(
+			matches!(module.rename_export("c", "d"), Err(Error::ExportNotFound(name)) if name == "c")
+		);
+		module.rename_exports(|f| Some(f.repeat(2))).expect("exports to be renamed");
+		module.rename_export("aa", "memory").expect("export to be renamed");
+		let exports = module.export_section().unwrap().entries();
+		assert_eq!(exports[0].field(), "memory");
+		assert_eq!(exports[1].field(), "bb");
+	}
+
+	#[test]
+	fn canonicalize_types() {
+		use super::super::{
+			External, Func, FuncBody, FunctionType, ImportEntry, ImportSection, Instruction,
+			Instructions, Type, ValueType,
+		};
+
+		let unit = || Type::Function(FunctionType::new(vec![], vec![]));
+		let unary = Type::Function(FunctionType::new(vec![ValueType::I32], vec![]));
+		let code = Instructions::new(vec![
+			Instruction::I32Const(0),
+			Instruction::CallIndirect(2, 0),
+			Instruction::End,
+		]);
+		let mut module = Module::new(vec![
+			Section::Type(TypeSection::with_types(vec![unit(), unary.clone(), unit()])),
+			Section::Import(ImportSection::with_entries(vec![ImportEntry::new(
+				"env".into(),
+				"f".into(),
+				External::Function(2),
+			)])),
+			Section::Function(FunctionSection::with_entries(vec![Func::new(2), Func::new(1)])),
+			Section::Code(CodeSection::with_bodies(vec![
+				FuncBody::new(vec![], code),
+				FuncBody::new(vec![], Instructions::new(vec![Instruction::End])),
+			])),
+		]);
+
+		assert_eq!(module.canonicalize_types().expect("types to be canonicalized"), 1);
+		assert_eq!(module.type_section().unwrap().types(), &[unit(), unary]);
+		assert_eq!(
+			*module.import_section().unwrap().entries()[0].external(),
+			External::Function(0)
+		);
+		let functions = module.function_section().unwrap().entries();
+		assert_eq!((functions[0].type_ref(), functions[1].type_ref()), (0, 1));
+		assert_eq!(
+			module.code_section().unwrap().bodies()[0].code().elements()[1],
+			Instruction::CallIndirect(0, 0)
+		);
+		assert_eq!(module.canonicalize_types().expect("types to be canonicalized"), 0);
+	}
+
+	#[test]
+	fn canonicalize_types_out_of_range() {
+		use super::super::{Func, FuncBody, FunctionType, Instruction, Instructions, Type};
+
+		let unit = || Type::Function(FunctionType::new(vec![], vec![]));
+		let module = |code| {
+			Module::new(vec![
+				Section::Type(TypeSection::with_types(vec![unit(), unit()])),
+				Section::Function(FunctionSection::with_entries(vec![Func::new(1)])),
+				Section::Code(CodeSection::with_bodies(vec![FuncBody::new(
+					vec![],
+					Instructions::new(code),
+				)])),
+			])
+		};
+
+		// Only type references are rewritten; other indices are left alone.
+		let mut calls =
+			module(vec![Instruction::Call(99), Instruction::GetGlobal(99), Instruction::End]);
+		assert_eq!(calls.canonicalize_types().expect("types to be canonicalized"), 1);
+		assert_eq!(
+			calls.code_section().unwrap().bodies()[0].code().elements(),
+			&[Instruction::Call(99), Instruction::GetGlobal(99), Instruction::End]
+		);
+
+		let mut indirect = module(vec![
+			Instruction::I32Const(0),
+			Instruction::CallIndirect(7, 0),
+			Instruction::End,
+		]);
+		assert!(matches!(indirect.canonicalize_types(), Err(Error::IndexOutOfRange(7))));
+
+		let mut lazy = Module::from_bytes_lazy(calls.into_bytes().unwrap()).unwrap();
+		assert!(matches!(lazy.canonicalize_types(), Err(Error::UnparsedCode)));
+	}
+
+	#[test]
+	fn alignment() {
+		use super::super::{Error, Instruction, Instructions};
+
+		let with_code = |code| {
+			crate::builder::module()
+				.function()
+				.signature()
+				.build()
+				.body()
+				.with_instructions(Instructions::new(code))
+				.build()
+				.build()
+				.build()
+		};
+		let load = |align| {
+			with_code(vec![
+				Instruction::I32Const(0),
+				Instruction::I64Load16U(align, 0),
+				Instruction::Drop,
+				Instruction::End,
+			])
+		};
+		assert!(load(0).check_alignment().is_ok());
+		assert!(load(1).check_alignment().is_ok());
+		assert!(matches!(
+			load(2).check_alignment(),
+			Err(Error::InvalidAlignment { function: 0, position: 1, .. })
+		));
+		assert_eq!(Instruction::F64Store(3, 0).natural_alignment(), Some(3));
+		assert_eq!(Instruction::I32Add.natural_alignment(), None);
+	}
+
+	#[test]
+	fn no_floats() {
+		use super::super::{Error, Instruction, Instructions, Local, ValueType};
+
+		let module = deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		assert!(module.check_no_floats().is_ok());
+
+		let with_code = |code| {
+			crate::builder::module()
+				.function()
+				.signature()
+				.build()
+				.body()
+				.with_instructions(Instructions::new(code))
+				.build()
+				.build()
+				.build()
+		};
+		let module = with_code(vec![
+			Instruction::I32Const(0),
+			Instruction::F32ConvertSI32,
+			Instruction::Drop,
+			Instruction::End,
+		]);
+		assert!(matches!(
+			module.check_no_floats(),
+			Err(Error::DeniedInstruction { function: 0, position: 1, .. })
+		));
+
+		let module = crate::builder::module()
+			.function()
+			.signature()
+			.with_result(ValueType::F64)
+			.build()
+			.build()
+			.build();
+		assert!(matches!(module.check_no_floats(), Err(Error::FloatingPointDisabled)));
+
+		let module = crate::builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_locals(vec![Local::new(1, ValueType::F32)])
+			.build()
+			.build()
+			.build();
+		let bytes = module.into_bytes().expect("module to serialize");
+		let module = Module::from_bytes_lazy(&bytes).expect("module to deserialize");
+		assert!(matches!(module.check_no_floats(), Err(Error::FloatingPointDisabled)));
+	}
+
+	#[test]
+	fn denied_instructions() {
+		use super::super::{Error, Instruction};
+
+		let module = deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		assert!(module.check_instructions(|i| matches!(i, Instruction::GrowMemory(_))).is_ok());
+
+		let module = crate::builder::module()
+			.import()
+			.module("env")
+			.field("f")
+			.external()
+			.func(0)
+			.build()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(super::super::Instructions::new(vec![
+				Instruction::I32Const(1),
+				Instruction::GrowMemory(0),
+				Instruction::Drop,
+				Instruction::End,
+			]))
+			.build()
+			.build()
+			.build();
+		match module.check_instructions(|i| matches!(i, Instruction::GrowMemory(_))) {
+			Err(Error::DeniedInstruction { function, position, offset }) => {
+				assert_eq!(function, 1);
+				assert_eq!(position, 1);
+				assert_eq!(offset, 5);
+			},
+			other => panic!("Expected DeniedInstruction error, got {:?}", other),
+		}
+
+		// Lazily decoded bodies are checked too, with offsets in the original binary.
+		let bytes = module.into_bytes().expect("module to serialize");
+		let module = Module::from_bytes_lazy(&bytes).expect("module to deserialize");
+		match module.check_instructions(|i| matches!(i, Instruction::GrowMemory(_))) {
+			Err(Error::DeniedInstruction { function: 1, position: 1, offset: 5 }) => {},
+			other => panic!("Expected DeniedInstruction error, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn index_space_types() {
+		use super::super::{External, GlobalType, ImportEntry, MemoryType, ValueType};
+
+		let module = crate::builder::module()
+			.with_signatures(vec![
+				crate::builder::signature().with_param(ValueType::I32).build_sig(),
+				crate::builder::signature().with_result(ValueType::F64).build_sig(),
+			])
+			.with_import(ImportEntry::new("env".into(), "f".into(), External::Function(1)))
+			.with_import(ImportEntry::new(
+				"env".into(),
+				"g".into(),
+				External::Global(GlobalType::new(ValueType::I64, false)),
+			))
+			.function()
+			.signature()
+			.with_param(ValueType::I32)
+			.build()
+			.build()
+			.memory()
+			.with_min(2)
+			.build()
+			.global()
+			.value_type()
+			.f32()
+			.mutable()
+			.build()
+			.build();
+
+		assert_eq!(
+			module.function_type(0).expect("imported function").results(),
+			&[ValueType::F64]
+		);
+		assert_eq!(module.function_type(1).expect("defined function").params(), &[ValueType::I32]);
+		assert!(module.function_type(2).is_none());
+		assert_eq!(module.global_type(0), Some(GlobalType::new(ValueType::I64, false)));
+		assert_eq!(module.global_type(1), Some(GlobalType::new(ValueType::F32, true)));
+		assert_eq!(module.memory_type(0), Some(MemoryType::new(2, None)));
+		assert!(module.table_type(0).is_none());
+	}
+
+	#[test]
+	fn lazy_code() {
+		let module =
+			deserialize_file("./res/cases/v1/inc_i32.wasm").expect("failed to deserialize");
+		let bytes = module.clone().into_bytes().expect("failed to serialize");
+
+		let lazy = Module::from_bytes_lazy(&bytes).expect("failed to deserialize lazily");
+		assert!(lazy.code_section().is_none());
+		let code = lazy.lazy_code_section().expect("lazy code section to exist");
+		assert_eq!(
+			&code.parse_body(0).expect("first body to exist").expect("body to decode"),
+			&module.code_section().expect("code section to exist").bodies()[0]
+		);
+		assert!(code.parse_body(code.bodies().len()).is_none());
+
+		assert_eq!(lazy.clone().into_bytes().expect("failed to serialize"), bytes);
+		assert_eq!(lazy.parse_code().expect("failed to parse code"), module);
+	}
+
+	#[test]
+	fn content_hash() {
+		let module = deserialize_file("./res/cases/v1/test5.wasm").expect("Should be deserialized");
+		let hash = module.content_hash().expect("module to hash");
+		let bytes = module.clone().into_bytes().expect("module to serialize");
+		let lazy = Module::from_bytes_lazy(&bytes).expect("module to deserialize lazily");
+		assert_eq!(lazy.content_hash().expect("module to hash"), hash);
+
+		let mut changed = module;
+		changed.sections_mut().pop();
+		assert_ne!(changed.content_hash().expect("module to hash"), hash);
+	}
+
 	#[test]
 	fn serialization_roundtrip() {
 		let module = deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
@@ -1050,4 +2091,36 @@ mod integration_tests {
 		let module_copy = Module::from_bytes(&module_copy).expect("failed to deserialize");
 		assert_eq!(module, module_copy);
 	}
+
+	#[cfg(feature = "exceptions")]
+	#[test]
+	fn tags() {
+		use super::{
+			super::{ExportEntry, External, ImportEntry, Internal, TagSection, TagType, ValueType},
+			ImportCountType,
+		};
+
+		let mut module = crate::builder::module()
+			.with_signatures(vec![crate::builder::signature()
+				.with_param(ValueType::I32)
+				.build_sig()])
+			.with_import(ImportEntry::new(
+				"env".into(),
+				"error".into(),
+				External::Tag(TagType::new(0)),
+			))
+			.with_export(ExportEntry::new("local_error".into(), Internal::Tag(1)))
+			.build();
+		module
+			.insert_section(Section::Tag(TagSection::with_entries(vec![TagType::new(0)])))
+			.expect("tag section to be inserted");
+		assert_eq!(module.tag_space(), 2);
+		assert_eq!(module.import_count(ImportCountType::Tag), 1);
+
+		let sections = module.sections().iter().map(|s| s.order()).collect::<Vec<_>>();
+		assert_eq!(sections, vec![1, 2, 6, 8]);
+
+		let bytes = module.clone().into_bytes().expect("failed to serialize");
+		assert_eq!(Module::from_bytes(&bytes).expect("failed to deserialize"), module);
+	}
 }
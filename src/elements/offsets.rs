@@ -0,0 +1,381 @@
+use crate::io;
+use alloc::vec::Vec;
+
+use super::{
+	serialize, CountedList, CountedListWriter, Deserialize, Error, ImportCountType, Instruction,
+	LazyCodeSection, Local, Module, OpcodeReader, VarUint32,
+};
+
+/// Byte offsets of one function body.
+///
+/// Offsets are relative to the start of the code section payload, right after the section
+/// size, which is how DWARF addresses code in WebAssembly modules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionOffsets {
+	body: u32,
+	instructions: Vec<u32>,
+	end: u32,
+}
+
+impl FunctionOffsets {
+	/// Offset of the body, i.e. of its size prefix.
+	pub fn body(&self) -> u32 {
+		self.body
+	}
+
+	/// Offset of every instruction of the body.
+	pub fn instructions(&self) -> &[u32] {
+		&self.instructions
+	}
+
+	/// Offset just past the body.
+	pub fn end(&self) -> u32 {
+		self.end
+	}
+}
+
+fn encoded_len(value: usize) -> Result<u32, Error> {
+	Ok(serialize(VarUint32::from(value))?.len() as u32)
+}
+
+/// Iterator over the instructions of a function body in binary form, with their offsets.
+///
+/// Created by [`UnparsedFuncBody::instructions_with_offsets`] and
+/// [`LazyCodeSection::instructions_with_offsets`]. Iteration stops after the final `end`
+/// or the first error.
+///
+/// [`UnparsedFuncBody::instructions_with_offsets`]: super::UnparsedFuncBody::instructions_with_offsets
+#[derive(Debug)]
+pub struct InstructionsWithOffsets<'a> {
+	reader: OpcodeReader<io::Cursor<&'a [u8]>>,
+	base: u32,
+}
+
+impl<'a> InstructionsWithOffsets<'a> {
+	/// Skip the locals of `body`, reporting offsets relative to `base`.
+	pub(crate) fn new(body: &'a [u8], base: u32) -> Result<Self, Error> {
+		let mut reader = io::Cursor::new(body);
+		CountedList::<Local>::deserialize(&mut reader)?;
+		Ok(InstructionsWithOffsets { reader: OpcodeReader::new(reader), base })
+	}
+}
+
+impl<'a> Iterator for InstructionsWithOffsets<'a> {
+	type Item = Result<(u32, Instruction), Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let offset = self.base + self.reader.get_ref().position() as u32;
+		let instruction = self.reader.next()?;
+		Some(instruction.map(|instruction| (offset, instruction)))
+	}
+}
+
+impl LazyCodeSection {
+	/// Instructions of the body at `index`, if it exists, with their offsets relative to the
+	/// start of the code section payload, as in [`code_offsets`].
+	pub fn instructions_with_offsets(
+		&self,
+		index: usize,
+	) -> Option<Result<InstructionsWithOffsets<'_>, Error>> {
+		let body = self.bodies().get(index)?;
+		Some(InstructionsWithOffsets::new(body.bytes(), body.start()))
+	}
+}
+
+/// Offsets of the bodies of a lazy code section, as they are in the original binary.
+fn lazy_code_offsets(code: &LazyCodeSection) -> Result<Vec<FunctionOffsets>, Error> {
+	let mut functions = Vec::with_capacity(code.bodies().len());
+	for body in code.bodies() {
+		let instructions = InstructionsWithOffsets::new(body.bytes(), body.start())?
+			.map(|instruction| instruction.map(|(offset, _)| offset))
+			.collect::<Result<Vec<_>, _>>()?;
+		functions.push(FunctionOffsets {
+			body: body.offset(),
+			instructions,
+			end: body.start() + body.bytes().len() as u32,
+		});
+	}
+	Ok(functions)
+}
+
+/// Compute the offsets of all function bodies and their instructions in the code section.
+///
+/// Offsets of a lazily decoded code section are those of the original binary; otherwise
+/// they are those the module will have once serialized.
+pub fn code_offsets(module: &Module) -> Result<Vec<FunctionOffsets>, Error> {
+	if let Some(code) = module.lazy_code_section() {
+		return lazy_code_offsets(code)
+	}
+	let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+	let mut offset = encoded_len(bodies.len())?;
+	let mut functions = Vec::with_capacity(bodies.len());
+	for body in bodies {
+		let locals = body.locals().to_vec();
+		let locals_len = serialize(CountedListWriter(locals.len(), locals.into_iter()))?.len();
+		let instruction_lens = body
+			.code()
+			.elements()
+			.iter()
+			.map(|instruction| serialize(instruction.clone()).map(|bytes| bytes.len() as u32))
+			.collect::<Result<Vec<_>, _>>()?;
+		let content_len = locals_len + instruction_lens.iter().sum::<u32>() as usize;
+
+		let start = offset;
+		let mut position = start + encoded_len(content_len)? + locals_len as u32;
+		let mut instructions = Vec::with_capacity(instruction_lens.len());
+		for len in instruction_lens {
+			instructions.push(position);
+			position += len;
+		}
+		offset = position;
+		functions.push(FunctionOffsets { body: start, instructions, end: position });
+	}
+	Ok(functions)
+}
+
+/// Translation of code offsets from before a transform to after it.
+///
+/// Debug information such as DWARF line tables refers to code by offset, so it has to be
+/// adjusted when a transform adds or removes instructions. The map relates the start of
+/// every function body and every instruction kept by the transform, and can be used to
+/// rewrite such tables or be emitted as a remapping table alongside the module.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OffsetMap {
+	entries: Vec<(u32, u32)>,
+}
+
+impl OffsetMap {
+	/// Relate the offsets of the same function bodies before and after a transform.
+	///
+	/// `origins` has an entry for every transformed body, giving for each of its
+	/// instructions the index of the original instruction it was derived from, or `None`
+	/// for instructions inserted by the transform.
+	pub fn new(
+		before: &[FunctionOffsets],
+		after: &[FunctionOffsets],
+		origins: &[Vec<Option<usize>>],
+	) -> Result<Self, Error> {
+		if before.len() != after.len() || before.len() != origins.len() {
+			return Err(Error::InconsistentCode)
+		}
+
+		let mut entries = Vec::new();
+		for ((old, new), origins) in before.iter().zip(after).zip(origins) {
+			if new.instructions.len() != origins.len() {
+				return Err(Error::InconsistentCode)
+			}
+			entries.push((old.body, new.body));
+			for (new_offset, origin) in new.instructions.iter().zip(origins) {
+				if let Some(index) = *origin {
+					let old_offset = old.instructions.get(index).ok_or(Error::InconsistentCode)?;
+					entries.push((*old_offset, *new_offset));
+				}
+			}
+			entries.push((old.end, new.end));
+		}
+		entries.sort_unstable();
+		entries.dedup_by_key(|entry| entry.0);
+		Ok(OffsetMap { entries })
+	}
+
+	/// Pairs of (original, transformed) offsets, sorted by the original offset.
+	pub fn entries(&self) -> &[(u32, u32)] {
+		&self.entries
+	}
+
+	/// New offset of the body or instruction that started at `offset`.
+	///
+	/// Returns `None` if nothing started there or the instruction was removed.
+	pub fn map(&self, offset: u32) -> Option<u32> {
+		self.entries
+			.binary_search_by_key(&offset, |entry| entry.0)
+			.ok()
+			.map(|index| self.entries[index].1)
+	}
+}
+
+/// Function and instruction a code offset belongs to.
+///
+/// There is no source file or line, see [`SourceLocator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceLocation<'a> {
+	/// Index of the function in the function index space.
+	pub function: u32,
+	/// Index of the instruction within the function body.
+	pub instruction: usize,
+	/// Name of the function from the name section, if known.
+	pub name: Option<&'a str>,
+}
+
+/// Maps code offsets, e.g. of a trap, back to functions and their names.
+///
+/// Only the function, its name and the instruction within its body are resolved; names are
+/// taken from the name section, which has to be parsed with `parse_names` first. Source files
+/// and lines are not: `.debug_line` and other DWARF sections are not interpreted, even if the
+/// module carries them.
+#[derive(Debug)]
+pub struct SourceLocator<'a> {
+	module: &'a Module,
+	offsets: Vec<FunctionOffsets>,
+	imported_functions: u32,
+}
+
+impl<'a> SourceLocator<'a> {
+	/// Compute the code layout of `module`.
+	pub fn new(module: &'a Module) -> Result<Self, Error> {
+		Ok(SourceLocator {
+			module,
+			offsets: code_offsets(module)?,
+			imported_functions: module.import_count(ImportCountType::Function) as u32,
+		})
+	}
+
+	/// Name of the function with the given index, if known.
+	pub fn function_name(&self, function: u32) -> Option<&'a str> {
+		let names = self.module.names_section()?.functions()?.names();
+		names.get(function).map(|name| name.as_str())
+	}
+
+	/// Location of the instruction starting at or spanning `offset`.
+	///
+	/// `offset` is relative to the code section payload, as in [`code_offsets`].
+	pub fn locate(&self, offset: u32) -> Option<SourceLocation<'a>> {
+		let body = self.offsets.partition_point(|body| body.end <= offset);
+		let offsets = self.offsets.get(body)?;
+		let instruction = offsets.instructions.partition_point(|start| *start <= offset);
+		if instruction == 0 {
+			return None
+		}
+		let function = self.imported_functions + body as u32;
+		Some(SourceLocation {
+			function,
+			instruction: instruction - 1,
+			name: self.function_name(function),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{code_offsets, OffsetMap, SourceLocation, SourceLocator};
+	use crate::{
+		builder,
+		elements::{
+			FunctionNameSubsection, Instruction::*, Instructions, Local, Module, NameSection,
+			Section, ValueType,
+		},
+	};
+
+	#[test]
+	fn offsets() {
+		let module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![I32Const(300), Drop, End]))
+			.build()
+			.build()
+			.build();
+
+		// One body: count, size, no locals, i32.const 300 (3 bytes), drop, end.
+		let offsets = code_offsets(&module).expect("offsets to be computed");
+		assert_eq!(offsets[0].body(), 1);
+		assert_eq!(offsets[0].instructions(), &[3, 6, 7]);
+		assert_eq!(offsets[0].end(), 8);
+
+		let after = vec![offsets[0].clone()];
+		let origins = vec![vec![Some(0), None, Some(2)]];
+		let map = OffsetMap::new(&offsets, &after, &origins).expect("consistent origins");
+		assert_eq!(map.map(3), Some(3));
+		assert_eq!(map.map(6), None);
+		assert_eq!(map.map(7), Some(7));
+	}
+
+	#[test]
+	fn original_offsets() {
+		let module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.build()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_locals(vec![Local::new(1, ValueType::I32)])
+			.with_instructions(Instructions::new(vec![I32Const(300), Drop, End]))
+			.build()
+			.build()
+			.build();
+		let bytes = module.clone().into_bytes().expect("module to serialize");
+		let lazy = Module::from_bytes_lazy(&bytes).expect("module to deserialize");
+		let code = lazy.lazy_code_section().expect("lazy code section to exist");
+
+		let instructions = code
+			.instructions_with_offsets(1)
+			.expect("body to exist")
+			.and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+			.expect("body to decode");
+		assert_eq!(instructions, vec![(8, I32Const(300)), (11, Drop), (12, End)]);
+		let relative = code.bodies()[1]
+			.instructions_with_offsets()
+			.and_then(|mut iter| iter.next().expect("an instruction"))
+			.expect("body to decode");
+		assert_eq!(relative, (3, I32Const(300)));
+
+		assert_eq!(
+			code_offsets(&lazy).expect("offsets of the binary"),
+			code_offsets(&module).expect("offsets to be computed")
+		);
+		assert!(code.instructions_with_offsets(2).is_none());
+	}
+
+	#[test]
+	fn padded_size_prefixes() {
+		// LLVM pads the size prefix of every body to 5 bytes.
+		let bytes = std::fs::read("./res/cases/v1/inc_i32.wasm").expect("fixture to exist");
+		let lazy = Module::from_bytes_lazy(&bytes).expect("module to deserialize");
+		let offsets = code_offsets(&lazy).expect("offsets of the binary");
+		assert_eq!(offsets.iter().map(|body| body.body()).collect::<Vec<_>>(), vec![1, 13, 21]);
+		for pair in offsets.windows(2) {
+			assert_eq!(pair[0].end(), pair[1].body());
+		}
+
+		let code = lazy.lazy_code_section().expect("lazy code section to exist");
+		let first = code
+			.instructions_with_offsets(1)
+			.expect("body to exist")
+			.and_then(|mut iter| iter.next().expect("an instruction"))
+			.expect("body to decode");
+		assert_eq!(first.0, offsets[1].instructions()[0]);
+		assert!(first.0 > 13 + 5);
+	}
+
+	#[test]
+	fn locate() {
+		let mut module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![I32Const(300), Drop, End]))
+			.build()
+			.build()
+			.build();
+		let mut names = FunctionNameSubsection::default();
+		names.names_mut().insert(0, "main".into());
+		module
+			.sections_mut()
+			.push(Section::Name(NameSection::new(None, Some(names), None)));
+
+		let locator = SourceLocator::new(&module).expect("layout to be computed");
+		assert_eq!(
+			locator.locate(4),
+			Some(SourceLocation { function: 0, instruction: 0, name: Some("main") })
+		);
+		assert_eq!(locator.locate(7).map(|location| location.instruction), Some(2));
+		assert_eq!(locator.locate(1), None);
+		assert_eq!(locator.locate(8), None);
+	}
+}
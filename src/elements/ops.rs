@@ -6,6 +6,13 @@ use crate::io;
 use alloc::{boxed::Box, vec::Vec};
 use core::fmt;
 
+/// Maximum nesting depth of control blocks (`block`, `loop` and `if`) accepted by the decoder.
+///
+/// Decoding itself keeps a plain counter instead of recursing, but code that walks the
+/// decoded instructions block-by-block commonly does recurse, so deeper bodies are rejected
+/// with [`Error::NestingTooDeep`] up front.
+pub const MAX_NESTING_DEPTH: usize = 16384;
+
 /// List of instructions (usually inside a block section).
 #[derive(Debug, Clone, PartialEq)]
 pub struct Instructions(Vec<Instruction>);
@@ -32,10 +39,12 @@ impl Instructions {
 	}
 }
 
-impl Deserialize for Instructions {
-	type Error = Error;
-
-	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+impl Instructions {
+	/// Decode instructions, rejecting bodies nested deeper than `max_depth` blocks.
+	pub(crate) fn deserialize_with_depth<R: io::Read>(
+		reader: &mut R,
+		max_depth: usize,
+	) -> Result<Self, Error> {
 		let mut instructions = Vec::new();
 		let mut block_count = 1usize;
 
@@ -44,8 +53,10 @@ impl Deserialize for Instructions {
 			if instruction.is_terminal() {
 				block_count -= 1;
 			} else if instruction.is_block() {
-				block_count =
-					block_count.checked_add(1).ok_or(Error::Other("too many instructions"))?;
+				if block_count > max_depth {
+					return Err(Error::NestingTooDeep(max_depth))
+				}
+				block_count += 1;
 			}
 
 			instructions.push(instruction);
@@ -58,6 +69,14 @@ impl Deserialize for Instructions {
 	}
 }
 
+impl Deserialize for Instructions {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		Instructions::deserialize_with_depth(reader, MAX_NESTING_DEPTH)
+	}
+}
+
 /// Initialization expression.
 #[derive(Debug, Clone, PartialEq)]
 pub struct InitExpr(Vec<Instruction>);
@@ -159,9 +178,25 @@ pub enum Instruction {
 	I64Store16(u32, u32),
 	I64Store32(u32, u32),
 
+	#[cfg(not(feature = "multi_memory"))]
 	CurrentMemory(u8),
+	#[cfg(not(feature = "multi_memory"))]
 	GrowMemory(u8),
 
+	/// `memory.size` of the memory with the given index.
+	#[cfg(feature = "multi_memory")]
+	CurrentMemory(u32),
+	/// `memory.grow` of the memory with the given index.
+	#[cfg(feature = "multi_memory")]
+	GrowMemory(u32),
+
+	/// Memory instruction addressing the memory with the given index instead of memory 0.
+	///
+	/// Encoded by setting bit 6 of the alignment flags of the wrapped instruction and
+	/// placing the memory index before its offset.
+	#[cfg(feature = "multi_memory")]
+	MemoryIndexed(u32, Box<Instruction>),
+
 	I32Const(i32),
 	I64Const(i64),
 	F32Const(u32),
@@ -307,8 +342,14 @@ pub enum Instruction {
 	#[cfg(feature = "sign_ext")]
 	SignExt(SignExtInstruction),
 
+	#[cfg(feature = "sat_trunc")]
+	SatTrunc(SatTruncInstruction),
+
 	#[cfg(feature = "bulk")]
 	Bulk(BulkInstruction),
+
+	#[cfg(feature = "exceptions")]
+	Exceptions(ExceptionsInstruction),
 }
 
 #[allow(missing_docs)]
@@ -318,6 +359,7 @@ pub enum AtomicsInstruction {
 	AtomicWake(MemArg),
 	I32AtomicWait(MemArg),
 	I64AtomicWait(MemArg),
+	AtomicFence,
 
 	I32AtomicLoad(MemArg),
 	I64AtomicLoad(MemArg),
@@ -391,13 +433,32 @@ pub enum AtomicsInstruction {
 	I64AtomicRmwCmpxchg32u(MemArg),
 }
 
+/// Instruction of the fixed-width SIMD proposal (`0xfd` prefix).
+///
+/// Variants follow the text format mnemonics of the standardised proposal. Names from earlier
+/// drafts that were renamed are kept as deprecated associated items, e.g.
+/// `SimdInstruction::I8x16AddSaturateS`. Draft-only instructions that did not make it into the
+/// proposal (`i8x16.mul`, `i64x2.any_true`, `i64x2.trunc_sat_f64x2_s/u`,
+/// `f64x2.convert_i64x2_s/u`) were removed without a replacement.
 #[allow(missing_docs)]
 #[cfg(feature = "simd")]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SimdInstruction {
-	V128Const(Box<[u8; 16]>),
 	V128Load(MemArg),
+	V128Load8x8S(MemArg),
+	V128Load8x8U(MemArg),
+	V128Load16x4S(MemArg),
+	V128Load16x4U(MemArg),
+	V128Load32x2S(MemArg),
+	V128Load32x2U(MemArg),
+	V128Load8Splat(MemArg),
+	V128Load16Splat(MemArg),
+	V128Load32Splat(MemArg),
+	V128Load64Splat(MemArg),
 	V128Store(MemArg),
+	V128Const(Box<[u8; 16]>),
+	I8x16Shuffle(Box<[u8; 16]>),
+	I8x16Swizzle,
 	I8x16Splat,
 	I16x8Splat,
 	I32x4Splat,
@@ -406,146 +467,219 @@ pub enum SimdInstruction {
 	F64x2Splat,
 	I8x16ExtractLaneS(u8),
 	I8x16ExtractLaneU(u8),
+	I8x16ReplaceLane(u8),
 	I16x8ExtractLaneS(u8),
 	I16x8ExtractLaneU(u8),
-	I32x4ExtractLane(u8),
-	I64x2ExtractLane(u8),
-	F32x4ExtractLane(u8),
-	F64x2ExtractLane(u8),
-	I8x16ReplaceLane(u8),
 	I16x8ReplaceLane(u8),
+	I32x4ExtractLane(u8),
 	I32x4ReplaceLane(u8),
+	I64x2ExtractLane(u8),
 	I64x2ReplaceLane(u8),
+	F32x4ExtractLane(u8),
 	F32x4ReplaceLane(u8),
+	F64x2ExtractLane(u8),
 	F64x2ReplaceLane(u8),
-	V8x16Shuffle(Box<[u8; 16]>),
-	I8x16Add,
-	I16x8Add,
-	I32x4Add,
-	I64x2Add,
-	I8x16Sub,
-	I16x8Sub,
-	I32x4Sub,
-	I64x2Sub,
-	I8x16Mul,
-	I16x8Mul,
-	I32x4Mul,
-	// I64x2Mul,
-	I8x16Neg,
-	I16x8Neg,
-	I32x4Neg,
-	I64x2Neg,
-	I8x16AddSaturateS,
-	I8x16AddSaturateU,
-	I16x8AddSaturateS,
-	I16x8AddSaturateU,
-	I8x16SubSaturateS,
-	I8x16SubSaturateU,
-	I16x8SubSaturateS,
-	I16x8SubSaturateU,
-	I8x16Shl,
-	I16x8Shl,
-	I32x4Shl,
-	I64x2Shl,
-	I8x16ShrS,
-	I8x16ShrU,
-	I16x8ShrS,
-	I16x8ShrU,
-	I32x4ShrS,
-	I32x4ShrU,
-	I64x2ShrS,
-	I64x2ShrU,
-	V128And,
-	V128Or,
-	V128Xor,
-	V128Not,
-	V128Bitselect,
-	I8x16AnyTrue,
-	I16x8AnyTrue,
-	I32x4AnyTrue,
-	I64x2AnyTrue,
-	I8x16AllTrue,
-	I16x8AllTrue,
-	I32x4AllTrue,
-	I64x2AllTrue,
 	I8x16Eq,
-	I16x8Eq,
-	I32x4Eq,
-	// I64x2Eq,
-	F32x4Eq,
-	F64x2Eq,
 	I8x16Ne,
-	I16x8Ne,
-	I32x4Ne,
-	// I64x2Ne,
-	F32x4Ne,
-	F64x2Ne,
 	I8x16LtS,
 	I8x16LtU,
-	I16x8LtS,
-	I16x8LtU,
-	I32x4LtS,
-	I32x4LtU,
-	// I64x2LtS,
-	// I64x2LtU,
-	F32x4Lt,
-	F64x2Lt,
-	I8x16LeS,
-	I8x16LeU,
-	I16x8LeS,
-	I16x8LeU,
-	I32x4LeS,
-	I32x4LeU,
-	// I64x2LeS,
-	// I64x2LeU,
-	F32x4Le,
-	F64x2Le,
 	I8x16GtS,
 	I8x16GtU,
-	I16x8GtS,
-	I16x8GtU,
-	I32x4GtS,
-	I32x4GtU,
-	// I64x2GtS,
-	// I64x2GtU,
-	F32x4Gt,
-	F64x2Gt,
+	I8x16LeS,
+	I8x16LeU,
 	I8x16GeS,
 	I8x16GeU,
+	I16x8Eq,
+	I16x8Ne,
+	I16x8LtS,
+	I16x8LtU,
+	I16x8GtS,
+	I16x8GtU,
+	I16x8LeS,
+	I16x8LeU,
 	I16x8GeS,
 	I16x8GeU,
+	I32x4Eq,
+	I32x4Ne,
+	I32x4LtS,
+	I32x4LtU,
+	I32x4GtS,
+	I32x4GtU,
+	I32x4LeS,
+	I32x4LeU,
 	I32x4GeS,
 	I32x4GeU,
-	// I64x2GeS,
-	// I64x2GeU,
+	F32x4Eq,
+	F32x4Ne,
+	F32x4Lt,
+	F32x4Gt,
+	F32x4Le,
 	F32x4Ge,
+	F64x2Eq,
+	F64x2Ne,
+	F64x2Lt,
+	F64x2Gt,
+	F64x2Le,
 	F64x2Ge,
-	F32x4Neg,
-	F64x2Neg,
+	V128Not,
+	V128And,
+	V128Andnot,
+	V128Or,
+	V128Xor,
+	V128Bitselect,
+	V128AnyTrue,
+	V128Load8Lane(MemArg, u8),
+	V128Load16Lane(MemArg, u8),
+	V128Load32Lane(MemArg, u8),
+	V128Load64Lane(MemArg, u8),
+	V128Store8Lane(MemArg, u8),
+	V128Store16Lane(MemArg, u8),
+	V128Store32Lane(MemArg, u8),
+	V128Store64Lane(MemArg, u8),
+	V128Load32Zero(MemArg),
+	V128Load64Zero(MemArg),
+	F32x4DemoteF64x2Zero,
+	F64x2PromoteLowF32x4,
+	I8x16Abs,
+	I8x16Neg,
+	I8x16Popcnt,
+	I8x16AllTrue,
+	I8x16Bitmask,
+	I8x16NarrowI16x8S,
+	I8x16NarrowI16x8U,
+	F32x4Ceil,
+	F32x4Floor,
+	F32x4Trunc,
+	F32x4Nearest,
+	I8x16Shl,
+	I8x16ShrS,
+	I8x16ShrU,
+	I8x16Add,
+	I8x16AddSatS,
+	I8x16AddSatU,
+	I8x16Sub,
+	I8x16SubSatS,
+	I8x16SubSatU,
+	F64x2Ceil,
+	F64x2Floor,
+	I8x16MinS,
+	I8x16MinU,
+	I8x16MaxS,
+	I8x16MaxU,
+	F64x2Trunc,
+	I8x16AvgrU,
+	I16x8ExtaddPairwiseI8x16S,
+	I16x8ExtaddPairwiseI8x16U,
+	I32x4ExtaddPairwiseI16x8S,
+	I32x4ExtaddPairwiseI16x8U,
+	I16x8Abs,
+	I16x8Neg,
+	I16x8Q15mulrSatS,
+	I16x8AllTrue,
+	I16x8Bitmask,
+	I16x8NarrowI32x4S,
+	I16x8NarrowI32x4U,
+	I16x8ExtendLowI8x16S,
+	I16x8ExtendHighI8x16S,
+	I16x8ExtendLowI8x16U,
+	I16x8ExtendHighI8x16U,
+	I16x8Shl,
+	I16x8ShrS,
+	I16x8ShrU,
+	I16x8Add,
+	I16x8AddSatS,
+	I16x8AddSatU,
+	I16x8Sub,
+	I16x8SubSatS,
+	I16x8SubSatU,
+	F64x2Nearest,
+	I16x8Mul,
+	I16x8MinS,
+	I16x8MinU,
+	I16x8MaxS,
+	I16x8MaxU,
+	I16x8AvgrU,
+	I16x8ExtmulLowI8x16S,
+	I16x8ExtmulHighI8x16S,
+	I16x8ExtmulLowI8x16U,
+	I16x8ExtmulHighI8x16U,
+	I32x4Abs,
+	I32x4Neg,
+	I32x4AllTrue,
+	I32x4Bitmask,
+	I32x4ExtendLowI16x8S,
+	I32x4ExtendHighI16x8S,
+	I32x4ExtendLowI16x8U,
+	I32x4ExtendHighI16x8U,
+	I32x4Shl,
+	I32x4ShrS,
+	I32x4ShrU,
+	I32x4Add,
+	I32x4Sub,
+	I32x4Mul,
+	I32x4MinS,
+	I32x4MinU,
+	I32x4MaxS,
+	I32x4MaxU,
+	I32x4DotI16x8S,
+	I32x4ExtmulLowI16x8S,
+	I32x4ExtmulHighI16x8S,
+	I32x4ExtmulLowI16x8U,
+	I32x4ExtmulHighI16x8U,
+	I64x2Abs,
+	I64x2Neg,
+	I64x2AllTrue,
+	I64x2Bitmask,
+	I64x2ExtendLowI32x4S,
+	I64x2ExtendHighI32x4S,
+	I64x2ExtendLowI32x4U,
+	I64x2ExtendHighI32x4U,
+	I64x2Shl,
+	I64x2ShrS,
+	I64x2ShrU,
+	I64x2Add,
+	I64x2Sub,
+	I64x2Mul,
+	I64x2Eq,
+	I64x2Ne,
+	I64x2LtS,
+	I64x2GtS,
+	I64x2LeS,
+	I64x2GeS,
+	I64x2ExtmulLowI32x4S,
+	I64x2ExtmulHighI32x4S,
+	I64x2ExtmulLowI32x4U,
+	I64x2ExtmulHighI32x4U,
 	F32x4Abs,
-	F64x2Abs,
+	F32x4Neg,
+	F32x4Sqrt,
+	F32x4Add,
+	F32x4Sub,
+	F32x4Mul,
+	F32x4Div,
 	F32x4Min,
-	F64x2Min,
 	F32x4Max,
-	F64x2Max,
-	F32x4Add,
+	F32x4Pmin,
+	F32x4Pmax,
+	F64x2Abs,
+	F64x2Neg,
+	F64x2Sqrt,
 	F64x2Add,
-	F32x4Sub,
 	F64x2Sub,
-	F32x4Div,
-	F64x2Div,
-	F32x4Mul,
 	F64x2Mul,
-	F32x4Sqrt,
-	F64x2Sqrt,
-	F32x4ConvertSI32x4,
-	F32x4ConvertUI32x4,
-	F64x2ConvertSI64x2,
-	F64x2ConvertUI64x2,
-	I32x4TruncSF32x4Sat,
-	I32x4TruncUF32x4Sat,
-	I64x2TruncSF64x2Sat,
-	I64x2TruncUF64x2Sat,
+	F64x2Div,
+	F64x2Min,
+	F64x2Max,
+	F64x2Pmin,
+	F64x2Pmax,
+	I32x4TruncSatF32x4S,
+	I32x4TruncSatF32x4U,
+	F32x4ConvertI32x4S,
+	F32x4ConvertI32x4U,
+	I32x4TruncSatF64x2SZero,
+	I32x4TruncSatF64x2UZero,
+	F64x2ConvertLowI32x4S,
+	F64x2ConvertLowI32x4U,
 }
 
 #[allow(missing_docs)]
@@ -559,6 +693,20 @@ pub enum SignExtInstruction {
 	I64Extend32S,
 }
 
+#[allow(missing_docs)]
+#[cfg(feature = "sat_trunc")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SatTruncInstruction {
+	I32TruncSatSF32,
+	I32TruncSatUF32,
+	I32TruncSatSF64,
+	I32TruncSatUF64,
+	I64TruncSatSF32,
+	I64TruncSatUF32,
+	I64TruncSatSF64,
+	I64TruncSatUF64,
+}
+
 #[allow(missing_docs)]
 #[cfg(feature = "bulk")]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -572,6 +720,18 @@ pub enum BulkInstruction {
 	TableCopy,
 }
 
+#[allow(missing_docs)]
+#[cfg(feature = "exceptions")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExceptionsInstruction {
+	Try(BlockType),
+	Catch(u32),
+	CatchAll,
+	Throw(u32),
+	Rethrow(u32),
+	Delegate(u32),
+}
+
 #[cfg(any(feature = "simd", feature = "atomics"))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
@@ -590,15 +750,383 @@ pub struct BrTableData {
 impl Instruction {
 	/// Is this instruction starts the new block (which should end with terminal instruction).
 	pub fn is_block(&self) -> bool {
+		#[cfg(feature = "exceptions")]
+		if let Instruction::Exceptions(ExceptionsInstruction::Try(_)) = self {
+			return true
+		}
 		matches!(self, &Instruction::Block(_) | &Instruction::Loop(_) | &Instruction::If(_))
 	}
 
 	/// Is this instruction determines the termination of instruction sequence?
 	///
-	/// `true` for `Instruction::End`
+	/// `true` for `Instruction::End` (and `delegate`, which closes a `try` block).
 	pub fn is_terminal(&self) -> bool {
+		#[cfg(feature = "exceptions")]
+		if let Instruction::Exceptions(ExceptionsInstruction::Delegate(_)) = self {
+			return true
+		}
 		matches!(self, &Instruction::End)
 	}
+
+	/// Does this instruction operate on, produce or convert floating point values?
+	///
+	/// Useful with `Module::check_instructions` to reject floating point code.
+	pub fn is_float(&self) -> bool {
+		use Instruction::*;
+		match self {
+			F32Load(..) | F64Load(..) | F32Store(..) | F64Store(..) | F32Const(_) |
+			F64Const(_) | F32Eq | F32Ne | F32Lt | F32Gt | F32Le | F32Ge | F64Eq | F64Ne |
+			F64Lt | F64Gt | F64Le | F64Ge | F32Abs | F32Neg | F32Ceil | F32Floor | F32Trunc |
+			F32Nearest | F32Sqrt | F32Add | F32Sub | F32Mul | F32Div | F32Min | F32Max |
+			F32Copysign | F64Abs | F64Neg | F64Ceil | F64Floor | F64Trunc | F64Nearest |
+			F64Sqrt | F64Add | F64Sub | F64Mul | F64Div | F64Min | F64Max | F64Copysign |
+			I32TruncSF32 | I32TruncUF32 | I32TruncSF64 | I32TruncUF64 | I64TruncSF32 |
+			I64TruncUF32 | I64TruncSF64 | I64TruncUF64 | F32ConvertSI32 | F32ConvertUI32 |
+			F32ConvertSI64 | F32ConvertUI64 | F32DemoteF64 | F64ConvertSI32 | F64ConvertUI32 |
+			F64ConvertSI64 | F64ConvertUI64 | F64PromoteF32 | I32ReinterpretF32 |
+			I64ReinterpretF64 | F32ReinterpretI32 | F64ReinterpretI64 => true,
+			#[cfg(feature = "multi_memory")]
+			MemoryIndexed(_, instruction) => instruction.is_float(),
+			#[cfg(feature = "sat_trunc")]
+			SatTrunc(_) => true,
+			#[cfg(feature = "simd")]
+			Simd(simd) => {
+				use SimdInstruction::*;
+				matches!(
+					simd,
+					F32x4Splat |
+						F64x2Splat | F32x4ExtractLane(_) |
+						F32x4ReplaceLane(_) |
+						F64x2ExtractLane(_) |
+						F64x2ReplaceLane(_) |
+						F32x4Eq | F32x4Ne | F32x4Lt |
+						F32x4Gt | F32x4Le | F32x4Ge |
+						F64x2Eq | F64x2Ne | F64x2Lt |
+						F64x2Gt | F64x2Le | F64x2Ge |
+						F32x4DemoteF64x2Zero |
+						F64x2PromoteLowF32x4 |
+						F32x4Ceil | F32x4Floor |
+						F32x4Trunc | F32x4Nearest |
+						F64x2Ceil | F64x2Floor |
+						F64x2Trunc | F64x2Nearest |
+						F32x4Abs | F32x4Neg |
+						F32x4Sqrt | F32x4Add |
+						F32x4Sub | F32x4Mul |
+						F32x4Div | F32x4Min |
+						F32x4Max | F32x4Pmin |
+						F32x4Pmax | F64x2Abs |
+						F64x2Neg | F64x2Sqrt |
+						F64x2Add | F64x2Sub |
+						F64x2Mul | F64x2Div |
+						F64x2Min | F64x2Max |
+						F64x2Pmin | F64x2Pmax |
+						F32x4ConvertI32x4S | F32x4ConvertI32x4U |
+						F64x2ConvertLowI32x4S |
+						F64x2ConvertLowI32x4U |
+						I32x4TruncSatF32x4S |
+						I32x4TruncSatF32x4U |
+						I32x4TruncSatF64x2SZero |
+						I32x4TruncSatF64x2UZero
+				)
+			},
+			_ => false,
+		}
+	}
+
+	/// Log2 of the access width of a memory instruction, which its alignment must not exceed.
+	///
+	/// Returns `None` for instructions that do not access memory.
+	pub fn natural_alignment(&self) -> Option<u32> {
+		use Instruction::*;
+		match self {
+			I32Load8S(..) | I32Load8U(..) | I64Load8S(..) | I64Load8U(..) | I32Store8(..) |
+			I64Store8(..) => Some(0),
+			I32Load16S(..) | I32Load16U(..) | I64Load16S(..) | I64Load16U(..) |
+			I32Store16(..) | I64Store16(..) => Some(1),
+			I32Load(..) | F32Load(..) | I64Load32S(..) | I64Load32U(..) | I32Store(..) |
+			F32Store(..) | I64Store32(..) => Some(2),
+			I64Load(..) | F64Load(..) | I64Store(..) | F64Store(..) => Some(3),
+			#[cfg(feature = "multi_memory")]
+			MemoryIndexed(_, instruction) => instruction.natural_alignment(),
+			#[cfg(feature = "atomics")]
+			Atomics(atomic) => atomic.memarg().map(|_| atomic.natural_alignment()),
+			#[cfg(feature = "simd")]
+			Simd(simd) => {
+				use SimdInstruction::*;
+				match simd {
+					V128Load8Splat(_) | V128Load8Lane(..) | V128Store8Lane(..) => Some(0),
+					V128Load16Splat(_) | V128Load16Lane(..) | V128Store16Lane(..) => Some(1),
+					V128Load32Splat(_) | V128Load32Lane(..) | V128Store32Lane(..) |
+					V128Load32Zero(_) => Some(2),
+					V128Load8x8S(_) | V128Load8x8U(_) | V128Load16x4S(_) | V128Load16x4U(_) |
+					V128Load32x2S(_) | V128Load32x2U(_) | V128Load64Splat(_) |
+					V128Load64Lane(..) | V128Store64Lane(..) | V128Load64Zero(_) => Some(3),
+					V128Load(_) | V128Store(_) => Some(4),
+					_ => None,
+				}
+			},
+			_ => None,
+		}
+	}
+
+	/// Log2 of the alignment hint of a memory instruction.
+	///
+	/// Returns `None` for instructions that do not access memory.
+	pub fn alignment(&self) -> Option<u32> {
+		use Instruction::*;
+		match self {
+			I32Load(align, _) |
+			I64Load(align, _) |
+			F32Load(align, _) |
+			F64Load(align, _) |
+			I32Load8S(align, _) |
+			I32Load8U(align, _) |
+			I32Load16S(align, _) |
+			I32Load16U(align, _) |
+			I64Load8S(align, _) |
+			I64Load8U(align, _) |
+			I64Load16S(align, _) |
+			I64Load16U(align, _) |
+			I64Load32S(align, _) |
+			I64Load32U(align, _) |
+			I32Store(align, _) |
+			I64Store(align, _) |
+			F32Store(align, _) |
+			F64Store(align, _) |
+			I32Store8(align, _) |
+			I32Store16(align, _) |
+			I64Store8(align, _) |
+			I64Store16(align, _) |
+			I64Store32(align, _) => Some(*align),
+			#[cfg(feature = "multi_memory")]
+			MemoryIndexed(_, instruction) => instruction.alignment(),
+			#[cfg(feature = "atomics")]
+			Atomics(atomic) => atomic.memarg().map(|memarg| u32::from(memarg.align)),
+			#[cfg(feature = "simd")]
+			Simd(simd) => simd.memarg().map(|memarg| u32::from(memarg.align)),
+			_ => None,
+		}
+	}
+
+	/// Is the alignment hint of a memory instruction allowed?
+	///
+	/// It must not exceed the natural alignment, and must equal it for atomic instructions.
+	/// Instructions that do not access memory are always valid.
+	pub fn has_valid_alignment(&self) -> bool {
+		#[cfg(feature = "multi_memory")]
+		if let Instruction::MemoryIndexed(_, instruction) = self {
+			return instruction.has_valid_alignment()
+		}
+		match (self.alignment(), self.natural_alignment()) {
+			#[cfg(feature = "atomics")]
+			(Some(alignment), Some(natural)) if matches!(self, Instruction::Atomics(_)) =>
+				alignment == natural,
+			(Some(alignment), Some(natural)) => alignment <= natural,
+			_ => true,
+		}
+	}
+}
+
+#[cfg(feature = "atomics")]
+impl AtomicsInstruction {
+	fn memarg(&self) -> Option<&MemArg> {
+		use AtomicsInstruction::*;
+		match self {
+			AtomicWake(memarg) |
+			I32AtomicWait(memarg) |
+			I64AtomicWait(memarg) |
+			I32AtomicLoad(memarg) |
+			I64AtomicLoad(memarg) |
+			I32AtomicLoad8u(memarg) |
+			I32AtomicLoad16u(memarg) |
+			I64AtomicLoad8u(memarg) |
+			I64AtomicLoad16u(memarg) |
+			I64AtomicLoad32u(memarg) |
+			I32AtomicStore(memarg) |
+			I64AtomicStore(memarg) |
+			I32AtomicStore8u(memarg) |
+			I32AtomicStore16u(memarg) |
+			I64AtomicStore8u(memarg) |
+			I64AtomicStore16u(memarg) |
+			I64AtomicStore32u(memarg) |
+			I32AtomicRmwAdd(memarg) |
+			I64AtomicRmwAdd(memarg) |
+			I32AtomicRmwAdd8u(memarg) |
+			I32AtomicRmwAdd16u(memarg) |
+			I64AtomicRmwAdd8u(memarg) |
+			I64AtomicRmwAdd16u(memarg) |
+			I64AtomicRmwAdd32u(memarg) |
+			I32AtomicRmwSub(memarg) |
+			I64AtomicRmwSub(memarg) |
+			I32AtomicRmwSub8u(memarg) |
+			I32AtomicRmwSub16u(memarg) |
+			I64AtomicRmwSub8u(memarg) |
+			I64AtomicRmwSub16u(memarg) |
+			I64AtomicRmwSub32u(memarg) |
+			I32AtomicRmwAnd(memarg) |
+			I64AtomicRmwAnd(memarg) |
+			I32AtomicRmwAnd8u(memarg) |
+			I32AtomicRmwAnd16u(memarg) |
+			I64AtomicRmwAnd8u(memarg) |
+			I64AtomicRmwAnd16u(memarg) |
+			I64AtomicRmwAnd32u(memarg) |
+			I32AtomicRmwOr(memarg) |
+			I64AtomicRmwOr(memarg) |
+			I32AtomicRmwOr8u(memarg) |
+			I32AtomicRmwOr16u(memarg) |
+			I64AtomicRmwOr8u(memarg) |
+			I64AtomicRmwOr16u(memarg) |
+			I64AtomicRmwOr32u(memarg) |
+			I32AtomicRmwXor(memarg) |
+			I64AtomicRmwXor(memarg) |
+			I32AtomicRmwXor8u(memarg) |
+			I32AtomicRmwXor16u(memarg) |
+			I64AtomicRmwXor8u(memarg) |
+			I64AtomicRmwXor16u(memarg) |
+			I64AtomicRmwXor32u(memarg) |
+			I32AtomicRmwXchg(memarg) |
+			I64AtomicRmwXchg(memarg) |
+			I32AtomicRmwXchg8u(memarg) |
+			I32AtomicRmwXchg16u(memarg) |
+			I64AtomicRmwXchg8u(memarg) |
+			I64AtomicRmwXchg16u(memarg) |
+			I64AtomicRmwXchg32u(memarg) |
+			I32AtomicRmwCmpxchg(memarg) |
+			I64AtomicRmwCmpxchg(memarg) |
+			I32AtomicRmwCmpxchg8u(memarg) |
+			I32AtomicRmwCmpxchg16u(memarg) |
+			I64AtomicRmwCmpxchg8u(memarg) |
+			I64AtomicRmwCmpxchg16u(memarg) |
+			I64AtomicRmwCmpxchg32u(memarg) => Some(memarg),
+			AtomicFence => None,
+		}
+	}
+
+	fn natural_alignment(&self) -> u32 {
+		use AtomicsInstruction::*;
+		match self {
+			I32AtomicLoad8u(_) |
+			I64AtomicLoad8u(_) |
+			I32AtomicStore8u(_) |
+			I64AtomicStore8u(_) |
+			I32AtomicRmwAdd8u(_) |
+			I64AtomicRmwAdd8u(_) |
+			I32AtomicRmwSub8u(_) |
+			I64AtomicRmwSub8u(_) |
+			I32AtomicRmwAnd8u(_) |
+			I64AtomicRmwAnd8u(_) |
+			I32AtomicRmwOr8u(_) |
+			I64AtomicRmwOr8u(_) |
+			I32AtomicRmwXor8u(_) |
+			I64AtomicRmwXor8u(_) |
+			I32AtomicRmwXchg8u(_) |
+			I64AtomicRmwXchg8u(_) |
+			I32AtomicRmwCmpxchg8u(_) |
+			I64AtomicRmwCmpxchg8u(_) => 0,
+			I32AtomicLoad16u(_) |
+			I64AtomicLoad16u(_) |
+			I32AtomicStore16u(_) |
+			I64AtomicStore16u(_) |
+			I32AtomicRmwAdd16u(_) |
+			I64AtomicRmwAdd16u(_) |
+			I32AtomicRmwSub16u(_) |
+			I64AtomicRmwSub16u(_) |
+			I32AtomicRmwAnd16u(_) |
+			I64AtomicRmwAnd16u(_) |
+			I32AtomicRmwOr16u(_) |
+			I64AtomicRmwOr16u(_) |
+			I32AtomicRmwXor16u(_) |
+			I64AtomicRmwXor16u(_) |
+			I32AtomicRmwXchg16u(_) |
+			I64AtomicRmwXchg16u(_) |
+			I32AtomicRmwCmpxchg16u(_) |
+			I64AtomicRmwCmpxchg16u(_) => 1,
+			I64AtomicWait(_) |
+			I64AtomicLoad(_) |
+			I64AtomicStore(_) |
+			I64AtomicRmwAdd(_) |
+			I64AtomicRmwSub(_) |
+			I64AtomicRmwAnd(_) |
+			I64AtomicRmwOr(_) |
+			I64AtomicRmwXor(_) |
+			I64AtomicRmwXchg(_) |
+			I64AtomicRmwCmpxchg(_) => 3,
+			_ => 2,
+		}
+	}
+}
+
+#[cfg(feature = "simd")]
+#[allow(missing_docs, non_upper_case_globals)]
+impl SimdInstruction {
+	#[deprecated(note = "renamed to `I8x16AddSatS`")]
+	pub const I8x16AddSaturateS: SimdInstruction = SimdInstruction::I8x16AddSatS;
+	#[deprecated(note = "renamed to `I8x16AddSatU`")]
+	pub const I8x16AddSaturateU: SimdInstruction = SimdInstruction::I8x16AddSatU;
+	#[deprecated(note = "renamed to `I8x16SubSatS`")]
+	pub const I8x16SubSaturateS: SimdInstruction = SimdInstruction::I8x16SubSatS;
+	#[deprecated(note = "renamed to `I8x16SubSatU`")]
+	pub const I8x16SubSaturateU: SimdInstruction = SimdInstruction::I8x16SubSatU;
+	#[deprecated(note = "renamed to `I16x8AddSatS`")]
+	pub const I16x8AddSaturateS: SimdInstruction = SimdInstruction::I16x8AddSatS;
+	#[deprecated(note = "renamed to `I16x8AddSatU`")]
+	pub const I16x8AddSaturateU: SimdInstruction = SimdInstruction::I16x8AddSatU;
+	#[deprecated(note = "renamed to `I16x8SubSatS`")]
+	pub const I16x8SubSaturateS: SimdInstruction = SimdInstruction::I16x8SubSatS;
+	#[deprecated(note = "renamed to `I16x8SubSatU`")]
+	pub const I16x8SubSaturateU: SimdInstruction = SimdInstruction::I16x8SubSatU;
+	#[deprecated(note = "renamed to `V128AnyTrue`")]
+	pub const I8x16AnyTrue: SimdInstruction = SimdInstruction::V128AnyTrue;
+	#[deprecated(note = "renamed to `V128AnyTrue`")]
+	pub const I16x8AnyTrue: SimdInstruction = SimdInstruction::V128AnyTrue;
+	#[deprecated(note = "renamed to `V128AnyTrue`")]
+	pub const I32x4AnyTrue: SimdInstruction = SimdInstruction::V128AnyTrue;
+	#[deprecated(note = "renamed to `I32x4TruncSatF32x4S`")]
+	pub const I32x4TruncSF32x4Sat: SimdInstruction = SimdInstruction::I32x4TruncSatF32x4S;
+	#[deprecated(note = "renamed to `I32x4TruncSatF32x4U`")]
+	pub const I32x4TruncUF32x4Sat: SimdInstruction = SimdInstruction::I32x4TruncSatF32x4U;
+	#[deprecated(note = "renamed to `F32x4ConvertI32x4S`")]
+	pub const F32x4ConvertSI32x4: SimdInstruction = SimdInstruction::F32x4ConvertI32x4S;
+	#[deprecated(note = "renamed to `F32x4ConvertI32x4U`")]
+	pub const F32x4ConvertUI32x4: SimdInstruction = SimdInstruction::F32x4ConvertI32x4U;
+
+	#[allow(non_snake_case)]
+	#[deprecated(note = "renamed to `I8x16Shuffle`")]
+	pub fn V8x16Shuffle(lanes: Box<[u8; 16]>) -> SimdInstruction {
+		SimdInstruction::I8x16Shuffle(lanes)
+	}
+}
+
+#[cfg(feature = "simd")]
+impl SimdInstruction {
+	fn memarg(&self) -> Option<&MemArg> {
+		use SimdInstruction::*;
+		match self {
+			V128Load(memarg) |
+			V128Load8x8S(memarg) |
+			V128Load8x8U(memarg) |
+			V128Load16x4S(memarg) |
+			V128Load16x4U(memarg) |
+			V128Load32x2S(memarg) |
+			V128Load32x2U(memarg) |
+			V128Load8Splat(memarg) |
+			V128Load16Splat(memarg) |
+			V128Load32Splat(memarg) |
+			V128Load64Splat(memarg) |
+			V128Store(memarg) |
+			V128Load32Zero(memarg) |
+			V128Load64Zero(memarg) |
+			V128Load8Lane(memarg, _) |
+			V128Load16Lane(memarg, _) |
+			V128Load32Lane(memarg, _) |
+			V128Load64Lane(memarg, _) |
+			V128Store8Lane(memarg, _) |
+			V128Store16Lane(memarg, _) |
+			V128Store32Lane(memarg, _) |
+			V128Store64Lane(memarg, _) => Some(memarg),
+			_ => None,
+		}
+	}
 }
 
 #[allow(missing_docs)]
@@ -791,12 +1319,36 @@ pub mod opcodes {
 		pub const I64_EXTEND32_S: u8 = 0xc4;
 	}
 
+	#[cfg(feature = "exceptions")]
+	pub mod exceptions {
+		pub const TRY: u8 = 0x06;
+		pub const CATCH: u8 = 0x07;
+		pub const THROW: u8 = 0x08;
+		pub const RETHROW: u8 = 0x09;
+		pub const DELEGATE: u8 = 0x18;
+		pub const CATCH_ALL: u8 = 0x19;
+	}
+
+	#[cfg(feature = "sat_trunc")]
+	pub mod sat_trunc {
+		pub const SAT_TRUNC_PREFIX: u8 = 0xfc;
+		pub const I32_TRUNC_SAT_S_F32: u8 = 0x00;
+		pub const I32_TRUNC_SAT_U_F32: u8 = 0x01;
+		pub const I32_TRUNC_SAT_S_F64: u8 = 0x02;
+		pub const I32_TRUNC_SAT_U_F64: u8 = 0x03;
+		pub const I64_TRUNC_SAT_S_F32: u8 = 0x04;
+		pub const I64_TRUNC_SAT_U_F32: u8 = 0x05;
+		pub const I64_TRUNC_SAT_S_F64: u8 = 0x06;
+		pub const I64_TRUNC_SAT_U_F64: u8 = 0x07;
+	}
+
 	#[cfg(feature = "atomics")]
 	pub mod atomics {
 		pub const ATOMIC_PREFIX: u8 = 0xfe;
 		pub const ATOMIC_WAKE: u8 = 0x00;
 		pub const I32_ATOMIC_WAIT: u8 = 0x01;
 		pub const I64_ATOMIC_WAIT: u8 = 0x02;
+		pub const ATOMIC_FENCE: u8 = 0x03;
 
 		pub const I32_ATOMIC_LOAD: u8 = 0x10;
 		pub const I64_ATOMIC_LOAD: u8 = 0x11;
@@ -876,164 +1428,315 @@ pub mod opcodes {
 		pub const SIMD_PREFIX: u8 = 0xfd;
 
 		pub const V128_LOAD: u32 = 0x00;
-		pub const V128_STORE: u32 = 0x01;
-		pub const V128_CONST: u32 = 0x02;
-		pub const V8X16_SHUFFLE: u32 = 0x03;
-
-		pub const I8X16_SPLAT: u32 = 0x04;
-		pub const I8X16_EXTRACT_LANE_S: u32 = 0x05;
-		pub const I8X16_EXTRACT_LANE_U: u32 = 0x06;
-		pub const I8X16_REPLACE_LANE: u32 = 0x07;
-		pub const I16X8_SPLAT: u32 = 0x08;
-		pub const I16X8_EXTRACT_LANE_S: u32 = 0x09;
-		pub const I16X8_EXTRACT_LANE_U: u32 = 0xa;
-		pub const I16X8_REPLACE_LANE: u32 = 0x0b;
-		pub const I32X4_SPLAT: u32 = 0x0c;
-		pub const I32X4_EXTRACT_LANE: u32 = 0x0d;
-		pub const I32X4_REPLACE_LANE: u32 = 0x0e;
-		pub const I64X2_SPLAT: u32 = 0x0f;
-		pub const I64X2_EXTRACT_LANE: u32 = 0x10;
-		pub const I64X2_REPLACE_LANE: u32 = 0x11;
-		pub const F32X4_SPLAT: u32 = 0x12;
-		pub const F32X4_EXTRACT_LANE: u32 = 0x13;
-		pub const F32X4_REPLACE_LANE: u32 = 0x14;
-		pub const F64X2_SPLAT: u32 = 0x15;
-		pub const F64X2_EXTRACT_LANE: u32 = 0x16;
-		pub const F64X2_REPLACE_LANE: u32 = 0x17;
-
-		pub const I8X16_EQ: u32 = 0x18;
-		pub const I8X16_NE: u32 = 0x19;
-		pub const I8X16_LT_S: u32 = 0x1a;
-		pub const I8X16_LT_U: u32 = 0x1b;
-		pub const I8X16_GT_S: u32 = 0x1c;
-		pub const I8X16_GT_U: u32 = 0x1d;
-		pub const I8X16_LE_S: u32 = 0x1e;
-		pub const I8X16_LE_U: u32 = 0x1f;
-		pub const I8X16_GE_S: u32 = 0x20;
-		pub const I8X16_GE_U: u32 = 0x21;
-
-		pub const I16X8_EQ: u32 = 0x22;
-		pub const I16X8_NE: u32 = 0x23;
-		pub const I16X8_LT_S: u32 = 0x24;
-		pub const I16X8_LT_U: u32 = 0x25;
-		pub const I16X8_GT_S: u32 = 0x26;
-		pub const I16X8_GT_U: u32 = 0x27;
-		pub const I16X8_LE_S: u32 = 0x28;
-		pub const I16X8_LE_U: u32 = 0x29;
-		pub const I16X8_GE_S: u32 = 0x2a;
-		pub const I16X8_GE_U: u32 = 0x2b;
-
-		pub const I32X4_EQ: u32 = 0x2c;
-		pub const I32X4_NE: u32 = 0x2d;
-		pub const I32X4_LT_S: u32 = 0x2e;
-		pub const I32X4_LT_U: u32 = 0x2f;
-		pub const I32X4_GT_S: u32 = 0x30;
-		pub const I32X4_GT_U: u32 = 0x31;
-		pub const I32X4_LE_S: u32 = 0x32;
-		pub const I32X4_LE_U: u32 = 0x33;
-		pub const I32X4_GE_S: u32 = 0x34;
-		pub const I32X4_GE_U: u32 = 0x35;
-
-		pub const F32X4_EQ: u32 = 0x40;
-		pub const F32X4_NE: u32 = 0x41;
-		pub const F32X4_LT: u32 = 0x42;
-		pub const F32X4_GT: u32 = 0x43;
-		pub const F32X4_LE: u32 = 0x44;
-		pub const F32X4_GE: u32 = 0x45;
-
-		pub const F64X2_EQ: u32 = 0x46;
-		pub const F64X2_NE: u32 = 0x47;
-		pub const F64X2_LT: u32 = 0x48;
-		pub const F64X2_GT: u32 = 0x49;
-		pub const F64X2_LE: u32 = 0x4a;
-		pub const F64X2_GE: u32 = 0x4b;
-
-		pub const V128_NOT: u32 = 0x4c;
-		pub const V128_AND: u32 = 0x4d;
-		pub const V128_OR: u32 = 0x4e;
-		pub const V128_XOR: u32 = 0x4f;
-		pub const V128_BITSELECT: u32 = 0x50;
-
-		pub const I8X16_NEG: u32 = 0x51;
-		pub const I8X16_ANY_TRUE: u32 = 0x52;
-		pub const I8X16_ALL_TRUE: u32 = 0x53;
-		pub const I8X16_SHL: u32 = 0x54;
-		pub const I8X16_SHR_S: u32 = 0x55;
-		pub const I8X16_SHR_U: u32 = 0x56;
-		pub const I8X16_ADD: u32 = 0x57;
-		pub const I8X16_ADD_SATURATE_S: u32 = 0x58;
-		pub const I8X16_ADD_SATURATE_U: u32 = 0x59;
-		pub const I8X16_SUB: u32 = 0x5a;
-		pub const I8X16_SUB_SATURATE_S: u32 = 0x5b;
-		pub const I8X16_SUB_SATURATE_U: u32 = 0x5c;
-		pub const I8X16_MUL: u32 = 0x5d;
-
-		pub const I16X8_NEG: u32 = 0x62;
-		pub const I16X8_ANY_TRUE: u32 = 0x63;
-		pub const I16X8_ALL_TRUE: u32 = 0x64;
-		pub const I16X8_SHL: u32 = 0x65;
-		pub const I16X8_SHR_S: u32 = 0x66;
-		pub const I16X8_SHR_U: u32 = 0x67;
-		pub const I16X8_ADD: u32 = 0x68;
-		pub const I16X8_ADD_SATURATE_S: u32 = 0x69;
-		pub const I16X8_ADD_SATURATE_U: u32 = 0x6a;
-		pub const I16X8_SUB: u32 = 0x6b;
-		pub const I16X8_SUB_SATURATE_S: u32 = 0x6c;
-		pub const I16X8_SUB_SATURATE_U: u32 = 0x6d;
-		pub const I16X8_MUL: u32 = 0x6e;
-
-		pub const I32X4_NEG: u32 = 0x73;
-		pub const I32X4_ANY_TRUE: u32 = 0x74;
-		pub const I32X4_ALL_TRUE: u32 = 0x75;
-		pub const I32X4_SHL: u32 = 0x76;
-		pub const I32X4_SHR_S: u32 = 0x77;
-		pub const I32X4_SHR_U: u32 = 0x78;
-		pub const I32X4_ADD: u32 = 0x79;
-		pub const I32X4_ADD_SATURATE_S: u32 = 0x7a;
-		pub const I32X4_ADD_SATURATE_U: u32 = 0x7b;
-		pub const I32X4_SUB: u32 = 0x7c;
-		pub const I32X4_SUB_SATURATE_S: u32 = 0x7d;
-		pub const I32X4_SUB_SATURATE_U: u32 = 0x7e;
-		pub const I32X4_MUL: u32 = 0x7f;
-
-		pub const I64X2_NEG: u32 = 0x84;
-		pub const I64X2_ANY_TRUE: u32 = 0x85;
-		pub const I64X2_ALL_TRUE: u32 = 0x86;
-		pub const I64X2_SHL: u32 = 0x87;
-		pub const I64X2_SHR_S: u32 = 0x88;
-		pub const I64X2_SHR_U: u32 = 0x89;
-		pub const I64X2_ADD: u32 = 0x8a;
-		pub const I64X2_SUB: u32 = 0x8d;
-
-		pub const F32X4_ABS: u32 = 0x95;
-		pub const F32X4_NEG: u32 = 0x96;
-		pub const F32X4_SQRT: u32 = 0x97;
-		pub const F32X4_ADD: u32 = 0x9a;
-		pub const F32X4_SUB: u32 = 0x9b;
-		pub const F32X4_MUL: u32 = 0x9c;
-		pub const F32X4_DIV: u32 = 0x9d;
-		pub const F32X4_MIN: u32 = 0x9e;
-		pub const F32X4_MAX: u32 = 0x9f;
-
-		pub const F64X2_ABS: u32 = 0xa0;
-		pub const F64X2_NEG: u32 = 0xa1;
-		pub const F64X2_SQRT: u32 = 0xa2;
-		pub const F64X2_ADD: u32 = 0xa5;
-		pub const F64X2_SUB: u32 = 0xa6;
-		pub const F64X2_MUL: u32 = 0xa7;
-		pub const F64X2_DIV: u32 = 0xa8;
-		pub const F64X2_MIN: u32 = 0xa9;
-		pub const F64X2_MAX: u32 = 0xaa;
-
-		pub const I32X4_TRUNC_S_F32X4_SAT: u32 = 0xab;
-		pub const I32X4_TRUNC_U_F32X4_SAT: u32 = 0xac;
-		pub const I64X2_TRUNC_S_F64X2_SAT: u32 = 0xad;
-		pub const I64X2_TRUNC_U_F64X2_SAT: u32 = 0xae;
-
-		pub const F32X4_CONVERT_S_I32X4: u32 = 0xaf;
-		pub const F32X4_CONVERT_U_I32X4: u32 = 0xb0;
-		pub const F64X2_CONVERT_S_I64X2: u32 = 0xb1;
-		pub const F64X2_CONVERT_U_I64X2: u32 = 0xb2;
+		pub const V128_LOAD8X8_S: u32 = 0x01;
+		pub const V128_LOAD8X8_U: u32 = 0x02;
+		pub const V128_LOAD16X4_S: u32 = 0x03;
+		pub const V128_LOAD16X4_U: u32 = 0x04;
+		pub const V128_LOAD32X2_S: u32 = 0x05;
+		pub const V128_LOAD32X2_U: u32 = 0x06;
+		pub const V128_LOAD8_SPLAT: u32 = 0x07;
+		pub const V128_LOAD16_SPLAT: u32 = 0x08;
+		pub const V128_LOAD32_SPLAT: u32 = 0x09;
+		pub const V128_LOAD64_SPLAT: u32 = 0x0a;
+		pub const V128_STORE: u32 = 0x0b;
+		pub const V128_CONST: u32 = 0x0c;
+
+		pub const I8X16_SHUFFLE: u32 = 0x0d;
+		pub const I8X16_SWIZZLE: u32 = 0x0e;
+		pub const I8X16_SPLAT: u32 = 0x0f;
+
+		pub const I16X8_SPLAT: u32 = 0x10;
+
+		pub const I32X4_SPLAT: u32 = 0x11;
+
+		pub const I64X2_SPLAT: u32 = 0x12;
+
+		pub const F32X4_SPLAT: u32 = 0x13;
+
+		pub const F64X2_SPLAT: u32 = 0x14;
+
+		pub const I8X16_EXTRACT_LANE_S: u32 = 0x15;
+		pub const I8X16_EXTRACT_LANE_U: u32 = 0x16;
+		pub const I8X16_REPLACE_LANE: u32 = 0x17;
+
+		pub const I16X8_EXTRACT_LANE_S: u32 = 0x18;
+		pub const I16X8_EXTRACT_LANE_U: u32 = 0x19;
+		pub const I16X8_REPLACE_LANE: u32 = 0x1a;
+
+		pub const I32X4_EXTRACT_LANE: u32 = 0x1b;
+		pub const I32X4_REPLACE_LANE: u32 = 0x1c;
+
+		pub const I64X2_EXTRACT_LANE: u32 = 0x1d;
+		pub const I64X2_REPLACE_LANE: u32 = 0x1e;
+
+		pub const F32X4_EXTRACT_LANE: u32 = 0x1f;
+		pub const F32X4_REPLACE_LANE: u32 = 0x20;
+
+		pub const F64X2_EXTRACT_LANE: u32 = 0x21;
+		pub const F64X2_REPLACE_LANE: u32 = 0x22;
+
+		pub const I8X16_EQ: u32 = 0x23;
+		pub const I8X16_NE: u32 = 0x24;
+		pub const I8X16_LT_S: u32 = 0x25;
+		pub const I8X16_LT_U: u32 = 0x26;
+		pub const I8X16_GT_S: u32 = 0x27;
+		pub const I8X16_GT_U: u32 = 0x28;
+		pub const I8X16_LE_S: u32 = 0x29;
+		pub const I8X16_LE_U: u32 = 0x2a;
+		pub const I8X16_GE_S: u32 = 0x2b;
+		pub const I8X16_GE_U: u32 = 0x2c;
+
+		pub const I16X8_EQ: u32 = 0x2d;
+		pub const I16X8_NE: u32 = 0x2e;
+		pub const I16X8_LT_S: u32 = 0x2f;
+		pub const I16X8_LT_U: u32 = 0x30;
+		pub const I16X8_GT_S: u32 = 0x31;
+		pub const I16X8_GT_U: u32 = 0x32;
+		pub const I16X8_LE_S: u32 = 0x33;
+		pub const I16X8_LE_U: u32 = 0x34;
+		pub const I16X8_GE_S: u32 = 0x35;
+		pub const I16X8_GE_U: u32 = 0x36;
+
+		pub const I32X4_EQ: u32 = 0x37;
+		pub const I32X4_NE: u32 = 0x38;
+		pub const I32X4_LT_S: u32 = 0x39;
+		pub const I32X4_LT_U: u32 = 0x3a;
+		pub const I32X4_GT_S: u32 = 0x3b;
+		pub const I32X4_GT_U: u32 = 0x3c;
+		pub const I32X4_LE_S: u32 = 0x3d;
+		pub const I32X4_LE_U: u32 = 0x3e;
+		pub const I32X4_GE_S: u32 = 0x3f;
+		pub const I32X4_GE_U: u32 = 0x40;
+
+		pub const F32X4_EQ: u32 = 0x41;
+		pub const F32X4_NE: u32 = 0x42;
+		pub const F32X4_LT: u32 = 0x43;
+		pub const F32X4_GT: u32 = 0x44;
+		pub const F32X4_LE: u32 = 0x45;
+		pub const F32X4_GE: u32 = 0x46;
+
+		pub const F64X2_EQ: u32 = 0x47;
+		pub const F64X2_NE: u32 = 0x48;
+		pub const F64X2_LT: u32 = 0x49;
+		pub const F64X2_GT: u32 = 0x4a;
+		pub const F64X2_LE: u32 = 0x4b;
+		pub const F64X2_GE: u32 = 0x4c;
+
+		pub const V128_NOT: u32 = 0x4d;
+		pub const V128_AND: u32 = 0x4e;
+		pub const V128_ANDNOT: u32 = 0x4f;
+		pub const V128_OR: u32 = 0x50;
+		pub const V128_XOR: u32 = 0x51;
+		pub const V128_BITSELECT: u32 = 0x52;
+		pub const V128_ANY_TRUE: u32 = 0x53;
+		pub const V128_LOAD8_LANE: u32 = 0x54;
+		pub const V128_LOAD16_LANE: u32 = 0x55;
+		pub const V128_LOAD32_LANE: u32 = 0x56;
+		pub const V128_LOAD64_LANE: u32 = 0x57;
+		pub const V128_STORE8_LANE: u32 = 0x58;
+		pub const V128_STORE16_LANE: u32 = 0x59;
+		pub const V128_STORE32_LANE: u32 = 0x5a;
+		pub const V128_STORE64_LANE: u32 = 0x5b;
+		pub const V128_LOAD32_ZERO: u32 = 0x5c;
+		pub const V128_LOAD64_ZERO: u32 = 0x5d;
+
+		pub const F32X4_DEMOTE_F64X2_ZERO: u32 = 0x5e;
+
+		pub const F64X2_PROMOTE_LOW_F32X4: u32 = 0x5f;
+
+		pub const I8X16_ABS: u32 = 0x60;
+		pub const I8X16_NEG: u32 = 0x61;
+		pub const I8X16_POPCNT: u32 = 0x62;
+		pub const I8X16_ALL_TRUE: u32 = 0x63;
+		pub const I8X16_BITMASK: u32 = 0x64;
+		pub const I8X16_NARROW_I16X8_S: u32 = 0x65;
+		pub const I8X16_NARROW_I16X8_U: u32 = 0x66;
+
+		pub const F32X4_CEIL: u32 = 0x67;
+		pub const F32X4_FLOOR: u32 = 0x68;
+		pub const F32X4_TRUNC: u32 = 0x69;
+		pub const F32X4_NEAREST: u32 = 0x6a;
+
+		pub const I8X16_SHL: u32 = 0x6b;
+		pub const I8X16_SHR_S: u32 = 0x6c;
+		pub const I8X16_SHR_U: u32 = 0x6d;
+		pub const I8X16_ADD: u32 = 0x6e;
+		pub const I8X16_ADD_SAT_S: u32 = 0x6f;
+		pub const I8X16_ADD_SAT_U: u32 = 0x70;
+		pub const I8X16_SUB: u32 = 0x71;
+		pub const I8X16_SUB_SAT_S: u32 = 0x72;
+		pub const I8X16_SUB_SAT_U: u32 = 0x73;
+
+		pub const F64X2_CEIL: u32 = 0x74;
+		pub const F64X2_FLOOR: u32 = 0x75;
+
+		pub const I8X16_MIN_S: u32 = 0x76;
+		pub const I8X16_MIN_U: u32 = 0x77;
+		pub const I8X16_MAX_S: u32 = 0x78;
+		pub const I8X16_MAX_U: u32 = 0x79;
+
+		pub const F64X2_TRUNC: u32 = 0x7a;
+
+		pub const I8X16_AVGR_U: u32 = 0x7b;
+
+		pub const I16X8_EXTADD_PAIRWISE_I8X16_S: u32 = 0x7c;
+		pub const I16X8_EXTADD_PAIRWISE_I8X16_U: u32 = 0x7d;
+
+		pub const I32X4_EXTADD_PAIRWISE_I16X8_S: u32 = 0x7e;
+		pub const I32X4_EXTADD_PAIRWISE_I16X8_U: u32 = 0x7f;
+
+		pub const I16X8_ABS: u32 = 0x80;
+		pub const I16X8_NEG: u32 = 0x81;
+		pub const I16X8_Q15MULR_SAT_S: u32 = 0x82;
+		pub const I16X8_ALL_TRUE: u32 = 0x83;
+		pub const I16X8_BITMASK: u32 = 0x84;
+		pub const I16X8_NARROW_I32X4_S: u32 = 0x85;
+		pub const I16X8_NARROW_I32X4_U: u32 = 0x86;
+		pub const I16X8_EXTEND_LOW_I8X16_S: u32 = 0x87;
+		pub const I16X8_EXTEND_HIGH_I8X16_S: u32 = 0x88;
+		pub const I16X8_EXTEND_LOW_I8X16_U: u32 = 0x89;
+		pub const I16X8_EXTEND_HIGH_I8X16_U: u32 = 0x8a;
+		pub const I16X8_SHL: u32 = 0x8b;
+		pub const I16X8_SHR_S: u32 = 0x8c;
+		pub const I16X8_SHR_U: u32 = 0x8d;
+		pub const I16X8_ADD: u32 = 0x8e;
+		pub const I16X8_ADD_SAT_S: u32 = 0x8f;
+		pub const I16X8_ADD_SAT_U: u32 = 0x90;
+		pub const I16X8_SUB: u32 = 0x91;
+		pub const I16X8_SUB_SAT_S: u32 = 0x92;
+		pub const I16X8_SUB_SAT_U: u32 = 0x93;
+
+		pub const F64X2_NEAREST: u32 = 0x94;
+
+		pub const I16X8_MUL: u32 = 0x95;
+		pub const I16X8_MIN_S: u32 = 0x96;
+		pub const I16X8_MIN_U: u32 = 0x97;
+		pub const I16X8_MAX_S: u32 = 0x98;
+		pub const I16X8_MAX_U: u32 = 0x99;
+		pub const I16X8_AVGR_U: u32 = 0x9b;
+		pub const I16X8_EXTMUL_LOW_I8X16_S: u32 = 0x9c;
+		pub const I16X8_EXTMUL_HIGH_I8X16_S: u32 = 0x9d;
+		pub const I16X8_EXTMUL_LOW_I8X16_U: u32 = 0x9e;
+		pub const I16X8_EXTMUL_HIGH_I8X16_U: u32 = 0x9f;
+
+		pub const I32X4_ABS: u32 = 0xa0;
+		pub const I32X4_NEG: u32 = 0xa1;
+		pub const I32X4_ALL_TRUE: u32 = 0xa3;
+		pub const I32X4_BITMASK: u32 = 0xa4;
+		pub const I32X4_EXTEND_LOW_I16X8_S: u32 = 0xa7;
+		pub const I32X4_EXTEND_HIGH_I16X8_S: u32 = 0xa8;
+		pub const I32X4_EXTEND_LOW_I16X8_U: u32 = 0xa9;
+		pub const I32X4_EXTEND_HIGH_I16X8_U: u32 = 0xaa;
+		pub const I32X4_SHL: u32 = 0xab;
+		pub const I32X4_SHR_S: u32 = 0xac;
+		pub const I32X4_SHR_U: u32 = 0xad;
+		pub const I32X4_ADD: u32 = 0xae;
+		pub const I32X4_SUB: u32 = 0xb1;
+		pub const I32X4_MUL: u32 = 0xb5;
+		pub const I32X4_MIN_S: u32 = 0xb6;
+		pub const I32X4_MIN_U: u32 = 0xb7;
+		pub const I32X4_MAX_S: u32 = 0xb8;
+		pub const I32X4_MAX_U: u32 = 0xb9;
+		pub const I32X4_DOT_I16X8_S: u32 = 0xba;
+		pub const I32X4_EXTMUL_LOW_I16X8_S: u32 = 0xbc;
+		pub const I32X4_EXTMUL_HIGH_I16X8_S: u32 = 0xbd;
+		pub const I32X4_EXTMUL_LOW_I16X8_U: u32 = 0xbe;
+		pub const I32X4_EXTMUL_HIGH_I16X8_U: u32 = 0xbf;
+
+		pub const I64X2_ABS: u32 = 0xc0;
+		pub const I64X2_NEG: u32 = 0xc1;
+		pub const I64X2_ALL_TRUE: u32 = 0xc3;
+		pub const I64X2_BITMASK: u32 = 0xc4;
+		pub const I64X2_EXTEND_LOW_I32X4_S: u32 = 0xc7;
+		pub const I64X2_EXTEND_HIGH_I32X4_S: u32 = 0xc8;
+		pub const I64X2_EXTEND_LOW_I32X4_U: u32 = 0xc9;
+		pub const I64X2_EXTEND_HIGH_I32X4_U: u32 = 0xca;
+		pub const I64X2_SHL: u32 = 0xcb;
+		pub const I64X2_SHR_S: u32 = 0xcc;
+		pub const I64X2_SHR_U: u32 = 0xcd;
+		pub const I64X2_ADD: u32 = 0xce;
+		pub const I64X2_SUB: u32 = 0xd1;
+		pub const I64X2_MUL: u32 = 0xd5;
+		pub const I64X2_EQ: u32 = 0xd6;
+		pub const I64X2_NE: u32 = 0xd7;
+		pub const I64X2_LT_S: u32 = 0xd8;
+		pub const I64X2_GT_S: u32 = 0xd9;
+		pub const I64X2_LE_S: u32 = 0xda;
+		pub const I64X2_GE_S: u32 = 0xdb;
+		pub const I64X2_EXTMUL_LOW_I32X4_S: u32 = 0xdc;
+		pub const I64X2_EXTMUL_HIGH_I32X4_S: u32 = 0xdd;
+		pub const I64X2_EXTMUL_LOW_I32X4_U: u32 = 0xde;
+		pub const I64X2_EXTMUL_HIGH_I32X4_U: u32 = 0xdf;
+
+		pub const F32X4_ABS: u32 = 0xe0;
+		pub const F32X4_NEG: u32 = 0xe1;
+		pub const F32X4_SQRT: u32 = 0xe3;
+		pub const F32X4_ADD: u32 = 0xe4;
+		pub const F32X4_SUB: u32 = 0xe5;
+		pub const F32X4_MUL: u32 = 0xe6;
+		pub const F32X4_DIV: u32 = 0xe7;
+		pub const F32X4_MIN: u32 = 0xe8;
+		pub const F32X4_MAX: u32 = 0xe9;
+		pub const F32X4_PMIN: u32 = 0xea;
+		pub const F32X4_PMAX: u32 = 0xeb;
+
+		pub const F64X2_ABS: u32 = 0xec;
+		pub const F64X2_NEG: u32 = 0xed;
+		pub const F64X2_SQRT: u32 = 0xef;
+		pub const F64X2_ADD: u32 = 0xf0;
+		pub const F64X2_SUB: u32 = 0xf1;
+		pub const F64X2_MUL: u32 = 0xf2;
+		pub const F64X2_DIV: u32 = 0xf3;
+		pub const F64X2_MIN: u32 = 0xf4;
+		pub const F64X2_MAX: u32 = 0xf5;
+		pub const F64X2_PMIN: u32 = 0xf6;
+		pub const F64X2_PMAX: u32 = 0xf7;
+
+		pub const I32X4_TRUNC_SAT_F32X4_S: u32 = 0xf8;
+		pub const I32X4_TRUNC_SAT_F32X4_U: u32 = 0xf9;
+
+		pub const F32X4_CONVERT_I32X4_S: u32 = 0xfa;
+		pub const F32X4_CONVERT_I32X4_U: u32 = 0xfb;
+
+		pub const I32X4_TRUNC_SAT_F64X2_S_ZERO: u32 = 0xfc;
+		pub const I32X4_TRUNC_SAT_F64X2_U_ZERO: u32 = 0xfd;
+
+		pub const F64X2_CONVERT_LOW_I32X4_S: u32 = 0xfe;
+		pub const F64X2_CONVERT_LOW_I32X4_U: u32 = 0xff;
+
+		// Draft names of renamed instructions.
+		#[deprecated(note = "renamed to `I8X16_ADD_SAT_S`")]
+		pub const I8X16_ADD_SATURATE_S: u32 = I8X16_ADD_SAT_S;
+		#[deprecated(note = "renamed to `I8X16_ADD_SAT_U`")]
+		pub const I8X16_ADD_SATURATE_U: u32 = I8X16_ADD_SAT_U;
+		#[deprecated(note = "renamed to `I8X16_SUB_SAT_S`")]
+		pub const I8X16_SUB_SATURATE_S: u32 = I8X16_SUB_SAT_S;
+		#[deprecated(note = "renamed to `I8X16_SUB_SAT_U`")]
+		pub const I8X16_SUB_SATURATE_U: u32 = I8X16_SUB_SAT_U;
+		#[deprecated(note = "renamed to `I16X8_ADD_SAT_S`")]
+		pub const I16X8_ADD_SATURATE_S: u32 = I16X8_ADD_SAT_S;
+		#[deprecated(note = "renamed to `I16X8_ADD_SAT_U`")]
+		pub const I16X8_ADD_SATURATE_U: u32 = I16X8_ADD_SAT_U;
+		#[deprecated(note = "renamed to `I16X8_SUB_SAT_S`")]
+		pub const I16X8_SUB_SATURATE_S: u32 = I16X8_SUB_SAT_S;
+		#[deprecated(note = "renamed to `I16X8_SUB_SAT_U`")]
+		pub const I16X8_SUB_SATURATE_U: u32 = I16X8_SUB_SAT_U;
+		#[deprecated(note = "renamed to `V128_ANY_TRUE`")]
+		pub const I8X16_ANY_TRUE: u32 = V128_ANY_TRUE;
+		#[deprecated(note = "renamed to `V128_ANY_TRUE`")]
+		pub const I16X8_ANY_TRUE: u32 = V128_ANY_TRUE;
+		#[deprecated(note = "renamed to `V128_ANY_TRUE`")]
+		pub const I32X4_ANY_TRUE: u32 = V128_ANY_TRUE;
+		#[deprecated(note = "renamed to `I32X4_TRUNC_SAT_F32X4_S`")]
+		pub const I32X4_TRUNC_S_F32X4_SAT: u32 = I32X4_TRUNC_SAT_F32X4_S;
+		#[deprecated(note = "renamed to `I32X4_TRUNC_SAT_F32X4_U`")]
+		pub const I32X4_TRUNC_U_F32X4_SAT: u32 = I32X4_TRUNC_SAT_F32X4_U;
+		#[deprecated(note = "renamed to `F32X4_CONVERT_I32X4_S`")]
+		pub const F32X4_CONVERT_S_I32X4: u32 = F32X4_CONVERT_I32X4_S;
+		#[deprecated(note = "renamed to `F32X4_CONVERT_I32X4_U`")]
+		pub const F32X4_CONVERT_U_I32X4: u32 = F32X4_CONVERT_I32X4_U;
+		#[deprecated(note = "renamed to `I8X16_SHUFFLE`")]
+		pub const V8X16_SHUFFLE: u32 = I8X16_SHUFFLE;
 	}
 
 	#[cfg(feature = "bulk")]
@@ -1053,6 +1756,23 @@ impl Deserialize for Instruction {
 	type Error = Error;
 
 	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		let mut memory = None;
+		let instruction = Instruction::deserialize_with_memory(reader, &mut memory)?;
+		#[cfg(feature = "multi_memory")]
+		if let Some(memory) = memory {
+			return Ok(Instruction::MemoryIndexed(memory, Box::new(instruction)))
+		}
+		Ok(instruction)
+	}
+}
+
+impl Instruction {
+	/// Deserialize an instruction, storing the memory index of its memory argument, if any, in
+	/// `memory`.
+	fn deserialize_with_memory<R: io::Read>(
+		reader: &mut R,
+		memory: &mut Option<u32>,
+	) -> Result<Self, Error> {
 		use self::{opcodes::*, Instruction::*};
 
 		#[cfg(feature = "sign_ext")]
@@ -1103,140 +1823,128 @@ impl Deserialize for Instruction {
 			GETGLOBAL => GetGlobal(VarUint32::deserialize(reader)?.into()),
 			SETGLOBAL => SetGlobal(VarUint32::deserialize(reader)?.into()),
 
-			I32LOAD => I32Load(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD => I64Load(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			F32LOAD => F32Load(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			F64LOAD => F64Load(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32LOAD8S => I32Load8S(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32LOAD8U => I32Load8U(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32LOAD16S => I32Load16S(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32LOAD16U => I32Load16U(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD8S => I64Load8S(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD8U => I64Load8U(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD16S => I64Load16S(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD16U => I64Load16U(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD32S => I64Load32S(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64LOAD32U => I64Load32U(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32STORE => I32Store(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64STORE => I64Store(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			F32STORE => F32Store(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			F64STORE => F64Store(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32STORE8 => I32Store8(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I32STORE16 => I32Store16(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64STORE8 => I64Store8(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64STORE16 => I64Store16(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			I64STORE32 => I64Store32(
-				VarUint32::deserialize(reader)?.into(),
-				VarUint32::deserialize(reader)?.into(),
-			),
-
-			CURRENTMEMORY => {
-				let mem_ref: u8 = Uint8::deserialize(reader)?.into();
-				if mem_ref != 0 {
-					return Err(Error::InvalidMemoryReference(mem_ref))
-				}
-				CurrentMemory(mem_ref)
+			I32LOAD => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Load(flags, offset)
 			},
-			GROWMEMORY => {
-				let mem_ref: u8 = Uint8::deserialize(reader)?.into();
-				if mem_ref != 0 {
-					return Err(Error::InvalidMemoryReference(mem_ref))
-				}
-				GrowMemory(mem_ref)
+
+			I64LOAD => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load(flags, offset)
 			},
 
-			I32CONST => I32Const(VarInt32::deserialize(reader)?.into()),
-			I64CONST => I64Const(VarInt64::deserialize(reader)?.into()),
-			F32CONST => F32Const(Uint32::deserialize(reader)?.into()),
-			F64CONST => F64Const(Uint64::deserialize(reader)?.into()),
+			F32LOAD => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				F32Load(flags, offset)
+			},
+
+			F64LOAD => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				F64Load(flags, offset)
+			},
+
+			I32LOAD8S => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Load8S(flags, offset)
+			},
+
+			I32LOAD8U => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Load8U(flags, offset)
+			},
+
+			I32LOAD16S => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Load16S(flags, offset)
+			},
+
+			I32LOAD16U => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Load16U(flags, offset)
+			},
+
+			I64LOAD8S => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load8S(flags, offset)
+			},
+
+			I64LOAD8U => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load8U(flags, offset)
+			},
+
+			I64LOAD16S => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load16S(flags, offset)
+			},
+
+			I64LOAD16U => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load16U(flags, offset)
+			},
+
+			I64LOAD32S => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load32S(flags, offset)
+			},
+
+			I64LOAD32U => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Load32U(flags, offset)
+			},
+
+			I32STORE => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Store(flags, offset)
+			},
+
+			I64STORE => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Store(flags, offset)
+			},
+
+			F32STORE => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				F32Store(flags, offset)
+			},
+
+			F64STORE => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				F64Store(flags, offset)
+			},
+
+			I32STORE8 => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Store8(flags, offset)
+			},
+
+			I32STORE16 => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I32Store16(flags, offset)
+			},
+
+			I64STORE8 => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Store8(flags, offset)
+			},
+
+			I64STORE16 => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Store16(flags, offset)
+			},
+
+			I64STORE32 => {
+				let (flags, offset) = deserialize_memarg(reader, memory)?;
+				I64Store32(flags, offset)
+			},
+
+			CURRENTMEMORY => CurrentMemory(deserialize_memory_reference(reader)?),
+			GROWMEMORY => GrowMemory(deserialize_memory_reference(reader)?),
+
+			I32CONST => I32Const(VarInt32::deserialize(reader)?.into()),
+			I64CONST => I64Const(VarInt64::deserialize(reader)?.into()),
+			F32CONST => F32Const(Uint32::deserialize(reader)?.into()),
+			F64CONST => F64Const(Uint64::deserialize(reader)?.into()),
 			I32EQZ => I32Eqz,
 			I32EQ => I32Eq,
 			I32NE => I32Ne,
@@ -1378,26 +2086,147 @@ impl Deserialize for Instruction {
 				_ => return Err(Error::UnknownOpcode(val)),
 			},
 
+			#[cfg(feature = "exceptions")]
+			exceptions::TRY => Exceptions(ExceptionsInstruction::Try(BlockType::deserialize(reader)?)),
+			#[cfg(feature = "exceptions")]
+			exceptions::CATCH =>
+				Exceptions(ExceptionsInstruction::Catch(VarUint32::deserialize(reader)?.into())),
+			#[cfg(feature = "exceptions")]
+			exceptions::CATCH_ALL => Exceptions(ExceptionsInstruction::CatchAll),
+			#[cfg(feature = "exceptions")]
+			exceptions::THROW =>
+				Exceptions(ExceptionsInstruction::Throw(VarUint32::deserialize(reader)?.into())),
+			#[cfg(feature = "exceptions")]
+			exceptions::RETHROW =>
+				Exceptions(ExceptionsInstruction::Rethrow(VarUint32::deserialize(reader)?.into())),
+			#[cfg(feature = "exceptions")]
+			exceptions::DELEGATE =>
+				Exceptions(ExceptionsInstruction::Delegate(VarUint32::deserialize(reader)?.into())),
+
 			#[cfg(feature = "atomics")]
-			atomics::ATOMIC_PREFIX => return deserialize_atomic(reader),
+			atomics::ATOMIC_PREFIX => return deserialize_atomic(reader, memory),
 
 			#[cfg(feature = "simd")]
-			simd::SIMD_PREFIX => return deserialize_simd(reader),
+			simd::SIMD_PREFIX => return deserialize_simd(reader, memory),
 
 			#[cfg(feature = "bulk")]
-			bulk::BULK_PREFIX => return deserialize_bulk(reader),
+			bulk::BULK_PREFIX => return deserialize_fc_prefixed(reader),
+
+			#[cfg(all(feature = "sat_trunc", not(feature = "bulk")))]
+			sat_trunc::SAT_TRUNC_PREFIX => return deserialize_fc_prefixed(reader),
 
 			_ => return Err(Error::UnknownOpcode(val)),
 		})
 	}
 }
 
+#[cfg(not(feature = "multi_memory"))]
+fn deserialize_memory_reference<R: io::Read>(reader: &mut R) -> Result<u8, Error> {
+	let mem_ref: u8 = Uint8::deserialize(reader)?.into();
+	if mem_ref != 0 {
+		return Err(Error::InvalidMemoryReference(mem_ref))
+	}
+	Ok(mem_ref)
+}
+
+#[cfg(feature = "multi_memory")]
+fn deserialize_memory_reference<R: io::Read>(reader: &mut R) -> Result<u32, Error> {
+	Ok(VarUint32::deserialize(reader)?.into())
+}
+
+/// Bit of the alignment flags announcing an explicit memory index in a memory argument.
+const MEMORY_INDEX_FLAG: u32 = 0x40;
+
+/// Read the alignment flags and offset of a memory argument.
+///
+/// With `multi_memory`, flags with `MEMORY_INDEX_FLAG` set are followed by a memory index,
+/// which is stored in `memory`; the returned flags have the bit cleared.
+#[cfg_attr(not(feature = "multi_memory"), allow(unused_variables))]
+fn deserialize_memarg<R: io::Read>(
+	reader: &mut R,
+	memory: &mut Option<u32>,
+) -> Result<(u32, u32), Error> {
+	let flags: u32 = VarUint32::deserialize(reader)?.into();
+	#[cfg(feature = "multi_memory")]
+	let flags = if flags & MEMORY_INDEX_FLAG != 0 {
+		*memory = Some(VarUint32::deserialize(reader)?.into());
+		flags & !MEMORY_INDEX_FLAG
+	} else {
+		flags
+	};
+	Ok((flags, VarUint32::deserialize(reader)?.into()))
+}
+
+/// Write the alignment flags and offset of a memory argument, with the memory index in
+/// between if there is one.
+fn serialize_memarg<W: io::Write>(
+	writer: &mut W,
+	flags: u32,
+	memory: Option<u32>,
+	offset: u32,
+) -> Result<(), Error> {
+	match memory {
+		Some(memory) => {
+			VarUint32::from(flags | MEMORY_INDEX_FLAG).serialize(writer)?;
+			VarUint32::from(memory).serialize(writer)?;
+		},
+		None => VarUint32::from(flags).serialize(writer)?,
+	}
+	VarUint32::from(offset).serialize(writer)
+}
+
+/// `deserialize_memarg` for the memory arguments of atomic and SIMD instructions.
+#[cfg(any(feature = "simd", feature = "atomics"))]
+#[cfg_attr(not(feature = "multi_memory"), allow(unused_variables))]
+fn deserialize_mem_arg<R: io::Read>(
+	reader: &mut R,
+	memory: &mut Option<u32>,
+) -> Result<MemArg, Error> {
+	let align: u8 = Uint8::deserialize(reader)?.into();
+	#[cfg(feature = "multi_memory")]
+	let align = if u32::from(align) & MEMORY_INDEX_FLAG != 0 {
+		*memory = Some(VarUint32::deserialize(reader)?.into());
+		align & !(MEMORY_INDEX_FLAG as u8)
+	} else {
+		align
+	};
+	Ok(MemArg { align, offset: VarUint32::deserialize(reader)?.into() })
+}
+
+/// `serialize_memarg` for the memory arguments of atomic and SIMD instructions.
+#[cfg(any(feature = "simd", feature = "atomics"))]
+fn serialize_mem_arg<W: io::Write>(
+	writer: &mut W,
+	memarg: MemArg,
+	memory: Option<u32>,
+) -> Result<(), Error> {
+	match memory {
+		Some(memory) => {
+			Uint8::from(memarg.align | MEMORY_INDEX_FLAG as u8).serialize(writer)?;
+			VarUint32::from(memory).serialize(writer)?;
+			VarUint32::from(memarg.offset).serialize(writer)
+		},
+		None => memarg.serialize(writer),
+	}
+}
+
 #[cfg(feature = "atomics")]
-fn deserialize_atomic<R: io::Read>(reader: &mut R) -> Result<Instruction, Error> {
+fn deserialize_atomic<R: io::Read>(
+	reader: &mut R,
+	memory: &mut Option<u32>,
+) -> Result<Instruction, Error> {
 	use self::{opcodes::atomics::*, AtomicsInstruction::*};
 
 	let val: u8 = Uint8::deserialize(reader)?.into();
-	let mem = MemArg::deserialize(reader)?;
+	if val == ATOMIC_FENCE {
+		// The fence has no memory argument, only a reserved zero byte.
+		if u8::from(Uint8::deserialize(reader)?) != 0 {
+			return Err(Error::UnknownOpcode(val))
+		}
+		return Ok(Instruction::Atomics(AtomicFence))
+	}
+
+	let mem = deserialize_mem_arg(reader, memory)?;
 	Ok(Instruction::Atomics(match val {
 		ATOMIC_WAKE => AtomicWake(mem),
 		I32_ATOMIC_WAIT => I32AtomicWait(mem),
@@ -1479,18 +2308,37 @@ fn deserialize_atomic<R: io::Read>(reader: &mut R) -> Result<Instruction, Error>
 }
 
 #[cfg(feature = "simd")]
-fn deserialize_simd<R: io::Read>(reader: &mut R) -> Result<Instruction, Error> {
+fn deserialize_simd<R: io::Read>(
+	reader: &mut R,
+	memory: &mut Option<u32>,
+) -> Result<Instruction, Error> {
 	use self::{opcodes::simd::*, SimdInstruction::*};
 
 	let val = VarUint32::deserialize(reader)?.into();
 	Ok(Instruction::Simd(match val {
+		V128_LOAD => V128Load(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD8X8_S => V128Load8x8S(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD8X8_U => V128Load8x8U(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD16X4_S => V128Load16x4S(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD16X4_U => V128Load16x4U(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD32X2_S => V128Load32x2S(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD32X2_U => V128Load32x2U(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD8_SPLAT => V128Load8Splat(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD16_SPLAT => V128Load16Splat(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD32_SPLAT => V128Load32Splat(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD64_SPLAT => V128Load64Splat(deserialize_mem_arg(reader, memory)?),
+		V128_STORE => V128Store(deserialize_mem_arg(reader, memory)?),
 		V128_CONST => {
 			let mut buf = [0; 16];
 			reader.read(&mut buf)?;
 			V128Const(Box::new(buf))
 		},
-		V128_LOAD => V128Load(MemArg::deserialize(reader)?),
-		V128_STORE => V128Store(MemArg::deserialize(reader)?),
+		I8X16_SHUFFLE => {
+			let mut buf = [0; 16];
+			reader.read(&mut buf)?;
+			I8x16Shuffle(Box::new(buf))
+		},
+		I8X16_SWIZZLE => I8x16Swizzle,
 		I8X16_SPLAT => I8x16Splat,
 		I16X8_SPLAT => I16x8Splat,
 		I32X4_SPLAT => I32x4Splat,
@@ -1499,161 +2347,280 @@ fn deserialize_simd<R: io::Read>(reader: &mut R) -> Result<Instruction, Error> {
 		F64X2_SPLAT => F64x2Splat,
 		I8X16_EXTRACT_LANE_S => I8x16ExtractLaneS(Uint8::deserialize(reader)?.into()),
 		I8X16_EXTRACT_LANE_U => I8x16ExtractLaneU(Uint8::deserialize(reader)?.into()),
+		I8X16_REPLACE_LANE => I8x16ReplaceLane(Uint8::deserialize(reader)?.into()),
 		I16X8_EXTRACT_LANE_S => I16x8ExtractLaneS(Uint8::deserialize(reader)?.into()),
 		I16X8_EXTRACT_LANE_U => I16x8ExtractLaneU(Uint8::deserialize(reader)?.into()),
-		I32X4_EXTRACT_LANE => I32x4ExtractLane(Uint8::deserialize(reader)?.into()),
-		I64X2_EXTRACT_LANE => I64x2ExtractLane(Uint8::deserialize(reader)?.into()),
-		F32X4_EXTRACT_LANE => F32x4ExtractLane(Uint8::deserialize(reader)?.into()),
-		F64X2_EXTRACT_LANE => F64x2ExtractLane(Uint8::deserialize(reader)?.into()),
-		I8X16_REPLACE_LANE => I8x16ReplaceLane(Uint8::deserialize(reader)?.into()),
 		I16X8_REPLACE_LANE => I16x8ReplaceLane(Uint8::deserialize(reader)?.into()),
+		I32X4_EXTRACT_LANE => I32x4ExtractLane(Uint8::deserialize(reader)?.into()),
 		I32X4_REPLACE_LANE => I32x4ReplaceLane(Uint8::deserialize(reader)?.into()),
+		I64X2_EXTRACT_LANE => I64x2ExtractLane(Uint8::deserialize(reader)?.into()),
 		I64X2_REPLACE_LANE => I64x2ReplaceLane(Uint8::deserialize(reader)?.into()),
+		F32X4_EXTRACT_LANE => F32x4ExtractLane(Uint8::deserialize(reader)?.into()),
 		F32X4_REPLACE_LANE => F32x4ReplaceLane(Uint8::deserialize(reader)?.into()),
+		F64X2_EXTRACT_LANE => F64x2ExtractLane(Uint8::deserialize(reader)?.into()),
 		F64X2_REPLACE_LANE => F64x2ReplaceLane(Uint8::deserialize(reader)?.into()),
-		V8X16_SHUFFLE => {
-			let mut buf = [0; 16];
-			reader.read(&mut buf)?;
-			V8x16Shuffle(Box::new(buf))
-		},
-		I8X16_ADD => I8x16Add,
-		I16X8_ADD => I16x8Add,
-		I32X4_ADD => I32x4Add,
-		I64X2_ADD => I64x2Add,
-		I8X16_SUB => I8x16Sub,
-		I16X8_SUB => I16x8Sub,
-		I32X4_SUB => I32x4Sub,
-		I64X2_SUB => I64x2Sub,
-		I8X16_MUL => I8x16Mul,
-		I16X8_MUL => I16x8Mul,
-		I32X4_MUL => I32x4Mul,
-		// I64X2_MUL => I64x2Mul,
-		I8X16_NEG => I8x16Neg,
-		I16X8_NEG => I16x8Neg,
-		I32X4_NEG => I32x4Neg,
-		I64X2_NEG => I64x2Neg,
-
-		I8X16_ADD_SATURATE_S => I8x16AddSaturateS,
-		I8X16_ADD_SATURATE_U => I8x16AddSaturateU,
-		I16X8_ADD_SATURATE_S => I16x8AddSaturateS,
-		I16X8_ADD_SATURATE_U => I16x8AddSaturateU,
-		I8X16_SUB_SATURATE_S => I8x16SubSaturateS,
-		I8X16_SUB_SATURATE_U => I8x16SubSaturateU,
-		I16X8_SUB_SATURATE_S => I16x8SubSaturateS,
-		I16X8_SUB_SATURATE_U => I16x8SubSaturateU,
-		I8X16_SHL => I8x16Shl,
-		I16X8_SHL => I16x8Shl,
-		I32X4_SHL => I32x4Shl,
-		I64X2_SHL => I64x2Shl,
-		I8X16_SHR_S => I8x16ShrS,
-		I8X16_SHR_U => I8x16ShrU,
-		I16X8_SHR_S => I16x8ShrS,
-		I16X8_SHR_U => I16x8ShrU,
-		I32X4_SHR_S => I32x4ShrS,
-		I32X4_SHR_U => I32x4ShrU,
-		I64X2_SHR_S => I64x2ShrS,
-		I64X2_SHR_U => I64x2ShrU,
-		V128_AND => V128And,
-		V128_OR => V128Or,
-		V128_XOR => V128Xor,
-		V128_NOT => V128Not,
-		V128_BITSELECT => V128Bitselect,
-		I8X16_ANY_TRUE => I8x16AnyTrue,
-		I16X8_ANY_TRUE => I16x8AnyTrue,
-		I32X4_ANY_TRUE => I32x4AnyTrue,
-		I64X2_ANY_TRUE => I64x2AnyTrue,
-		I8X16_ALL_TRUE => I8x16AllTrue,
-		I16X8_ALL_TRUE => I16x8AllTrue,
-		I32X4_ALL_TRUE => I32x4AllTrue,
-		I64X2_ALL_TRUE => I64x2AllTrue,
 		I8X16_EQ => I8x16Eq,
-		I16X8_EQ => I16x8Eq,
-		I32X4_EQ => I32x4Eq,
-		// I64X2_EQ => I64x2Eq,
-		F32X4_EQ => F32x4Eq,
-		F64X2_EQ => F64x2Eq,
 		I8X16_NE => I8x16Ne,
-		I16X8_NE => I16x8Ne,
-		I32X4_NE => I32x4Ne,
-		// I64X2_NE => I64x2Ne,
-		F32X4_NE => F32x4Ne,
-		F64X2_NE => F64x2Ne,
 		I8X16_LT_S => I8x16LtS,
 		I8X16_LT_U => I8x16LtU,
-		I16X8_LT_S => I16x8LtS,
-		I16X8_LT_U => I16x8LtU,
-		I32X4_LT_S => I32x4LtS,
-		I32X4_LT_U => I32x4LtU,
-		// I64X2_LT_S => I64x2LtS,
-		// I64X2_LT_U => I64x2LtU,
-		F32X4_LT => F32x4Lt,
-		F64X2_LT => F64x2Lt,
-		I8X16_LE_S => I8x16LeS,
-		I8X16_LE_U => I8x16LeU,
-		I16X8_LE_S => I16x8LeS,
-		I16X8_LE_U => I16x8LeU,
-		I32X4_LE_S => I32x4LeS,
-		I32X4_LE_U => I32x4LeU,
-		// I64X2_LE_S => I64x2LeS,
-		// I64X2_LE_U => I64x2LeU,
-		F32X4_LE => F32x4Le,
-		F64X2_LE => F64x2Le,
 		I8X16_GT_S => I8x16GtS,
 		I8X16_GT_U => I8x16GtU,
-		I16X8_GT_S => I16x8GtS,
-		I16X8_GT_U => I16x8GtU,
-		I32X4_GT_S => I32x4GtS,
-		I32X4_GT_U => I32x4GtU,
-		// I64X2_GT_S => I64x2GtS,
-		// I64X2_GT_U => I64x2GtU,
-		F32X4_GT => F32x4Gt,
-		F64X2_GT => F64x2Gt,
+		I8X16_LE_S => I8x16LeS,
+		I8X16_LE_U => I8x16LeU,
 		I8X16_GE_S => I8x16GeS,
 		I8X16_GE_U => I8x16GeU,
+		I16X8_EQ => I16x8Eq,
+		I16X8_NE => I16x8Ne,
+		I16X8_LT_S => I16x8LtS,
+		I16X8_LT_U => I16x8LtU,
+		I16X8_GT_S => I16x8GtS,
+		I16X8_GT_U => I16x8GtU,
+		I16X8_LE_S => I16x8LeS,
+		I16X8_LE_U => I16x8LeU,
 		I16X8_GE_S => I16x8GeS,
 		I16X8_GE_U => I16x8GeU,
+		I32X4_EQ => I32x4Eq,
+		I32X4_NE => I32x4Ne,
+		I32X4_LT_S => I32x4LtS,
+		I32X4_LT_U => I32x4LtU,
+		I32X4_GT_S => I32x4GtS,
+		I32X4_GT_U => I32x4GtU,
+		I32X4_LE_S => I32x4LeS,
+		I32X4_LE_U => I32x4LeU,
 		I32X4_GE_S => I32x4GeS,
 		I32X4_GE_U => I32x4GeU,
-		// I64X2_GE_S => I64x2GeS,
-		// I64X2_GE_U => I64x2GeU,
+		F32X4_EQ => F32x4Eq,
+		F32X4_NE => F32x4Ne,
+		F32X4_LT => F32x4Lt,
+		F32X4_GT => F32x4Gt,
+		F32X4_LE => F32x4Le,
 		F32X4_GE => F32x4Ge,
+		F64X2_EQ => F64x2Eq,
+		F64X2_NE => F64x2Ne,
+		F64X2_LT => F64x2Lt,
+		F64X2_GT => F64x2Gt,
+		F64X2_LE => F64x2Le,
 		F64X2_GE => F64x2Ge,
-		F32X4_NEG => F32x4Neg,
-		F64X2_NEG => F64x2Neg,
+		V128_NOT => V128Not,
+		V128_AND => V128And,
+		V128_ANDNOT => V128Andnot,
+		V128_OR => V128Or,
+		V128_XOR => V128Xor,
+		V128_BITSELECT => V128Bitselect,
+		V128_ANY_TRUE => V128AnyTrue,
+		V128_LOAD8_LANE =>
+			V128Load8Lane(deserialize_mem_arg(reader, memory)?, Uint8::deserialize(reader)?.into()),
+		V128_LOAD16_LANE =>
+			V128Load16Lane(deserialize_mem_arg(reader, memory)?, Uint8::deserialize(reader)?.into()),
+		V128_LOAD32_LANE =>
+			V128Load32Lane(deserialize_mem_arg(reader, memory)?, Uint8::deserialize(reader)?.into()),
+		V128_LOAD64_LANE =>
+			V128Load64Lane(deserialize_mem_arg(reader, memory)?, Uint8::deserialize(reader)?.into()),
+		V128_STORE8_LANE =>
+			V128Store8Lane(deserialize_mem_arg(reader, memory)?, Uint8::deserialize(reader)?.into()),
+		V128_STORE16_LANE => V128Store16Lane(
+			deserialize_mem_arg(reader, memory)?,
+			Uint8::deserialize(reader)?.into(),
+		),
+		V128_STORE32_LANE => V128Store32Lane(
+			deserialize_mem_arg(reader, memory)?,
+			Uint8::deserialize(reader)?.into(),
+		),
+		V128_STORE64_LANE => V128Store64Lane(
+			deserialize_mem_arg(reader, memory)?,
+			Uint8::deserialize(reader)?.into(),
+		),
+		V128_LOAD32_ZERO => V128Load32Zero(deserialize_mem_arg(reader, memory)?),
+		V128_LOAD64_ZERO => V128Load64Zero(deserialize_mem_arg(reader, memory)?),
+		F32X4_DEMOTE_F64X2_ZERO => F32x4DemoteF64x2Zero,
+		F64X2_PROMOTE_LOW_F32X4 => F64x2PromoteLowF32x4,
+		I8X16_ABS => I8x16Abs,
+		I8X16_NEG => I8x16Neg,
+		I8X16_POPCNT => I8x16Popcnt,
+		I8X16_ALL_TRUE => I8x16AllTrue,
+		I8X16_BITMASK => I8x16Bitmask,
+		I8X16_NARROW_I16X8_S => I8x16NarrowI16x8S,
+		I8X16_NARROW_I16X8_U => I8x16NarrowI16x8U,
+		F32X4_CEIL => F32x4Ceil,
+		F32X4_FLOOR => F32x4Floor,
+		F32X4_TRUNC => F32x4Trunc,
+		F32X4_NEAREST => F32x4Nearest,
+		I8X16_SHL => I8x16Shl,
+		I8X16_SHR_S => I8x16ShrS,
+		I8X16_SHR_U => I8x16ShrU,
+		I8X16_ADD => I8x16Add,
+		I8X16_ADD_SAT_S => I8x16AddSatS,
+		I8X16_ADD_SAT_U => I8x16AddSatU,
+		I8X16_SUB => I8x16Sub,
+		I8X16_SUB_SAT_S => I8x16SubSatS,
+		I8X16_SUB_SAT_U => I8x16SubSatU,
+		F64X2_CEIL => F64x2Ceil,
+		F64X2_FLOOR => F64x2Floor,
+		I8X16_MIN_S => I8x16MinS,
+		I8X16_MIN_U => I8x16MinU,
+		I8X16_MAX_S => I8x16MaxS,
+		I8X16_MAX_U => I8x16MaxU,
+		F64X2_TRUNC => F64x2Trunc,
+		I8X16_AVGR_U => I8x16AvgrU,
+		I16X8_EXTADD_PAIRWISE_I8X16_S => I16x8ExtaddPairwiseI8x16S,
+		I16X8_EXTADD_PAIRWISE_I8X16_U => I16x8ExtaddPairwiseI8x16U,
+		I32X4_EXTADD_PAIRWISE_I16X8_S => I32x4ExtaddPairwiseI16x8S,
+		I32X4_EXTADD_PAIRWISE_I16X8_U => I32x4ExtaddPairwiseI16x8U,
+		I16X8_ABS => I16x8Abs,
+		I16X8_NEG => I16x8Neg,
+		I16X8_Q15MULR_SAT_S => I16x8Q15mulrSatS,
+		I16X8_ALL_TRUE => I16x8AllTrue,
+		I16X8_BITMASK => I16x8Bitmask,
+		I16X8_NARROW_I32X4_S => I16x8NarrowI32x4S,
+		I16X8_NARROW_I32X4_U => I16x8NarrowI32x4U,
+		I16X8_EXTEND_LOW_I8X16_S => I16x8ExtendLowI8x16S,
+		I16X8_EXTEND_HIGH_I8X16_S => I16x8ExtendHighI8x16S,
+		I16X8_EXTEND_LOW_I8X16_U => I16x8ExtendLowI8x16U,
+		I16X8_EXTEND_HIGH_I8X16_U => I16x8ExtendHighI8x16U,
+		I16X8_SHL => I16x8Shl,
+		I16X8_SHR_S => I16x8ShrS,
+		I16X8_SHR_U => I16x8ShrU,
+		I16X8_ADD => I16x8Add,
+		I16X8_ADD_SAT_S => I16x8AddSatS,
+		I16X8_ADD_SAT_U => I16x8AddSatU,
+		I16X8_SUB => I16x8Sub,
+		I16X8_SUB_SAT_S => I16x8SubSatS,
+		I16X8_SUB_SAT_U => I16x8SubSatU,
+		F64X2_NEAREST => F64x2Nearest,
+		I16X8_MUL => I16x8Mul,
+		I16X8_MIN_S => I16x8MinS,
+		I16X8_MIN_U => I16x8MinU,
+		I16X8_MAX_S => I16x8MaxS,
+		I16X8_MAX_U => I16x8MaxU,
+		I16X8_AVGR_U => I16x8AvgrU,
+		I16X8_EXTMUL_LOW_I8X16_S => I16x8ExtmulLowI8x16S,
+		I16X8_EXTMUL_HIGH_I8X16_S => I16x8ExtmulHighI8x16S,
+		I16X8_EXTMUL_LOW_I8X16_U => I16x8ExtmulLowI8x16U,
+		I16X8_EXTMUL_HIGH_I8X16_U => I16x8ExtmulHighI8x16U,
+		I32X4_ABS => I32x4Abs,
+		I32X4_NEG => I32x4Neg,
+		I32X4_ALL_TRUE => I32x4AllTrue,
+		I32X4_BITMASK => I32x4Bitmask,
+		I32X4_EXTEND_LOW_I16X8_S => I32x4ExtendLowI16x8S,
+		I32X4_EXTEND_HIGH_I16X8_S => I32x4ExtendHighI16x8S,
+		I32X4_EXTEND_LOW_I16X8_U => I32x4ExtendLowI16x8U,
+		I32X4_EXTEND_HIGH_I16X8_U => I32x4ExtendHighI16x8U,
+		I32X4_SHL => I32x4Shl,
+		I32X4_SHR_S => I32x4ShrS,
+		I32X4_SHR_U => I32x4ShrU,
+		I32X4_ADD => I32x4Add,
+		I32X4_SUB => I32x4Sub,
+		I32X4_MUL => I32x4Mul,
+		I32X4_MIN_S => I32x4MinS,
+		I32X4_MIN_U => I32x4MinU,
+		I32X4_MAX_S => I32x4MaxS,
+		I32X4_MAX_U => I32x4MaxU,
+		I32X4_DOT_I16X8_S => I32x4DotI16x8S,
+		I32X4_EXTMUL_LOW_I16X8_S => I32x4ExtmulLowI16x8S,
+		I32X4_EXTMUL_HIGH_I16X8_S => I32x4ExtmulHighI16x8S,
+		I32X4_EXTMUL_LOW_I16X8_U => I32x4ExtmulLowI16x8U,
+		I32X4_EXTMUL_HIGH_I16X8_U => I32x4ExtmulHighI16x8U,
+		I64X2_ABS => I64x2Abs,
+		I64X2_NEG => I64x2Neg,
+		I64X2_ALL_TRUE => I64x2AllTrue,
+		I64X2_BITMASK => I64x2Bitmask,
+		I64X2_EXTEND_LOW_I32X4_S => I64x2ExtendLowI32x4S,
+		I64X2_EXTEND_HIGH_I32X4_S => I64x2ExtendHighI32x4S,
+		I64X2_EXTEND_LOW_I32X4_U => I64x2ExtendLowI32x4U,
+		I64X2_EXTEND_HIGH_I32X4_U => I64x2ExtendHighI32x4U,
+		I64X2_SHL => I64x2Shl,
+		I64X2_SHR_S => I64x2ShrS,
+		I64X2_SHR_U => I64x2ShrU,
+		I64X2_ADD => I64x2Add,
+		I64X2_SUB => I64x2Sub,
+		I64X2_MUL => I64x2Mul,
+		I64X2_EQ => I64x2Eq,
+		I64X2_NE => I64x2Ne,
+		I64X2_LT_S => I64x2LtS,
+		I64X2_GT_S => I64x2GtS,
+		I64X2_LE_S => I64x2LeS,
+		I64X2_GE_S => I64x2GeS,
+		I64X2_EXTMUL_LOW_I32X4_S => I64x2ExtmulLowI32x4S,
+		I64X2_EXTMUL_HIGH_I32X4_S => I64x2ExtmulHighI32x4S,
+		I64X2_EXTMUL_LOW_I32X4_U => I64x2ExtmulLowI32x4U,
+		I64X2_EXTMUL_HIGH_I32X4_U => I64x2ExtmulHighI32x4U,
 		F32X4_ABS => F32x4Abs,
-		F64X2_ABS => F64x2Abs,
+		F32X4_NEG => F32x4Neg,
+		F32X4_SQRT => F32x4Sqrt,
+		F32X4_ADD => F32x4Add,
+		F32X4_SUB => F32x4Sub,
+		F32X4_MUL => F32x4Mul,
+		F32X4_DIV => F32x4Div,
 		F32X4_MIN => F32x4Min,
-		F64X2_MIN => F64x2Min,
 		F32X4_MAX => F32x4Max,
-		F64X2_MAX => F64x2Max,
-		F32X4_ADD => F32x4Add,
+		F32X4_PMIN => F32x4Pmin,
+		F32X4_PMAX => F32x4Pmax,
+		F64X2_ABS => F64x2Abs,
+		F64X2_NEG => F64x2Neg,
+		F64X2_SQRT => F64x2Sqrt,
 		F64X2_ADD => F64x2Add,
-		F32X4_SUB => F32x4Sub,
 		F64X2_SUB => F64x2Sub,
-		F32X4_DIV => F32x4Div,
-		F64X2_DIV => F64x2Div,
-		F32X4_MUL => F32x4Mul,
 		F64X2_MUL => F64x2Mul,
-		F32X4_SQRT => F32x4Sqrt,
-		F64X2_SQRT => F64x2Sqrt,
-		F32X4_CONVERT_S_I32X4 => F32x4ConvertSI32x4,
-		F32X4_CONVERT_U_I32X4 => F32x4ConvertUI32x4,
-		F64X2_CONVERT_S_I64X2 => F64x2ConvertSI64x2,
-		F64X2_CONVERT_U_I64X2 => F64x2ConvertUI64x2,
-		I32X4_TRUNC_S_F32X4_SAT => I32x4TruncSF32x4Sat,
-		I32X4_TRUNC_U_F32X4_SAT => I32x4TruncUF32x4Sat,
-		I64X2_TRUNC_S_F64X2_SAT => I64x2TruncSF64x2Sat,
-		I64X2_TRUNC_U_F64X2_SAT => I64x2TruncUF64x2Sat,
+		F64X2_DIV => F64x2Div,
+		F64X2_MIN => F64x2Min,
+		F64X2_MAX => F64x2Max,
+		F64X2_PMIN => F64x2Pmin,
+		F64X2_PMAX => F64x2Pmax,
+		I32X4_TRUNC_SAT_F32X4_S => I32x4TruncSatF32x4S,
+		I32X4_TRUNC_SAT_F32X4_U => I32x4TruncSatF32x4U,
+		F32X4_CONVERT_I32X4_S => F32x4ConvertI32x4S,
+		F32X4_CONVERT_I32X4_U => F32x4ConvertI32x4U,
+		I32X4_TRUNC_SAT_F64X2_S_ZERO => I32x4TruncSatF64x2SZero,
+		I32X4_TRUNC_SAT_F64X2_U_ZERO => I32x4TruncSatF64x2UZero,
+		F64X2_CONVERT_LOW_I32X4_S => F64x2ConvertLowI32x4S,
+		F64X2_CONVERT_LOW_I32X4_U => F64x2ConvertLowI32x4U,
 
 		_ => return Err(Error::UnknownSimdOpcode(val)),
 	}))
 }
 
+// Bulk memory and saturating truncation instructions share the 0xfc prefix.
+#[cfg(any(feature = "bulk", feature = "sat_trunc"))]
+fn deserialize_fc_prefixed<R: io::Read>(reader: &mut R) -> Result<Instruction, Error> {
+	let val: u32 = VarUint32::deserialize(reader)?.into();
+	if val > u8::MAX as u32 {
+		return Err(Error::UnknownOpcode(0xfc))
+	}
+	let val = val as u8;
+
+	match val {
+		#[cfg(feature = "sat_trunc")]
+		opcodes::sat_trunc::I32_TRUNC_SAT_S_F32..=opcodes::sat_trunc::I64_TRUNC_SAT_U_F64 =>
+			deserialize_sat_trunc(val),
+		#[cfg(feature = "bulk")]
+		_ => deserialize_bulk(reader, val),
+		#[cfg(not(feature = "bulk"))]
+		_ => Err(Error::UnknownOpcode(val)),
+	}
+}
+
+#[cfg(feature = "sat_trunc")]
+fn deserialize_sat_trunc(val: u8) -> Result<Instruction, Error> {
+	use self::{opcodes::sat_trunc::*, SatTruncInstruction::*};
+
+	Ok(Instruction::SatTrunc(match val {
+		I32_TRUNC_SAT_S_F32 => I32TruncSatSF32,
+		I32_TRUNC_SAT_U_F32 => I32TruncSatUF32,
+		I32_TRUNC_SAT_S_F64 => I32TruncSatSF64,
+		I32_TRUNC_SAT_U_F64 => I32TruncSatUF64,
+		I64_TRUNC_SAT_S_F32 => I64TruncSatSF32,
+		I64_TRUNC_SAT_U_F32 => I64TruncSatUF32,
+		I64_TRUNC_SAT_S_F64 => I64TruncSatSF64,
+		I64_TRUNC_SAT_U_F64 => I64TruncSatUF64,
+
+		_ => return Err(Error::UnknownOpcode(val)),
+	}))
+}
+
 #[cfg(feature = "bulk")]
-fn deserialize_bulk<R: io::Read>(reader: &mut R) -> Result<Instruction, Error> {
+fn deserialize_bulk<R: io::Read>(reader: &mut R, val: u8) -> Result<Instruction, Error> {
 	use self::{opcodes::bulk::*, BulkInstruction::*};
 
-	let val: u8 = Uint8::deserialize(reader)?.into();
 	Ok(Instruction::Bulk(match val {
 		MEMORY_INIT => {
 			if u8::from(Uint8::deserialize(reader)?) != 0 {
@@ -1717,9 +2684,9 @@ macro_rules! op {
 
 #[cfg(feature = "atomics")]
 macro_rules! atomic {
-	($writer: expr, $byte: expr, $mem:expr) => {{
+	($writer: expr, $memory: expr, $byte: expr, $mem:expr) => {{
 		$writer.write(&[ATOMIC_PREFIX, $byte])?;
-		MemArg::serialize($mem, $writer)?;
+		serialize_mem_arg($writer, $mem, $memory)?;
 	}};
 }
 
@@ -1732,6 +2699,13 @@ macro_rules! simd {
 	}};
 }
 
+#[cfg(feature = "sat_trunc")]
+macro_rules! sat_trunc {
+	($writer: expr, $byte: expr) => {{
+		$writer.write(&[SAT_TRUNC_PREFIX, $byte])?;
+	}};
+}
+
 #[cfg(feature = "bulk")]
 macro_rules! bulk {
 	($writer: expr, $byte: expr) => {{
@@ -1747,6 +2721,17 @@ impl Serialize for Instruction {
 	type Error = Error;
 
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		self.serialize_with_memory(writer, None)
+	}
+}
+
+impl Instruction {
+	/// Serialize an instruction, with `memory` as the memory index of its memory argument.
+	fn serialize_with_memory<W: io::Write>(
+		self,
+		writer: &mut W,
+		memory: Option<u32>,
+	) -> Result<(), Error> {
 		use self::{opcodes::*, Instruction::*};
 
 		match self {
@@ -1803,103 +2788,90 @@ impl Serialize for Instruction {
 				VarUint32::from(index).serialize(writer)?;
 			}),
 			I32Load(flags, offset) => op!(writer, I32LOAD, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load(flags, offset) => op!(writer, I64LOAD, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			F32Load(flags, offset) => op!(writer, F32LOAD, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			F64Load(flags, offset) => op!(writer, F64LOAD, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Load8S(flags, offset) => op!(writer, I32LOAD8S, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Load8U(flags, offset) => op!(writer, I32LOAD8U, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Load16S(flags, offset) => op!(writer, I32LOAD16S, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Load16U(flags, offset) => op!(writer, I32LOAD16U, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load8S(flags, offset) => op!(writer, I64LOAD8S, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load8U(flags, offset) => op!(writer, I64LOAD8U, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load16S(flags, offset) => op!(writer, I64LOAD16S, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load16U(flags, offset) => op!(writer, I64LOAD16U, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load32S(flags, offset) => op!(writer, I64LOAD32S, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Load32U(flags, offset) => op!(writer, I64LOAD32U, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Store(flags, offset) => op!(writer, I32STORE, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Store(flags, offset) => op!(writer, I64STORE, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			F32Store(flags, offset) => op!(writer, F32STORE, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			F64Store(flags, offset) => op!(writer, F64STORE, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Store8(flags, offset) => op!(writer, I32STORE8, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I32Store16(flags, offset) => op!(writer, I32STORE16, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Store8(flags, offset) => op!(writer, I64STORE8, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Store16(flags, offset) => op!(writer, I64STORE16, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
 			I64Store32(flags, offset) => op!(writer, I64STORE32, {
-				VarUint32::from(flags).serialize(writer)?;
-				VarUint32::from(offset).serialize(writer)?;
+				serialize_memarg(writer, flags, memory, offset)?;
 			}),
+			#[cfg(not(feature = "multi_memory"))]
 			CurrentMemory(flag) => op!(writer, CURRENTMEMORY, {
 				Uint8::from(flag).serialize(writer)?;
 			}),
+			#[cfg(not(feature = "multi_memory"))]
 			GrowMemory(flag) => op!(writer, GROWMEMORY, {
 				Uint8::from(flag).serialize(writer)?;
 			}),
+			#[cfg(feature = "multi_memory")]
+			CurrentMemory(flag) => op!(writer, CURRENTMEMORY, {
+				VarUint32::from(flag).serialize(writer)?;
+			}),
+			#[cfg(feature = "multi_memory")]
+			GrowMemory(flag) => op!(writer, GROWMEMORY, {
+				VarUint32::from(flag).serialize(writer)?;
+			}),
 			I32Const(def) => op!(writer, I32CONST, {
 				VarInt32::from(def).serialize(writer)?;
 			}),
@@ -2052,11 +3024,44 @@ impl Serialize for Instruction {
 				SignExtInstruction::I64Extend32S => op!(writer, sign_ext::I64_EXTEND32_S),
 			},
 
+			#[cfg(feature = "exceptions")]
+			Exceptions(a) => match a {
+				ExceptionsInstruction::Try(block_type) => op!(writer, exceptions::TRY, {
+					block_type.serialize(writer)?;
+				}),
+				ExceptionsInstruction::Catch(tag) => op!(writer, exceptions::CATCH, {
+					VarUint32::from(tag).serialize(writer)?;
+				}),
+				ExceptionsInstruction::CatchAll => op!(writer, exceptions::CATCH_ALL),
+				ExceptionsInstruction::Throw(tag) => op!(writer, exceptions::THROW, {
+					VarUint32::from(tag).serialize(writer)?;
+				}),
+				ExceptionsInstruction::Rethrow(depth) => op!(writer, exceptions::RETHROW, {
+					VarUint32::from(depth).serialize(writer)?;
+				}),
+				ExceptionsInstruction::Delegate(depth) => op!(writer, exceptions::DELEGATE, {
+					VarUint32::from(depth).serialize(writer)?;
+				}),
+			},
+
+			#[cfg(feature = "multi_memory")]
+			MemoryIndexed(index, instruction) => {
+				if memory.is_some() || instruction.alignment().is_none() {
+					return Err(Error::Other(
+						"Memory index on an instruction without memory argument",
+					))
+				}
+				return instruction.serialize_with_memory(writer, Some(index))
+			},
+
 			#[cfg(feature = "atomics")]
-			Atomics(a) => return a.serialize(writer),
+			Atomics(a) => return a.serialize_with_memory(writer, memory),
 
 			#[cfg(feature = "simd")]
-			Simd(a) => return a.serialize(writer),
+			Simd(a) => return a.serialize_with_memory(writer, memory),
+
+			#[cfg(feature = "sat_trunc")]
+			SatTrunc(a) => return a.serialize(writer),
 
 			#[cfg(feature = "bulk")]
 			Bulk(a) => return a.serialize(writer),
@@ -2071,83 +3076,96 @@ impl Serialize for AtomicsInstruction {
 	type Error = Error;
 
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		self.serialize_with_memory(writer, None)
+	}
+}
+
+#[cfg(feature = "atomics")]
+impl AtomicsInstruction {
+	/// `Instruction::serialize_with_memory` for atomic instructions.
+	fn serialize_with_memory<W: io::Write>(
+		self,
+		writer: &mut W,
+		memory: Option<u32>,
+	) -> Result<(), Error> {
 		use self::{opcodes::atomics::*, AtomicsInstruction::*};
 
 		match self {
-			AtomicWake(m) => atomic!(writer, ATOMIC_WAKE, m),
-			I32AtomicWait(m) => atomic!(writer, I32_ATOMIC_WAIT, m),
-			I64AtomicWait(m) => atomic!(writer, I64_ATOMIC_WAIT, m),
-
-			I32AtomicLoad(m) => atomic!(writer, I32_ATOMIC_LOAD, m),
-			I64AtomicLoad(m) => atomic!(writer, I64_ATOMIC_LOAD, m),
-			I32AtomicLoad8u(m) => atomic!(writer, I32_ATOMIC_LOAD8U, m),
-			I32AtomicLoad16u(m) => atomic!(writer, I32_ATOMIC_LOAD16U, m),
-			I64AtomicLoad8u(m) => atomic!(writer, I64_ATOMIC_LOAD8U, m),
-			I64AtomicLoad16u(m) => atomic!(writer, I64_ATOMIC_LOAD16U, m),
-			I64AtomicLoad32u(m) => atomic!(writer, I64_ATOMIC_LOAD32U, m),
-			I32AtomicStore(m) => atomic!(writer, I32_ATOMIC_STORE, m),
-			I64AtomicStore(m) => atomic!(writer, I64_ATOMIC_STORE, m),
-			I32AtomicStore8u(m) => atomic!(writer, I32_ATOMIC_STORE8U, m),
-			I32AtomicStore16u(m) => atomic!(writer, I32_ATOMIC_STORE16U, m),
-			I64AtomicStore8u(m) => atomic!(writer, I64_ATOMIC_STORE8U, m),
-			I64AtomicStore16u(m) => atomic!(writer, I64_ATOMIC_STORE16U, m),
-			I64AtomicStore32u(m) => atomic!(writer, I64_ATOMIC_STORE32U, m),
-
-			I32AtomicRmwAdd(m) => atomic!(writer, I32_ATOMIC_RMW_ADD, m),
-			I64AtomicRmwAdd(m) => atomic!(writer, I64_ATOMIC_RMW_ADD, m),
-			I32AtomicRmwAdd8u(m) => atomic!(writer, I32_ATOMIC_RMW_ADD8U, m),
-			I32AtomicRmwAdd16u(m) => atomic!(writer, I32_ATOMIC_RMW_ADD16U, m),
-			I64AtomicRmwAdd8u(m) => atomic!(writer, I64_ATOMIC_RMW_ADD8U, m),
-			I64AtomicRmwAdd16u(m) => atomic!(writer, I64_ATOMIC_RMW_ADD16U, m),
-			I64AtomicRmwAdd32u(m) => atomic!(writer, I64_ATOMIC_RMW_ADD32U, m),
-
-			I32AtomicRmwSub(m) => atomic!(writer, I32_ATOMIC_RMW_SUB, m),
-			I64AtomicRmwSub(m) => atomic!(writer, I64_ATOMIC_RMW_SUB, m),
-			I32AtomicRmwSub8u(m) => atomic!(writer, I32_ATOMIC_RMW_SUB8U, m),
-			I32AtomicRmwSub16u(m) => atomic!(writer, I32_ATOMIC_RMW_SUB16U, m),
-			I64AtomicRmwSub8u(m) => atomic!(writer, I64_ATOMIC_RMW_SUB8U, m),
-			I64AtomicRmwSub16u(m) => atomic!(writer, I64_ATOMIC_RMW_SUB16U, m),
-			I64AtomicRmwSub32u(m) => atomic!(writer, I64_ATOMIC_RMW_SUB32U, m),
-
-			I32AtomicRmwAnd(m) => atomic!(writer, I32_ATOMIC_RMW_AND, m),
-			I64AtomicRmwAnd(m) => atomic!(writer, I64_ATOMIC_RMW_AND, m),
-			I32AtomicRmwAnd8u(m) => atomic!(writer, I32_ATOMIC_RMW_AND8U, m),
-			I32AtomicRmwAnd16u(m) => atomic!(writer, I32_ATOMIC_RMW_AND16U, m),
-			I64AtomicRmwAnd8u(m) => atomic!(writer, I64_ATOMIC_RMW_AND8U, m),
-			I64AtomicRmwAnd16u(m) => atomic!(writer, I64_ATOMIC_RMW_AND16U, m),
-			I64AtomicRmwAnd32u(m) => atomic!(writer, I64_ATOMIC_RMW_AND32U, m),
-
-			I32AtomicRmwOr(m) => atomic!(writer, I32_ATOMIC_RMW_OR, m),
-			I64AtomicRmwOr(m) => atomic!(writer, I64_ATOMIC_RMW_OR, m),
-			I32AtomicRmwOr8u(m) => atomic!(writer, I32_ATOMIC_RMW_OR8U, m),
-			I32AtomicRmwOr16u(m) => atomic!(writer, I32_ATOMIC_RMW_OR16U, m),
-			I64AtomicRmwOr8u(m) => atomic!(writer, I64_ATOMIC_RMW_OR8U, m),
-			I64AtomicRmwOr16u(m) => atomic!(writer, I64_ATOMIC_RMW_OR16U, m),
-			I64AtomicRmwOr32u(m) => atomic!(writer, I64_ATOMIC_RMW_OR32U, m),
-
-			I32AtomicRmwXor(m) => atomic!(writer, I32_ATOMIC_RMW_XOR, m),
-			I64AtomicRmwXor(m) => atomic!(writer, I64_ATOMIC_RMW_XOR, m),
-			I32AtomicRmwXor8u(m) => atomic!(writer, I32_ATOMIC_RMW_XOR8U, m),
-			I32AtomicRmwXor16u(m) => atomic!(writer, I32_ATOMIC_RMW_XOR16U, m),
-			I64AtomicRmwXor8u(m) => atomic!(writer, I64_ATOMIC_RMW_XOR8U, m),
-			I64AtomicRmwXor16u(m) => atomic!(writer, I64_ATOMIC_RMW_XOR16U, m),
-			I64AtomicRmwXor32u(m) => atomic!(writer, I64_ATOMIC_RMW_XOR32U, m),
-
-			I32AtomicRmwXchg(m) => atomic!(writer, I32_ATOMIC_RMW_XCHG, m),
-			I64AtomicRmwXchg(m) => atomic!(writer, I64_ATOMIC_RMW_XCHG, m),
-			I32AtomicRmwXchg8u(m) => atomic!(writer, I32_ATOMIC_RMW_XCHG8U, m),
-			I32AtomicRmwXchg16u(m) => atomic!(writer, I32_ATOMIC_RMW_XCHG16U, m),
-			I64AtomicRmwXchg8u(m) => atomic!(writer, I64_ATOMIC_RMW_XCHG8U, m),
-			I64AtomicRmwXchg16u(m) => atomic!(writer, I64_ATOMIC_RMW_XCHG16U, m),
-			I64AtomicRmwXchg32u(m) => atomic!(writer, I64_ATOMIC_RMW_XCHG32U, m),
-
-			I32AtomicRmwCmpxchg(m) => atomic!(writer, I32_ATOMIC_RMW_CMPXCHG, m),
-			I64AtomicRmwCmpxchg(m) => atomic!(writer, I64_ATOMIC_RMW_CMPXCHG, m),
-			I32AtomicRmwCmpxchg8u(m) => atomic!(writer, I32_ATOMIC_RMW_CMPXCHG8U, m),
-			I32AtomicRmwCmpxchg16u(m) => atomic!(writer, I32_ATOMIC_RMW_CMPXCHG16U, m),
-			I64AtomicRmwCmpxchg8u(m) => atomic!(writer, I64_ATOMIC_RMW_CMPXCHG8U, m),
-			I64AtomicRmwCmpxchg16u(m) => atomic!(writer, I64_ATOMIC_RMW_CMPXCHG16U, m),
-			I64AtomicRmwCmpxchg32u(m) => atomic!(writer, I64_ATOMIC_RMW_CMPXCHG32U, m),
+			AtomicWake(m) => atomic!(writer, memory, ATOMIC_WAKE, m),
+			I32AtomicWait(m) => atomic!(writer, memory, I32_ATOMIC_WAIT, m),
+			I64AtomicWait(m) => atomic!(writer, memory, I64_ATOMIC_WAIT, m),
+			AtomicFence => writer.write(&[ATOMIC_PREFIX, ATOMIC_FENCE, 0x00])?,
+
+			I32AtomicLoad(m) => atomic!(writer, memory, I32_ATOMIC_LOAD, m),
+			I64AtomicLoad(m) => atomic!(writer, memory, I64_ATOMIC_LOAD, m),
+			I32AtomicLoad8u(m) => atomic!(writer, memory, I32_ATOMIC_LOAD8U, m),
+			I32AtomicLoad16u(m) => atomic!(writer, memory, I32_ATOMIC_LOAD16U, m),
+			I64AtomicLoad8u(m) => atomic!(writer, memory, I64_ATOMIC_LOAD8U, m),
+			I64AtomicLoad16u(m) => atomic!(writer, memory, I64_ATOMIC_LOAD16U, m),
+			I64AtomicLoad32u(m) => atomic!(writer, memory, I64_ATOMIC_LOAD32U, m),
+			I32AtomicStore(m) => atomic!(writer, memory, I32_ATOMIC_STORE, m),
+			I64AtomicStore(m) => atomic!(writer, memory, I64_ATOMIC_STORE, m),
+			I32AtomicStore8u(m) => atomic!(writer, memory, I32_ATOMIC_STORE8U, m),
+			I32AtomicStore16u(m) => atomic!(writer, memory, I32_ATOMIC_STORE16U, m),
+			I64AtomicStore8u(m) => atomic!(writer, memory, I64_ATOMIC_STORE8U, m),
+			I64AtomicStore16u(m) => atomic!(writer, memory, I64_ATOMIC_STORE16U, m),
+			I64AtomicStore32u(m) => atomic!(writer, memory, I64_ATOMIC_STORE32U, m),
+
+			I32AtomicRmwAdd(m) => atomic!(writer, memory, I32_ATOMIC_RMW_ADD, m),
+			I64AtomicRmwAdd(m) => atomic!(writer, memory, I64_ATOMIC_RMW_ADD, m),
+			I32AtomicRmwAdd8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_ADD8U, m),
+			I32AtomicRmwAdd16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_ADD16U, m),
+			I64AtomicRmwAdd8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_ADD8U, m),
+			I64AtomicRmwAdd16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_ADD16U, m),
+			I64AtomicRmwAdd32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_ADD32U, m),
+
+			I32AtomicRmwSub(m) => atomic!(writer, memory, I32_ATOMIC_RMW_SUB, m),
+			I64AtomicRmwSub(m) => atomic!(writer, memory, I64_ATOMIC_RMW_SUB, m),
+			I32AtomicRmwSub8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_SUB8U, m),
+			I32AtomicRmwSub16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_SUB16U, m),
+			I64AtomicRmwSub8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_SUB8U, m),
+			I64AtomicRmwSub16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_SUB16U, m),
+			I64AtomicRmwSub32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_SUB32U, m),
+
+			I32AtomicRmwAnd(m) => atomic!(writer, memory, I32_ATOMIC_RMW_AND, m),
+			I64AtomicRmwAnd(m) => atomic!(writer, memory, I64_ATOMIC_RMW_AND, m),
+			I32AtomicRmwAnd8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_AND8U, m),
+			I32AtomicRmwAnd16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_AND16U, m),
+			I64AtomicRmwAnd8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_AND8U, m),
+			I64AtomicRmwAnd16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_AND16U, m),
+			I64AtomicRmwAnd32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_AND32U, m),
+
+			I32AtomicRmwOr(m) => atomic!(writer, memory, I32_ATOMIC_RMW_OR, m),
+			I64AtomicRmwOr(m) => atomic!(writer, memory, I64_ATOMIC_RMW_OR, m),
+			I32AtomicRmwOr8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_OR8U, m),
+			I32AtomicRmwOr16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_OR16U, m),
+			I64AtomicRmwOr8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_OR8U, m),
+			I64AtomicRmwOr16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_OR16U, m),
+			I64AtomicRmwOr32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_OR32U, m),
+
+			I32AtomicRmwXor(m) => atomic!(writer, memory, I32_ATOMIC_RMW_XOR, m),
+			I64AtomicRmwXor(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XOR, m),
+			I32AtomicRmwXor8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_XOR8U, m),
+			I32AtomicRmwXor16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_XOR16U, m),
+			I64AtomicRmwXor8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XOR8U, m),
+			I64AtomicRmwXor16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XOR16U, m),
+			I64AtomicRmwXor32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XOR32U, m),
+
+			I32AtomicRmwXchg(m) => atomic!(writer, memory, I32_ATOMIC_RMW_XCHG, m),
+			I64AtomicRmwXchg(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XCHG, m),
+			I32AtomicRmwXchg8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_XCHG8U, m),
+			I32AtomicRmwXchg16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_XCHG16U, m),
+			I64AtomicRmwXchg8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XCHG8U, m),
+			I64AtomicRmwXchg16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XCHG16U, m),
+			I64AtomicRmwXchg32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_XCHG32U, m),
+
+			I32AtomicRmwCmpxchg(m) => atomic!(writer, memory, I32_ATOMIC_RMW_CMPXCHG, m),
+			I64AtomicRmwCmpxchg(m) => atomic!(writer, memory, I64_ATOMIC_RMW_CMPXCHG, m),
+			I32AtomicRmwCmpxchg8u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_CMPXCHG8U, m),
+			I32AtomicRmwCmpxchg16u(m) => atomic!(writer, memory, I32_ATOMIC_RMW_CMPXCHG16U, m),
+			I64AtomicRmwCmpxchg8u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_CMPXCHG8U, m),
+			I64AtomicRmwCmpxchg16u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_CMPXCHG16U, m),
+			I64AtomicRmwCmpxchg32u(m) => atomic!(writer, memory, I64_ATOMIC_RMW_CMPXCHG32U, m),
 		}
 
 		Ok(())
@@ -2159,12 +3177,44 @@ impl Serialize for SimdInstruction {
 	type Error = Error;
 
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		self.serialize_with_memory(writer, None)
+	}
+}
+
+#[cfg(feature = "simd")]
+impl SimdInstruction {
+	/// `Instruction::serialize_with_memory` for SIMD instructions.
+	fn serialize_with_memory<W: io::Write>(
+		self,
+		writer: &mut W,
+		memory: Option<u32>,
+	) -> Result<(), Error> {
 		use self::{opcodes::simd::*, SimdInstruction::*};
 
 		match self {
-			V128Const(ref c) => simd!(writer, V128_CONST, writer.write(&c[..])?),
-			V128Load(m) => simd!(writer, V128_LOAD, MemArg::serialize(m, writer)?),
-			V128Store(m) => simd!(writer, V128_STORE, MemArg::serialize(m, writer)?),
+			V128Load(m) => simd!(writer, V128_LOAD, serialize_mem_arg(writer, m, memory)?),
+			V128Load8x8S(m) => simd!(writer, V128_LOAD8X8_S, serialize_mem_arg(writer, m, memory)?),
+			V128Load8x8U(m) => simd!(writer, V128_LOAD8X8_U, serialize_mem_arg(writer, m, memory)?),
+			V128Load16x4S(m) =>
+				simd!(writer, V128_LOAD16X4_S, serialize_mem_arg(writer, m, memory)?),
+			V128Load16x4U(m) =>
+				simd!(writer, V128_LOAD16X4_U, serialize_mem_arg(writer, m, memory)?),
+			V128Load32x2S(m) =>
+				simd!(writer, V128_LOAD32X2_S, serialize_mem_arg(writer, m, memory)?),
+			V128Load32x2U(m) =>
+				simd!(writer, V128_LOAD32X2_U, serialize_mem_arg(writer, m, memory)?),
+			V128Load8Splat(m) =>
+				simd!(writer, V128_LOAD8_SPLAT, serialize_mem_arg(writer, m, memory)?),
+			V128Load16Splat(m) =>
+				simd!(writer, V128_LOAD16_SPLAT, serialize_mem_arg(writer, m, memory)?),
+			V128Load32Splat(m) =>
+				simd!(writer, V128_LOAD32_SPLAT, serialize_mem_arg(writer, m, memory)?),
+			V128Load64Splat(m) =>
+				simd!(writer, V128_LOAD64_SPLAT, serialize_mem_arg(writer, m, memory)?),
+			V128Store(m) => simd!(writer, V128_STORE, serialize_mem_arg(writer, m, memory)?),
+			V128Const(ref i) => simd!(writer, V128_CONST, writer.write(&i[..])?),
+			I8x16Shuffle(ref i) => simd!(writer, I8X16_SHUFFLE, writer.write(&i[..])?),
+			I8x16Swizzle => simd!(writer, I8X16_SWIZZLE, {}),
 			I8x16Splat => simd!(writer, I8X16_SPLAT, {}),
 			I16x8Splat => simd!(writer, I16X8_SPLAT, {}),
 			I32x4Splat => simd!(writer, I32X4_SPLAT, {}),
@@ -2173,146 +3223,267 @@ impl Serialize for SimdInstruction {
 			F64x2Splat => simd!(writer, F64X2_SPLAT, {}),
 			I8x16ExtractLaneS(i) => simd!(writer, I8X16_EXTRACT_LANE_S, writer.write(&[i])?),
 			I8x16ExtractLaneU(i) => simd!(writer, I8X16_EXTRACT_LANE_U, writer.write(&[i])?),
+			I8x16ReplaceLane(i) => simd!(writer, I8X16_REPLACE_LANE, writer.write(&[i])?),
 			I16x8ExtractLaneS(i) => simd!(writer, I16X8_EXTRACT_LANE_S, writer.write(&[i])?),
 			I16x8ExtractLaneU(i) => simd!(writer, I16X8_EXTRACT_LANE_U, writer.write(&[i])?),
-			I32x4ExtractLane(i) => simd!(writer, I32X4_EXTRACT_LANE, writer.write(&[i])?),
-			I64x2ExtractLane(i) => simd!(writer, I64X2_EXTRACT_LANE, writer.write(&[i])?),
-			F32x4ExtractLane(i) => simd!(writer, F32X4_EXTRACT_LANE, writer.write(&[i])?),
-			F64x2ExtractLane(i) => simd!(writer, F64X2_EXTRACT_LANE, writer.write(&[i])?),
-			I8x16ReplaceLane(i) => simd!(writer, I8X16_REPLACE_LANE, writer.write(&[i])?),
 			I16x8ReplaceLane(i) => simd!(writer, I16X8_REPLACE_LANE, writer.write(&[i])?),
+			I32x4ExtractLane(i) => simd!(writer, I32X4_EXTRACT_LANE, writer.write(&[i])?),
 			I32x4ReplaceLane(i) => simd!(writer, I32X4_REPLACE_LANE, writer.write(&[i])?),
+			I64x2ExtractLane(i) => simd!(writer, I64X2_EXTRACT_LANE, writer.write(&[i])?),
 			I64x2ReplaceLane(i) => simd!(writer, I64X2_REPLACE_LANE, writer.write(&[i])?),
+			F32x4ExtractLane(i) => simd!(writer, F32X4_EXTRACT_LANE, writer.write(&[i])?),
 			F32x4ReplaceLane(i) => simd!(writer, F32X4_REPLACE_LANE, writer.write(&[i])?),
+			F64x2ExtractLane(i) => simd!(writer, F64X2_EXTRACT_LANE, writer.write(&[i])?),
 			F64x2ReplaceLane(i) => simd!(writer, F64X2_REPLACE_LANE, writer.write(&[i])?),
-			V8x16Shuffle(ref i) => simd!(writer, V8X16_SHUFFLE, writer.write(&i[..])?),
-			I8x16Add => simd!(writer, I8X16_ADD, {}),
-			I16x8Add => simd!(writer, I16X8_ADD, {}),
-			I32x4Add => simd!(writer, I32X4_ADD, {}),
-			I64x2Add => simd!(writer, I64X2_ADD, {}),
-			I8x16Sub => simd!(writer, I8X16_SUB, {}),
-			I16x8Sub => simd!(writer, I16X8_SUB, {}),
-			I32x4Sub => simd!(writer, I32X4_SUB, {}),
-			I64x2Sub => simd!(writer, I64X2_SUB, {}),
-			I8x16Mul => simd!(writer, I8X16_MUL, {}),
-			I16x8Mul => simd!(writer, I16X8_MUL, {}),
-			I32x4Mul => simd!(writer, I32X4_MUL, {}),
-			// I64x2Mul => simd!(writer, I64X2_MUL, {}),
-			I8x16Neg => simd!(writer, I8X16_NEG, {}),
-			I16x8Neg => simd!(writer, I16X8_NEG, {}),
-			I32x4Neg => simd!(writer, I32X4_NEG, {}),
-			I64x2Neg => simd!(writer, I64X2_NEG, {}),
-			I8x16AddSaturateS => simd!(writer, I8X16_ADD_SATURATE_S, {}),
-			I8x16AddSaturateU => simd!(writer, I8X16_ADD_SATURATE_U, {}),
-			I16x8AddSaturateS => simd!(writer, I16X8_ADD_SATURATE_S, {}),
-			I16x8AddSaturateU => simd!(writer, I16X8_ADD_SATURATE_U, {}),
-			I8x16SubSaturateS => simd!(writer, I8X16_SUB_SATURATE_S, {}),
-			I8x16SubSaturateU => simd!(writer, I8X16_SUB_SATURATE_U, {}),
-			I16x8SubSaturateS => simd!(writer, I16X8_SUB_SATURATE_S, {}),
-			I16x8SubSaturateU => simd!(writer, I16X8_SUB_SATURATE_U, {}),
-			I8x16Shl => simd!(writer, I8X16_SHL, {}),
-			I16x8Shl => simd!(writer, I16X8_SHL, {}),
-			I32x4Shl => simd!(writer, I32X4_SHL, {}),
-			I64x2Shl => simd!(writer, I64X2_SHL, {}),
-			I8x16ShrS => simd!(writer, I8X16_SHR_S, {}),
-			I8x16ShrU => simd!(writer, I8X16_SHR_U, {}),
-			I16x8ShrS => simd!(writer, I16X8_SHR_S, {}),
-			I16x8ShrU => simd!(writer, I16X8_SHR_U, {}),
-			I32x4ShrU => simd!(writer, I32X4_SHR_U, {}),
-			I32x4ShrS => simd!(writer, I32X4_SHR_S, {}),
-			I64x2ShrU => simd!(writer, I64X2_SHR_U, {}),
-			I64x2ShrS => simd!(writer, I64X2_SHR_S, {}),
-			V128And => simd!(writer, V128_AND, {}),
-			V128Or => simd!(writer, V128_OR, {}),
-			V128Xor => simd!(writer, V128_XOR, {}),
-			V128Not => simd!(writer, V128_NOT, {}),
-			V128Bitselect => simd!(writer, V128_BITSELECT, {}),
-			I8x16AnyTrue => simd!(writer, I8X16_ANY_TRUE, {}),
-			I16x8AnyTrue => simd!(writer, I16X8_ANY_TRUE, {}),
-			I32x4AnyTrue => simd!(writer, I32X4_ANY_TRUE, {}),
-			I64x2AnyTrue => simd!(writer, I64X2_ANY_TRUE, {}),
-			I8x16AllTrue => simd!(writer, I8X16_ALL_TRUE, {}),
-			I16x8AllTrue => simd!(writer, I16X8_ALL_TRUE, {}),
-			I32x4AllTrue => simd!(writer, I32X4_ALL_TRUE, {}),
-			I64x2AllTrue => simd!(writer, I64X2_ALL_TRUE, {}),
 			I8x16Eq => simd!(writer, I8X16_EQ, {}),
-			I16x8Eq => simd!(writer, I16X8_EQ, {}),
-			I32x4Eq => simd!(writer, I32X4_EQ, {}),
-			// I64x2Eq => simd!(writer, I64X2_EQ, {}),
-			F32x4Eq => simd!(writer, F32X4_EQ, {}),
-			F64x2Eq => simd!(writer, F64X2_EQ, {}),
 			I8x16Ne => simd!(writer, I8X16_NE, {}),
-			I16x8Ne => simd!(writer, I16X8_NE, {}),
-			I32x4Ne => simd!(writer, I32X4_NE, {}),
-			// I64x2Ne => simd!(writer, I64X2_NE, {}),
-			F32x4Ne => simd!(writer, F32X4_NE, {}),
-			F64x2Ne => simd!(writer, F64X2_NE, {}),
 			I8x16LtS => simd!(writer, I8X16_LT_S, {}),
 			I8x16LtU => simd!(writer, I8X16_LT_U, {}),
-			I16x8LtS => simd!(writer, I16X8_LT_S, {}),
-			I16x8LtU => simd!(writer, I16X8_LT_U, {}),
-			I32x4LtS => simd!(writer, I32X4_LT_S, {}),
-			I32x4LtU => simd!(writer, I32X4_LT_U, {}),
-			// I64x2LtS => simd!(writer, I64X2_LT_S, {}),
-			// I64x2LtU => simd!(writer, I64X2_LT_U, {}),
-			F32x4Lt => simd!(writer, F32X4_LT, {}),
-			F64x2Lt => simd!(writer, F64X2_LT, {}),
-			I8x16LeS => simd!(writer, I8X16_LE_S, {}),
-			I8x16LeU => simd!(writer, I8X16_LE_U, {}),
-			I16x8LeS => simd!(writer, I16X8_LE_S, {}),
-			I16x8LeU => simd!(writer, I16X8_LE_U, {}),
-			I32x4LeS => simd!(writer, I32X4_LE_S, {}),
-			I32x4LeU => simd!(writer, I32X4_LE_U, {}),
-			// I64x2LeS => simd!(writer, I64X2_LE_S, {}),
-			// I64x2LeU => simd!(writer, I64X2_LE_U, {}),
-			F32x4Le => simd!(writer, F32X4_LE, {}),
-			F64x2Le => simd!(writer, F64X2_LE, {}),
 			I8x16GtS => simd!(writer, I8X16_GT_S, {}),
 			I8x16GtU => simd!(writer, I8X16_GT_U, {}),
-			I16x8GtS => simd!(writer, I16X8_GT_S, {}),
-			I16x8GtU => simd!(writer, I16X8_GT_U, {}),
-			I32x4GtS => simd!(writer, I32X4_GT_S, {}),
-			I32x4GtU => simd!(writer, I32X4_GT_U, {}),
-			// I64x2GtS => simd!(writer, I64X2_GT_S, {}),
-			// I64x2GtU => simd!(writer, I64X2_GT_U, {}),
-			F32x4Gt => simd!(writer, F32X4_GT, {}),
-			F64x2Gt => simd!(writer, F64X2_GT, {}),
+			I8x16LeS => simd!(writer, I8X16_LE_S, {}),
+			I8x16LeU => simd!(writer, I8X16_LE_U, {}),
 			I8x16GeS => simd!(writer, I8X16_GE_S, {}),
 			I8x16GeU => simd!(writer, I8X16_GE_U, {}),
+			I16x8Eq => simd!(writer, I16X8_EQ, {}),
+			I16x8Ne => simd!(writer, I16X8_NE, {}),
+			I16x8LtS => simd!(writer, I16X8_LT_S, {}),
+			I16x8LtU => simd!(writer, I16X8_LT_U, {}),
+			I16x8GtS => simd!(writer, I16X8_GT_S, {}),
+			I16x8GtU => simd!(writer, I16X8_GT_U, {}),
+			I16x8LeS => simd!(writer, I16X8_LE_S, {}),
+			I16x8LeU => simd!(writer, I16X8_LE_U, {}),
 			I16x8GeS => simd!(writer, I16X8_GE_S, {}),
 			I16x8GeU => simd!(writer, I16X8_GE_U, {}),
+			I32x4Eq => simd!(writer, I32X4_EQ, {}),
+			I32x4Ne => simd!(writer, I32X4_NE, {}),
+			I32x4LtS => simd!(writer, I32X4_LT_S, {}),
+			I32x4LtU => simd!(writer, I32X4_LT_U, {}),
+			I32x4GtS => simd!(writer, I32X4_GT_S, {}),
+			I32x4GtU => simd!(writer, I32X4_GT_U, {}),
+			I32x4LeS => simd!(writer, I32X4_LE_S, {}),
+			I32x4LeU => simd!(writer, I32X4_LE_U, {}),
 			I32x4GeS => simd!(writer, I32X4_GE_S, {}),
 			I32x4GeU => simd!(writer, I32X4_GE_U, {}),
-			// I64x2GeS => simd!(writer, I64X2_GE_S, {}),
-			// I64x2GeU => simd!(writer, I64X2_GE_U, {}),
+			F32x4Eq => simd!(writer, F32X4_EQ, {}),
+			F32x4Ne => simd!(writer, F32X4_NE, {}),
+			F32x4Lt => simd!(writer, F32X4_LT, {}),
+			F32x4Gt => simd!(writer, F32X4_GT, {}),
+			F32x4Le => simd!(writer, F32X4_LE, {}),
 			F32x4Ge => simd!(writer, F32X4_GE, {}),
+			F64x2Eq => simd!(writer, F64X2_EQ, {}),
+			F64x2Ne => simd!(writer, F64X2_NE, {}),
+			F64x2Lt => simd!(writer, F64X2_LT, {}),
+			F64x2Gt => simd!(writer, F64X2_GT, {}),
+			F64x2Le => simd!(writer, F64X2_LE, {}),
 			F64x2Ge => simd!(writer, F64X2_GE, {}),
-			F32x4Neg => simd!(writer, F32X4_NEG, {}),
-			F64x2Neg => simd!(writer, F64X2_NEG, {}),
+			V128Not => simd!(writer, V128_NOT, {}),
+			V128And => simd!(writer, V128_AND, {}),
+			V128Andnot => simd!(writer, V128_ANDNOT, {}),
+			V128Or => simd!(writer, V128_OR, {}),
+			V128Xor => simd!(writer, V128_XOR, {}),
+			V128Bitselect => simd!(writer, V128_BITSELECT, {}),
+			V128AnyTrue => simd!(writer, V128_ANY_TRUE, {}),
+			V128Load8Lane(m, i) => simd!(writer, V128_LOAD8_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Load16Lane(m, i) => simd!(writer, V128_LOAD16_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Load32Lane(m, i) => simd!(writer, V128_LOAD32_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Load64Lane(m, i) => simd!(writer, V128_LOAD64_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Store8Lane(m, i) => simd!(writer, V128_STORE8_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Store16Lane(m, i) => simd!(writer, V128_STORE16_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Store32Lane(m, i) => simd!(writer, V128_STORE32_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Store64Lane(m, i) => simd!(writer, V128_STORE64_LANE, {
+				serialize_mem_arg(writer, m, memory)?;
+				writer.write(&[i])?;
+			}),
+			V128Load32Zero(m) =>
+				simd!(writer, V128_LOAD32_ZERO, serialize_mem_arg(writer, m, memory)?),
+			V128Load64Zero(m) =>
+				simd!(writer, V128_LOAD64_ZERO, serialize_mem_arg(writer, m, memory)?),
+			F32x4DemoteF64x2Zero => simd!(writer, F32X4_DEMOTE_F64X2_ZERO, {}),
+			F64x2PromoteLowF32x4 => simd!(writer, F64X2_PROMOTE_LOW_F32X4, {}),
+			I8x16Abs => simd!(writer, I8X16_ABS, {}),
+			I8x16Neg => simd!(writer, I8X16_NEG, {}),
+			I8x16Popcnt => simd!(writer, I8X16_POPCNT, {}),
+			I8x16AllTrue => simd!(writer, I8X16_ALL_TRUE, {}),
+			I8x16Bitmask => simd!(writer, I8X16_BITMASK, {}),
+			I8x16NarrowI16x8S => simd!(writer, I8X16_NARROW_I16X8_S, {}),
+			I8x16NarrowI16x8U => simd!(writer, I8X16_NARROW_I16X8_U, {}),
+			F32x4Ceil => simd!(writer, F32X4_CEIL, {}),
+			F32x4Floor => simd!(writer, F32X4_FLOOR, {}),
+			F32x4Trunc => simd!(writer, F32X4_TRUNC, {}),
+			F32x4Nearest => simd!(writer, F32X4_NEAREST, {}),
+			I8x16Shl => simd!(writer, I8X16_SHL, {}),
+			I8x16ShrS => simd!(writer, I8X16_SHR_S, {}),
+			I8x16ShrU => simd!(writer, I8X16_SHR_U, {}),
+			I8x16Add => simd!(writer, I8X16_ADD, {}),
+			I8x16AddSatS => simd!(writer, I8X16_ADD_SAT_S, {}),
+			I8x16AddSatU => simd!(writer, I8X16_ADD_SAT_U, {}),
+			I8x16Sub => simd!(writer, I8X16_SUB, {}),
+			I8x16SubSatS => simd!(writer, I8X16_SUB_SAT_S, {}),
+			I8x16SubSatU => simd!(writer, I8X16_SUB_SAT_U, {}),
+			F64x2Ceil => simd!(writer, F64X2_CEIL, {}),
+			F64x2Floor => simd!(writer, F64X2_FLOOR, {}),
+			I8x16MinS => simd!(writer, I8X16_MIN_S, {}),
+			I8x16MinU => simd!(writer, I8X16_MIN_U, {}),
+			I8x16MaxS => simd!(writer, I8X16_MAX_S, {}),
+			I8x16MaxU => simd!(writer, I8X16_MAX_U, {}),
+			F64x2Trunc => simd!(writer, F64X2_TRUNC, {}),
+			I8x16AvgrU => simd!(writer, I8X16_AVGR_U, {}),
+			I16x8ExtaddPairwiseI8x16S => simd!(writer, I16X8_EXTADD_PAIRWISE_I8X16_S, {}),
+			I16x8ExtaddPairwiseI8x16U => simd!(writer, I16X8_EXTADD_PAIRWISE_I8X16_U, {}),
+			I32x4ExtaddPairwiseI16x8S => simd!(writer, I32X4_EXTADD_PAIRWISE_I16X8_S, {}),
+			I32x4ExtaddPairwiseI16x8U => simd!(writer, I32X4_EXTADD_PAIRWISE_I16X8_U, {}),
+			I16x8Abs => simd!(writer, I16X8_ABS, {}),
+			I16x8Neg => simd!(writer, I16X8_NEG, {}),
+			I16x8Q15mulrSatS => simd!(writer, I16X8_Q15MULR_SAT_S, {}),
+			I16x8AllTrue => simd!(writer, I16X8_ALL_TRUE, {}),
+			I16x8Bitmask => simd!(writer, I16X8_BITMASK, {}),
+			I16x8NarrowI32x4S => simd!(writer, I16X8_NARROW_I32X4_S, {}),
+			I16x8NarrowI32x4U => simd!(writer, I16X8_NARROW_I32X4_U, {}),
+			I16x8ExtendLowI8x16S => simd!(writer, I16X8_EXTEND_LOW_I8X16_S, {}),
+			I16x8ExtendHighI8x16S => simd!(writer, I16X8_EXTEND_HIGH_I8X16_S, {}),
+			I16x8ExtendLowI8x16U => simd!(writer, I16X8_EXTEND_LOW_I8X16_U, {}),
+			I16x8ExtendHighI8x16U => simd!(writer, I16X8_EXTEND_HIGH_I8X16_U, {}),
+			I16x8Shl => simd!(writer, I16X8_SHL, {}),
+			I16x8ShrS => simd!(writer, I16X8_SHR_S, {}),
+			I16x8ShrU => simd!(writer, I16X8_SHR_U, {}),
+			I16x8Add => simd!(writer, I16X8_ADD, {}),
+			I16x8AddSatS => simd!(writer, I16X8_ADD_SAT_S, {}),
+			I16x8AddSatU => simd!(writer, I16X8_ADD_SAT_U, {}),
+			I16x8Sub => simd!(writer, I16X8_SUB, {}),
+			I16x8SubSatS => simd!(writer, I16X8_SUB_SAT_S, {}),
+			I16x8SubSatU => simd!(writer, I16X8_SUB_SAT_U, {}),
+			F64x2Nearest => simd!(writer, F64X2_NEAREST, {}),
+			I16x8Mul => simd!(writer, I16X8_MUL, {}),
+			I16x8MinS => simd!(writer, I16X8_MIN_S, {}),
+			I16x8MinU => simd!(writer, I16X8_MIN_U, {}),
+			I16x8MaxS => simd!(writer, I16X8_MAX_S, {}),
+			I16x8MaxU => simd!(writer, I16X8_MAX_U, {}),
+			I16x8AvgrU => simd!(writer, I16X8_AVGR_U, {}),
+			I16x8ExtmulLowI8x16S => simd!(writer, I16X8_EXTMUL_LOW_I8X16_S, {}),
+			I16x8ExtmulHighI8x16S => simd!(writer, I16X8_EXTMUL_HIGH_I8X16_S, {}),
+			I16x8ExtmulLowI8x16U => simd!(writer, I16X8_EXTMUL_LOW_I8X16_U, {}),
+			I16x8ExtmulHighI8x16U => simd!(writer, I16X8_EXTMUL_HIGH_I8X16_U, {}),
+			I32x4Abs => simd!(writer, I32X4_ABS, {}),
+			I32x4Neg => simd!(writer, I32X4_NEG, {}),
+			I32x4AllTrue => simd!(writer, I32X4_ALL_TRUE, {}),
+			I32x4Bitmask => simd!(writer, I32X4_BITMASK, {}),
+			I32x4ExtendLowI16x8S => simd!(writer, I32X4_EXTEND_LOW_I16X8_S, {}),
+			I32x4ExtendHighI16x8S => simd!(writer, I32X4_EXTEND_HIGH_I16X8_S, {}),
+			I32x4ExtendLowI16x8U => simd!(writer, I32X4_EXTEND_LOW_I16X8_U, {}),
+			I32x4ExtendHighI16x8U => simd!(writer, I32X4_EXTEND_HIGH_I16X8_U, {}),
+			I32x4Shl => simd!(writer, I32X4_SHL, {}),
+			I32x4ShrS => simd!(writer, I32X4_SHR_S, {}),
+			I32x4ShrU => simd!(writer, I32X4_SHR_U, {}),
+			I32x4Add => simd!(writer, I32X4_ADD, {}),
+			I32x4Sub => simd!(writer, I32X4_SUB, {}),
+			I32x4Mul => simd!(writer, I32X4_MUL, {}),
+			I32x4MinS => simd!(writer, I32X4_MIN_S, {}),
+			I32x4MinU => simd!(writer, I32X4_MIN_U, {}),
+			I32x4MaxS => simd!(writer, I32X4_MAX_S, {}),
+			I32x4MaxU => simd!(writer, I32X4_MAX_U, {}),
+			I32x4DotI16x8S => simd!(writer, I32X4_DOT_I16X8_S, {}),
+			I32x4ExtmulLowI16x8S => simd!(writer, I32X4_EXTMUL_LOW_I16X8_S, {}),
+			I32x4ExtmulHighI16x8S => simd!(writer, I32X4_EXTMUL_HIGH_I16X8_S, {}),
+			I32x4ExtmulLowI16x8U => simd!(writer, I32X4_EXTMUL_LOW_I16X8_U, {}),
+			I32x4ExtmulHighI16x8U => simd!(writer, I32X4_EXTMUL_HIGH_I16X8_U, {}),
+			I64x2Abs => simd!(writer, I64X2_ABS, {}),
+			I64x2Neg => simd!(writer, I64X2_NEG, {}),
+			I64x2AllTrue => simd!(writer, I64X2_ALL_TRUE, {}),
+			I64x2Bitmask => simd!(writer, I64X2_BITMASK, {}),
+			I64x2ExtendLowI32x4S => simd!(writer, I64X2_EXTEND_LOW_I32X4_S, {}),
+			I64x2ExtendHighI32x4S => simd!(writer, I64X2_EXTEND_HIGH_I32X4_S, {}),
+			I64x2ExtendLowI32x4U => simd!(writer, I64X2_EXTEND_LOW_I32X4_U, {}),
+			I64x2ExtendHighI32x4U => simd!(writer, I64X2_EXTEND_HIGH_I32X4_U, {}),
+			I64x2Shl => simd!(writer, I64X2_SHL, {}),
+			I64x2ShrS => simd!(writer, I64X2_SHR_S, {}),
+			I64x2ShrU => simd!(writer, I64X2_SHR_U, {}),
+			I64x2Add => simd!(writer, I64X2_ADD, {}),
+			I64x2Sub => simd!(writer, I64X2_SUB, {}),
+			I64x2Mul => simd!(writer, I64X2_MUL, {}),
+			I64x2Eq => simd!(writer, I64X2_EQ, {}),
+			I64x2Ne => simd!(writer, I64X2_NE, {}),
+			I64x2LtS => simd!(writer, I64X2_LT_S, {}),
+			I64x2GtS => simd!(writer, I64X2_GT_S, {}),
+			I64x2LeS => simd!(writer, I64X2_LE_S, {}),
+			I64x2GeS => simd!(writer, I64X2_GE_S, {}),
+			I64x2ExtmulLowI32x4S => simd!(writer, I64X2_EXTMUL_LOW_I32X4_S, {}),
+			I64x2ExtmulHighI32x4S => simd!(writer, I64X2_EXTMUL_HIGH_I32X4_S, {}),
+			I64x2ExtmulLowI32x4U => simd!(writer, I64X2_EXTMUL_LOW_I32X4_U, {}),
+			I64x2ExtmulHighI32x4U => simd!(writer, I64X2_EXTMUL_HIGH_I32X4_U, {}),
 			F32x4Abs => simd!(writer, F32X4_ABS, {}),
-			F64x2Abs => simd!(writer, F64X2_ABS, {}),
+			F32x4Neg => simd!(writer, F32X4_NEG, {}),
+			F32x4Sqrt => simd!(writer, F32X4_SQRT, {}),
+			F32x4Add => simd!(writer, F32X4_ADD, {}),
+			F32x4Sub => simd!(writer, F32X4_SUB, {}),
+			F32x4Mul => simd!(writer, F32X4_MUL, {}),
+			F32x4Div => simd!(writer, F32X4_DIV, {}),
 			F32x4Min => simd!(writer, F32X4_MIN, {}),
-			F64x2Min => simd!(writer, F64X2_MIN, {}),
 			F32x4Max => simd!(writer, F32X4_MAX, {}),
-			F64x2Max => simd!(writer, F64X2_MAX, {}),
-			F32x4Add => simd!(writer, F32X4_ADD, {}),
+			F32x4Pmin => simd!(writer, F32X4_PMIN, {}),
+			F32x4Pmax => simd!(writer, F32X4_PMAX, {}),
+			F64x2Abs => simd!(writer, F64X2_ABS, {}),
+			F64x2Neg => simd!(writer, F64X2_NEG, {}),
+			F64x2Sqrt => simd!(writer, F64X2_SQRT, {}),
 			F64x2Add => simd!(writer, F64X2_ADD, {}),
-			F32x4Sub => simd!(writer, F32X4_SUB, {}),
 			F64x2Sub => simd!(writer, F64X2_SUB, {}),
-			F32x4Div => simd!(writer, F32X4_DIV, {}),
-			F64x2Div => simd!(writer, F64X2_DIV, {}),
-			F32x4Mul => simd!(writer, F32X4_MUL, {}),
 			F64x2Mul => simd!(writer, F64X2_MUL, {}),
-			F32x4Sqrt => simd!(writer, F32X4_SQRT, {}),
-			F64x2Sqrt => simd!(writer, F64X2_SQRT, {}),
-			F32x4ConvertSI32x4 => simd!(writer, F32X4_CONVERT_S_I32X4, {}),
-			F32x4ConvertUI32x4 => simd!(writer, F32X4_CONVERT_U_I32X4, {}),
-			F64x2ConvertSI64x2 => simd!(writer, F64X2_CONVERT_S_I64X2, {}),
-			F64x2ConvertUI64x2 => simd!(writer, F64X2_CONVERT_U_I64X2, {}),
-			I32x4TruncSF32x4Sat => simd!(writer, I32X4_TRUNC_S_F32X4_SAT, {}),
-			I32x4TruncUF32x4Sat => simd!(writer, I32X4_TRUNC_U_F32X4_SAT, {}),
-			I64x2TruncSF64x2Sat => simd!(writer, I64X2_TRUNC_S_F64X2_SAT, {}),
-			I64x2TruncUF64x2Sat => simd!(writer, I64X2_TRUNC_U_F64X2_SAT, {}),
+			F64x2Div => simd!(writer, F64X2_DIV, {}),
+			F64x2Min => simd!(writer, F64X2_MIN, {}),
+			F64x2Max => simd!(writer, F64X2_MAX, {}),
+			F64x2Pmin => simd!(writer, F64X2_PMIN, {}),
+			F64x2Pmax => simd!(writer, F64X2_PMAX, {}),
+			I32x4TruncSatF32x4S => simd!(writer, I32X4_TRUNC_SAT_F32X4_S, {}),
+			I32x4TruncSatF32x4U => simd!(writer, I32X4_TRUNC_SAT_F32X4_U, {}),
+			F32x4ConvertI32x4S => simd!(writer, F32X4_CONVERT_I32X4_S, {}),
+			F32x4ConvertI32x4U => simd!(writer, F32X4_CONVERT_I32X4_U, {}),
+			I32x4TruncSatF64x2SZero => simd!(writer, I32X4_TRUNC_SAT_F64X2_S_ZERO, {}),
+			I32x4TruncSatF64x2UZero => simd!(writer, I32X4_TRUNC_SAT_F64X2_U_ZERO, {}),
+			F64x2ConvertLowI32x4S => simd!(writer, F64X2_CONVERT_LOW_I32X4_S, {}),
+			F64x2ConvertLowI32x4U => simd!(writer, F64X2_CONVERT_LOW_I32X4_U, {}),
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(feature = "sat_trunc")]
+impl Serialize for SatTruncInstruction {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		use self::{opcodes::sat_trunc::*, SatTruncInstruction::*};
+
+		match self {
+			I32TruncSatSF32 => sat_trunc!(writer, I32_TRUNC_SAT_S_F32),
+			I32TruncSatUF32 => sat_trunc!(writer, I32_TRUNC_SAT_U_F32),
+			I32TruncSatSF64 => sat_trunc!(writer, I32_TRUNC_SAT_S_F64),
+			I32TruncSatUF64 => sat_trunc!(writer, I32_TRUNC_SAT_U_F64),
+			I64TruncSatSF32 => sat_trunc!(writer, I64_TRUNC_SAT_S_F32),
+			I64TruncSatUF32 => sat_trunc!(writer, I64_TRUNC_SAT_U_F32),
+			I64TruncSatSF64 => sat_trunc!(writer, I64_TRUNC_SAT_S_F64),
+			I64TruncSatUF64 => sat_trunc!(writer, I64_TRUNC_SAT_U_F64),
 		}
 
 		Ok(())
@@ -2474,6 +3645,8 @@ impl fmt::Display for Instruction {
 			I64Store32(_, offset) => write!(f, "i64.store32 offset={}", offset),
 
 			CurrentMemory(_) => fmt_op!(f, "current_memory"),
+			#[cfg(feature = "multi_memory")]
+			MemoryIndexed(memory, ref instruction) => write!(f, "{} memory={}", instruction, memory),
 			GrowMemory(_) => fmt_op!(f, "grow_memory"),
 
 			I32Const(def) => fmt_op!(f, "i32.const", def),
@@ -2627,12 +3800,29 @@ impl fmt::Display for Instruction {
 				SignExtInstruction::I64Extend32S => write!(f, "i64.extend32_s"),
 			},
 
+			#[cfg(feature = "exceptions")]
+			Exceptions(ref i) => match *i {
+				ExceptionsInstruction::Try(BlockType::NoResult) => fmt_op!(f, "try"),
+				ExceptionsInstruction::Try(BlockType::Value(value_type)) =>
+					fmt_op!(f, "try", value_type),
+				#[cfg(feature = "multi_value")]
+				ExceptionsInstruction::Try(BlockType::TypeIndex(idx)) => write!(f, "try type_idx={}", idx),
+				ExceptionsInstruction::Catch(tag) => fmt_op!(f, "catch", tag),
+				ExceptionsInstruction::CatchAll => fmt_op!(f, "catch_all"),
+				ExceptionsInstruction::Throw(tag) => fmt_op!(f, "throw", tag),
+				ExceptionsInstruction::Rethrow(depth) => fmt_op!(f, "rethrow", depth),
+				ExceptionsInstruction::Delegate(depth) => fmt_op!(f, "delegate", depth),
+			},
+
 			#[cfg(feature = "atomics")]
 			Atomics(ref i) => i.fmt(f),
 
 			#[cfg(feature = "simd")]
 			Simd(ref i) => i.fmt(f),
 
+			#[cfg(feature = "sat_trunc")]
+			SatTrunc(ref i) => i.fmt(f),
+
 			#[cfg(feature = "bulk")]
 			Bulk(ref i) => i.fmt(f),
 		}
@@ -2648,6 +3838,7 @@ impl fmt::Display for AtomicsInstruction {
 			AtomicWake(_) => write!(f, "atomic.wake"),
 			I32AtomicWait(_) => write!(f, "i32.atomic.wait"),
 			I64AtomicWait(_) => write!(f, "i64.atomic.wait"),
+			AtomicFence => write!(f, "atomic.fence"),
 
 			I32AtomicLoad(_) => write!(f, "i32.atomic.load"),
 			I64AtomicLoad(_) => write!(f, "i64.atomic.load"),
@@ -2729,9 +3920,21 @@ impl fmt::Display for SimdInstruction {
 		use self::SimdInstruction::*;
 
 		match *self {
-			V128Const(_) => write!(f, "v128.const"),
 			V128Load(_) => write!(f, "v128.load"),
+			V128Load8x8S(_) => write!(f, "v128.load8x8_s"),
+			V128Load8x8U(_) => write!(f, "v128.load8x8_u"),
+			V128Load16x4S(_) => write!(f, "v128.load16x4_s"),
+			V128Load16x4U(_) => write!(f, "v128.load16x4_u"),
+			V128Load32x2S(_) => write!(f, "v128.load32x2_s"),
+			V128Load32x2U(_) => write!(f, "v128.load32x2_u"),
+			V128Load8Splat(_) => write!(f, "v128.load8_splat"),
+			V128Load16Splat(_) => write!(f, "v128.load16_splat"),
+			V128Load32Splat(_) => write!(f, "v128.load32_splat"),
+			V128Load64Splat(_) => write!(f, "v128.load64_splat"),
 			V128Store(_) => write!(f, "v128.store"),
+			V128Const(_) => write!(f, "v128.const"),
+			I8x16Shuffle(_) => write!(f, "i8x16.shuffle"),
+			I8x16Swizzle => write!(f, "i8x16.swizzle"),
 			I8x16Splat => write!(f, "i8x16.splat"),
 			I16x8Splat => write!(f, "i16x8.splat"),
 			I32x4Splat => write!(f, "i32x4.splat"),
@@ -2740,146 +3943,237 @@ impl fmt::Display for SimdInstruction {
 			F64x2Splat => write!(f, "f64x2.splat"),
 			I8x16ExtractLaneS(_) => write!(f, "i8x16.extract_lane_s"),
 			I8x16ExtractLaneU(_) => write!(f, "i8x16.extract_lane_u"),
+			I8x16ReplaceLane(_) => write!(f, "i8x16.replace_lane"),
 			I16x8ExtractLaneS(_) => write!(f, "i16x8.extract_lane_s"),
 			I16x8ExtractLaneU(_) => write!(f, "i16x8.extract_lane_u"),
-			I32x4ExtractLane(_) => write!(f, "i32x4.extract_lane"),
-			I64x2ExtractLane(_) => write!(f, "i64x2.extract_lane"),
-			F32x4ExtractLane(_) => write!(f, "f32x4.extract_lane"),
-			F64x2ExtractLane(_) => write!(f, "f64x2.extract_lane"),
-			I8x16ReplaceLane(_) => write!(f, "i8x16.replace_lane"),
 			I16x8ReplaceLane(_) => write!(f, "i16x8.replace_lane"),
+			I32x4ExtractLane(_) => write!(f, "i32x4.extract_lane"),
 			I32x4ReplaceLane(_) => write!(f, "i32x4.replace_lane"),
+			I64x2ExtractLane(_) => write!(f, "i64x2.extract_lane"),
 			I64x2ReplaceLane(_) => write!(f, "i64x2.replace_lane"),
+			F32x4ExtractLane(_) => write!(f, "f32x4.extract_lane"),
 			F32x4ReplaceLane(_) => write!(f, "f32x4.replace_lane"),
+			F64x2ExtractLane(_) => write!(f, "f64x2.extract_lane"),
 			F64x2ReplaceLane(_) => write!(f, "f64x2.replace_lane"),
-			V8x16Shuffle(_) => write!(f, "v8x16.shuffle"),
-			I8x16Add => write!(f, "i8x16.add"),
-			I16x8Add => write!(f, "i16x8.add"),
-			I32x4Add => write!(f, "i32x4.add"),
-			I64x2Add => write!(f, "i64x2.add"),
-			I8x16Sub => write!(f, "i8x16.sub"),
-			I16x8Sub => write!(f, "i16x8.sub"),
-			I32x4Sub => write!(f, "i32x4.sub"),
-			I64x2Sub => write!(f, "i64x2.sub"),
-			I8x16Mul => write!(f, "i8x16.mul"),
-			I16x8Mul => write!(f, "i16x8.mul"),
-			I32x4Mul => write!(f, "i32x4.mul"),
-			// I64x2Mul => write!(f, "i64x2.mul"),
-			I8x16Neg => write!(f, "i8x16.neg"),
-			I16x8Neg => write!(f, "i16x8.neg"),
-			I32x4Neg => write!(f, "i32x4.neg"),
-			I64x2Neg => write!(f, "i64x2.neg"),
-			I8x16AddSaturateS => write!(f, "i8x16.add_saturate_s"),
-			I8x16AddSaturateU => write!(f, "i8x16.add_saturate_u"),
-			I16x8AddSaturateS => write!(f, "i16x8.add_saturate_S"),
-			I16x8AddSaturateU => write!(f, "i16x8.add_saturate_u"),
-			I8x16SubSaturateS => write!(f, "i8x16.sub_saturate_S"),
-			I8x16SubSaturateU => write!(f, "i8x16.sub_saturate_u"),
-			I16x8SubSaturateS => write!(f, "i16x8.sub_saturate_S"),
-			I16x8SubSaturateU => write!(f, "i16x8.sub_saturate_u"),
-			I8x16Shl => write!(f, "i8x16.shl"),
-			I16x8Shl => write!(f, "i16x8.shl"),
-			I32x4Shl => write!(f, "i32x4.shl"),
-			I64x2Shl => write!(f, "i64x2.shl"),
-			I8x16ShrS => write!(f, "i8x16.shr_s"),
-			I8x16ShrU => write!(f, "i8x16.shr_u"),
-			I16x8ShrS => write!(f, "i16x8.shr_s"),
-			I16x8ShrU => write!(f, "i16x8.shr_u"),
-			I32x4ShrS => write!(f, "i32x4.shr_s"),
-			I32x4ShrU => write!(f, "i32x4.shr_u"),
-			I64x2ShrS => write!(f, "i64x2.shr_s"),
-			I64x2ShrU => write!(f, "i64x2.shr_u"),
-			V128And => write!(f, "v128.and"),
-			V128Or => write!(f, "v128.or"),
-			V128Xor => write!(f, "v128.xor"),
-			V128Not => write!(f, "v128.not"),
-			V128Bitselect => write!(f, "v128.bitselect"),
-			I8x16AnyTrue => write!(f, "i8x16.any_true"),
-			I16x8AnyTrue => write!(f, "i16x8.any_true"),
-			I32x4AnyTrue => write!(f, "i32x4.any_true"),
-			I64x2AnyTrue => write!(f, "i64x2.any_true"),
-			I8x16AllTrue => write!(f, "i8x16.all_true"),
-			I16x8AllTrue => write!(f, "i16x8.all_true"),
-			I32x4AllTrue => write!(f, "i32x4.all_true"),
-			I64x2AllTrue => write!(f, "i64x2.all_true"),
 			I8x16Eq => write!(f, "i8x16.eq"),
-			I16x8Eq => write!(f, "i16x8.eq"),
-			I32x4Eq => write!(f, "i32x4.eq"),
-			// I64x2Eq => write!(f, "i64x2.eq"),
-			F32x4Eq => write!(f, "f32x4.eq"),
-			F64x2Eq => write!(f, "f64x2.eq"),
 			I8x16Ne => write!(f, "i8x16.ne"),
-			I16x8Ne => write!(f, "i16x8.ne"),
-			I32x4Ne => write!(f, "i32x4.ne"),
-			// I64x2Ne => write!(f, "i64x2.ne"),
-			F32x4Ne => write!(f, "f32x4.ne"),
-			F64x2Ne => write!(f, "f64x2.ne"),
 			I8x16LtS => write!(f, "i8x16.lt_s"),
 			I8x16LtU => write!(f, "i8x16.lt_u"),
-			I16x8LtS => write!(f, "i16x8.lt_s"),
-			I16x8LtU => write!(f, "i16x8.lt_u"),
-			I32x4LtS => write!(f, "i32x4.lt_s"),
-			I32x4LtU => write!(f, "i32x4.lt_u"),
-			// I64x2LtS => write!(f, "// I64x2.lt_s"),
-			// I64x2LtU => write!(f, "// I64x2.lt_u"),
-			F32x4Lt => write!(f, "f32x4.lt"),
-			F64x2Lt => write!(f, "f64x2.lt"),
-			I8x16LeS => write!(f, "i8x16.le_s"),
-			I8x16LeU => write!(f, "i8x16.le_u"),
-			I16x8LeS => write!(f, "i16x8.le_s"),
-			I16x8LeU => write!(f, "i16x8.le_u"),
-			I32x4LeS => write!(f, "i32x4.le_s"),
-			I32x4LeU => write!(f, "i32x4.le_u"),
-			// I64x2LeS => write!(f, "// I64x2.le_s"),
-			// I64x2LeU => write!(f, "// I64x2.le_u"),
-			F32x4Le => write!(f, "f32x4.le"),
-			F64x2Le => write!(f, "f64x2.le"),
 			I8x16GtS => write!(f, "i8x16.gt_s"),
 			I8x16GtU => write!(f, "i8x16.gt_u"),
-			I16x8GtS => write!(f, "i16x8.gt_s"),
-			I16x8GtU => write!(f, "i16x8.gt_u"),
-			I32x4GtS => write!(f, "i32x4.gt_s"),
-			I32x4GtU => write!(f, "i32x4.gt_u"),
-			// I64x2GtS => write!(f, "// I64x2.gt_s"),
-			// I64x2GtU => write!(f, "// I64x2.gt_u"),
-			F32x4Gt => write!(f, "f32x4.gt"),
-			F64x2Gt => write!(f, "f64x2.gt"),
+			I8x16LeS => write!(f, "i8x16.le_s"),
+			I8x16LeU => write!(f, "i8x16.le_u"),
 			I8x16GeS => write!(f, "i8x16.ge_s"),
 			I8x16GeU => write!(f, "i8x16.ge_u"),
+			I16x8Eq => write!(f, "i16x8.eq"),
+			I16x8Ne => write!(f, "i16x8.ne"),
+			I16x8LtS => write!(f, "i16x8.lt_s"),
+			I16x8LtU => write!(f, "i16x8.lt_u"),
+			I16x8GtS => write!(f, "i16x8.gt_s"),
+			I16x8GtU => write!(f, "i16x8.gt_u"),
+			I16x8LeS => write!(f, "i16x8.le_s"),
+			I16x8LeU => write!(f, "i16x8.le_u"),
 			I16x8GeS => write!(f, "i16x8.ge_s"),
 			I16x8GeU => write!(f, "i16x8.ge_u"),
+			I32x4Eq => write!(f, "i32x4.eq"),
+			I32x4Ne => write!(f, "i32x4.ne"),
+			I32x4LtS => write!(f, "i32x4.lt_s"),
+			I32x4LtU => write!(f, "i32x4.lt_u"),
+			I32x4GtS => write!(f, "i32x4.gt_s"),
+			I32x4GtU => write!(f, "i32x4.gt_u"),
+			I32x4LeS => write!(f, "i32x4.le_s"),
+			I32x4LeU => write!(f, "i32x4.le_u"),
 			I32x4GeS => write!(f, "i32x4.ge_s"),
 			I32x4GeU => write!(f, "i32x4.ge_u"),
-			// I64x2GeS => write!(f, "// I64x2.ge_s"),
-			// I64x2GeU => write!(f, "// I64x2.ge_u"),
+			F32x4Eq => write!(f, "f32x4.eq"),
+			F32x4Ne => write!(f, "f32x4.ne"),
+			F32x4Lt => write!(f, "f32x4.lt"),
+			F32x4Gt => write!(f, "f32x4.gt"),
+			F32x4Le => write!(f, "f32x4.le"),
 			F32x4Ge => write!(f, "f32x4.ge"),
+			F64x2Eq => write!(f, "f64x2.eq"),
+			F64x2Ne => write!(f, "f64x2.ne"),
+			F64x2Lt => write!(f, "f64x2.lt"),
+			F64x2Gt => write!(f, "f64x2.gt"),
+			F64x2Le => write!(f, "f64x2.le"),
 			F64x2Ge => write!(f, "f64x2.ge"),
-			F32x4Neg => write!(f, "f32x4.neg"),
-			F64x2Neg => write!(f, "f64x2.neg"),
+			V128Not => write!(f, "v128.not"),
+			V128And => write!(f, "v128.and"),
+			V128Andnot => write!(f, "v128.andnot"),
+			V128Or => write!(f, "v128.or"),
+			V128Xor => write!(f, "v128.xor"),
+			V128Bitselect => write!(f, "v128.bitselect"),
+			V128AnyTrue => write!(f, "v128.any_true"),
+			V128Load8Lane(..) => write!(f, "v128.load8_lane"),
+			V128Load16Lane(..) => write!(f, "v128.load16_lane"),
+			V128Load32Lane(..) => write!(f, "v128.load32_lane"),
+			V128Load64Lane(..) => write!(f, "v128.load64_lane"),
+			V128Store8Lane(..) => write!(f, "v128.store8_lane"),
+			V128Store16Lane(..) => write!(f, "v128.store16_lane"),
+			V128Store32Lane(..) => write!(f, "v128.store32_lane"),
+			V128Store64Lane(..) => write!(f, "v128.store64_lane"),
+			V128Load32Zero(_) => write!(f, "v128.load32_zero"),
+			V128Load64Zero(_) => write!(f, "v128.load64_zero"),
+			F32x4DemoteF64x2Zero => write!(f, "f32x4.demote_f64x2_zero"),
+			F64x2PromoteLowF32x4 => write!(f, "f64x2.promote_low_f32x4"),
+			I8x16Abs => write!(f, "i8x16.abs"),
+			I8x16Neg => write!(f, "i8x16.neg"),
+			I8x16Popcnt => write!(f, "i8x16.popcnt"),
+			I8x16AllTrue => write!(f, "i8x16.all_true"),
+			I8x16Bitmask => write!(f, "i8x16.bitmask"),
+			I8x16NarrowI16x8S => write!(f, "i8x16.narrow_i16x8_s"),
+			I8x16NarrowI16x8U => write!(f, "i8x16.narrow_i16x8_u"),
+			F32x4Ceil => write!(f, "f32x4.ceil"),
+			F32x4Floor => write!(f, "f32x4.floor"),
+			F32x4Trunc => write!(f, "f32x4.trunc"),
+			F32x4Nearest => write!(f, "f32x4.nearest"),
+			I8x16Shl => write!(f, "i8x16.shl"),
+			I8x16ShrS => write!(f, "i8x16.shr_s"),
+			I8x16ShrU => write!(f, "i8x16.shr_u"),
+			I8x16Add => write!(f, "i8x16.add"),
+			I8x16AddSatS => write!(f, "i8x16.add_sat_s"),
+			I8x16AddSatU => write!(f, "i8x16.add_sat_u"),
+			I8x16Sub => write!(f, "i8x16.sub"),
+			I8x16SubSatS => write!(f, "i8x16.sub_sat_s"),
+			I8x16SubSatU => write!(f, "i8x16.sub_sat_u"),
+			F64x2Ceil => write!(f, "f64x2.ceil"),
+			F64x2Floor => write!(f, "f64x2.floor"),
+			I8x16MinS => write!(f, "i8x16.min_s"),
+			I8x16MinU => write!(f, "i8x16.min_u"),
+			I8x16MaxS => write!(f, "i8x16.max_s"),
+			I8x16MaxU => write!(f, "i8x16.max_u"),
+			F64x2Trunc => write!(f, "f64x2.trunc"),
+			I8x16AvgrU => write!(f, "i8x16.avgr_u"),
+			I16x8ExtaddPairwiseI8x16S => write!(f, "i16x8.extadd_pairwise_i8x16_s"),
+			I16x8ExtaddPairwiseI8x16U => write!(f, "i16x8.extadd_pairwise_i8x16_u"),
+			I32x4ExtaddPairwiseI16x8S => write!(f, "i32x4.extadd_pairwise_i16x8_s"),
+			I32x4ExtaddPairwiseI16x8U => write!(f, "i32x4.extadd_pairwise_i16x8_u"),
+			I16x8Abs => write!(f, "i16x8.abs"),
+			I16x8Neg => write!(f, "i16x8.neg"),
+			I16x8Q15mulrSatS => write!(f, "i16x8.q15mulr_sat_s"),
+			I16x8AllTrue => write!(f, "i16x8.all_true"),
+			I16x8Bitmask => write!(f, "i16x8.bitmask"),
+			I16x8NarrowI32x4S => write!(f, "i16x8.narrow_i32x4_s"),
+			I16x8NarrowI32x4U => write!(f, "i16x8.narrow_i32x4_u"),
+			I16x8ExtendLowI8x16S => write!(f, "i16x8.extend_low_i8x16_s"),
+			I16x8ExtendHighI8x16S => write!(f, "i16x8.extend_high_i8x16_s"),
+			I16x8ExtendLowI8x16U => write!(f, "i16x8.extend_low_i8x16_u"),
+			I16x8ExtendHighI8x16U => write!(f, "i16x8.extend_high_i8x16_u"),
+			I16x8Shl => write!(f, "i16x8.shl"),
+			I16x8ShrS => write!(f, "i16x8.shr_s"),
+			I16x8ShrU => write!(f, "i16x8.shr_u"),
+			I16x8Add => write!(f, "i16x8.add"),
+			I16x8AddSatS => write!(f, "i16x8.add_sat_s"),
+			I16x8AddSatU => write!(f, "i16x8.add_sat_u"),
+			I16x8Sub => write!(f, "i16x8.sub"),
+			I16x8SubSatS => write!(f, "i16x8.sub_sat_s"),
+			I16x8SubSatU => write!(f, "i16x8.sub_sat_u"),
+			F64x2Nearest => write!(f, "f64x2.nearest"),
+			I16x8Mul => write!(f, "i16x8.mul"),
+			I16x8MinS => write!(f, "i16x8.min_s"),
+			I16x8MinU => write!(f, "i16x8.min_u"),
+			I16x8MaxS => write!(f, "i16x8.max_s"),
+			I16x8MaxU => write!(f, "i16x8.max_u"),
+			I16x8AvgrU => write!(f, "i16x8.avgr_u"),
+			I16x8ExtmulLowI8x16S => write!(f, "i16x8.extmul_low_i8x16_s"),
+			I16x8ExtmulHighI8x16S => write!(f, "i16x8.extmul_high_i8x16_s"),
+			I16x8ExtmulLowI8x16U => write!(f, "i16x8.extmul_low_i8x16_u"),
+			I16x8ExtmulHighI8x16U => write!(f, "i16x8.extmul_high_i8x16_u"),
+			I32x4Abs => write!(f, "i32x4.abs"),
+			I32x4Neg => write!(f, "i32x4.neg"),
+			I32x4AllTrue => write!(f, "i32x4.all_true"),
+			I32x4Bitmask => write!(f, "i32x4.bitmask"),
+			I32x4ExtendLowI16x8S => write!(f, "i32x4.extend_low_i16x8_s"),
+			I32x4ExtendHighI16x8S => write!(f, "i32x4.extend_high_i16x8_s"),
+			I32x4ExtendLowI16x8U => write!(f, "i32x4.extend_low_i16x8_u"),
+			I32x4ExtendHighI16x8U => write!(f, "i32x4.extend_high_i16x8_u"),
+			I32x4Shl => write!(f, "i32x4.shl"),
+			I32x4ShrS => write!(f, "i32x4.shr_s"),
+			I32x4ShrU => write!(f, "i32x4.shr_u"),
+			I32x4Add => write!(f, "i32x4.add"),
+			I32x4Sub => write!(f, "i32x4.sub"),
+			I32x4Mul => write!(f, "i32x4.mul"),
+			I32x4MinS => write!(f, "i32x4.min_s"),
+			I32x4MinU => write!(f, "i32x4.min_u"),
+			I32x4MaxS => write!(f, "i32x4.max_s"),
+			I32x4MaxU => write!(f, "i32x4.max_u"),
+			I32x4DotI16x8S => write!(f, "i32x4.dot_i16x8_s"),
+			I32x4ExtmulLowI16x8S => write!(f, "i32x4.extmul_low_i16x8_s"),
+			I32x4ExtmulHighI16x8S => write!(f, "i32x4.extmul_high_i16x8_s"),
+			I32x4ExtmulLowI16x8U => write!(f, "i32x4.extmul_low_i16x8_u"),
+			I32x4ExtmulHighI16x8U => write!(f, "i32x4.extmul_high_i16x8_u"),
+			I64x2Abs => write!(f, "i64x2.abs"),
+			I64x2Neg => write!(f, "i64x2.neg"),
+			I64x2AllTrue => write!(f, "i64x2.all_true"),
+			I64x2Bitmask => write!(f, "i64x2.bitmask"),
+			I64x2ExtendLowI32x4S => write!(f, "i64x2.extend_low_i32x4_s"),
+			I64x2ExtendHighI32x4S => write!(f, "i64x2.extend_high_i32x4_s"),
+			I64x2ExtendLowI32x4U => write!(f, "i64x2.extend_low_i32x4_u"),
+			I64x2ExtendHighI32x4U => write!(f, "i64x2.extend_high_i32x4_u"),
+			I64x2Shl => write!(f, "i64x2.shl"),
+			I64x2ShrS => write!(f, "i64x2.shr_s"),
+			I64x2ShrU => write!(f, "i64x2.shr_u"),
+			I64x2Add => write!(f, "i64x2.add"),
+			I64x2Sub => write!(f, "i64x2.sub"),
+			I64x2Mul => write!(f, "i64x2.mul"),
+			I64x2Eq => write!(f, "i64x2.eq"),
+			I64x2Ne => write!(f, "i64x2.ne"),
+			I64x2LtS => write!(f, "i64x2.lt_s"),
+			I64x2GtS => write!(f, "i64x2.gt_s"),
+			I64x2LeS => write!(f, "i64x2.le_s"),
+			I64x2GeS => write!(f, "i64x2.ge_s"),
+			I64x2ExtmulLowI32x4S => write!(f, "i64x2.extmul_low_i32x4_s"),
+			I64x2ExtmulHighI32x4S => write!(f, "i64x2.extmul_high_i32x4_s"),
+			I64x2ExtmulLowI32x4U => write!(f, "i64x2.extmul_low_i32x4_u"),
+			I64x2ExtmulHighI32x4U => write!(f, "i64x2.extmul_high_i32x4_u"),
 			F32x4Abs => write!(f, "f32x4.abs"),
-			F64x2Abs => write!(f, "f64x2.abs"),
+			F32x4Neg => write!(f, "f32x4.neg"),
+			F32x4Sqrt => write!(f, "f32x4.sqrt"),
+			F32x4Add => write!(f, "f32x4.add"),
+			F32x4Sub => write!(f, "f32x4.sub"),
+			F32x4Mul => write!(f, "f32x4.mul"),
+			F32x4Div => write!(f, "f32x4.div"),
 			F32x4Min => write!(f, "f32x4.min"),
-			F64x2Min => write!(f, "f64x2.min"),
 			F32x4Max => write!(f, "f32x4.max"),
-			F64x2Max => write!(f, "f64x2.max"),
-			F32x4Add => write!(f, "f32x4.add"),
+			F32x4Pmin => write!(f, "f32x4.pmin"),
+			F32x4Pmax => write!(f, "f32x4.pmax"),
+			F64x2Abs => write!(f, "f64x2.abs"),
+			F64x2Neg => write!(f, "f64x2.neg"),
+			F64x2Sqrt => write!(f, "f64x2.sqrt"),
 			F64x2Add => write!(f, "f64x2.add"),
-			F32x4Sub => write!(f, "f32x4.sub"),
 			F64x2Sub => write!(f, "f64x2.sub"),
-			F32x4Div => write!(f, "f32x4.div"),
-			F64x2Div => write!(f, "f64x2.div"),
-			F32x4Mul => write!(f, "f32x4.mul"),
 			F64x2Mul => write!(f, "f64x2.mul"),
-			F32x4Sqrt => write!(f, "f32x4.sqrt"),
-			F64x2Sqrt => write!(f, "f64x2.sqrt"),
-			F32x4ConvertSI32x4 => write!(f, "f32x4.convert_s/i32x4"),
-			F32x4ConvertUI32x4 => write!(f, "f32x4.convert_u/i32x4"),
-			F64x2ConvertSI64x2 => write!(f, "f64x2.convert_s/i64x2"),
-			F64x2ConvertUI64x2 => write!(f, "f64x2.convert_u/i64x2"),
-			I32x4TruncSF32x4Sat => write!(f, "i32x4.trunc_s/f32x4:sat"),
-			I32x4TruncUF32x4Sat => write!(f, "i32x4.trunc_u/f32x4:sat"),
-			I64x2TruncSF64x2Sat => write!(f, "i64x2.trunc_s/f64x2:sat"),
-			I64x2TruncUF64x2Sat => write!(f, "i64x2.trunc_u/f64x2:sat"),
+			F64x2Div => write!(f, "f64x2.div"),
+			F64x2Min => write!(f, "f64x2.min"),
+			F64x2Max => write!(f, "f64x2.max"),
+			F64x2Pmin => write!(f, "f64x2.pmin"),
+			F64x2Pmax => write!(f, "f64x2.pmax"),
+			I32x4TruncSatF32x4S => write!(f, "i32x4.trunc_sat_f32x4_s"),
+			I32x4TruncSatF32x4U => write!(f, "i32x4.trunc_sat_f32x4_u"),
+			F32x4ConvertI32x4S => write!(f, "f32x4.convert_i32x4_s"),
+			F32x4ConvertI32x4U => write!(f, "f32x4.convert_i32x4_u"),
+			I32x4TruncSatF64x2SZero => write!(f, "i32x4.trunc_sat_f64x2_s_zero"),
+			I32x4TruncSatF64x2UZero => write!(f, "i32x4.trunc_sat_f64x2_u_zero"),
+			F64x2ConvertLowI32x4S => write!(f, "f64x2.convert_low_i32x4_s"),
+			F64x2ConvertLowI32x4U => write!(f, "f64x2.convert_low_i32x4_u"),
+		}
+	}
+}
+
+#[cfg(feature = "sat_trunc")]
+impl fmt::Display for SatTruncInstruction {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		use self::SatTruncInstruction::*;
+
+		match *self {
+			I32TruncSatSF32 => write!(f, "i32.trunc_s:sat/f32"),
+			I32TruncSatUF32 => write!(f, "i32.trunc_u:sat/f32"),
+			I32TruncSatSF64 => write!(f, "i32.trunc_s:sat/f64"),
+			I32TruncSatUF64 => write!(f, "i32.trunc_u:sat/f64"),
+			I64TruncSatSF32 => write!(f, "i64.trunc_s:sat/f32"),
+			I64TruncSatUF32 => write!(f, "i64.trunc_u:sat/f32"),
+			I64TruncSatSF64 => write!(f, "i64.trunc_s:sat/f64"),
+			I64TruncSatUF64 => write!(f, "i64.trunc_u:sat/f64"),
 		}
 	}
 }
@@ -2951,6 +4245,27 @@ fn ifelse() {
 	assert_eq!(before_else, after_else);
 }
 
+#[test]
+fn nesting_depth() {
+	fn nested(depth: usize) -> Vec<u8> {
+		let mut code = Vec::new();
+		for _ in 0..depth {
+			code.extend_from_slice(&[0x02, 0x40]);
+		}
+		code.resize(code.len() + depth + 1, 0x0b);
+		code
+	}
+
+	let instructions = super::deserialize_buffer::<Instructions>(&nested(MAX_NESTING_DEPTH))
+		.expect("maximum nesting depth to be accepted");
+	assert_eq!(instructions.elements().len(), MAX_NESTING_DEPTH * 2 + 1);
+
+	match super::deserialize_buffer::<Instructions>(&nested(MAX_NESTING_DEPTH + 1)) {
+		Err(Error::NestingTooDeep(limit)) => assert_eq!(limit, MAX_NESTING_DEPTH),
+		other => panic!("Expected NestingTooDeep error, got {:?}", other),
+	}
+}
+
 #[test]
 fn display() {
 	let instruction = Instruction::GetLocal(0);
@@ -2977,3 +4292,502 @@ fn instructions_hashset() {
 		vec![Call(1), Block(Value(ValueType::I32)), Drop].into_iter().collect();
 	assert!(set.contains(&Drop));
 }
+
+#[cfg(feature = "sat_trunc")]
+#[test]
+fn sat_trunc() {
+	let instruction = super::deserialize_buffer::<Instruction>(&[0xfc, 0x07])
+		.expect("i64.trunc_u:sat/f64 to deserialize");
+	assert_eq!(instruction, Instruction::SatTrunc(SatTruncInstruction::I64TruncSatUF64));
+	assert_eq!("i64.trunc_u:sat/f64", format!("{}", instruction));
+	assert_eq!(super::serialize(instruction).expect("serialization to succeed"), vec![0xfc, 0x07]);
+
+	// The sub-opcode is a LEB128 and may use a redundant continuation byte.
+	let instruction = super::deserialize_buffer::<Instruction>(&[0xfc, 0x81, 0x00])
+		.expect("overlong sub-opcode to deserialize");
+	assert_eq!(instruction, Instruction::SatTrunc(SatTruncInstruction::I32TruncSatUF32));
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn simd_roundtrip() {
+	use self::SimdInstruction::*;
+
+	let cases: &[(&[u8], SimdInstruction)] = &[
+		(&[0xfd, 0x0e], I8x16Swizzle),
+		// opcodes above 0x7f take two LEB128 bytes
+		(&[0xfd, 0xd5, 0x01], I64x2Mul),
+		(&[0xfd, 0xff, 0x01], F64x2ConvertLowI32x4U),
+		(&[0xfd, 0x5d, 0x03, 0x10], V128Load64Zero(MemArg { align: 3, offset: 16 })),
+		(&[0xfd, 0x56, 0x02, 0x00, 0x03], V128Load32Lane(MemArg { align: 2, offset: 0 }, 3)),
+		(&[0xfd, 0x1d, 0x01], I64x2ExtractLane(1)),
+	];
+
+	for (bytes, instruction) in cases {
+		let instruction = Instruction::Simd(instruction.clone());
+		assert_eq!(
+			super::deserialize_buffer::<Instruction>(bytes)
+				.expect("SIMD instruction to deserialize"),
+			instruction
+		);
+		assert_eq!(&super::serialize(instruction).expect("serialization to succeed")[..], *bytes);
+	}
+
+	let mut shuffle = vec![0xfd, 0x0d];
+	shuffle.extend(0..16u8);
+	let instruction =
+		super::deserialize_buffer::<Instruction>(&shuffle).expect("shuffle to deserialize");
+	assert_eq!("i8x16.shuffle", format!("{}", instruction));
+	assert_eq!(super::serialize(instruction).expect("serialization to succeed"), shuffle);
+
+	#[allow(deprecated)]
+	{
+		assert!(matches!(I8x16AddSatS, SimdInstruction::I8x16AddSaturateS));
+		assert_eq!(SimdInstruction::I32x4AnyTrue, V128AnyTrue);
+		assert_eq!(
+			SimdInstruction::V8x16Shuffle(Box::new([1; 16])),
+			I8x16Shuffle(Box::new([1; 16]))
+		);
+		assert_eq!(opcodes::simd::V8X16_SHUFFLE, opcodes::simd::I8X16_SHUFFLE);
+	}
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn simd_opcodes() {
+	use super::{Serialize, VarUint32};
+
+	enum Immediate {
+		Plain,
+		MemArg,
+		MemArgLane,
+		Lane,
+		Bytes,
+	}
+	use Immediate::*;
+
+	// Every instruction of the fixed-width SIMD proposal, as listed in the specification.
+	let table: &[(u32, &str, Immediate)] = &[
+		(0x00, "v128.load", MemArg),
+		(0x01, "v128.load8x8_s", MemArg),
+		(0x02, "v128.load8x8_u", MemArg),
+		(0x03, "v128.load16x4_s", MemArg),
+		(0x04, "v128.load16x4_u", MemArg),
+		(0x05, "v128.load32x2_s", MemArg),
+		(0x06, "v128.load32x2_u", MemArg),
+		(0x07, "v128.load8_splat", MemArg),
+		(0x08, "v128.load16_splat", MemArg),
+		(0x09, "v128.load32_splat", MemArg),
+		(0x0a, "v128.load64_splat", MemArg),
+		(0x0b, "v128.store", MemArg),
+		(0x0c, "v128.const", Bytes),
+		(0x0d, "i8x16.shuffle", Bytes),
+		(0x0e, "i8x16.swizzle", Plain),
+		(0x0f, "i8x16.splat", Plain),
+		(0x10, "i16x8.splat", Plain),
+		(0x11, "i32x4.splat", Plain),
+		(0x12, "i64x2.splat", Plain),
+		(0x13, "f32x4.splat", Plain),
+		(0x14, "f64x2.splat", Plain),
+		(0x15, "i8x16.extract_lane_s", Lane),
+		(0x16, "i8x16.extract_lane_u", Lane),
+		(0x17, "i8x16.replace_lane", Lane),
+		(0x18, "i16x8.extract_lane_s", Lane),
+		(0x19, "i16x8.extract_lane_u", Lane),
+		(0x1a, "i16x8.replace_lane", Lane),
+		(0x1b, "i32x4.extract_lane", Lane),
+		(0x1c, "i32x4.replace_lane", Lane),
+		(0x1d, "i64x2.extract_lane", Lane),
+		(0x1e, "i64x2.replace_lane", Lane),
+		(0x1f, "f32x4.extract_lane", Lane),
+		(0x20, "f32x4.replace_lane", Lane),
+		(0x21, "f64x2.extract_lane", Lane),
+		(0x22, "f64x2.replace_lane", Lane),
+		(0x23, "i8x16.eq", Plain),
+		(0x24, "i8x16.ne", Plain),
+		(0x25, "i8x16.lt_s", Plain),
+		(0x26, "i8x16.lt_u", Plain),
+		(0x27, "i8x16.gt_s", Plain),
+		(0x28, "i8x16.gt_u", Plain),
+		(0x29, "i8x16.le_s", Plain),
+		(0x2a, "i8x16.le_u", Plain),
+		(0x2b, "i8x16.ge_s", Plain),
+		(0x2c, "i8x16.ge_u", Plain),
+		(0x2d, "i16x8.eq", Plain),
+		(0x2e, "i16x8.ne", Plain),
+		(0x2f, "i16x8.lt_s", Plain),
+		(0x30, "i16x8.lt_u", Plain),
+		(0x31, "i16x8.gt_s", Plain),
+		(0x32, "i16x8.gt_u", Plain),
+		(0x33, "i16x8.le_s", Plain),
+		(0x34, "i16x8.le_u", Plain),
+		(0x35, "i16x8.ge_s", Plain),
+		(0x36, "i16x8.ge_u", Plain),
+		(0x37, "i32x4.eq", Plain),
+		(0x38, "i32x4.ne", Plain),
+		(0x39, "i32x4.lt_s", Plain),
+		(0x3a, "i32x4.lt_u", Plain),
+		(0x3b, "i32x4.gt_s", Plain),
+		(0x3c, "i32x4.gt_u", Plain),
+		(0x3d, "i32x4.le_s", Plain),
+		(0x3e, "i32x4.le_u", Plain),
+		(0x3f, "i32x4.ge_s", Plain),
+		(0x40, "i32x4.ge_u", Plain),
+		(0x41, "f32x4.eq", Plain),
+		(0x42, "f32x4.ne", Plain),
+		(0x43, "f32x4.lt", Plain),
+		(0x44, "f32x4.gt", Plain),
+		(0x45, "f32x4.le", Plain),
+		(0x46, "f32x4.ge", Plain),
+		(0x47, "f64x2.eq", Plain),
+		(0x48, "f64x2.ne", Plain),
+		(0x49, "f64x2.lt", Plain),
+		(0x4a, "f64x2.gt", Plain),
+		(0x4b, "f64x2.le", Plain),
+		(0x4c, "f64x2.ge", Plain),
+		(0x4d, "v128.not", Plain),
+		(0x4e, "v128.and", Plain),
+		(0x4f, "v128.andnot", Plain),
+		(0x50, "v128.or", Plain),
+		(0x51, "v128.xor", Plain),
+		(0x52, "v128.bitselect", Plain),
+		(0x53, "v128.any_true", Plain),
+		(0x54, "v128.load8_lane", MemArgLane),
+		(0x55, "v128.load16_lane", MemArgLane),
+		(0x56, "v128.load32_lane", MemArgLane),
+		(0x57, "v128.load64_lane", MemArgLane),
+		(0x58, "v128.store8_lane", MemArgLane),
+		(0x59, "v128.store16_lane", MemArgLane),
+		(0x5a, "v128.store32_lane", MemArgLane),
+		(0x5b, "v128.store64_lane", MemArgLane),
+		(0x5c, "v128.load32_zero", MemArg),
+		(0x5d, "v128.load64_zero", MemArg),
+		(0x5e, "f32x4.demote_f64x2_zero", Plain),
+		(0x5f, "f64x2.promote_low_f32x4", Plain),
+		(0x60, "i8x16.abs", Plain),
+		(0x61, "i8x16.neg", Plain),
+		(0x62, "i8x16.popcnt", Plain),
+		(0x63, "i8x16.all_true", Plain),
+		(0x64, "i8x16.bitmask", Plain),
+		(0x65, "i8x16.narrow_i16x8_s", Plain),
+		(0x66, "i8x16.narrow_i16x8_u", Plain),
+		(0x67, "f32x4.ceil", Plain),
+		(0x68, "f32x4.floor", Plain),
+		(0x69, "f32x4.trunc", Plain),
+		(0x6a, "f32x4.nearest", Plain),
+		(0x6b, "i8x16.shl", Plain),
+		(0x6c, "i8x16.shr_s", Plain),
+		(0x6d, "i8x16.shr_u", Plain),
+		(0x6e, "i8x16.add", Plain),
+		(0x6f, "i8x16.add_sat_s", Plain),
+		(0x70, "i8x16.add_sat_u", Plain),
+		(0x71, "i8x16.sub", Plain),
+		(0x72, "i8x16.sub_sat_s", Plain),
+		(0x73, "i8x16.sub_sat_u", Plain),
+		(0x74, "f64x2.ceil", Plain),
+		(0x75, "f64x2.floor", Plain),
+		(0x76, "i8x16.min_s", Plain),
+		(0x77, "i8x16.min_u", Plain),
+		(0x78, "i8x16.max_s", Plain),
+		(0x79, "i8x16.max_u", Plain),
+		(0x7a, "f64x2.trunc", Plain),
+		(0x7b, "i8x16.avgr_u", Plain),
+		(0x7c, "i16x8.extadd_pairwise_i8x16_s", Plain),
+		(0x7d, "i16x8.extadd_pairwise_i8x16_u", Plain),
+		(0x7e, "i32x4.extadd_pairwise_i16x8_s", Plain),
+		(0x7f, "i32x4.extadd_pairwise_i16x8_u", Plain),
+		(0x80, "i16x8.abs", Plain),
+		(0x81, "i16x8.neg", Plain),
+		(0x82, "i16x8.q15mulr_sat_s", Plain),
+		(0x83, "i16x8.all_true", Plain),
+		(0x84, "i16x8.bitmask", Plain),
+		(0x85, "i16x8.narrow_i32x4_s", Plain),
+		(0x86, "i16x8.narrow_i32x4_u", Plain),
+		(0x87, "i16x8.extend_low_i8x16_s", Plain),
+		(0x88, "i16x8.extend_high_i8x16_s", Plain),
+		(0x89, "i16x8.extend_low_i8x16_u", Plain),
+		(0x8a, "i16x8.extend_high_i8x16_u", Plain),
+		(0x8b, "i16x8.shl", Plain),
+		(0x8c, "i16x8.shr_s", Plain),
+		(0x8d, "i16x8.shr_u", Plain),
+		(0x8e, "i16x8.add", Plain),
+		(0x8f, "i16x8.add_sat_s", Plain),
+		(0x90, "i16x8.add_sat_u", Plain),
+		(0x91, "i16x8.sub", Plain),
+		(0x92, "i16x8.sub_sat_s", Plain),
+		(0x93, "i16x8.sub_sat_u", Plain),
+		(0x94, "f64x2.nearest", Plain),
+		(0x95, "i16x8.mul", Plain),
+		(0x96, "i16x8.min_s", Plain),
+		(0x97, "i16x8.min_u", Plain),
+		(0x98, "i16x8.max_s", Plain),
+		(0x99, "i16x8.max_u", Plain),
+		(0x9b, "i16x8.avgr_u", Plain),
+		(0x9c, "i16x8.extmul_low_i8x16_s", Plain),
+		(0x9d, "i16x8.extmul_high_i8x16_s", Plain),
+		(0x9e, "i16x8.extmul_low_i8x16_u", Plain),
+		(0x9f, "i16x8.extmul_high_i8x16_u", Plain),
+		(0xa0, "i32x4.abs", Plain),
+		(0xa1, "i32x4.neg", Plain),
+		(0xa3, "i32x4.all_true", Plain),
+		(0xa4, "i32x4.bitmask", Plain),
+		(0xa7, "i32x4.extend_low_i16x8_s", Plain),
+		(0xa8, "i32x4.extend_high_i16x8_s", Plain),
+		(0xa9, "i32x4.extend_low_i16x8_u", Plain),
+		(0xaa, "i32x4.extend_high_i16x8_u", Plain),
+		(0xab, "i32x4.shl", Plain),
+		(0xac, "i32x4.shr_s", Plain),
+		(0xad, "i32x4.shr_u", Plain),
+		(0xae, "i32x4.add", Plain),
+		(0xb1, "i32x4.sub", Plain),
+		(0xb5, "i32x4.mul", Plain),
+		(0xb6, "i32x4.min_s", Plain),
+		(0xb7, "i32x4.min_u", Plain),
+		(0xb8, "i32x4.max_s", Plain),
+		(0xb9, "i32x4.max_u", Plain),
+		(0xba, "i32x4.dot_i16x8_s", Plain),
+		(0xbc, "i32x4.extmul_low_i16x8_s", Plain),
+		(0xbd, "i32x4.extmul_high_i16x8_s", Plain),
+		(0xbe, "i32x4.extmul_low_i16x8_u", Plain),
+		(0xbf, "i32x4.extmul_high_i16x8_u", Plain),
+		(0xc0, "i64x2.abs", Plain),
+		(0xc1, "i64x2.neg", Plain),
+		(0xc3, "i64x2.all_true", Plain),
+		(0xc4, "i64x2.bitmask", Plain),
+		(0xc7, "i64x2.extend_low_i32x4_s", Plain),
+		(0xc8, "i64x2.extend_high_i32x4_s", Plain),
+		(0xc9, "i64x2.extend_low_i32x4_u", Plain),
+		(0xca, "i64x2.extend_high_i32x4_u", Plain),
+		(0xcb, "i64x2.shl", Plain),
+		(0xcc, "i64x2.shr_s", Plain),
+		(0xcd, "i64x2.shr_u", Plain),
+		(0xce, "i64x2.add", Plain),
+		(0xd1, "i64x2.sub", Plain),
+		(0xd5, "i64x2.mul", Plain),
+		(0xd6, "i64x2.eq", Plain),
+		(0xd7, "i64x2.ne", Plain),
+		(0xd8, "i64x2.lt_s", Plain),
+		(0xd9, "i64x2.gt_s", Plain),
+		(0xda, "i64x2.le_s", Plain),
+		(0xdb, "i64x2.ge_s", Plain),
+		(0xdc, "i64x2.extmul_low_i32x4_s", Plain),
+		(0xdd, "i64x2.extmul_high_i32x4_s", Plain),
+		(0xde, "i64x2.extmul_low_i32x4_u", Plain),
+		(0xdf, "i64x2.extmul_high_i32x4_u", Plain),
+		(0xe0, "f32x4.abs", Plain),
+		(0xe1, "f32x4.neg", Plain),
+		(0xe3, "f32x4.sqrt", Plain),
+		(0xe4, "f32x4.add", Plain),
+		(0xe5, "f32x4.sub", Plain),
+		(0xe6, "f32x4.mul", Plain),
+		(0xe7, "f32x4.div", Plain),
+		(0xe8, "f32x4.min", Plain),
+		(0xe9, "f32x4.max", Plain),
+		(0xea, "f32x4.pmin", Plain),
+		(0xeb, "f32x4.pmax", Plain),
+		(0xec, "f64x2.abs", Plain),
+		(0xed, "f64x2.neg", Plain),
+		(0xef, "f64x2.sqrt", Plain),
+		(0xf0, "f64x2.add", Plain),
+		(0xf1, "f64x2.sub", Plain),
+		(0xf2, "f64x2.mul", Plain),
+		(0xf3, "f64x2.div", Plain),
+		(0xf4, "f64x2.min", Plain),
+		(0xf5, "f64x2.max", Plain),
+		(0xf6, "f64x2.pmin", Plain),
+		(0xf7, "f64x2.pmax", Plain),
+		(0xf8, "i32x4.trunc_sat_f32x4_s", Plain),
+		(0xf9, "i32x4.trunc_sat_f32x4_u", Plain),
+		(0xfa, "f32x4.convert_i32x4_s", Plain),
+		(0xfb, "f32x4.convert_i32x4_u", Plain),
+		(0xfc, "i32x4.trunc_sat_f64x2_s_zero", Plain),
+		(0xfd, "i32x4.trunc_sat_f64x2_u_zero", Plain),
+		(0xfe, "f64x2.convert_low_i32x4_s", Plain),
+		(0xff, "f64x2.convert_low_i32x4_u", Plain),
+	];
+
+	for (opcode, mnemonic, immediate) in table {
+		let mut bytes = vec![0xfd];
+		VarUint32::from(*opcode).serialize(&mut bytes).expect("opcode to serialize");
+		match immediate {
+			Plain => {},
+			MemArg => bytes.extend_from_slice(&[0x04, 0x10]),
+			MemArgLane => bytes.extend_from_slice(&[0x04, 0x10, 0x01]),
+			Lane => bytes.push(0x01),
+			Bytes => bytes.extend(0..16u8),
+		}
+		let instruction = super::deserialize_buffer::<Instruction>(&bytes)
+			.unwrap_or_else(|e| panic!("{} to deserialize: {:?}", mnemonic, e));
+		assert!(matches!(instruction, Instruction::Simd(_)));
+		assert_eq!(&format!("{}", instruction), mnemonic);
+		assert_eq!(super::serialize(instruction).expect("serialization to succeed"), bytes);
+	}
+
+	assert_eq!(table.len(), 236);
+	for opcode in 0..0x120 {
+		if table.iter().any(|(known, _, _)| *known == opcode) {
+			continue
+		}
+		let mut bytes = vec![0xfd];
+		VarUint32::from(opcode).serialize(&mut bytes).expect("opcode to serialize");
+		bytes.extend_from_slice(&[0; 16]);
+		assert!(super::deserialize_buffer::<Instruction>(&bytes).is_err(), "{:#x}", opcode);
+	}
+}
+
+#[cfg(feature = "atomics")]
+#[test]
+fn atomic_fence() {
+	let instruction = super::deserialize_buffer::<Instruction>(&[0xfe, 0x03, 0x00])
+		.expect("atomic.fence to deserialize");
+	assert_eq!(instruction, Instruction::Atomics(AtomicsInstruction::AtomicFence));
+	assert_eq!("atomic.fence", format!("{}", instruction));
+	assert_eq!(
+		super::serialize(instruction).expect("serialization to succeed"),
+		vec![0xfe, 0x03, 0x00]
+	);
+
+	assert!(super::deserialize_buffer::<Instruction>(&[0xfe, 0x03, 0x01]).is_err());
+}
+
+#[cfg(feature = "multi_memory")]
+#[test]
+fn memory_index() {
+	let instruction = super::deserialize_buffer::<Instruction>(&[0x40, 0x81, 0x01])
+		.expect("memory.grow to deserialize");
+	assert_eq!(instruction, Instruction::GrowMemory(129));
+	assert_eq!(
+		super::serialize(instruction).expect("serialization to succeed"),
+		vec![0x40, 0x81, 0x01]
+	);
+
+	let instruction = super::deserialize_buffer::<Instruction>(&[0x3f, 0x80, 0x02])
+		.expect("memory.size to deserialize");
+	assert_eq!(instruction, Instruction::CurrentMemory(256));
+	assert_eq!(
+		super::serialize(instruction).expect("serialization to succeed"),
+		vec![0x3f, 0x80, 0x02]
+	);
+}
+
+#[cfg(feature = "multi_memory")]
+#[test]
+fn memarg_memory_index() {
+	let instruction = super::deserialize_buffer::<Instruction>(&[0x28, 0x42, 0x01, 0x10])
+		.expect("i32.load to deserialize");
+	assert_eq!(instruction, Instruction::MemoryIndexed(1, Box::new(Instruction::I32Load(2, 16))));
+	assert_eq!("i32.load offset=16 memory=1", format!("{}", instruction));
+	assert_eq!(instruction.alignment(), Some(2));
+	assert!(instruction.has_valid_alignment());
+	assert_eq!(
+		super::serialize(instruction).expect("serialization to succeed"),
+		vec![0x28, 0x42, 0x01, 0x10]
+	);
+
+	// An explicit index of memory 0 is kept.
+	let instruction = super::deserialize_buffer::<Instruction>(&[0x36, 0x40, 0x00, 0x00])
+		.expect("i32.store to deserialize");
+	assert_eq!(instruction, Instruction::MemoryIndexed(0, Box::new(Instruction::I32Store(0, 0))));
+	assert_eq!(
+		super::serialize(instruction).expect("serialization to succeed"),
+		vec![0x36, 0x40, 0x00, 0x00]
+	);
+
+	assert!(super::serialize(Instruction::MemoryIndexed(1, Box::new(Instruction::Nop))).is_err());
+	let nested = Instruction::MemoryIndexed(1, Box::new(Instruction::I32Load(2, 0)));
+	assert!(super::serialize(Instruction::MemoryIndexed(2, Box::new(nested))).is_err());
+}
+
+#[cfg(all(feature = "multi_memory", feature = "simd"))]
+#[test]
+fn simd_memarg_memory_index() {
+	for (bytes, instruction) in [
+		(
+			&[0xfd, 0x00, 0x44, 0x02, 0x08][..],
+			SimdInstruction::V128Load(MemArg { align: 4, offset: 8 }),
+		),
+		(
+			&[0xfd, 0x54, 0x40, 0x01, 0x00, 0x03][..],
+			SimdInstruction::V128Load8Lane(MemArg { align: 0, offset: 0 }, 3),
+		),
+	] {
+		let deserialized =
+			super::deserialize_buffer::<Instruction>(bytes).expect("SIMD load to deserialize");
+		assert_eq!(
+			deserialized,
+			Instruction::MemoryIndexed(bytes[3] as u32, Box::new(Instruction::Simd(instruction)))
+		);
+		assert_eq!(super::serialize(deserialized).expect("serialization to succeed"), bytes);
+	}
+}
+
+#[cfg(all(feature = "multi_memory", feature = "atomics"))]
+#[test]
+fn atomic_memarg_memory_index() {
+	let instruction = super::deserialize_buffer::<Instruction>(&[0xfe, 0x10, 0x42, 0x03, 0x00])
+		.expect("i32.atomic.load to deserialize");
+	assert_eq!(
+		instruction,
+		Instruction::MemoryIndexed(
+			3,
+			Box::new(Instruction::Atomics(AtomicsInstruction::I32AtomicLoad(MemArg {
+				align: 2,
+				offset: 0
+			})))
+		)
+	);
+	assert!(instruction.has_valid_alignment());
+	assert_eq!(
+		super::serialize(instruction).expect("serialization to succeed"),
+		vec![0xfe, 0x10, 0x42, 0x03, 0x00]
+	);
+}
+
+#[cfg(feature = "exceptions")]
+#[test]
+fn try_catch() {
+	use self::ExceptionsInstruction::*;
+	use super::ValueType;
+
+	let code = vec![
+		0x06, 0x40, // try
+		0x08, 0x00, // throw 0
+		0x07, 0x00, // catch 0
+		0x19, // catch_all
+		0x09, 0x00, // rethrow 0
+		0x0b, // end
+		0x06, 0x7f, // try (result i32)
+		0x41, 0x00, // i32.const 0
+		0x18, 0x00, // delegate 0
+		0x0b, // end
+	];
+	let instructions: Instructions =
+		super::deserialize_buffer(&code).expect("instructions to deserialize");
+	assert_eq!(
+		instructions.elements(),
+		&[
+			Instruction::Exceptions(Try(BlockType::NoResult)),
+			Instruction::Exceptions(Throw(0)),
+			Instruction::Exceptions(Catch(0)),
+			Instruction::Exceptions(CatchAll),
+			Instruction::Exceptions(Rethrow(0)),
+			Instruction::End,
+			Instruction::Exceptions(Try(BlockType::Value(ValueType::I32))),
+			Instruction::I32Const(0),
+			Instruction::Exceptions(Delegate(0)),
+			Instruction::End,
+		]
+	);
+	assert_eq!("catch_all", format!("{}", Instruction::Exceptions(CatchAll)));
+	assert_eq!(super::serialize(instructions).expect("serialization to succeed"), code);
+}
+
+#[test]
+fn float_instructions() {
+	assert!(Instruction::F32Add.is_float());
+	assert!(Instruction::F64Load(3, 0).is_float());
+	assert!(Instruction::I32TruncSF64.is_float());
+	assert!(!Instruction::I32Add.is_float());
+	assert!(Instruction::I64ReinterpretF64.is_float());
+	assert!(!Instruction::Drop.is_float());
+}
@@ -0,0 +1,480 @@
+use alloc::vec::Vec;
+
+#[cfg(feature = "multi_value")]
+use super::BlockType;
+#[cfg(feature = "bulk")]
+use super::BulkInstruction;
+#[cfg(all(feature = "exceptions", feature = "multi_value"))]
+use super::ExceptionsInstruction;
+use super::{
+	Error, External, ImportCountType, IndexMap, InitExpr, Instruction, Internal, Module, Section,
+};
+
+/// Function or global reached while walking the module from its roots.
+enum Item {
+	Function(u32),
+	Global(u32),
+}
+
+/// Liveness of the items of one index space.
+struct Space {
+	live: Vec<bool>,
+}
+
+impl Space {
+	fn new(len: usize) -> Self {
+		Space { live: vec![false; len] }
+	}
+
+	/// Mark `index` as live, returning whether it was dead before.
+	fn mark(&mut self, index: u32) -> Result<bool, Error> {
+		match self.live.get_mut(index as usize) {
+			Some(live) => Ok(!core::mem::replace(live, true)),
+			None => Err(Error::IndexOutOfRange(index)),
+		}
+	}
+
+	/// New index of every live item.
+	fn remapping(&self) -> Vec<Option<u32>> {
+		let mut next = 0;
+		self.live
+			.iter()
+			.map(|live| {
+				live.then(|| {
+					next += 1;
+					next - 1
+				})
+			})
+			.collect()
+	}
+}
+
+/// Index translation from the original to the transformed module.
+pub(crate) struct Remapping {
+	pub(crate) functions: Vec<Option<u32>>,
+	pub(crate) globals: Vec<Option<u32>>,
+	pub(crate) types: Vec<Option<u32>>,
+}
+
+impl Remapping {
+	pub(crate) fn function(&self, index: u32) -> u32 {
+		self.functions[index as usize].expect("only live functions are referenced")
+	}
+
+	fn global(&self, index: u32) -> u32 {
+		self.globals[index as usize].expect("only live globals are referenced")
+	}
+
+	pub(crate) fn type_ref(&self, index: u32) -> u32 {
+		self.types[index as usize].expect("only live types are referenced")
+	}
+
+	pub(crate) fn instructions(&self, instructions: &mut [Instruction]) {
+		for instruction in instructions {
+			match instruction {
+				Instruction::Call(index) => *index = self.function(*index),
+				Instruction::CallIndirect(type_ref, _) => *type_ref = self.type_ref(*type_ref),
+				Instruction::GetGlobal(index) | Instruction::SetGlobal(index) =>
+					*index = self.global(*index),
+				#[cfg(feature = "multi_value")]
+				Instruction::Block(BlockType::TypeIndex(type_ref)) |
+				Instruction::Loop(BlockType::TypeIndex(type_ref)) |
+				Instruction::If(BlockType::TypeIndex(type_ref)) => *type_ref = self.type_ref(*type_ref),
+				#[cfg(all(feature = "exceptions", feature = "multi_value"))]
+				Instruction::Exceptions(ExceptionsInstruction::Try(BlockType::TypeIndex(
+					type_ref,
+				))) => *type_ref = self.type_ref(*type_ref),
+				_ => {},
+			}
+		}
+	}
+
+	fn init_expr(&self, init_expr: &mut InitExpr) {
+		self.instructions(init_expr.code_mut())
+	}
+
+	pub(crate) fn names<T>(&self, map: IndexMap<T>, remapping: &[Option<u32>]) -> IndexMap<T> {
+		map.into_iter()
+			.filter_map(|(index, value)| {
+				remapping.get(index as usize).copied().flatten().map(|index| (index, value))
+			})
+			.collect()
+	}
+}
+
+fn global_references(code: &[Instruction]) -> impl Iterator<Item = u32> + '_ {
+	code.iter().filter_map(|instruction| match instruction {
+		Instruction::GetGlobal(index) | Instruction::SetGlobal(index) => Some(*index),
+		_ => None,
+	})
+}
+
+fn uses_table(instruction: &Instruction) -> bool {
+	match instruction {
+		Instruction::CallIndirect(..) => true,
+		#[cfg(feature = "bulk")]
+		Instruction::Bulk(
+			BulkInstruction::TableInit(_) |
+			BulkInstruction::TableDrop(_) |
+			BulkInstruction::TableCopy,
+		) => true,
+		_ => false,
+	}
+}
+
+/// Remove everything that can not be reached from the exports named in `roots`.
+///
+/// Starting from the root exports and the start function, all functions and globals that
+/// are called or accessed are kept, together with the types they use. Element segments are
+/// kept, and their members considered reachable, only if live code accesses a table, a
+/// table is among the roots or a table is imported. All other functions, globals, types and exports are removed,
+/// including unused imports, and the remaining indices are renumbered in code, exports,
+/// segments and the name section. Tables, memories and data segments are left as they are.
+///
+/// Fails with [`Error::UnparsedCode`] if the code section was decoded lazily, with
+/// [`Error::ExportNotFound`] if a root is not exported and with [`Error::IndexOutOfRange`] if
+/// live code refers to a function or global that does not exist.
+pub fn optimize(module: &mut Module, roots: &[&str]) -> Result<(), Error> {
+	if module.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+
+	let imported_functions = module.import_count(ImportCountType::Function) as u32;
+	let imported_globals = module.import_count(ImportCountType::Global) as u32;
+	let mut functions = Space::new(module.functions_space());
+	let mut globals = Space::new(module.globals_space());
+	let mut types = Space::new(module.type_section().map(|s| s.types().len()).unwrap_or(0));
+	let mut queue = Vec::new();
+	// The host or other modules can call the functions placed in an imported table.
+	let mut tables_live = module.imports().any(|external| matches!(external, External::Table(_)));
+
+	let exports = module.export_section().map(|s| s.entries()).unwrap_or(&[]);
+	for root in roots {
+		let export = exports
+			.iter()
+			.find(|export| export.field() == *root)
+			.ok_or_else(|| Error::ExportNotFound((*root).into()))?;
+		match *export.internal() {
+			Internal::Function(index) => queue.push(Item::Function(index)),
+			Internal::Global(index) => queue.push(Item::Global(index)),
+			Internal::Table(_) => tables_live = true,
+			_ => {},
+		}
+	}
+	queue.extend(module.start_section().map(Item::Function));
+	for segment in module.data_section().map(|s| s.entries()).unwrap_or(&[]) {
+		let offset = segment.offset().as_ref().map(|offset| offset.code()).unwrap_or(&[]);
+		queue.extend(global_references(offset).map(Item::Global));
+	}
+
+	let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+	let global_entries = module.global_section().map(|s| s.entries()).unwrap_or(&[]);
+	let segments = module.elements_section().map(|s| s.entries()).unwrap_or(&[]);
+	let mut segments_marked = false;
+	loop {
+		if tables_live && !segments_marked {
+			segments_marked = true;
+			for segment in segments {
+				queue.extend(segment.members().iter().copied().map(Item::Function));
+				let offset = segment.offset().as_ref().map(|offset| offset.code()).unwrap_or(&[]);
+				queue.extend(global_references(offset).map(Item::Global));
+			}
+		}
+		let code = match queue.pop() {
+			Some(Item::Function(index))
+				if functions.mark(index)? && index >= imported_functions =>
+				match bodies.get((index - imported_functions) as usize) {
+					Some(body) => body.code().elements(),
+					None => return Err(Error::InconsistentCode),
+				},
+			Some(Item::Global(index)) if globals.mark(index)? && index >= imported_globals =>
+				global_entries[(index - imported_globals) as usize].init_expr().code(),
+			Some(_) => continue,
+			None => break,
+		};
+		for instruction in code {
+			match *instruction {
+				Instruction::Call(index) => queue.push(Item::Function(index)),
+				Instruction::GetGlobal(index) | Instruction::SetGlobal(index) =>
+					queue.push(Item::Global(index)),
+				ref instruction if uses_table(instruction) => tables_live = true,
+				_ => {},
+			}
+		}
+	}
+
+	// Types of live functions and tags, and those referenced from live code.
+	let imports = module.import_section().map(|s| s.entries()).unwrap_or(&[]);
+	let mut function_index = 0;
+	for import in imports {
+		match *import.external() {
+			External::Function(type_ref) => {
+				if functions.live[function_index] {
+					types.mark(type_ref)?;
+				}
+				function_index += 1;
+			},
+			#[cfg(feature = "exceptions")]
+			External::Tag(tag) => {
+				types.mark(tag.type_ref())?;
+			},
+			_ => {},
+		}
+	}
+	for (func, live) in module
+		.function_section()
+		.map(|s| s.entries())
+		.unwrap_or(&[])
+		.iter()
+		.zip(&functions.live[imported_functions as usize..])
+	{
+		if *live {
+			types.mark(func.type_ref())?;
+		}
+	}
+	#[cfg(feature = "exceptions")]
+	for tag in module.tag_section().map(|s| s.entries()).unwrap_or(&[]) {
+		types.mark(tag.type_ref())?;
+	}
+	for (body, live) in bodies.iter().zip(&functions.live[imported_functions as usize..]) {
+		if !*live {
+			continue
+		}
+		for instruction in body.code().elements() {
+			match *instruction {
+				Instruction::CallIndirect(type_ref, _) => {
+					types.mark(type_ref)?;
+				},
+				#[cfg(feature = "multi_value")]
+				Instruction::Block(BlockType::TypeIndex(type_ref)) |
+				Instruction::Loop(BlockType::TypeIndex(type_ref)) |
+				Instruction::If(BlockType::TypeIndex(type_ref)) => {
+					types.mark(type_ref)?;
+				},
+				#[cfg(all(feature = "exceptions", feature = "multi_value"))]
+				Instruction::Exceptions(ExceptionsInstruction::Try(BlockType::TypeIndex(
+					type_ref,
+				))) => {
+					types.mark(type_ref)?;
+				},
+				_ => {},
+			}
+		}
+	}
+
+	let remapping = Remapping {
+		functions: functions.remapping(),
+		globals: globals.remapping(),
+		types: types.remapping(),
+	};
+	let defined_functions = &functions.live[imported_functions as usize..];
+	let defined_globals = &globals.live[imported_globals as usize..];
+
+	for section in module.sections_mut() {
+		match section {
+			Section::Type(section) => retain_live(section.types_mut(), &types.live),
+			Section::Import(section) => {
+				let (mut function_index, mut global_index) = (0, 0);
+				section.entries_mut().retain(|import| match *import.external() {
+					External::Function(_) => {
+						function_index += 1;
+						functions.live[function_index - 1]
+					},
+					External::Global(_) => {
+						global_index += 1;
+						globals.live[global_index - 1]
+					},
+					_ => true,
+				});
+				for import in section.entries_mut() {
+					match import.external_mut() {
+						External::Function(type_ref) => *type_ref = remapping.type_ref(*type_ref),
+						#[cfg(feature = "exceptions")]
+						External::Tag(tag) => *tag = super::TagType::new(remapping.type_ref(tag.type_ref())),
+						_ => {},
+					}
+				}
+			},
+			Section::Function(section) => {
+				retain_live(section.entries_mut(), defined_functions);
+				for func in section.entries_mut() {
+					*func.type_ref_mut() = remapping.type_ref(func.type_ref());
+				}
+			},
+			Section::Code(section) => {
+				retain_live(section.bodies_mut(), defined_functions);
+				for body in section.bodies_mut() {
+					remapping.instructions(body.code_mut().elements_mut());
+				}
+			},
+			Section::Global(section) => {
+				retain_live(section.entries_mut(), defined_globals);
+				for global in section.entries_mut() {
+					remapping.init_expr(global.init_expr_mut());
+				}
+			},
+			#[cfg(feature = "exceptions")]
+			Section::Tag(section) =>
+				for tag in section.entries_mut() {
+					*tag = super::TagType::new(remapping.type_ref(tag.type_ref()));
+				},
+			Section::Export(section) => {
+				section.entries_mut().retain(|export| roots.contains(&export.field()));
+				for export in section.entries_mut() {
+					match export.internal_mut() {
+						Internal::Function(index) => *index = remapping.function(*index),
+						Internal::Global(index) => *index = remapping.global(*index),
+						_ => {},
+					}
+				}
+			},
+			Section::Start(index) => *index = remapping.function(*index),
+			Section::Element(section) if !tables_live => section.entries_mut().clear(),
+			Section::Element(section) =>
+				for segment in section.entries_mut() {
+					for member in segment.members_mut() {
+						*member = remapping.function(*member);
+					}
+					if let Some(offset) = segment.offset_mut() {
+						remapping.init_expr(offset);
+					}
+				},
+			Section::Data(section) =>
+				for segment in section.entries_mut() {
+					if let Some(offset) = segment.offset_mut() {
+						remapping.init_expr(offset);
+					}
+				},
+			Section::Name(names) => {
+				if let Some(subsection) = names.functions_mut() {
+					let map = core::mem::take(subsection.names_mut());
+					*subsection.names_mut() = remapping.names(map, &remapping.functions);
+				}
+				if let Some(subsection) = names.locals_mut() {
+					let map = core::mem::take(subsection.local_names_mut());
+					*subsection.local_names_mut() = remapping.names(map, &remapping.functions);
+				}
+			},
+			_ => {},
+		}
+	}
+
+	Ok(())
+}
+
+fn retain_live<T>(entries: &mut Vec<T>, live: &[bool]) {
+	let mut index = 0;
+	entries.retain(|_| {
+		index += 1;
+		live.get(index - 1).copied().unwrap_or(false)
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::optimize;
+	use crate::{
+		builder,
+		elements::{
+			ElementSection, ElementSegment, Error, External, ImportEntry, InitExpr, Instruction::*,
+			Instructions, Internal, Module, Section, TableType,
+		},
+	};
+
+	fn function(code: Vec<crate::elements::Instruction>) -> builder::FunctionDefinition {
+		builder::function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(code))
+			.build()
+			.build()
+	}
+
+	#[test]
+	fn removes_unreachable() {
+		let mut module = builder::module()
+			.global()
+			.value_type()
+			.i32()
+			.build()
+			.global()
+			.value_type()
+			.i64()
+			.build()
+			.export()
+			.field("main")
+			.internal()
+			.func(0)
+			.build()
+			.export()
+			.field("unused")
+			.internal()
+			.func(1)
+			.build();
+		module.push_function(function(vec![Call(2), End]));
+		module.push_function(function(vec![GetGlobal(0), Drop, End]));
+		module.push_function(function(vec![GetGlobal(1), Drop, End]));
+		let mut module = module.build();
+
+		optimize(&mut module, &["main"]).expect("optimization to succeed");
+
+		let bodies = module.code_section().expect("code section").bodies();
+		assert_eq!(bodies.len(), 2);
+		assert_eq!(bodies[0].code().elements(), &[Call(1), End]);
+		assert_eq!(bodies[1].code().elements(), &[GetGlobal(0), Drop, End]);
+		assert_eq!(module.global_section().unwrap().entries().len(), 1);
+		assert_eq!(module.type_section().unwrap().types().len(), 1);
+
+		let exports = module.export_section().unwrap().entries();
+		assert_eq!(exports.len(), 1);
+		assert_eq!(*exports[0].internal(), Internal::Function(0));
+	}
+
+	#[test]
+	fn imported_table() {
+		let mut module = builder::module()
+			.with_import(ImportEntry::new(
+				"env".into(),
+				"table".into(),
+				External::Table(TableType::new(1, None)),
+			))
+			.export()
+			.field("main")
+			.internal()
+			.func(0)
+			.build();
+		module.push_function(function(vec![End]));
+		module.push_function(function(vec![End]));
+		let mut module = module
+			.with_section(Section::Element(ElementSection::with_entries(vec![
+				ElementSegment::new(0, Some(InitExpr::new(vec![I32Const(0), End])), vec![1]),
+			])))
+			.build();
+
+		optimize(&mut module, &["main"]).expect("optimization to succeed");
+
+		assert_eq!(module.code_section().expect("code section").bodies().len(), 2);
+		let segments = module.elements_section().expect("element section").entries();
+		assert_eq!(segments.len(), 1);
+		assert_eq!(segments[0].members(), &[1]);
+	}
+
+	#[test]
+	fn errors() {
+		let mut module = builder::module().build();
+		assert!(matches!(
+			optimize(&mut module, &["main"]),
+			Err(Error::ExportNotFound(name)) if name == "main"
+		));
+
+		let mut module =
+			builder::module().export().field("main").internal().func(5).build().build();
+		assert!(matches!(optimize(&mut module, &["main"]), Err(Error::IndexOutOfRange(5))));
+
+		let module =
+			builder::module().function().signature().build().body().build().build().build();
+		let mut module = Module::from_bytes_lazy(module.into_bytes().unwrap()).unwrap();
+		assert!(matches!(optimize(&mut module, &[]), Err(Error::UnparsedCode)));
+	}
+}
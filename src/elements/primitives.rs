@@ -33,7 +33,7 @@ impl From<u32> for VarUint32 {
 
 impl From<usize> for VarUint32 {
 	fn from(i: usize) -> VarUint32 {
-		assert!(i <= u32::max_value() as usize);
+		assert!(i <= u32::MAX as usize);
 		VarUint32(i as u32)
 	}
 }
@@ -88,7 +88,7 @@ impl Serialize for VarUint32 {
 }
 
 /// Unsigned variable-length integer, limited to 64 bits,
-/// represented by at most 9 bytes that may contain padding 0x80 bytes.
+/// represented by at most 10 bytes that may contain padding 0x80 bytes.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct VarUint64(u64);
 
@@ -352,7 +352,7 @@ impl Serialize for VarInt32 {
 	}
 }
 
-/// 64-bit signed integer, encoded in LEB128 (can be 1-9 bytes length).
+/// 64-bit signed integer, encoded in LEB128 (can be 1-10 bytes length).
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct VarInt64(i64);
 
@@ -651,6 +651,62 @@ impl<I: Serialize<Error = elements::Error>, T: IntoIterator<Item = I>> Serialize
 	}
 }
 
+fn read_leb128<T: Deserialize<Error = Error>>(bytes: &[u8]) -> Result<(T, usize), Error> {
+	let mut reader = io::Cursor::new(bytes);
+	let value = T::deserialize(&mut reader)?;
+	Ok((value, reader.position()))
+}
+
+/// Decode an unsigned LEB128 `u32` from the start of `bytes`.
+///
+/// Returns the value and the number of bytes it occupied. Trailing bytes are left alone, so
+/// consecutive values can be read by advancing the slice. Fails if the encoding is truncated,
+/// longer than 5 bytes or does not fit into 32 bits.
+pub fn read_varuint32(bytes: &[u8]) -> Result<(u32, usize), Error> {
+	read_leb128::<VarUint32>(bytes).map(|(value, len)| (value.into(), len))
+}
+
+/// Decode a signed LEB128 `i32` from the start of `bytes`.
+///
+/// Same as [`read_varuint32`], with the value sign-extended from its last byte.
+pub fn read_varint32(bytes: &[u8]) -> Result<(i32, usize), Error> {
+	read_leb128::<VarInt32>(bytes).map(|(value, len)| (value.into(), len))
+}
+
+/// Decode an unsigned LEB128 `u64` from the start of `bytes`.
+///
+/// Same as [`read_varuint32`], with encodings of up to 10 bytes.
+pub fn read_varuint64(bytes: &[u8]) -> Result<(u64, usize), Error> {
+	read_leb128::<VarUint64>(bytes).map(|(value, len)| (value.into(), len))
+}
+
+/// Decode a signed LEB128 `i64` from the start of `bytes`.
+///
+/// Same as [`read_varint32`], with encodings of up to 10 bytes.
+pub fn read_varint64(bytes: &[u8]) -> Result<(i64, usize), Error> {
+	read_leb128::<VarInt64>(bytes).map(|(value, len)| (value.into(), len))
+}
+
+/// Append the shortest unsigned LEB128 encoding of `value` to `buf`.
+pub fn write_varuint32(buf: &mut Vec<u8>, value: u32) -> Result<(), Error> {
+	VarUint32::from(value).serialize(buf)
+}
+
+/// Append the shortest signed LEB128 encoding of `value` to `buf`.
+pub fn write_varint32(buf: &mut Vec<u8>, value: i32) -> Result<(), Error> {
+	VarInt32::from(value).serialize(buf)
+}
+
+/// Append the shortest unsigned LEB128 encoding of `value` to `buf`.
+pub fn write_varuint64(buf: &mut Vec<u8>, value: u64) -> Result<(), Error> {
+	VarUint64::from(value).serialize(buf)
+}
+
+/// Append the shortest signed LEB128 encoding of `value` to `buf`.
+pub fn write_varint64(buf: &mut Vec<u8>, value: i64) -> Result<(), Error> {
+	VarInt64::from(value).serialize(buf)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -898,6 +954,36 @@ mod tests {
 		varint32_serde_test(vec![0xff, 0xff, 0xff, 0xff, 0x07], 2147483647);
 	}
 
+	#[test]
+	fn slice_api() {
+		use super::{
+			read_varint32, read_varint64, read_varuint32, read_varuint64, write_varint32,
+			write_varint64, write_varuint32, write_varuint64,
+		};
+
+		let mut buf = Vec::new();
+		write_varuint32(&mut buf, 624485).expect("to be written");
+		write_varint32(&mut buf, -123456).expect("to be written");
+		write_varuint64(&mut buf, u64::MAX).expect("to be written");
+		write_varint64(&mut buf, i64::MIN).expect("to be written");
+		assert_eq!(&buf[..6], &[0xe5, 0x8e, 0x26, 0xc0, 0xbb, 0x78]);
+
+		let (value, len) = read_varuint32(&buf).expect("to be read");
+		assert_eq!((value, len), (624485, 3));
+		let (value, len) = read_varint32(&buf[3..]).expect("to be read");
+		assert_eq!((value, len), (-123456, 3));
+		let (value, len) = read_varuint64(&buf[6..]).expect("to be read");
+		assert_eq!((value, len), (u64::MAX, 10));
+		let (value, len) = read_varint64(&buf[16..]).expect("to be read");
+		assert_eq!((value, len), (i64::MIN, 10));
+
+		// Padding is accepted up to the maximum length only.
+		assert_eq!(read_varuint32(&[0x80, 0x80, 0x80, 0x80, 0x00]).expect("to be read"), (0, 5));
+		assert!(read_varuint32(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00]).is_err());
+		assert!(read_varuint32(&[0xff, 0xff, 0xff, 0xff, 0x1f]).is_err());
+		assert!(read_varint64(&[0x80]).is_err());
+	}
+
 	#[test]
 	fn counted_list() {
 		let payload = [
@@ -0,0 +1,165 @@
+use crate::io;
+use alloc::{string::String, vec::Vec};
+
+use super::{CountedList, CountedListWriter, Deserialize, Error, Serialize};
+
+/// Name of the field listing the tools that processed the module.
+pub const PROCESSED_BY: &str = "processed-by";
+
+/// Toolchain provenance, as stored in the `producers` custom section.
+///
+/// The section holds a list of fields (`language`, `processed-by` and `sdk` are the ones
+/// defined by the tool conventions), each listing products with their versions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProducersSection {
+	fields: Vec<ProducersField>,
+}
+
+impl ProducersSection {
+	/// Fields of the section.
+	pub fn fields(&self) -> &[ProducersField] {
+		&self.fields
+	}
+
+	/// Fields of the section (mutable).
+	pub fn fields_mut(&mut self) -> &mut Vec<ProducersField> {
+		&mut self.fields
+	}
+
+	/// Field with the given name, if present.
+	pub fn field(&self, name: &str) -> Option<&ProducersField> {
+		self.fields.iter().find(|field| field.name == name)
+	}
+
+	/// Add a product to the given field, creating the field if needed.
+	///
+	/// A product of the same name already listed in the field gets its version replaced.
+	pub fn add_value(
+		&mut self,
+		field: impl Into<String>,
+		name: impl Into<String>,
+		version: impl Into<String>,
+	) {
+		let field = field.into();
+		let value = ProducerValue { name: name.into(), version: version.into() };
+		let index = match self.fields.iter().position(|existing| existing.name == field) {
+			Some(index) => index,
+			None => {
+				self.fields.push(ProducersField { name: field, values: Vec::new() });
+				self.fields.len() - 1
+			},
+		};
+		let values = &mut self.fields[index].values;
+		match values.iter_mut().find(|existing| existing.name == value.name) {
+			Some(existing) => *existing = value,
+			None => values.push(value),
+		}
+	}
+
+	/// List this crate in the `processed-by` field.
+	pub fn add_processed_by_self(&mut self) {
+		self.add_value(PROCESSED_BY, "parity-wasm", env!("CARGO_PKG_VERSION"));
+	}
+}
+
+impl Deserialize for ProducersSection {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		Ok(ProducersSection { fields: CountedList::deserialize(reader)?.into_inner() })
+	}
+}
+
+impl Serialize for ProducersSection {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		CountedListWriter(self.fields.len(), self.fields.into_iter()).serialize(writer)
+	}
+}
+
+/// Single field of the producers section.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProducersField {
+	name: String,
+	values: Vec<ProducerValue>,
+}
+
+impl ProducersField {
+	/// Name of the field.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Products listed in the field.
+	pub fn values(&self) -> &[ProducerValue] {
+		&self.values
+	}
+
+	/// Products listed in the field (mutable).
+	pub fn values_mut(&mut self) -> &mut Vec<ProducerValue> {
+		&mut self.values
+	}
+}
+
+impl Deserialize for ProducersField {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		let name = String::deserialize(reader)?;
+		let values = CountedList::deserialize(reader)?.into_inner();
+		Ok(ProducersField { name, values })
+	}
+}
+
+impl Serialize for ProducersField {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		self.name.serialize(writer)?;
+		CountedListWriter(self.values.len(), self.values.into_iter()).serialize(writer)
+	}
+}
+
+/// Product name and version.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProducerValue {
+	name: String,
+	version: String,
+}
+
+impl ProducerValue {
+	/// New product entry.
+	pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+		ProducerValue { name: name.into(), version: version.into() }
+	}
+
+	/// Name of the product.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Version of the product, possibly empty.
+	pub fn version(&self) -> &str {
+		&self.version
+	}
+}
+
+impl Deserialize for ProducerValue {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		let name = String::deserialize(reader)?;
+		let version = String::deserialize(reader)?;
+		Ok(ProducerValue { name, version })
+	}
+}
+
+impl Serialize for ProducerValue {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		self.name.serialize(writer)?;
+		self.version.serialize(writer)
+	}
+}
@@ -14,6 +14,11 @@ const MEMORY_ADDR_SLEB: u8 = 4;
 const MEMORY_ADDR_I32: u8 = 5;
 const TYPE_INDEX_LEB: u8 = 6;
 const GLOBAL_INDEX_LEB: u8 = 7;
+const FUNCTION_OFFSET_I32: u8 = 8;
+const SECTION_OFFSET_I32: u8 = 9;
+const TAG_INDEX_LEB: u8 = 10;
+const GLOBAL_INDEX_I32: u8 = 13;
+const TABLE_NUMBER_LEB: u8 = 20;
 
 /// Relocation information.
 #[derive(Clone, Debug, PartialEq)]
@@ -193,6 +198,57 @@ pub enum RelocationEntry {
 		/// Index of the global symbol in the symbol table.
 		index: u32,
 	},
+
+	/// Byte offset of a function in the code section.
+	FunctionOffsetI32 {
+		/// Offset of the value to rewrite.
+		offset: u32,
+
+		/// Index of the function symbol in the symbol table.
+		index: u32,
+
+		/// Addend to add to the offset.
+		addend: i32,
+	},
+
+	/// Byte offset within a section.
+	SectionOffsetI32 {
+		/// Offset of the value to rewrite.
+		offset: u32,
+
+		/// Index of the section symbol in the symbol table.
+		index: u32,
+
+		/// Addend to add to the offset.
+		addend: i32,
+	},
+
+	/// Tag index.
+	TagIndexLeb {
+		/// Offset of the value to rewrite.
+		offset: u32,
+
+		/// Index of the tag symbol in the symbol table.
+		index: u32,
+	},
+
+	/// Global index.
+	GlobalIndexI32 {
+		/// Offset of the value to rewrite.
+		offset: u32,
+
+		/// Index of the global symbol in the symbol table.
+		index: u32,
+	},
+
+	/// Table index.
+	TableNumberLeb {
+		/// Offset of the value to rewrite.
+		offset: u32,
+
+		/// Index of the table symbol in the symbol table.
+		index: u32,
+	},
 }
 
 impl Deserialize for RelocationEntry {
@@ -243,6 +299,33 @@ impl Deserialize for RelocationEntry {
 				index: VarUint32::deserialize(rdr)?.into(),
 			}),
 
+			FUNCTION_OFFSET_I32 => Ok(RelocationEntry::FunctionOffsetI32 {
+				offset: VarUint32::deserialize(rdr)?.into(),
+				index: VarUint32::deserialize(rdr)?.into(),
+				addend: VarInt32::deserialize(rdr)?.into(),
+			}),
+
+			SECTION_OFFSET_I32 => Ok(RelocationEntry::SectionOffsetI32 {
+				offset: VarUint32::deserialize(rdr)?.into(),
+				index: VarUint32::deserialize(rdr)?.into(),
+				addend: VarInt32::deserialize(rdr)?.into(),
+			}),
+
+			TAG_INDEX_LEB => Ok(RelocationEntry::TagIndexLeb {
+				offset: VarUint32::deserialize(rdr)?.into(),
+				index: VarUint32::deserialize(rdr)?.into(),
+			}),
+
+			GLOBAL_INDEX_I32 => Ok(RelocationEntry::GlobalIndexI32 {
+				offset: VarUint32::deserialize(rdr)?.into(),
+				index: VarUint32::deserialize(rdr)?.into(),
+			}),
+
+			TABLE_NUMBER_LEB => Ok(RelocationEntry::TableNumberLeb {
+				offset: VarUint32::deserialize(rdr)?.into(),
+				index: VarUint32::deserialize(rdr)?.into(),
+			}),
+
 			entry_type => Err(Error::UnknownValueType(entry_type as i8)),
 		}
 	}
@@ -303,6 +386,38 @@ impl Serialize for RelocationEntry {
 				VarUint32::from(offset).serialize(wtr)?;
 				VarUint32::from(index).serialize(wtr)?;
 			},
+
+			RelocationEntry::FunctionOffsetI32 { offset, index, addend } => {
+				VarUint7::from(FUNCTION_OFFSET_I32).serialize(wtr)?;
+				VarUint32::from(offset).serialize(wtr)?;
+				VarUint32::from(index).serialize(wtr)?;
+				VarInt32::from(addend).serialize(wtr)?;
+			},
+
+			RelocationEntry::SectionOffsetI32 { offset, index, addend } => {
+				VarUint7::from(SECTION_OFFSET_I32).serialize(wtr)?;
+				VarUint32::from(offset).serialize(wtr)?;
+				VarUint32::from(index).serialize(wtr)?;
+				VarInt32::from(addend).serialize(wtr)?;
+			},
+
+			RelocationEntry::TagIndexLeb { offset, index } => {
+				VarUint7::from(TAG_INDEX_LEB).serialize(wtr)?;
+				VarUint32::from(offset).serialize(wtr)?;
+				VarUint32::from(index).serialize(wtr)?;
+			},
+
+			RelocationEntry::GlobalIndexI32 { offset, index } => {
+				VarUint7::from(GLOBAL_INDEX_I32).serialize(wtr)?;
+				VarUint32::from(offset).serialize(wtr)?;
+				VarUint32::from(index).serialize(wtr)?;
+			},
+
+			RelocationEntry::TableNumberLeb { offset, index } => {
+				VarUint7::from(TABLE_NUMBER_LEB).serialize(wtr)?;
+				VarUint32::from(offset).serialize(wtr)?;
+				VarUint32::from(index).serialize(wtr)?;
+			},
 		}
 
 		Ok(())
@@ -1,18 +1,23 @@
+#[cfg(feature = "exceptions")]
+use super::TagType;
 use super::{
 	serialize, CountedList, CountedListWriter, CountedWriter, DataSegment, Deserialize,
 	ElementSegment, Error, ExportEntry, External, Func, FuncBody, GlobalEntry, ImportEntry,
-	MemoryType, Serialize, TableType, VarUint32, VarUint7,
+	MemoryType, Serialize, TableType, UnparsedFuncBody, VarUint32, VarUint7,
 };
 use crate::{elements, io};
 use alloc::{borrow::ToOwned, string::String, vec::Vec};
 
-use super::{name_section::NameSection, reloc_section::RelocSection, types::Type};
+use super::{
+	linking_section::LinkingSection, name_section::NameSection, reloc_section::RelocSection,
+	typed_section::TypedCustomSection, types::Type,
+};
 
 #[cfg(feature = "reduced-stack-buffer")]
-const ENTRIES_BUFFER_LENGTH: usize = 256;
+pub(crate) const ENTRIES_BUFFER_LENGTH: usize = 256;
 
 #[cfg(not(feature = "reduced-stack-buffer"))]
-const ENTRIES_BUFFER_LENGTH: usize = 16384;
+pub(crate) const ENTRIES_BUFFER_LENGTH: usize = 16384;
 
 /// Section in the WebAssembly module.
 #[derive(Debug, Clone, PartialEq)]
@@ -36,6 +41,9 @@ pub enum Section {
 	Table(TableSection),
 	/// Memory definition section.
 	Memory(MemorySection),
+	/// Tag definition section.
+	#[cfg(feature = "exceptions")]
+	Tag(TagSection),
 	/// Global entries section.
 	Global(GlobalSection),
 	/// Export definitions.
@@ -48,6 +56,10 @@ pub enum Section {
 	DataCount(u32),
 	/// Function bodies section.
 	Code(CodeSection),
+	/// Function bodies section with undecoded bodies.
+	///
+	/// Only produced by `Module::from_bytes_lazy`, see `Module::parse_code`.
+	LazyCode(LazyCodeSection),
 	/// Data definition section.
 	Data(DataSection),
 	/// Name section.
@@ -60,12 +72,30 @@ pub enum Section {
 	/// Also note that currently there are serialization (but not de-serialization)
 	///   issues with this section (#198).
 	Reloc(RelocSection),
+	/// Linking metadata section.
+	///
+	/// Note that initially it is not parsed until `parse_linking` is called explicitly.
+	Linking(LinkingSection),
+	/// Custom section decoded with a user-provided codec.
+	///
+	/// Note that initially it is not decoded until `decode_custom_sections` is called explicitly.
+	Typed(TypedCustomSection),
 }
 
 impl Deserialize for Section {
 	type Error = Error;
 
 	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		Section::deserialize_with(reader, false)
+	}
+}
+
+impl Section {
+	/// Deserialize a section, keeping function bodies undecoded if `lazy_code` is set.
+	pub(crate) fn deserialize_with<R: io::Read>(
+		reader: &mut R,
+		lazy_code: bool,
+	) -> Result<Self, Error> {
 		let id = match VarUint7::deserialize(reader) {
 			// todo: be more selective detecting no more section
 			Err(_) => return Err(Error::UnexpectedEof),
@@ -88,6 +118,7 @@ impl Deserialize for Section {
 				Section::Start(start_idx.into())
 			},
 			9 => Section::Element(ElementSection::deserialize(reader)?),
+			10 if lazy_code => Section::LazyCode(LazyCodeSection::deserialize(reader)?),
 			10 => Section::Code(CodeSection::deserialize(reader)?),
 			11 => Section::Data(DataSection::deserialize(reader)?),
 			12 => {
@@ -96,6 +127,8 @@ impl Deserialize for Section {
 				section_reader.close()?;
 				Section::DataCount(count.into())
 			},
+			#[cfg(feature = "exceptions")]
+			13 => Section::Tag(TagSection::deserialize(reader)?),
 			invalid_id => return Err(Error::InvalidSectionId(invalid_id)),
 		})
 	}
@@ -134,6 +167,11 @@ impl Serialize for Section {
 				VarUint7::from(0x05).serialize(writer)?;
 				memory_section.serialize(writer)?;
 			},
+			#[cfg(feature = "exceptions")]
+			Section::Tag(tag_section) => {
+				VarUint7::from(0x0d).serialize(writer)?;
+				tag_section.serialize(writer)?;
+			},
 			Section::Global(global_section) => {
 				VarUint7::from(0x06).serialize(writer)?;
 				global_section.serialize(writer)?;
@@ -162,6 +200,10 @@ impl Serialize for Section {
 				VarUint7::from(0x0a).serialize(writer)?;
 				code_section.serialize(writer)?;
 			},
+			Section::LazyCode(code_section) => {
+				VarUint7::from(0x0a).serialize(writer)?;
+				code_section.serialize(writer)?;
+			},
 			Section::Data(data_section) => {
 				VarUint7::from(0x0b).serialize(writer)?;
 				data_section.serialize(writer)?;
@@ -176,6 +218,14 @@ impl Serialize for Section {
 				VarUint7::from(0x00).serialize(writer)?;
 				reloc_section.serialize(writer)?;
 			},
+			Section::Linking(linking_section) => {
+				VarUint7::from(0x00).serialize(writer)?;
+				linking_section.serialize(writer)?;
+			},
+			Section::Typed(typed_section) => {
+				VarUint7::from(0x00).serialize(writer)?;
+				typed_section.to_custom()?.serialize(writer)?;
+			},
 		}
 		Ok(())
 	}
@@ -191,15 +241,19 @@ impl Section {
 			Section::Function(_) => 0x3,
 			Section::Table(_) => 0x4,
 			Section::Memory(_) => 0x5,
-			Section::Global(_) => 0x6,
-			Section::Export(_) => 0x7,
-			Section::Start(_) => 0x8,
-			Section::Element(_) => 0x9,
-			Section::DataCount(_) => 0x0a,
-			Section::Code(_) => 0x0b,
-			Section::Data(_) => 0x0c,
+			#[cfg(feature = "exceptions")]
+			Section::Tag(_) => 0x6,
+			Section::Global(_) => 0x7,
+			Section::Export(_) => 0x8,
+			Section::Start(_) => 0x9,
+			Section::Element(_) => 0x0a,
+			Section::DataCount(_) => 0x0b,
+			Section::Code(_) | Section::LazyCode(_) => 0x0c,
+			Section::Data(_) => 0x0d,
 			Section::Name(_) => 0x00,
 			Section::Reloc(_) => 0x00,
+			Section::Linking(_) => 0x00,
+			Section::Typed(_) => 0x00,
 		}
 	}
 }
@@ -219,6 +273,11 @@ impl SectionReader {
 		Ok(SectionReader { cursor, declared_length })
 	}
 
+	/// Number of payload bytes read so far.
+	pub fn position(&self) -> usize {
+		self.cursor.position()
+	}
+
 	pub fn close(self) -> Result<(), io::Error> {
 		let cursor = self.cursor;
 		let buf_length = self.declared_length;
@@ -289,7 +348,7 @@ impl Deserialize for CustomSection {
 		let buf = buffered_read!(ENTRIES_BUFFER_LENGTH, section_length, reader);
 		let mut cursor = io::Cursor::new(&buf[..]);
 		let name = String::deserialize(&mut cursor)?;
-		let payload = buf[cursor.position() as usize..].to_vec();
+		let payload = buf[cursor.position()..].to_vec();
 		Ok(CustomSection { name, payload })
 	}
 }
@@ -343,8 +402,7 @@ impl Serialize for TypeSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<Type, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<Type, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -402,8 +460,7 @@ impl Serialize for ImportSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<ImportEntry, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<ImportEntry, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -490,8 +547,7 @@ impl Serialize for TableSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<TableType, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<TableType, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -533,8 +589,53 @@ impl Serialize for MemorySection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<MemoryType, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<MemoryType, _>(data.len(), data.into_iter());
+		counted_list.serialize(&mut counted_writer)?;
+		counted_writer.done()?;
+		Ok(())
+	}
+}
+
+/// Tag definition section (exception handling proposal).
+#[cfg(feature = "exceptions")]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TagSection(Vec<TagType>);
+
+#[cfg(feature = "exceptions")]
+impl TagSection {
+	/// List of all tag entries in the section.
+	pub fn entries(&self) -> &[TagType] {
+		&self.0
+	}
+
+	/// New tag section with tag types.
+	pub fn with_entries(entries: Vec<TagType>) -> Self {
+		TagSection(entries)
+	}
+
+	/// Mutable list of all tag entries in the section.
+	pub fn entries_mut(&mut self) -> &mut Vec<TagType> {
+		&mut self.0
+	}
+}
+
+#[cfg(feature = "exceptions")]
+impl Deserialize for TagSection {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		Ok(TagSection(read_entries(reader)?))
+	}
+}
+
+#[cfg(feature = "exceptions")]
+impl Serialize for TagSection {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		let mut counted_writer = CountedWriter::new(writer);
+		let data = self.0;
+		let counted_list = CountedListWriter::<TagType, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -576,8 +677,7 @@ impl Serialize for GlobalSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<GlobalEntry, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<GlobalEntry, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -619,8 +719,7 @@ impl Serialize for ExportSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<ExportEntry, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<ExportEntry, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -662,8 +761,61 @@ impl Serialize for CodeSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<FuncBody, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<FuncBody, _>(data.len(), data.into_iter());
+		counted_list.serialize(&mut counted_writer)?;
+		counted_writer.done()?;
+		Ok(())
+	}
+}
+
+/// Function bodies section with bodies decoded on demand.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LazyCodeSection(Vec<UnparsedFuncBody>);
+
+impl LazyCodeSection {
+	/// All undecoded function bodies in the section.
+	pub fn bodies(&self) -> &[UnparsedFuncBody] {
+		&self.0
+	}
+
+	/// All undecoded function bodies in the section, mutable.
+	pub fn bodies_mut(&mut self) -> &mut Vec<UnparsedFuncBody> {
+		&mut self.0
+	}
+
+	/// Decode the body at `index`, if it exists.
+	pub fn parse_body(&self, index: usize) -> Option<Result<FuncBody, Error>> {
+		self.0.get(index).map(UnparsedFuncBody::parse)
+	}
+
+	/// Decode all bodies into a regular code section.
+	pub fn parse(&self) -> Result<CodeSection, Error> {
+		Ok(CodeSection(self.0.iter().map(UnparsedFuncBody::parse).collect::<Result<_, _>>()?))
+	}
+}
+
+impl Deserialize for LazyCodeSection {
+	type Error = Error;
+
+	fn deserialize<R: io::Read>(reader: &mut R) -> Result<Self, Self::Error> {
+		let mut section_reader = SectionReader::new(reader)?;
+		let count: usize = VarUint32::deserialize(&mut section_reader)?.into();
+		let mut bodies = Vec::new();
+		for _ in 0..count {
+			bodies.push(UnparsedFuncBody::deserialize_in_section(&mut section_reader)?);
+		}
+		section_reader.close()?;
+		Ok(LazyCodeSection(bodies))
+	}
+}
+
+impl Serialize for LazyCodeSection {
+	type Error = Error;
+
+	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
+		let mut counted_writer = CountedWriter::new(writer);
+		let data = self.0;
+		let counted_list = CountedListWriter::<UnparsedFuncBody, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -705,8 +857,7 @@ impl Serialize for ElementSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<ElementSegment, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<ElementSegment, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
@@ -748,8 +899,7 @@ impl Serialize for DataSection {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		let mut counted_writer = CountedWriter::new(writer);
 		let data = self.0;
-		let counted_list =
-			CountedListWriter::<DataSegment, _>(data.len(), data.into_iter().map(Into::into));
+		let counted_list = CountedListWriter::<DataSegment, _>(data.len(), data.into_iter());
 		counted_list.serialize(&mut counted_writer)?;
 		counted_writer.done()?;
 		Ok(())
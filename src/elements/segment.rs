@@ -1,3 +1,5 @@
+#[cfg(feature = "bulk")]
+use super::{opcodes, Uint8};
 use super::{CountedList, CountedListWriter, Deserialize, Error, InitExpr, Serialize, VarUint32};
 use crate::io;
 use alloc::vec::Vec;
@@ -9,6 +11,19 @@ const FLAG_PASSIVE: u32 = 1;
 #[cfg(feature = "bulk")]
 const FLAG_MEM_NONZERO: u32 = 2;
 
+// Element segments additionally use bit 1 of passive segments to mark declared ones, and bit 2
+// for members encoded as expressions.
+#[cfg(feature = "bulk")]
+const FLAG_DECLARED: u32 = 3;
+#[cfg(feature = "bulk")]
+const FLAG_EXPRESSIONS: u32 = 4;
+#[cfg(feature = "bulk")]
+const ELEM_KIND_FUNCREF: u8 = 0x00;
+#[cfg(feature = "bulk")]
+const REF_TYPE_FUNCREF: u8 = 0x70;
+#[cfg(feature = "bulk")]
+const REF_FUNC: u8 = 0xd2;
+
 #[cfg(feature = "reduced-stack-buffer")]
 const VALUES_BUFFER_LENGTH: usize = 256;
 
@@ -24,6 +39,10 @@ pub struct ElementSegment {
 
 	#[cfg(feature = "bulk")]
 	passive: bool,
+	#[cfg(feature = "bulk")]
+	declared: bool,
+	#[cfg(feature = "bulk")]
+	expressions: bool,
 }
 
 impl ElementSegment {
@@ -36,6 +55,10 @@ impl ElementSegment {
 
 			#[cfg(feature = "bulk")]
 			passive: false,
+			#[cfg(feature = "bulk")]
+			declared: false,
+			#[cfg(feature = "bulk")]
+			expressions: false,
 		}
 	}
 
@@ -85,6 +108,42 @@ impl ElementSegment {
 	pub fn set_passive(&mut self, passive: bool) {
 		self.passive = passive;
 	}
+
+	/// Whether or not this table segment is "declared", i.e. only forward-declares functions
+	/// referenced by `ref.func` and is never copied into a table.
+	pub fn declared(&self) -> bool {
+		self.declared
+	}
+
+	/// Set whether or not this table segment is "declared"
+	pub fn set_declared(&mut self, declared: bool) {
+		self.declared = declared;
+	}
+
+	/// Whether the members are encoded as `ref.func` expressions rather than plain indices.
+	pub fn expressions(&self) -> bool {
+		self.expressions
+	}
+
+	/// Set whether the members are encoded as `ref.func` expressions rather than plain indices.
+	pub fn set_expressions(&mut self, expressions: bool) {
+		self.expressions = expressions;
+	}
+}
+
+/// Read a `ref.func` member of an expression-encoded segment.
+#[cfg(feature = "bulk")]
+fn read_member_expr<R: io::Read>(reader: &mut R) -> Result<u32, Error> {
+	match Uint8::deserialize(reader)?.into() {
+		REF_FUNC => {},
+		// `ref.null` members need the reference types proposal, which is not supported.
+		opcode => return Err(Error::UnknownOpcode(opcode)),
+	}
+	let index = VarUint32::deserialize(reader)?.into();
+	match Uint8::deserialize(reader)?.into() {
+		opcodes::END => Ok(index),
+		opcode => Err(Error::UnknownOpcode(opcode)),
+	}
 }
 
 impl Deserialize for ElementSegment {
@@ -108,23 +167,46 @@ impl Deserialize for ElementSegment {
 		// This piece of data was treated as `index` [of the table], but was repurposed
 		// for flags in bulk-memory operations proposal.
 		let flags: u32 = VarUint32::deserialize(reader)?.into();
-		let index = if flags == FLAG_MEMZERO || flags == FLAG_PASSIVE {
-			0u32
-		} else if flags == FLAG_MEM_NONZERO {
-			VarUint32::deserialize(reader)?.into()
-		} else {
+		if flags > (FLAG_DECLARED | FLAG_EXPRESSIONS) {
 			return Err(Error::InvalidSegmentFlags(flags))
+		}
+		let expressions = flags & FLAG_EXPRESSIONS != 0;
+		let mode = flags & !FLAG_EXPRESSIONS;
+		let index =
+			if mode == FLAG_MEM_NONZERO { VarUint32::deserialize(reader)?.into() } else { 0u32 };
+		let offset = if mode == FLAG_MEMZERO || mode == FLAG_MEM_NONZERO {
+			Some(InitExpr::deserialize(reader)?)
+		} else {
+			None
 		};
-		let offset =
-			if flags == FLAG_PASSIVE { None } else { Some(InitExpr::deserialize(reader)?) };
+		if mode != FLAG_MEMZERO {
+			// Only segments of functions are supported, whichever way they are encoded.
+			let kind: u8 = Uint8::deserialize(reader)?.into();
+			let expected = if expressions { REF_TYPE_FUNCREF } else { ELEM_KIND_FUNCREF };
+			if kind != expected {
+				return Err(Error::UnknownTableElementType(kind as i8))
+			}
+		}
 
-		let members: Vec<u32> = CountedList::<VarUint32>::deserialize(reader)?
-			.into_inner()
-			.into_iter()
-			.map(Into::into)
-			.collect();
+		let members: Vec<u32> = if expressions {
+			let count: u32 = VarUint32::deserialize(reader)?.into();
+			(0..count).map(|_| read_member_expr(reader)).collect::<Result<_, _>>()?
+		} else {
+			CountedList::<VarUint32>::deserialize(reader)?
+				.into_inner()
+				.into_iter()
+				.map(Into::into)
+				.collect()
+		};
 
-		Ok(ElementSegment { index, offset, members, passive: flags == FLAG_PASSIVE })
+		Ok(ElementSegment {
+			index,
+			offset,
+			members,
+			passive: mode == FLAG_PASSIVE,
+			declared: mode == FLAG_DECLARED,
+			expressions,
+		})
 	}
 }
 
@@ -134,21 +216,48 @@ impl Serialize for ElementSegment {
 	fn serialize<W: io::Write>(self, writer: &mut W) -> Result<(), Self::Error> {
 		#[cfg(feature = "bulk")]
 		{
-			if self.passive {
-				VarUint32::from(FLAG_PASSIVE).serialize(writer)?;
+			let mode = if self.declared {
+				FLAG_DECLARED
+			} else if self.passive {
+				FLAG_PASSIVE
 			} else if self.index != 0 {
-				VarUint32::from(FLAG_MEM_NONZERO).serialize(writer)?;
-				VarUint32::from(self.index).serialize(writer)?;
+				FLAG_MEM_NONZERO
 			} else {
-				VarUint32::from(FLAG_MEMZERO).serialize(writer)?;
+				FLAG_MEMZERO
+			};
+			let flags = if self.expressions { mode | FLAG_EXPRESSIONS } else { mode };
+			VarUint32::from(flags).serialize(writer)?;
+			if mode == FLAG_MEM_NONZERO {
+				VarUint32::from(self.index).serialize(writer)?;
+			}
+			// Only active segments carry an offset, passive and declared ones ignore it.
+			if mode == FLAG_MEMZERO || mode == FLAG_MEM_NONZERO {
+				if let Some(offset) = self.offset {
+					offset.serialize(writer)?;
+				}
+			}
+			if mode != FLAG_MEMZERO {
+				let kind = if self.expressions { REF_TYPE_FUNCREF } else { ELEM_KIND_FUNCREF };
+				Uint8::from(kind).serialize(writer)?;
+			}
+			if self.expressions {
+				VarUint32::from(self.members.len()).serialize(writer)?;
+				for member in self.members {
+					Uint8::from(REF_FUNC).serialize(writer)?;
+					VarUint32::from(member).serialize(writer)?;
+					Uint8::from(opcodes::END).serialize(writer)?;
+				}
+				return Ok(())
 			}
 		}
 		#[cfg(not(feature = "bulk"))]
-		VarUint32::from(self.index).serialize(writer)?;
-
-		if let Some(offset) = self.offset {
-			offset.serialize(writer)?;
+		{
+			VarUint32::from(self.index).serialize(writer)?;
+			if let Some(offset) = self.offset {
+				offset.serialize(writer)?;
+			}
 		}
+
 		let data = self.members;
 		let counted_list =
 			CountedListWriter::<VarUint32, _>(data.len(), data.into_iter().map(Into::into));
@@ -289,3 +398,51 @@ impl Serialize for DataSegment {
 		Ok(())
 	}
 }
+
+#[cfg(all(test, feature = "bulk"))]
+mod tests {
+	use super::ElementSegment;
+	use crate::elements::{deserialize_buffer, serialize, Error, InitExpr, Instruction};
+
+	#[test]
+	fn element_segment_encodings() {
+		let cases: &[(&[u8], u32, bool, bool, bool)] = &[
+			// flags, [table], [offset], [kind], members: table, passive, declared, expressions
+			(&[0x00, 0x41, 0x00, 0x0b, 0x01, 0x02], 0, false, false, false),
+			(&[0x01, 0x00, 0x01, 0x02], 0, true, false, false),
+			(&[0x02, 0x01, 0x41, 0x00, 0x0b, 0x00, 0x01, 0x02], 1, false, false, false),
+			(&[0x03, 0x00, 0x01, 0x02], 0, false, true, false),
+			(&[0x04, 0x41, 0x00, 0x0b, 0x01, 0xd2, 0x02, 0x0b], 0, false, false, true),
+			(&[0x05, 0x70, 0x01, 0xd2, 0x02, 0x0b], 0, true, false, true),
+			(&[0x06, 0x01, 0x41, 0x00, 0x0b, 0x70, 0x01, 0xd2, 0x02, 0x0b], 1, false, false, true),
+			(&[0x07, 0x70, 0x01, 0xd2, 0x02, 0x0b], 0, false, true, true),
+		];
+		for &(bytes, table, passive, declared, expressions) in cases {
+			let segment: ElementSegment = deserialize_buffer(bytes).expect("segment to decode");
+			assert_eq!(segment.members(), &[2]);
+			assert_eq!(segment.index(), table);
+			assert_eq!(segment.passive(), passive);
+			assert_eq!(segment.declared(), declared);
+			assert_eq!(segment.expressions(), expressions);
+			assert_eq!(segment.offset().is_some(), !passive && !declared);
+			assert_eq!(serialize(segment).unwrap(), bytes);
+		}
+
+		assert!(matches!(
+			deserialize_buffer::<ElementSegment>(&[0x08]),
+			Err(Error::InvalidSegmentFlags(8))
+		));
+		assert!(matches!(
+			deserialize_buffer::<ElementSegment>(&[0x05, 0x70, 0x01, 0xd0, 0x70, 0x0b]),
+			Err(Error::UnknownOpcode(0xd0))
+		));
+
+		let offset = InitExpr::new(vec![Instruction::I32Const(0), Instruction::End]);
+		let mut segment = ElementSegment::new(0, Some(offset), vec![2]);
+		segment.set_passive(true);
+		assert_eq!(serialize(segment.clone()).unwrap(), [0x01, 0x00, 0x01, 0x02]);
+		segment.set_passive(false);
+		segment.set_declared(true);
+		assert_eq!(serialize(segment).unwrap(), [0x03, 0x00, 0x01, 0x02]);
+	}
+}
@@ -0,0 +1,383 @@
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "multi_value")]
+use super::BlockType;
+#[cfg(feature = "bulk")]
+use super::BulkInstruction;
+use super::{
+	optimize::Remapping, CodeSection, Error, ExportEntry, ExportSection, External, Func,
+	FunctionSection, ImportCountType, ImportEntry, ImportSection, Instruction, Internal, Module,
+	Section, Type, TypeSection,
+};
+
+fn export_name(kind: &str, index: u32) -> String {
+	format!("__split.{}.{}", kind, index)
+}
+
+fn error(message: String) -> Error {
+	Error::HeapOther(message)
+}
+
+/// Move the defined functions listed in `functions` into a new module.
+///
+/// Returns the primary module, with the extracted functions replaced by imports from the
+/// module `secondary_name`, and the secondary module holding them. The secondary module
+/// imports whatever the extracted code needs from the module `primary_name`: the functions
+/// it calls, the globals it accesses and the memory and table of the primary module, which
+/// the primary module exports for it. Both sides use export names starting with `__split.`.
+///
+/// Indices in the primary module are renumbered for the new imports, in code, exports,
+/// element segments, the start section and the name section. Other custom sections are
+/// kept as they are.
+///
+/// Modules with more than one memory or table, and, with the corresponding features,
+/// extracted code accessing data or element segments or modules using tags, are not
+/// supported. The start function can not be extracted. Lazily decoded code sections have to
+/// be parsed first, otherwise [`Error::UnparsedCode`] is returned.
+pub fn split(
+	module: &Module,
+	functions: &[u32],
+	primary_name: &str,
+	secondary_name: &str,
+) -> Result<(Module, Module), Error> {
+	if module.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+	if module.memory_space() > 1 || module.table_space() > 1 {
+		return Err(error("modules with multiple memories or tables can not be split".into()))
+	}
+	#[cfg(feature = "exceptions")]
+	if module.tag_space() > 0 {
+		return Err(error("modules with tags can not be split".into()))
+	}
+
+	let imported_functions = module.import_count(ImportCountType::Function) as u32;
+	let space = module.functions_space() as u32;
+	let types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+	let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+	if bodies.len() as u32 != space - imported_functions {
+		return Err(Error::InconsistentCode)
+	}
+	let type_refs: Vec<u32> = module
+		.imports()
+		.filter_map(|external| match *external {
+			External::Function(type_ref) => Some(type_ref),
+			_ => None,
+		})
+		.chain(
+			module
+				.function_section()
+				.into_iter()
+				.flat_map(|s| s.entries())
+				.map(Func::type_ref),
+		)
+		.collect();
+	if type_refs.iter().any(|&type_ref| type_ref as usize >= types.len()) {
+		return Err(Error::InconsistentMetadata)
+	}
+
+	let mut extracted = vec![false; space as usize];
+	for &function in functions {
+		if function < imported_functions || function >= space {
+			return Err(error(format!("{} is not a defined function", function)))
+		}
+		extracted[function as usize] = true;
+	}
+	if module
+		.start_section()
+		.map_or(false, |start| extracted.get(start as usize) == Some(&true))
+	{
+		return Err(error("the start function can not be extracted".into()))
+	}
+	let extracted_list: Vec<u32> = (0..space).filter(|&f| extracted[f as usize]).collect();
+
+	// Everything the extracted code refers to.
+	let globals_space = module.globals_space();
+	let mut called = vec![false; space as usize];
+	let mut globals_used = vec![false; globals_space];
+	let mut types_used = vec![false; types.len()];
+	for &function in &extracted_list {
+		types_used[type_refs[function as usize] as usize] = true;
+		for instruction in bodies[(function - imported_functions) as usize].code().elements() {
+			let type_ref = match *instruction {
+				Instruction::Call(callee) => {
+					match extracted.get(callee as usize) {
+						Some(false) => called[callee as usize] = true,
+						Some(true) => {},
+						None => return Err(Error::InconsistentCode),
+					}
+					continue
+				},
+				Instruction::GetGlobal(global) | Instruction::SetGlobal(global) => {
+					*globals_used.get_mut(global as usize).ok_or(Error::InconsistentCode)? = true;
+					continue
+				},
+				Instruction::CallIndirect(type_ref, _) => type_ref,
+				#[cfg(feature = "multi_value")]
+				Instruction::Block(BlockType::TypeIndex(type_ref)) |
+				Instruction::Loop(BlockType::TypeIndex(type_ref)) |
+				Instruction::If(BlockType::TypeIndex(type_ref)) => type_ref,
+				#[cfg(feature = "bulk")]
+				Instruction::Bulk(
+					BulkInstruction::MemoryInit(_) |
+					BulkInstruction::MemoryDrop(_) |
+					BulkInstruction::TableInit(_) |
+					BulkInstruction::TableDrop(_),
+				) => return Err(error("extracted code can not access segments".into())),
+				_ => continue,
+			};
+			*types_used.get_mut(type_ref as usize).ok_or(Error::InconsistentCode)? = true;
+		}
+	}
+	for function in 0..space {
+		if called[function as usize] {
+			types_used[type_refs[function as usize] as usize] = true;
+		}
+	}
+
+	// Primary module: imports of the extracted functions come right after the original
+	// function imports.
+	let mut next_import = imported_functions;
+	let mut next_defined = imported_functions + extracted_list.len() as u32;
+	let primary_functions: Vec<Option<u32>> = (0..space)
+		.map(|function| {
+			let counter = if function < imported_functions {
+				return Some(function)
+			} else if extracted[function as usize] {
+				&mut next_import
+			} else {
+				&mut next_defined
+			};
+			*counter += 1;
+			Some(*counter - 1)
+		})
+		.collect();
+	let remapping = Remapping {
+		functions: primary_functions,
+		globals: (0..globals_space as u32).map(Some).collect(),
+		types: (0..types.len() as u32).map(Some).collect(),
+	};
+
+	let mut primary = module.clone();
+	if primary.import_section().is_none() {
+		primary.insert_section(Section::Import(ImportSection::default()))?;
+	}
+	if primary.export_section().is_none() {
+		primary.insert_section(Section::Export(ExportSection::default()))?;
+	}
+	let defined = &extracted[imported_functions as usize..];
+	for section in primary.sections_mut() {
+		match section {
+			Section::Import(section) => {
+				let imports = extracted_list.iter().map(|&function| {
+					ImportEntry::new(
+						secondary_name.into(),
+						export_name("func", function),
+						External::Function(type_refs[function as usize]),
+					)
+				});
+				// Appended after all function imports, so they get the indices right after them.
+				section.entries_mut().extend(imports);
+			},
+			Section::Function(section) => retain_kept(section.entries_mut(), defined),
+			Section::Code(section) => {
+				retain_kept(section.bodies_mut(), defined);
+				for body in section.bodies_mut() {
+					remapping.instructions(body.code_mut().elements_mut());
+				}
+			},
+			Section::Export(section) => {
+				for export in section.entries_mut() {
+					if let Internal::Function(index) = export.internal_mut() {
+						*index = remapping.function(*index);
+					}
+				}
+				let entries = section.entries_mut();
+				for function in (0..space).filter(|&f| called[f as usize]) {
+					let internal = Internal::Function(remapping.function(function));
+					entries.push(ExportEntry::new(export_name("func", function), internal));
+				}
+				for global in (0..globals_space as u32).filter(|&g| globals_used[g as usize]) {
+					entries.push(ExportEntry::new(
+						export_name("global", global),
+						Internal::Global(global),
+					));
+				}
+				if module.memory_space() == 1 {
+					entries.push(ExportEntry::new(export_name("memory", 0), Internal::Memory(0)));
+				}
+				if module.table_space() == 1 {
+					entries.push(ExportEntry::new(export_name("table", 0), Internal::Table(0)));
+				}
+			},
+			Section::Start(index) => *index = remapping.function(*index),
+			Section::Element(section) =>
+				for segment in section.entries_mut() {
+					for member in segment.members_mut() {
+						*member = remapping.function(*member);
+					}
+				},
+			Section::Name(names) => {
+				if let Some(subsection) = names.functions_mut() {
+					let map = core::mem::take(subsection.names_mut());
+					*subsection.names_mut() = remapping.names(map, &remapping.functions);
+				}
+				if let Some(subsection) = names.locals_mut() {
+					let map = core::mem::take(subsection.local_names_mut());
+					*subsection.local_names_mut() = remapping.names(map, &remapping.functions);
+				}
+			},
+			_ => {},
+		}
+	}
+
+	// Secondary module: imports of the called functions, followed by the extracted ones.
+	let secondary_types = renumber(&types_used);
+	let mut secondary_functions = vec![None; space as usize];
+	let secondary_order =
+		(0..space).filter(|&f| called[f as usize]).chain(extracted_list.iter().copied());
+	for (function, index) in secondary_order.zip(0..) {
+		secondary_functions[function as usize] = Some(index);
+	}
+	let remapping = Remapping {
+		functions: secondary_functions,
+		globals: renumber(&globals_used),
+		types: secondary_types,
+	};
+
+	let import = |kind: &str, index: u32, external: External| {
+		ImportEntry::new(primary_name.into(), export_name(kind, index), external)
+	};
+	let mut imports = Vec::new();
+	for function in (0..space).filter(|&f| called[f as usize]) {
+		let type_ref = remapping.type_ref(type_refs[function as usize]);
+		imports.push(import("func", function, External::Function(type_ref)));
+	}
+	for global in (0..globals_space as u32).filter(|&g| globals_used[g as usize]) {
+		let global_type = module.global_type(global).ok_or(Error::InconsistentMetadata)?;
+		imports.push(import("global", global, External::Global(global_type)));
+	}
+	if let Some(memory_type) = module.memory_type(0) {
+		imports.push(import("memory", 0, External::Memory(memory_type)));
+	}
+	if let Some(table_type) = module.table_type(0) {
+		imports.push(import("table", 0, External::Table(table_type)));
+	}
+
+	let mut declared = Vec::new();
+	let mut code = Vec::new();
+	let mut exports = Vec::new();
+	for &function in &extracted_list {
+		declared.push(Func::new(remapping.type_ref(type_refs[function as usize])));
+		let mut body = bodies[(function - imported_functions) as usize].clone();
+		remapping.instructions(body.code_mut().elements_mut());
+		code.push(body);
+		let internal = Internal::Function(remapping.function(function));
+		exports.push(ExportEntry::new(export_name("func", function), internal));
+	}
+	let secondary_type_section = types
+		.iter()
+		.zip(&types_used)
+		.filter(|(_, used)| **used)
+		.map(|(Type::Function(func_type), _)| Type::Function(func_type.clone()))
+		.collect();
+
+	let secondary = Module::new(vec![
+		Section::Type(TypeSection::with_types(secondary_type_section)),
+		Section::Import(ImportSection::with_entries(imports)),
+		Section::Function(FunctionSection::with_entries(declared)),
+		Section::Export(ExportSection::with_entries(exports)),
+		Section::Code(CodeSection::with_bodies(code)),
+	]);
+
+	Ok((primary, secondary))
+}
+
+/// New index of every used item.
+fn renumber(used: &[bool]) -> Vec<Option<u32>> {
+	let mut next = 0;
+	used.iter()
+		.map(|used| {
+			used.then(|| {
+				next += 1;
+				next - 1
+			})
+		})
+		.collect()
+}
+
+fn retain_kept<T>(entries: &mut Vec<T>, extracted: &[bool]) {
+	let mut index = 0;
+	entries.retain(|_| {
+		index += 1;
+		!extracted.get(index - 1).copied().unwrap_or(false)
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::split;
+	use crate::{
+		builder,
+		elements::{External, Instruction::*, Instructions, Internal},
+	};
+
+	fn function(code: Vec<crate::elements::Instruction>) -> builder::FunctionDefinition {
+		builder::function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(code))
+			.build()
+			.build()
+	}
+
+	#[test]
+	fn split_module() {
+		let mut module = builder::module()
+			.global()
+			.mutable()
+			.value_type()
+			.i32()
+			.build()
+			.export()
+			.field("main")
+			.internal()
+			.func(1)
+			.build();
+		module.push_function(function(vec![End]));
+		module.push_function(function(vec![Call(2), Call(0), End]));
+		module.push_function(function(vec![Call(0), GetGlobal(0), Drop, End]));
+		let module = module.build();
+
+		let (primary, secondary) = split(&module, &[2], "primary", "secondary").expect("split");
+
+		let imports = primary.import_section().unwrap().entries();
+		assert_eq!(imports.len(), 1);
+		assert_eq!((imports[0].module(), imports[0].field()), ("secondary", "__split.func.2"));
+		let bodies = primary.code_section().unwrap().bodies();
+		assert_eq!(bodies.len(), 2);
+		assert_eq!(bodies[1].code().elements(), &[Call(0), Call(1), End]);
+		let exports = primary.export_section().unwrap().entries();
+		let fields: Vec<_> = exports.iter().map(|e| (e.field(), *e.internal())).collect();
+		assert_eq!(
+			fields,
+			[
+				("main", Internal::Function(2)),
+				("__split.func.0", Internal::Function(1)),
+				("__split.global.0", Internal::Global(0)),
+			]
+		);
+
+		let imports = secondary.import_section().unwrap().entries();
+		assert_eq!(imports.len(), 2);
+		assert_eq!(imports[0].field(), "__split.func.0");
+		assert!(matches!(imports[1].external(), External::Global(_)));
+		let bodies = secondary.code_section().unwrap().bodies();
+		assert_eq!(bodies[0].code().elements(), &[Call(0), GetGlobal(0), Drop, End]);
+		assert_eq!(secondary.export_section().unwrap().entries()[0].field(), "__split.func.2");
+		assert_eq!(secondary.type_section().unwrap().types().len(), 1);
+
+		assert!(split(&module, &[3], "primary", "secondary").is_err());
+	}
+}
@@ -0,0 +1,207 @@
+use alloc::vec::Vec;
+
+use super::{
+	code_offsets, BlockType, Error, ExportEntry, ExportSection, GlobalEntry, GlobalSection,
+	GlobalType, ImportCountType, InitExpr, Instruction, Internal, Module, OffsetMap, Section,
+	ValueType,
+};
+
+/// Name under which [`inject_stack_limiter`] exports the call depth counter.
+pub const STACK_DEPTH_EXPORT: &str = "__stack_depth";
+
+/// Bound the call depth of the module's own code with a counter kept in a new global.
+///
+/// Every `call` and `call_indirect` is wrapped so that the counter is incremented before
+/// the call, the code traps with `unreachable` if it exceeds `limit`, and the counter is
+/// decremented again after the call returns. The depth reached by guest recursion is thus
+/// a property of the module alone and does not depend on how the embedder executes it.
+///
+/// The counter is appended to the global index space as a mutable `i32` starting at 0 and
+/// exported as [`STACK_DEPTH_EXPORT`]; modules already exporting that name are rejected with
+/// [`Error::DuplicateExport`]. Calls made by the host into exported functions are not counted.
+///
+/// The counter is only decremented when a call returns normally. It is not restored when a
+/// callee traps, nor when an exception (with the `exceptions` feature) unwinds through the
+/// call, so an instance that keeps running after a trap, or whose code catches exceptions
+/// thrown by its callees, starts from a higher depth and eventually hits the limit early
+/// unless the host resets the exported counter to 0.
+///
+/// Modules with a lazily decoded code section are rejected with [`Error::UnparsedCode`];
+/// parse them with [`Module::parse_code`] first.
+pub fn inject_stack_limiter(module: Module, limit: u32) -> Result<Module, Error> {
+	Ok(inject(module, limit)?.0)
+}
+
+/// Same as [`inject_stack_limiter`], also returning how code offsets moved so debug
+/// information can be kept in sync.
+pub fn inject_stack_limiter_with_offsets(
+	module: Module,
+	limit: u32,
+) -> Result<(Module, OffsetMap), Error> {
+	let before = code_offsets(&module)?;
+	let (module, origins) = inject(module, limit)?;
+	let offsets = OffsetMap::new(&before, &code_offsets(&module)?, &origins)?;
+	Ok((module, offsets))
+}
+
+/// Original index of every instruction of every rewritten body, `None` for added ones.
+type Origins = Vec<Vec<Option<usize>>>;
+
+/// Instrumented module and the origins of the instructions of every body.
+fn inject(mut module: Module, limit: u32) -> Result<(Module, Origins), Error> {
+	if module.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+	let exports = module.export_section().map(|s| s.entries()).unwrap_or(&[]);
+	if exports.iter().any(|entry| entry.field() == STACK_DEPTH_EXPORT) {
+		return Err(Error::DuplicateExport(STACK_DEPTH_EXPORT.into()))
+	}
+	let counter = (module.import_count(ImportCountType::Global) +
+		module.global_section().map(|s| s.entries().len()).unwrap_or(0)) as u32;
+	let entry = GlobalEntry::new(
+		GlobalType::new(ValueType::I32, true),
+		InitExpr::new(vec![Instruction::I32Const(0), Instruction::End]),
+	);
+	match module.global_section_mut() {
+		Some(globals) => globals.entries_mut().push(entry),
+		None => module.insert_section(Section::Global(GlobalSection::with_entries(vec![entry])))?,
+	}
+	let export = ExportEntry::new(STACK_DEPTH_EXPORT.into(), Internal::Global(counter));
+	match module.export_section_mut() {
+		Some(exports) => exports.entries_mut().push(export),
+		None =>
+			module.insert_section(Section::Export(ExportSection::with_entries(vec![export])))?,
+	}
+
+	let mut origins = Vec::new();
+	if let Some(code) = module.code_section_mut() {
+		for body in code.bodies_mut() {
+			let instructions = body.code_mut().elements_mut();
+			let (instrumented, body_origins) = instrument_calls(instructions, counter, limit);
+			*instructions = instrumented;
+			origins.push(body_origins);
+		}
+	}
+
+	Ok((module, origins))
+}
+
+fn instrument_calls(
+	instructions: &[Instruction],
+	counter: u32,
+	limit: u32,
+) -> (Vec<Instruction>, Vec<Option<usize>>) {
+	let mut instrumented = Vec::with_capacity(instructions.len());
+	let mut origins = Vec::with_capacity(instructions.len());
+	for (index, instruction) in instructions.iter().enumerate() {
+		match instruction {
+			Instruction::Call(_) | Instruction::CallIndirect(..) => {
+				instrumented.extend_from_slice(&[
+					Instruction::GetGlobal(counter),
+					Instruction::I32Const(1),
+					Instruction::I32Add,
+					Instruction::SetGlobal(counter),
+					Instruction::GetGlobal(counter),
+					Instruction::I32Const(limit as i32),
+					Instruction::I32GtU,
+					Instruction::If(BlockType::NoResult),
+					Instruction::Unreachable,
+					Instruction::End,
+					instruction.clone(),
+					Instruction::GetGlobal(counter),
+					Instruction::I32Const(1),
+					Instruction::I32Sub,
+					Instruction::SetGlobal(counter),
+				]);
+				origins.extend([None; 10]);
+				origins.push(Some(index));
+				origins.extend([None; 4]);
+			},
+			_ => {
+				instrumented.push(instruction.clone());
+				origins.push(Some(index));
+			},
+		}
+	}
+	(instrumented, origins)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{inject_stack_limiter, STACK_DEPTH_EXPORT};
+	use crate::{
+		builder,
+		elements::{BlockType, Error, Instruction::*, Instructions, Internal, Module},
+	};
+
+	#[test]
+	fn wraps_calls() {
+		let module = builder::module()
+			.global()
+			.value_type()
+			.i64()
+			.build()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![Call(0), End]))
+			.build()
+			.build()
+			.build();
+
+		let module = inject_stack_limiter(module, 100).expect("module to be instrumented");
+
+		let globals = module.global_section().expect("global section").entries();
+		assert_eq!(globals.len(), 2);
+		assert!(globals[1].global_type().is_mutable());
+		let export = &module.export_section().expect("export section").entries()[0];
+		assert_eq!(export.field(), STACK_DEPTH_EXPORT);
+		assert_eq!(*export.internal(), Internal::Global(1));
+		assert_eq!(
+			module.code_section().unwrap().bodies()[0].code().elements(),
+			&[
+				GetGlobal(1),
+				I32Const(1),
+				I32Add,
+				SetGlobal(1),
+				GetGlobal(1),
+				I32Const(100),
+				I32GtU,
+				If(BlockType::NoResult),
+				Unreachable,
+				End,
+				Call(0),
+				GetGlobal(1),
+				I32Const(1),
+				I32Sub,
+				SetGlobal(1),
+				End,
+			]
+		);
+
+		assert!(matches!(
+			inject_stack_limiter(module, 100),
+			Err(Error::DuplicateExport(name)) if name == STACK_DEPTH_EXPORT
+		));
+	}
+
+	#[test]
+	fn lazy_code() {
+		let module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.with_instructions(Instructions::new(vec![Call(0), End]))
+			.build()
+			.build()
+			.build();
+		let lazy = Module::from_bytes_lazy(module.into_bytes().unwrap()).unwrap();
+		assert!(matches!(inject_stack_limiter(lazy.clone(), 100), Err(Error::UnparsedCode)));
+
+		let module = inject_stack_limiter(lazy.parse_code().unwrap(), 100)
+			.expect("parsed module to be instrumented");
+		assert_eq!(module.code_section().unwrap().bodies()[0].code().elements().len(), 16);
+	}
+}
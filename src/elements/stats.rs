@@ -0,0 +1,273 @@
+use alloc::{
+	collections::BTreeMap,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::fmt;
+
+use super::{
+	code_offsets, module::custom_section_name, serialize, Error, ImportCountType, Instruction,
+	Module, Section,
+};
+
+/// Number of entries listed per table by the [`Display`](fmt::Display) report.
+const REPORT_ROWS: usize = 10;
+
+/// Encoded size of a single section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionStats {
+	name: String,
+	size: usize,
+}
+
+impl SectionStats {
+	/// Name of the section, e.g. `code` or `custom:.debug_info`.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Size of the section in bytes, id and size prefix included.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+}
+
+/// Size of a single function body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionStats {
+	index: u32,
+	name: Option<String>,
+	size: usize,
+	instructions: usize,
+}
+
+impl FunctionStats {
+	/// Index of the function in the function index space.
+	pub fn index(&self) -> u32 {
+		self.index
+	}
+
+	/// Name of the function from the name section, if known.
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	/// Size of the body in bytes, size prefix included.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Number of instructions in the body.
+	pub fn instructions(&self) -> usize {
+		self.instructions
+	}
+}
+
+/// Size breakdown of a module, as computed by [`analyze`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleStats {
+	size: usize,
+	sections: Vec<SectionStats>,
+	functions: Vec<FunctionStats>,
+	opcodes: BTreeMap<String, usize>,
+	calls: BTreeMap<(u32, u32), usize>,
+}
+
+impl ModuleStats {
+	/// Size of the encoded module in bytes.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Sizes of all sections, in module order.
+	pub fn sections(&self) -> &[SectionStats] {
+		&self.sections
+	}
+
+	/// Sizes of all function bodies, largest first.
+	pub fn functions(&self) -> &[FunctionStats] {
+		&self.functions
+	}
+
+	/// Number of occurrences of every opcode in function bodies, by mnemonic.
+	pub fn opcodes(&self) -> &BTreeMap<String, usize> {
+		&self.opcodes
+	}
+
+	/// Number of direct calls for every (caller, callee) pair of function indices.
+	pub fn calls(&self) -> &BTreeMap<(u32, u32), usize> {
+		&self.calls
+	}
+}
+
+fn section_name(section: &Section) -> String {
+	if let Some(name) = custom_section_name(section) {
+		return ["custom:", name].concat()
+	}
+	match section {
+		Section::Type(_) => "type",
+		Section::Import(_) => "import",
+		Section::Function(_) => "function",
+		Section::Table(_) => "table",
+		Section::Memory(_) => "memory",
+		#[cfg(feature = "exceptions")]
+		Section::Tag(_) => "tag",
+		Section::Global(_) => "global",
+		Section::Export(_) => "export",
+		Section::Start(_) => "start",
+		Section::Element(_) => "element",
+		Section::DataCount(_) => "datacount",
+		Section::Code(_) | Section::LazyCode(_) => "code",
+		Section::Data(_) => "data",
+		Section::Unparsed { .. } => "unknown",
+		_ => "custom",
+	}
+	.to_string()
+}
+
+fn mnemonic(instruction: &Instruction) -> String {
+	let text = instruction.to_string();
+	text.split(' ').next().unwrap_or_default().to_string()
+}
+
+/// Compute section and function sizes, opcode counts and direct call edges of `module`.
+///
+/// Lazily decoded code sections have to be parsed first, otherwise [`Error::UnparsedCode`] is
+/// returned.
+pub fn analyze(module: &Module) -> Result<ModuleStats, Error> {
+	if module.lazy_code_section().is_some() {
+		return Err(Error::UnparsedCode)
+	}
+	let sections = module
+		.sections()
+		.iter()
+		.map(|section| {
+			Ok(SectionStats {
+				name: section_name(section),
+				size: serialize(section.clone())?.len(),
+			})
+		})
+		.collect::<Result<Vec<_>, Error>>()?;
+	// Magic number and version.
+	let size = 8 + sections.iter().map(|section| section.size).sum::<usize>();
+
+	let imported_functions = module.import_count(ImportCountType::Function) as u32;
+	let names = module.names_section().and_then(|names| names.functions());
+	let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+	let mut functions = Vec::with_capacity(bodies.len());
+	let mut opcodes = BTreeMap::new();
+	let mut calls = BTreeMap::new();
+	for ((index, body), offsets) in (imported_functions..).zip(bodies).zip(code_offsets(module)?) {
+		let code = body.code().elements();
+		for instruction in code {
+			*opcodes.entry(mnemonic(instruction)).or_insert(0) += 1;
+			if let Instruction::Call(callee) = *instruction {
+				*calls.entry((index, callee)).or_insert(0) += 1;
+			}
+		}
+		functions.push(FunctionStats {
+			index,
+			name: names.and_then(|names| names.names().get(index)).cloned(),
+			size: (offsets.end() - offsets.body()) as usize,
+			instructions: code.len(),
+		});
+	}
+	functions.sort_by(|a, b| b.size.cmp(&a.size).then(a.index.cmp(&b.index)));
+
+	Ok(ModuleStats { size, sections, functions, opcodes, calls })
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+	if total == 0 {
+		0.0
+	} else {
+		part as f64 * 100.0 / total as f64
+	}
+}
+
+impl fmt::Display for ModuleStats {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Total size: {} bytes", self.size)?;
+
+		writeln!(f, "\nSections:")?;
+		for section in &self.sections {
+			writeln!(
+				f,
+				"  {:<24} {:>10} bytes {:>6.2}%",
+				section.name,
+				section.size,
+				percent(section.size, self.size)
+			)?;
+		}
+
+		if !self.functions.is_empty() {
+			writeln!(f, "\nLargest functions:")?;
+			for function in self.functions.iter().take(REPORT_ROWS) {
+				let name = function.name.as_deref().unwrap_or("");
+				writeln!(
+					f,
+					"  #{:<6} {:<24} {:>10} bytes {:>8} instructions",
+					function.index, name, function.size, function.instructions
+				)?;
+			}
+		}
+
+		if !self.opcodes.is_empty() {
+			let mut opcodes: Vec<_> = self.opcodes.iter().collect();
+			opcodes.sort_by(|a, b| b.1.cmp(a.1));
+			writeln!(f, "\nMost used opcodes:")?;
+			for (mnemonic, count) in opcodes.into_iter().take(REPORT_ROWS) {
+				writeln!(f, "  {:<24} {:>10}", mnemonic, count)?;
+			}
+		}
+
+		let call_sites: usize = self.calls.values().sum();
+		writeln!(f, "\nCall edges: {} ({} call sites)", self.calls.len(), call_sites)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::analyze;
+	use crate::{
+		builder,
+		elements::{Error, Instruction::*, Instructions, Module},
+	};
+
+	#[test]
+	fn stats() {
+		let mut module = builder::module();
+		module.push_function(
+			builder::function()
+				.signature()
+				.build()
+				.body()
+				.with_instructions(Instructions::new(vec![
+					Call(1),
+					Call(1),
+					I32Const(1),
+					Drop,
+					End,
+				]))
+				.build()
+				.build(),
+		);
+		module.push_function(builder::function().signature().build().build());
+		let module = module.build();
+
+		let stats = analyze(&module).expect("module to be analyzed");
+		assert_eq!(stats.size(), module.clone().into_bytes().unwrap().len());
+		let names: Vec<_> = stats.sections().iter().map(|s| s.name()).collect();
+		assert_eq!(names, ["type", "function", "code"]);
+		assert_eq!(stats.functions()[0].index(), 0);
+		assert_eq!(stats.functions()[0].instructions(), 5);
+		assert_eq!(stats.opcodes()["call"], 2);
+		assert_eq!(stats.opcodes()["i32.const"], 1);
+		assert_eq!(stats.calls()[&(0, 1)], 2);
+		assert!(stats.to_string().contains("Call edges: 1 (2 call sites)"));
+
+		let bytes = module.into_bytes().expect("module to serialize");
+		let lazy = Module::from_bytes_lazy(&bytes).expect("module to deserialize");
+		assert!(matches!(analyze(&lazy), Err(Error::UnparsedCode)));
+	}
+}
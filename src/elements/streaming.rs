@@ -0,0 +1,200 @@
+use crate::io;
+use alloc::vec::Vec;
+
+use super::{module::WASM_MAGIC_NUMBER, Deserialize, Error, Section, Uint32, VarUint32};
+
+/// Incremental module decoder.
+///
+/// Bytes are fed in arbitrary chunks with [`push`](StreamingDeserializer::push) and every
+/// section is decoded as soon as its payload is fully buffered, so decoding can overlap
+/// with downloading the module. Section ordering is checked the same way as in
+/// [`Module`](super::Module) deserialization.
+///
+/// # Examples
+///
+/// ```
+/// use parity_wasm::elements::{Module, StreamingDeserializer};
+///
+/// let bytes = parity_wasm::builder::module().memory().build().build().into_bytes().unwrap();
+///
+/// let mut stream = StreamingDeserializer::new();
+/// let mut sections = Vec::new();
+/// for chunk in bytes.chunks(3) {
+///     stream.push(chunk);
+///     while let Some(section) = stream.next_section().unwrap() {
+///         sections.push(section);
+///     }
+/// }
+/// stream.finish().unwrap();
+/// assert!(Module::new(sections).memory_section().is_some());
+/// ```
+#[derive(Debug, Default)]
+pub struct StreamingDeserializer {
+	buffer: Vec<u8>,
+	position: usize,
+	version: Option<u32>,
+	last_section_order: u8,
+	function_count: usize,
+	body_count: usize,
+}
+
+impl StreamingDeserializer {
+	/// New decoder expecting the module header first.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Append the next chunk of module bytes.
+	pub fn push(&mut self, chunk: &[u8]) {
+		if self.position > 0 {
+			self.buffer.drain(..self.position);
+			self.position = 0;
+		}
+		self.buffer.extend_from_slice(chunk);
+	}
+
+	/// Module version, once the header has been decoded.
+	pub fn version(&self) -> Option<u32> {
+		self.version
+	}
+
+	/// Decode the next section if all of its bytes were pushed already.
+	///
+	/// Returns `Ok(None)` when more input is needed.
+	pub fn next_section(&mut self) -> Result<Option<Section>, Error> {
+		if self.version.is_none() && !self.read_header()? {
+			return Ok(None)
+		}
+
+		let pending = &self.buffer[self.position..];
+		let length = match section_length(pending)? {
+			Some(length) => length,
+			None => return Ok(None),
+		};
+		if pending.len() < length {
+			return Ok(None)
+		}
+
+		let mut reader = io::Cursor::new(&pending[..length]);
+		let section = Section::deserialize(&mut reader)?;
+		if reader.position() != length {
+			return Err(Error::InconsistentLength { expected: length, actual: reader.position() })
+		}
+		self.position += length;
+
+		if section.order() != 0 {
+			match self.last_section_order {
+				x if x > section.order() => return Err(Error::SectionsOutOfOrder),
+				x if x == section.order() => return Err(Error::DuplicatedSections(x)),
+				_ => {},
+			}
+			self.last_section_order = section.order();
+		}
+		match &section {
+			Section::Function(functions) => self.function_count = functions.entries().len(),
+			Section::Code(code) => self.body_count = code.bodies().len(),
+			_ => {},
+		}
+
+		Ok(Some(section))
+	}
+
+	/// Finish decoding, failing if the input stopped in the middle of the header or a section,
+	/// or if the number of function bodies does not match the number of declared functions.
+	pub fn finish(self) -> Result<(), Error> {
+		if self.version.is_none() || self.position != self.buffer.len() {
+			return Err(Error::UnexpectedEof)
+		}
+		if self.function_count != self.body_count {
+			return Err(Error::InconsistentCode)
+		}
+		Ok(())
+	}
+
+	fn read_header(&mut self) -> Result<bool, Error> {
+		let pending = &self.buffer[self.position..];
+		let magic_length = pending.len().min(WASM_MAGIC_NUMBER.len());
+		if pending[..magic_length] != WASM_MAGIC_NUMBER[..magic_length] {
+			return Err(Error::InvalidMagic)
+		}
+		if pending.len() < 8 {
+			return Ok(false)
+		}
+
+		let version: u32 = Uint32::deserialize(&mut io::Cursor::new(&pending[4..8]))?.into();
+		if version != 1 {
+			return Err(Error::UnsupportedVersion(version))
+		}
+		self.version = Some(version);
+		self.position += 8;
+		Ok(true)
+	}
+}
+
+/// Total length of the section starting at `bytes` (id, size and payload), if the size is
+/// already known.
+fn section_length(bytes: &[u8]) -> Result<Option<usize>, Error> {
+	// Section id followed by the payload size as a LEB128 of at most 5 bytes.
+	let size_end = match bytes.iter().skip(1).take(5).position(|byte| byte & 0x80 == 0) {
+		Some(index) => index + 2,
+		None if bytes.len() < 6 => return Ok(None),
+		None => bytes.len().min(6),
+	};
+	let size: u32 = VarUint32::deserialize(&mut io::Cursor::new(&bytes[1..size_end]))?.into();
+	Ok(Some(size_end + size as usize))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::StreamingDeserializer;
+	use crate::elements::{deserialize_file, serialize, Error, Module, Section};
+
+	fn decode_in_chunks(bytes: &[u8], chunk_size: usize) -> Result<Module, Error> {
+		let mut stream = StreamingDeserializer::new();
+		let mut sections = Vec::new();
+		for chunk in bytes.chunks(chunk_size) {
+			stream.push(chunk);
+			while let Some(section) = stream.next_section()? {
+				sections.push(section);
+			}
+		}
+		stream.finish()?;
+		Ok(Module::new(sections))
+	}
+
+	#[test]
+	fn chunked() {
+		let module = deserialize_file("./res/cases/v1/test.wasm").expect("failed to deserialize");
+		let bytes = serialize(module.clone()).expect("failed to serialize");
+
+		for chunk_size in [1, 7, bytes.len()] {
+			assert_eq!(decode_in_chunks(&bytes, chunk_size).expect("stream to decode"), module);
+		}
+	}
+
+	#[test]
+	fn truncated() {
+		let bytes = serialize(deserialize_file("./res/cases/v1/test.wasm").unwrap()).unwrap();
+		assert!(matches!(
+			decode_in_chunks(&bytes[..bytes.len() - 1], 16),
+			Err(Error::UnexpectedEof)
+		));
+	}
+
+	#[test]
+	fn inconsistent_code() {
+		let mut module = deserialize_file("./res/cases/v1/inc_i32.wasm").unwrap();
+		module.sections_mut().retain(|section| !matches!(section, Section::Code(_)));
+		let bytes = serialize(module).unwrap();
+		assert!(matches!(decode_in_chunks(&bytes, 16), Err(Error::InconsistentCode)));
+	}
+
+	#[test]
+	fn invalid_magic() {
+		let mut stream = StreamingDeserializer::new();
+		stream.push(&[0x00, 0x61, 0x73]);
+		assert!(matches!(stream.next_section(), Ok(None)));
+		stream.push(&[0x00]);
+		assert!(matches!(stream.next_section(), Err(Error::InvalidMagic)));
+	}
+}
@@ -0,0 +1,269 @@
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::{any::Any, fmt};
+
+use super::{CustomSection, Error};
+
+/// Typed representation of a custom section defined outside of this crate.
+///
+/// Implement it for the decoded form of a section and register it in a
+/// [`CustomSectionRegistry`] to have [`Module::decode_custom_sections`] replace matching
+/// custom sections with [`Section::Typed`] ones. Those are encoded back on serialization.
+///
+/// [`Module::decode_custom_sections`]: super::Module::decode_custom_sections
+/// [`Section::Typed`]: super::Section::Typed
+pub trait CustomSectionCodec: Any + Clone + fmt::Debug + PartialEq + Send + Sync {
+	/// Decode the payload of the custom section.
+	fn decode(payload: &[u8]) -> Result<Self, Error>;
+
+	/// Encode the value as the payload of the custom section.
+	fn encode(&self) -> Result<Vec<u8>, Error>;
+}
+
+/// Object-safe counterpart of `CustomSectionCodec`.
+trait TypedPayload: fmt::Debug + Send + Sync {
+	fn encode(&self) -> Result<Vec<u8>, Error>;
+	fn clone_box(&self) -> Box<dyn TypedPayload>;
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: CustomSectionCodec> TypedPayload for T {
+	fn encode(&self) -> Result<Vec<u8>, Error> {
+		CustomSectionCodec::encode(self)
+	}
+
+	fn clone_box(&self) -> Box<dyn TypedPayload> {
+		Box::new(self.clone())
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
+}
+
+fn decode_boxed<T: CustomSectionCodec>(payload: &[u8]) -> Result<Box<dyn TypedPayload>, Error> {
+	Ok(Box::new(T::decode(payload)?))
+}
+
+/// Custom section decoded with a [`CustomSectionCodec`].
+pub struct TypedCustomSection {
+	name: String,
+	value: Box<dyn TypedPayload>,
+	eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+fn eq_typed<T: CustomSectionCodec>(a: &dyn Any, b: &dyn Any) -> bool {
+	match (a.downcast_ref::<T>(), b.downcast_ref::<T>()) {
+		(Some(a), Some(b)) => a == b,
+		_ => false,
+	}
+}
+
+impl TypedCustomSection {
+	/// New typed custom section.
+	pub fn new<T: CustomSectionCodec>(name: impl Into<String>, value: T) -> Self {
+		TypedCustomSection { name: name.into(), value: Box::new(value), eq: eq_typed::<T> }
+	}
+
+	/// Name of the custom section.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Decoded value, if it is of type `T`.
+	pub fn value<T: CustomSectionCodec>(&self) -> Option<&T> {
+		self.value.as_any().downcast_ref()
+	}
+
+	/// Decoded value (mutable), if it is of type `T`.
+	pub fn value_mut<T: CustomSectionCodec>(&mut self) -> Option<&mut T> {
+		self.value.as_any_mut().downcast_mut()
+	}
+
+	/// Encode the value back into a plain custom section.
+	pub fn to_custom(&self) -> Result<CustomSection, Error> {
+		Ok(CustomSection::new(self.name.clone(), self.value.encode()?))
+	}
+}
+
+impl Clone for TypedCustomSection {
+	fn clone(&self) -> Self {
+		TypedCustomSection { name: self.name.clone(), value: self.value.clone_box(), eq: self.eq }
+	}
+}
+
+impl PartialEq for TypedCustomSection {
+	fn eq(&self, other: &Self) -> bool {
+		self.name == other.name && (self.eq)(self.value.as_any(), other.value.as_any())
+	}
+}
+
+impl fmt::Debug for TypedCustomSection {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TypedCustomSection")
+			.field("name", &self.name)
+			.field("value", &self.value)
+			.finish()
+	}
+}
+
+type Decoder = fn(&[u8]) -> Result<Box<dyn TypedPayload>, Error>;
+
+#[derive(Clone, Copy)]
+struct Codec {
+	decode: Decoder,
+	eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+/// Codecs of custom sections, by section name.
+#[derive(Clone, Default)]
+pub struct CustomSectionRegistry {
+	codecs: BTreeMap<String, Codec>,
+}
+
+impl CustomSectionRegistry {
+	/// New empty registry.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Decode custom sections named `name` as `T`, replacing any codec registered for it.
+	pub fn register<T: CustomSectionCodec>(&mut self, name: impl Into<String>) -> &mut Self {
+		self.codecs
+			.insert(name.into(), Codec { decode: decode_boxed::<T>, eq: eq_typed::<T> });
+		self
+	}
+
+	/// Whether a codec is registered for sections named `name`.
+	pub fn contains(&self, name: &str) -> bool {
+		self.codecs.contains_key(name)
+	}
+
+	/// Decode `section` with the codec registered for its name, if any.
+	pub fn decode(&self, section: &CustomSection) -> Option<Result<TypedCustomSection, Error>> {
+		let codec = self.codecs.get(section.name())?;
+		Some((codec.decode)(section.payload()).map(|value| TypedCustomSection {
+			name: section.name().into(),
+			value,
+			eq: codec.eq,
+		}))
+	}
+}
+
+impl fmt::Debug for CustomSectionRegistry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_set().entries(self.codecs.keys()).finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{CustomSectionCodec, CustomSectionRegistry, TypedCustomSection};
+	use crate::elements::{
+		deserialize_buffer, serialize, Error, Module, ProducersSection, Section, VarUint32,
+		VarUint64, PROCESSED_BY,
+	};
+
+	#[derive(Debug, Clone, PartialEq)]
+	struct Metering {
+		cost: u64,
+	}
+
+	impl CustomSectionCodec for Metering {
+		fn decode(payload: &[u8]) -> Result<Self, Error> {
+			Ok(Metering { cost: deserialize_buffer::<VarUint64>(payload)?.into() })
+		}
+
+		fn encode(&self) -> Result<Vec<u8>, Error> {
+			serialize(VarUint64::from(self.cost))
+		}
+	}
+
+	#[derive(Debug, Clone, PartialEq)]
+	struct Version(u32);
+
+	impl CustomSectionCodec for Version {
+		fn decode(payload: &[u8]) -> Result<Self, Error> {
+			Ok(Version(deserialize_buffer::<VarUint32>(payload)?.into()))
+		}
+
+		fn encode(&self) -> Result<Vec<u8>, Error> {
+			serialize(VarUint32::from(self.0))
+		}
+	}
+
+	#[derive(Debug, Clone, PartialEq)]
+	struct Producers(ProducersSection);
+
+	impl CustomSectionCodec for Producers {
+		fn decode(payload: &[u8]) -> Result<Self, Error> {
+			Ok(Producers(deserialize_buffer(payload)?))
+		}
+
+		fn encode(&self) -> Result<Vec<u8>, Error> {
+			serialize(self.0.clone())
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let mut registry = CustomSectionRegistry::new();
+		registry.register::<Metering>("metering").register::<Version>("version");
+
+		let mut module = Module::default();
+		module.set_custom_section("metering", vec![0x80, 0x01]);
+		module.set_custom_section("version", vec![0x80, 0x80]);
+		module.set_custom_section("other", vec![1, 2, 3]);
+
+		let (errors, module) =
+			module.decode_custom_sections(&registry).expect_err("version to be truncated");
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].0, 1);
+
+		let mut module = module.into_bytes().and_then(Module::from_bytes).expect("to round trip");
+		module.set_custom_section("version", vec![0x07]);
+		let mut module = module.decode_custom_sections(&registry).expect("sections to be decoded");
+		assert_eq!(module.typed_section::<Version>("version"), Some(&Version(7)));
+		assert_eq!(module.typed_section::<Version>("metering"), None);
+		module.typed_section_mut::<Metering>("metering").expect("metering section").cost = 300;
+
+		let bytes = module.clone().into_bytes().expect("module to serialize");
+		let decoded = Module::from_bytes(&bytes).expect("module to deserialize");
+		let payloads: Vec<_> = decoded.custom_sections().map(|s| (s.name(), s.payload())).collect();
+		assert_eq!(
+			payloads,
+			vec![("metering", &[0xac, 0x02][..]), ("version", &[7][..]), ("other", &[1, 2, 3][..])]
+		);
+
+		let typed = TypedCustomSection::new("version", Version(7));
+		assert!(module.sections().contains(&Section::Typed(typed)));
+		assert_ne!(
+			TypedCustomSection::new("version", Version(7)),
+			TypedCustomSection::new("version", Metering { cost: 7 })
+		);
+	}
+
+	#[test]
+	fn typed_sections_by_name() {
+		let mut producers = ProducersSection::default();
+		producers.add_value(PROCESSED_BY, "parity-wasm", "0.45");
+		let mut module = Module::default();
+		module.set_producers_section(producers.clone()).expect("producers to encode");
+
+		let mut registry = CustomSectionRegistry::new();
+		registry.register::<Producers>("producers");
+		let mut module = module.decode_custom_sections(&registry).expect("producers to decode");
+		assert!(matches!(module.sections(), [Section::Typed(_)]));
+		assert_eq!(module.producers_section().expect("producers to decode"), Some(producers));
+
+		module.set_custom_section("producers", vec![0]);
+		assert!(matches!(module.sections(), [Section::Custom(_)]));
+		let mut module = module.decode_custom_sections(&registry).expect("producers to decode");
+		assert!(module.clear_custom_section("producers").is_some());
+		assert!(module.sections().is_empty());
+	}
+}
@@ -40,6 +40,7 @@ pub trait Read {
 }
 
 /// Reader that saves the last position.
+#[derive(Debug)]
 pub struct Cursor<T> {
 	inner: T,
 	pos: usize,
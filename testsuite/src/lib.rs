@@ -4,11 +4,7 @@ use std::ffi::OsStr;
 
 mod run;
 
-const BASIC_BLACKLIST: [&str; 2] = [
-	// those use unsupported i32_trunc_sat_* instructions
-	"binary-leb128.wast",
-	"conversions.wast",
-];
+const BASIC_BLACKLIST: [&str; 0] = [];
 
 #[test_generator::test_resources("testsuite/spec/*.wast")]
 fn basic(path: &str) {